@@ -3,34 +3,58 @@ use tracing::instrument;
 
 use crate::{
     app_state::AppState,
+    config::get_config,
+    embed::Selection,
     modes::ModeManager,
-    services::{PreviewManager, create_data_provider, preview::GLOBAL_PICKER},
+    services::{PreviewManager, create_data_provider, dir_index, history_store},
+    terminal::InteractiveOptions,
     utils::AppMode,
 };
 
 pub struct App {
     pub state: AppState,
     pub mode_manager: ModeManager,
+    pub options: InteractiveOptions,
+    /// What was picked, once an embedded app was left with a selection
+    pub selection: Option<Selection>,
 }
 
 impl App {
     #[instrument]
-    pub fn new(initial_mode: AppMode) -> Result<Self> {
-        GLOBAL_PICKER.font_size();
+    pub fn new(initial_mode: AppMode, options: InteractiveOptions) -> Result<Self> {
         let mut state = AppState::new()?;
+        if let Some(dir) = &options.start_dir {
+            state.current_dir = dir.clone();
+        }
 
         // Load initial data using data provider
         let data_provider = create_data_provider(&initial_mode);
-        data_provider.load_data(&mut state)?;
+        data_provider.start_loading(&mut state)?;
+        if get_config().search.frecency_boost {
+            history_store::warm_frecency_scores();
+        }
+
+        if let Some(query) = options.query.clone().filter(|query| !query.is_empty()) {
+            state.start_search(query);
+        }
+
+        if initial_mode == AppMode::Normal && get_config().layout.dual_pane {
+            state.toggle_dual_pane();
+        }
 
         let app = App {
             state,
             mode_manager: ModeManager::new(&initial_mode),
+            options,
+            selection: None,
         };
 
         // Clear preview
         PreviewManager::clear_preview();
 
+        // Keep the index for Jump mode up to date while the app runs
+        dir_index::refresh_if_stale();
+
         Ok(app)
     }
 }
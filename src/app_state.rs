@@ -1,12 +1,31 @@
+use crossterm::event::KeyEvent;
 use ratatui::widgets::ListState;
-use std::{collections::HashMap, path::PathBuf, time::Instant};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tracing::{debug, instrument, warn};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{
-    core::layout::LayoutManager,
+    config::{CaseMatching, EntryFilter, get_config},
+    core::{
+        filter::{self, BACKGROUND_FILTER_THRESHOLD, FilterJob, SearchFilter},
+        keymap::{Action, key_label},
+        layout::LayoutManager,
+        listing::Listing,
+        messages::MessageQueue,
+    },
+    services::{Marks, history_store},
     utils::{DisplayItem, FileItem},
 };
 
+/// How long a type-ahead jump waits for the next character
+pub const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
 #[derive(Clone, Debug)]
 pub struct DoubleClickState {
     pub last_click_time: Option<Instant>,
@@ -14,17 +33,134 @@ pub struct DoubleClickState {
     pub last_clicked_index: Option<usize>,
 }
 
+/// What a drag with the left mouse button moves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragTarget {
+    /// The selection follows the pointer, `moved` once it left the pressed row
+    List { moved: bool },
+    /// The preview content follows the pointer, last seen at `row`
+    Preview { row: u16, moved: bool },
+}
+
+impl DragTarget {
+    /// Whether the pointer moved, so releasing it isn't a click
+    pub fn moved(&self) -> bool {
+        match self {
+            DragTarget::List { moved } | DragTarget::Preview { moved, .. } => *moved,
+        }
+    }
+}
+
+/// Wheel movement that didn't add up to a whole line yet
+#[derive(Clone, Debug, Default)]
+pub struct WheelState {
+    list: f32,
+    preview: f32,
+}
+
+impl WheelState {
+    /// Whole list items to move for a wheel notch of `step` items
+    pub fn list_lines(&mut self, step: f32) -> isize {
+        Self::take_lines(&mut self.list, step)
+    }
+
+    /// Whole preview lines to scroll for a wheel notch of `step` lines
+    pub fn preview_lines(&mut self, step: f32) -> isize {
+        Self::take_lines(&mut self.preview, step)
+    }
+
+    /// Add `step` to `pending` and take out the whole lines, dropping what
+    /// was left over from scrolling the other way
+    fn take_lines(pending: &mut f32, step: f32) -> isize {
+        if *pending * step < 0.0 {
+            *pending = 0.0;
+        }
+        *pending += step;
+        let lines = pending.trunc();
+        *pending -= lines;
+        lines as isize
+    }
+}
+
+/// The full-screen preview pager opened with Tab
+#[derive(Clone, Debug, Default)]
+pub struct PagerState {
+    /// Search pattern being typed after `/` or `?`, and whether it searches forward
+    pub search_input: Option<(String, bool)>,
+    /// The last searched pattern and direction, repeated by `n` and `N`
+    pub last_search: Option<(String, bool)>,
+    /// Shown in the status line instead of the key hints, like "Pattern not found"
+    pub status: Option<String>,
+}
+
+/// The file list of the unfocused pane in dual-pane mode
+///
+/// The focused pane lives in [`AppState`] itself, so data providers work on
+/// it unchanged, and switching focus swaps the two.
+#[derive(Clone, Debug, Default)]
+pub struct Pane {
+    pub current_dir: PathBuf,
+    pub files: Arc<Listing>,
+    pub filtered_files: Vec<usize>,
+    pub file_list_state: ListState,
+    pub search_input: String,
+    pub search_cursor: usize,
+}
+
 pub struct AppState {
     pub search_input: String,
+    /// Cursor position in the search input, counted in characters
+    pub search_cursor: usize,
     pub is_searching: bool,
     pub show_hidden_files: bool,
+    /// Whether files, directories or both are listed
+    pub entry_filter: EntryFilter,
+    /// Whether the search query matches letters of a different case
+    pub case_matching: CaseMatching,
     pub current_dir: PathBuf,
-    pub files: Vec<DisplayItem>,
+    /// Entries of the list, shared with a background search while one runs
+    pub files: Arc<Listing>,
     pub filtered_files: Vec<usize>,
+    /// Search of a long listing running on a worker thread
+    pub filter_job: Option<FilterJob>,
     pub file_list_state: ListState,
     pub dir_positions: HashMap<PathBuf, usize>,
     pub double_click_state: DoubleClickState,
+    pub wheel: WheelState,
     pub layout: LayoutManager,
+    /// Whether the panel divider is being dragged with the mouse
+    pub is_dragging_divider: bool,
+    /// The list or preview being dragged with the mouse
+    pub drag_target: Option<DragTarget>,
+    /// Whether the keybinding help popup is open
+    pub show_help_overlay: bool,
+    /// Notifications and errors waiting to be shown
+    pub messages: MessageQueue,
+    /// First key of an unfinished key sequence such as `gg`
+    pub pending_key: Option<KeyEvent>,
+    /// Numeric prefix typed before a movement, like the `5` in `5j`
+    pub pending_count: Option<usize>,
+    /// `m`, `'`, `q` or `@` waiting for the letter naming a mark or macro
+    pub pending_letter: Option<Action>,
+    /// Name and keys of the macro being recorded
+    pub recording: Option<(char, Vec<KeyEvent>)>,
+    /// Keys of a running macro that are still to be replayed
+    pub macro_queue: VecDeque<KeyEvent>,
+    /// Whether the keys being handled come from a macro
+    pub replaying_macro: bool,
+    /// Directories marked during this session
+    pub marks: Marks,
+    /// Entries marked for a multi-selection, in the order they were marked
+    pub marked: Vec<PathBuf>,
+    /// Alias being typed for the selected history entry
+    pub alias_input: Option<String>,
+    /// The full-screen pager over the preview, while it is open
+    pub pager: Option<PagerState>,
+    /// The unfocused pane while two file lists are shown side by side
+    pub other_pane: Option<Pane>,
+    /// Characters typed for a type-ahead jump, lowercased, and when the last one arrived
+    type_ahead: String,
+    type_ahead_at: Option<Instant>,
 }
 
 impl AppState {
@@ -34,22 +170,54 @@ impl AppState {
         debug!(dir = %current_dir.display(), "Build AppState");
         Ok(Self {
             search_input: String::new(),
+            search_cursor: 0,
             is_searching: false,
             show_hidden_files: false,
+            entry_filter: get_config().filter.show,
+            case_matching: get_config().search.case,
             current_dir,
-            files: Vec::new(),
+            files: Arc::default(),
             filtered_files: Vec::new(),
+            filter_job: None,
             file_list_state: ListState::default(),
             dir_positions: HashMap::new(),
+            wheel: WheelState::default(),
             double_click_state: DoubleClickState {
                 last_click_time: None,
                 last_click_position: None,
                 last_clicked_index: None,
             },
-            layout: LayoutManager::new(),
+            layout: Self::initial_layout(),
+            is_dragging_divider: false,
+            drag_target: None,
+            show_help_overlay: false,
+            messages: MessageQueue::default(),
+            pending_key: None,
+            pending_count: None,
+            pending_letter: None,
+            recording: None,
+            macro_queue: VecDeque::new(),
+            replaying_macro: false,
+            marks: Marks::default(),
+            marked: Vec::new(),
+            alias_input: None,
+            pager: None,
+            other_pane: None,
+            type_ahead: String::new(),
+            type_ahead_at: None,
         })
     }
 
+    /// Build the layout from the configured panel sizes
+    fn initial_layout() -> LayoutManager {
+        let config = &get_config().layout;
+        let mut layout = LayoutManager::with_preview_width(config.preview_width);
+        if !config.show_preview {
+            layout.toggle_preview();
+        }
+        layout
+    }
+
     /// Update the layout based on terminal size
     #[instrument(skip(self))]
     pub fn update_layout(&mut self, terminal_size: ratatui::layout::Rect) {
@@ -89,7 +257,7 @@ impl AppState {
     #[instrument(skip(self, file_items), fields(item_count = file_items.len()))]
     pub fn load_file_items(&mut self, file_items: Vec<FileItem>) {
         debug!("Loading {} file items", file_items.len());
-        self.files = file_items.into_iter().map(DisplayItem::File).collect();
+        self.files = Arc::new(Listing::from_files(file_items));
         self.reset_filter();
         debug!("File items loaded successfully");
     }
@@ -98,101 +266,395 @@ impl AppState {
     #[instrument(skip(self))]
     pub fn reset_filter(&mut self) {
         debug!("Resetting filter");
+        self.filter_job = None;
         self.filtered_files = self
             .files
-            .iter()
-            .enumerate()
-            .filter(|(_, item)| self.should_show_item(item))
-            .map(|(i, _)| i)
-            .collect();
+            .positions(0..self.files.len(), |item| self.should_show_item(item));
         self.file_list_state.select(None);
         debug!("Filter reset, {} items visible", self.filtered_files.len());
     }
 
     /// Apply search filter to current items
+    ///
+    /// The selected entry stays selected while it still matches, otherwise
+    /// the selection moves to the first match. A query selects its first
+    /// match even without a previous selection, so Enter takes the best one.
+    ///
+    /// Very long listings are searched on a worker thread, whose matches are
+    /// picked up by [`AppState::poll_filter_job`].
     #[instrument(skip(self), fields(search_term = %self.search_input))]
     pub fn apply_search_filter(&mut self) {
         debug!("Applying search filter with term: '{}'", self.search_input);
+        let selected_path = self.get_selected_item().map(|item| item.get_path().clone());
+        let mut filter = SearchFilter::new(
+            &self.search_input,
+            self.case_matching,
+            self.show_hidden_files,
+            self.entry_filter,
+        );
+        if !self.search_input.is_empty()
+            && get_config().search.frecency_boost
+            && let Some(scores) = history_store::frecency_scores()
+        {
+            filter = filter.with_frecency(scores);
+        }
 
-        if self.search_input.is_empty() {
-            self.filtered_files = self
-                .files
-                .iter()
-                .enumerate()
-                .filter(|(_, item)| self.should_show_item(item))
-                .map(|(i, _)| i)
-                .collect();
-        } else {
-            let search_lower = self.search_input.to_lowercase();
-            self.filtered_files = self
-                .files
-                .iter()
-                .enumerate()
-                .filter(|(_, item)| {
-                    self.should_show_item(item)
-                        && item
-                            .get_display_name()
-                            .to_lowercase()
-                            .contains(&search_lower)
-                })
-                .map(|(i, _)| i)
-                .collect();
+        self.filter_job = None;
+        if !self.search_input.is_empty() && self.files.len() >= BACKGROUND_FILTER_THRESHOLD {
+            debug!(items = self.files.len(), "Filtering in the background");
+            self.filtered_files.clear();
+            self.file_list_state.select(None);
+            self.filter_job = Some(FilterJob::spawn(filter, self.files.clone(), selected_path));
+            return;
         }
-        self.file_list_state.select(None);
+
+        self.filtered_files = filter.filter(&self.files);
+        filter.rank(&self.files, &mut self.filtered_files);
+        self.select_after_filter(selected_path);
         debug!(
             "Search filter applied, {} items matched",
             self.filtered_files.len()
         );
     }
 
+    /// Select `previous` again if it is still listed, or else the first match
+    fn select_after_filter(&mut self, previous: Option<PathBuf>) {
+        let selected = match previous {
+            _ if self.filtered_files.is_empty() => None,
+            Some(path) => self.position_of(&path).or(Some(0)),
+            None if !self.search_input.is_empty() => Some(0),
+            None => None,
+        };
+        self.file_list_state.select(selected);
+    }
+
+    /// Position of the entry at `path` in the filtered list
+    fn position_of(&self, path: &Path) -> Option<usize> {
+        self.files
+            .find_position(&self.filtered_files, |item| item.get_path() == path)
+    }
+
+    /// Whether a search is still running on a worker thread
+    pub fn is_filtering(&self) -> bool {
+        self.filter_job.is_some()
+    }
+
+    /// Add the matches a background search found since the last call
+    ///
+    /// Returns whether the list changed.
+    pub fn poll_filter_job(&mut self) -> bool {
+        let Some(job) = &self.filter_job else {
+            return false;
+        };
+        let (matches, done) = job.poll();
+        let changed = done || !matches.is_empty();
+        self.filtered_files.extend(matches);
+
+        if done {
+            if let Some(job) = self.filter_job.take() {
+                self.finish_filter_job(job);
+            }
+        } else if self.file_list_state.selected().is_none() {
+            // Select the previous entry once it shows up, or the first match
+            let selected = match &job.keep_selected {
+                Some(path) => self.position_of(path),
+                None => (!self.filtered_files.is_empty()).then_some(0),
+            };
+            self.file_list_state.select(selected);
+        }
+        changed
+    }
+
+    /// Wait for a background search to finish, for when the list is needed in full
+    pub fn wait_for_filter_job(&mut self) {
+        if let Some(job) = self.filter_job.take() {
+            self.filtered_files.extend(job.poll().0);
+            self.filtered_files.extend(job.wait());
+            self.finish_filter_job(job);
+        }
+    }
+
+    /// Rank the complete matches of a background search, keeping the selection
+    fn finish_filter_job(&mut self, job: FilterJob) {
+        let selected = self
+            .get_selected_item()
+            .map(|item| item.get_path().clone())
+            .or_else(|| job.keep_selected.clone());
+        job.filter.rank(&self.files, &mut self.filtered_files);
+        self.select_after_filter(selected);
+        debug!(
+            "Background filter done, {} items matched",
+            self.filtered_files.len()
+        );
+    }
+
+    /// Whether a type-ahead jump is in progress
+    pub fn type_ahead_active(&self) -> bool {
+        self.type_ahead_at
+            .is_some_and(|at| at.elapsed() < TYPE_AHEAD_TIMEOUT)
+    }
+
+    /// Start a type-ahead jump with an empty buffer, for names starting with a bound key
+    pub fn start_type_ahead(&mut self) {
+        self.type_ahead.clear();
+        self.type_ahead_at = Some(Instant::now());
+    }
+
+    /// Add a character to the type-ahead buffer and return the prefix to jump to
+    ///
+    /// The buffer starts over once [`TYPE_AHEAD_TIMEOUT`] passed since the last key.
+    pub fn push_type_ahead(&mut self, c: char) -> String {
+        if !self.type_ahead_active() {
+            self.type_ahead.clear();
+        }
+        self.type_ahead.extend(c.to_lowercase());
+        self.type_ahead_at = Some(Instant::now());
+        self.type_ahead.clone()
+    }
+
+    /// Forget a half typed count, key sequence or type-ahead jump
+    pub fn clear_pending_input(&mut self) {
+        self.pending_count = None;
+        self.pending_key = None;
+        self.pending_letter = None;
+        self.type_ahead_at = None;
+    }
+
+    /// Describe the keys typed so far for an unfinished command, e.g. `5g`
+    pub fn pending_input(&self) -> Option<String> {
+        if self.type_ahead_active() {
+            return Some(format!("jump: {}", self.type_ahead));
+        }
+        match self.pending_letter {
+            Some(Action::SetMark) => return Some("mark: a-z, A-Z".to_string()),
+            Some(Action::RecordMacro) => return Some("record macro: a-z".to_string()),
+            Some(Action::ReplayMacro) => return Some("run macro".to_string()),
+            Some(_) => return Some("go to mark".to_string()),
+            None => {}
+        }
+
+        let mut pending = self
+            .pending_count
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+        if let Some(key) = &self.pending_key {
+            pending.push_str(&key_label(key.code, key.modifiers));
+        }
+        if pending.is_empty()
+            && let Some((name, _)) = &self.recording
+        {
+            return Some(format!("recording @{name}"));
+        }
+        (!pending.is_empty()).then_some(pending)
+    }
+
+    /// Enter search mode with `query` typed in and the filter applied
+    pub fn start_search(&mut self, query: String) {
+        self.search_input = query;
+        self.search_cursor_end();
+        self.is_searching = true;
+        self.apply_search_filter();
+    }
+
+    /// Clear the search query and reset the cursor
+    pub fn clear_search(&mut self) {
+        self.search_input.clear();
+        self.search_cursor = 0;
+    }
+
+    /// Byte offset of the search cursor, clamped to the end of the input
+    fn search_cursor_byte(&self) -> usize {
+        self.search_input
+            .char_indices()
+            .nth(self.search_cursor)
+            .map_or(self.search_input.len(), |(index, _)| index)
+    }
+
+    /// Display width of the search input left of the cursor
+    pub fn search_cursor_width(&self) -> u16 {
+        self.search_input[..self.search_cursor_byte()].width() as u16
+    }
+
+    /// Insert a character at the cursor and refilter
+    pub fn search_insert_char(&mut self, c: char) {
+        let byte = self.search_cursor_byte();
+        self.search_input.insert(byte, c);
+        self.search_cursor = self.search_input[..byte].chars().count() + 1;
+        self.apply_search_filter();
+    }
+
+    /// Delete the character before the cursor (Backspace)
+    pub fn search_delete_backward(&mut self) {
+        let byte = self.search_cursor_byte();
+        if let Some((index, _)) = self.search_input[..byte].char_indices().next_back() {
+            self.search_input.remove(index);
+            self.search_cursor = self.search_input[..index].chars().count();
+            self.apply_search_filter();
+        }
+    }
+
+    /// Delete the character under the cursor (Delete)
+    pub fn search_delete_forward(&mut self) {
+        let byte = self.search_cursor_byte();
+        if byte < self.search_input.len() {
+            self.search_input.remove(byte);
+            self.apply_search_filter();
+        }
+    }
+
+    /// Delete the word before the cursor, like Ctrl+W in a shell
+    pub fn search_delete_word(&mut self) {
+        let end = self.search_cursor_byte();
+        let before = &self.search_input[..end];
+        let trimmed = before.trim_end();
+        let start = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        if start < end {
+            self.search_input.replace_range(start..end, "");
+            self.search_cursor = self.search_input[..start].chars().count();
+            self.apply_search_filter();
+        }
+    }
+
+    /// Move the search cursor by `delta` characters
+    pub fn move_search_cursor(&mut self, delta: isize) {
+        let len = self.search_input.chars().count();
+        self.search_cursor = self
+            .search_cursor
+            .min(len)
+            .saturating_add_signed(delta)
+            .min(len);
+    }
+
+    /// Move the search cursor to the start of the input
+    pub fn search_cursor_home(&mut self) {
+        self.search_cursor = 0;
+    }
+
+    /// Move the search cursor to the end of the input
+    pub fn search_cursor_end(&mut self) {
+        self.search_cursor = self.search_input.chars().count();
+    }
+
+    /// Move the search cursor before the character shown at display column
+    /// `column` of the input, or to the end when it is past the input
+    pub fn search_cursor_at_column(&mut self, column: u16) {
+        let mut width = 0;
+        self.search_cursor = self
+            .search_input
+            .chars()
+            .take_while(|c| {
+                width += c.width().unwrap_or(0);
+                width <= column as usize
+            })
+            .count();
+    }
+
     /// Get selected item
     #[instrument(skip(self))]
     pub fn get_selected_item(&self) -> Option<DisplayItem> {
-        if let Some(selected) = self.file_list_state.selected() {
-            if let Some(&file_index) = self.filtered_files.get(selected) {
-                if let Some(item) = self.files.get(file_index).cloned() {
-                    debug!(item_name = %item.get_display_name(), "Selected item retrieved");
-                    return Some(item);
-                }
-            }
+        if let Some(selected) = self.file_list_state.selected()
+            && let Some(&file_index) = self.filtered_files.get(selected)
+            && let Some(item) = self.files.get(file_index).map(Cow::into_owned)
+        {
+            debug!(item_name = %item.get_display_name(), "Selected item retrieved");
+            return Some(item);
         }
         debug!("No item selected");
         None
     }
 
     /// Check if an item should be shown based on current filter settings
-    #[instrument(skip(self, item), fields(item = %item.get_display_name()))]
     fn should_show_item(&self, item: &DisplayItem) -> bool {
-        // Always show non-file items (like history entries)
-        if !matches!(item, DisplayItem::File(_)) {
-            debug!("Showing non-file item");
-            return true;
-        }
-
-        let name = item.get_display_name();
-
-        // Check if it's a hidden file (starts with '.')
-        if name.starts_with('.') {
-            // Show hidden files only if show_hidden_files is true
-            let should_show = self.show_hidden_files;
-            debug!(
-                is_hidden = true,
-                show_hidden_files = self.show_hidden_files,
-                should_show,
-                "Hidden file visibility check"
-            );
-            should_show
+        filter::is_listed(item, self.show_hidden_files, self.entry_filter)
+    }
+
+    /// Show a second file list next to this one, or close it again
+    ///
+    /// The new pane starts out as a copy of the current one. Returns whether
+    /// two panes are shown now.
+    pub fn toggle_dual_pane(&mut self) -> bool {
+        if self.other_pane.is_some() {
+            self.close_dual_pane();
+            return false;
+        }
+        self.wait_for_filter_job();
+        self.other_pane = Some(Pane {
+            current_dir: self.current_dir.clone(),
+            files: self.files.clone(),
+            filtered_files: self.filtered_files.clone(),
+            file_list_state: self.file_list_state.clone(),
+            search_input: self.search_input.clone(),
+            search_cursor: self.search_cursor,
+        });
+        self.layout.set_dual_pane(true);
+        true
+    }
+
+    /// Go back to a single file list, keeping the focused one
+    pub fn close_dual_pane(&mut self) {
+        self.other_pane = None;
+        self.layout.set_dual_pane(false);
+    }
+
+    /// Move the focus to the other pane in dual-pane mode
+    pub fn swap_panes(&mut self) -> bool {
+        if self.other_pane.is_none() {
+            return false;
+        }
+        // The search results belong to the pane losing focus
+        self.wait_for_filter_job();
+        let Some(pane) = self.other_pane.as_mut() else {
+            return false;
+        };
+        std::mem::swap(&mut self.current_dir, &mut pane.current_dir);
+        std::mem::swap(&mut self.files, &mut pane.files);
+        std::mem::swap(&mut self.filtered_files, &mut pane.filtered_files);
+        std::mem::swap(&mut self.file_list_state, &mut pane.file_list_state);
+        std::mem::swap(&mut self.search_input, &mut pane.search_input);
+        std::mem::swap(&mut self.search_cursor, &mut pane.search_cursor);
+        self.is_searching = false;
+        self.clear_pending_input();
+        self.layout.swap_focus();
+        true
+    }
+
+    /// Mark or unmark the selected entry for a multi-selection
+    pub fn toggle_mark_selected(&mut self) {
+        let Some(item) = self.get_selected_item() else {
+            return;
+        };
+        let path = item.get_path().clone();
+        if let Some(index) = self.marked.iter().position(|marked| *marked == path) {
+            self.marked.remove(index);
         } else {
-            // Always show non-hidden files
-            debug!(
-                is_hidden = false,
-                should_show = true,
-                "Non-hidden file, showing"
-            );
-            true
+            self.marked.push(path);
         }
     }
 
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.marked.iter().any(|marked| marked == path)
+    }
+
+    /// Cycle between showing everything, only directories and only files
+    #[instrument(skip(self))]
+    pub fn cycle_entry_filter(&mut self) {
+        self.entry_filter = self.entry_filter.next();
+        debug!(filter = ?self.entry_filter, "Changed entry type filter");
+        self.apply_search_filter();
+    }
+
+    /// Cycle between smart-case, case-sensitive and case-insensitive search
+    #[instrument(skip(self))]
+    pub fn cycle_case_matching(&mut self) {
+        self.case_matching = self.case_matching.next();
+        debug!(case = ?self.case_matching, "Changed search case matching");
+        self.apply_search_filter();
+    }
+
     /// Toggle hidden files visibility and reapply filters
     #[instrument(skip(self))]
     pub fn toggle_hidden_files(&mut self) {
@@ -206,3 +668,71 @@ impl AppState {
         self.apply_search_filter();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_keeps_selection() {
+        let mut state = AppState::new().unwrap();
+        state.load_file_items(
+            ["api", "app", "docs"]
+                .into_iter()
+                .map(|name| FileItem::new(name.to_string(), PathBuf::from(name)))
+                .collect(),
+        );
+        state.search_input = "d".to_string();
+        state.apply_search_filter();
+        assert_eq!(
+            state.get_selected_item().unwrap().get_display_name(),
+            "docs"
+        );
+
+        state.search_input.clear();
+        state.apply_search_filter();
+        state.file_list_state.select(Some(1));
+
+        state.search_input = "ap".to_string();
+        state.apply_search_filter();
+        assert_eq!(state.get_selected_item().unwrap().get_display_name(), "app");
+
+        // The selected entry no longer matches, so the first match is selected
+        state.search_input = "api".to_string();
+        state.apply_search_filter();
+        assert_eq!(state.get_selected_item().unwrap().get_display_name(), "api");
+
+        state.search_input = "nothing".to_string();
+        state.apply_search_filter();
+        assert_eq!(state.file_list_state.selected(), None);
+    }
+
+    #[test]
+    fn test_wheel_steps_add_up() {
+        let mut wheel = WheelState::default();
+        let notches: Vec<isize> = (0..4).map(|_| wheel.list_lines(0.5)).collect();
+        assert_eq!(notches, vec![0, 1, 0, 1]);
+        assert_eq!(wheel.list_lines(3.0), 3);
+
+        // Half a notch down is dropped when scrolling back up
+        assert_eq!(wheel.preview_lines(0.5), 0);
+        assert_eq!(wheel.preview_lines(-0.5), 0);
+        assert_eq!(wheel.preview_lines(-0.5), -1);
+    }
+
+    #[test]
+    fn test_search_cursor_at_column() {
+        let mut state = AppState::new().unwrap();
+        state.search_input = "a文b".to_string();
+        let cursor_at = |state: &mut AppState, column| {
+            state.search_cursor_at_column(column);
+            state.search_cursor
+        };
+        assert_eq!(cursor_at(&mut state, 0), 0);
+        assert_eq!(cursor_at(&mut state, 1), 1);
+        // Both cells of the wide character put the cursor before it
+        assert_eq!(cursor_at(&mut state, 2), 1);
+        assert_eq!(cursor_at(&mut state, 3), 2);
+        assert_eq!(cursor_at(&mut state, 40), 3);
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, bail};
+use once_cell::sync::Lazy;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Result,
+    config::get_config,
+    core::{filter::SearchFilter, listing::Listing},
+    services::{
+        FilesystemService,
+        preview::{GLOBAL_PICKER, PreviewGenerator},
+    },
+    utils::FileItem,
+};
+
+/// Queries filtered with when none are given: a common letter, two terms
+/// and an exclusion
+const DEFAULT_QUERIES: &[&str] = &["e", "e s", "!e"];
+
+/// Slowest previews listed by name in the report
+const SLOWEST_PREVIEWS: usize = 5;
+
+/// Durations of repeated runs of one step
+#[derive(Debug, Default)]
+pub struct Timings(Vec<Duration>);
+
+impl Timings {
+    /// Time `runs` calls of `f`, keeping the result of the last one
+    fn measure<T>(runs: usize, mut f: impl FnMut() -> T) -> (Self, T) {
+        let mut timings = Self::default();
+        let mut result = None;
+        for _ in 0..runs.max(1) {
+            let start = Instant::now();
+            result = Some(f());
+            timings.0.push(start.elapsed());
+        }
+        (timings, result.expect("at least one run"))
+    }
+
+    pub fn push(&mut self, duration: Duration) {
+        self.0.push(duration);
+    }
+
+    pub fn total(&self) -> Duration {
+        self.0.iter().sum()
+    }
+
+    /// The fastest, median and slowest run
+    pub fn summary(&self) -> Option<(Duration, Duration, Duration)> {
+        let mut sorted = self.0.clone();
+        sorted.sort();
+        Some((*sorted.first()?, sorted[sorted.len() / 2], *sorted.last()?))
+    }
+
+    fn describe(&self) -> String {
+        match self.summary() {
+            Some((min, median, max)) => format!(
+                "median {}  min {}  max {}",
+                millis(median),
+                millis(min),
+                millis(max)
+            ),
+            None => "no runs".to_string(),
+        }
+    }
+}
+
+/// Time loading, filtering and previewing `dir` and print a report
+///
+/// Loading and filtering run `runs` times each, since they are quick and
+/// vary between runs. Each of the first `previews` entries is previewed once.
+pub async fn run_bench(dir: &Path, runs: usize, queries: &[String], previews: usize) -> Result<()> {
+    if !dir.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", dir.display()))?;
+    println!(
+        "quickswitch {} bench {}",
+        env!("CARGO_PKG_VERSION"),
+        dir.display()
+    );
+
+    println!("\nDirectory load ({runs} runs)");
+    let (load, files) = Timings::measure(runs, || FilesystemService::load_directory(&dir));
+    let files = files?;
+    let dirs = files.iter().filter(|file| file.is_dir).count();
+    row("entries", &format!("{} ({dirs} directories)", files.len()));
+    row("time", &load.describe());
+
+    println!("\nFilter ({runs} runs each)");
+    let items = Listing::from_files(files.clone());
+    let queries: Vec<&str> = if queries.is_empty() {
+        DEFAULT_QUERIES.to_vec()
+    } else {
+        queries.iter().map(String::as_str).collect()
+    };
+    for query in queries {
+        let filter = SearchFilter::new(
+            query,
+            get_config().search.case,
+            true,
+            get_config().filter.show,
+        );
+        let (timings, matches) = Timings::measure(runs, || {
+            let mut matches = filter.filter(&items);
+            filter.rank(&items, &mut matches);
+            matches.len()
+        });
+        row(
+            &format!("'{query}'"),
+            &format!("{matches} matches, {}", timings.describe()),
+        );
+    }
+
+    println!("\nPreview");
+    // Querying the terminal for its image protocol isn't part of any preview
+    Lazy::force(&GLOBAL_PICKER);
+    let mut targets = vec![FileItem::from_path(&dir)];
+    targets.extend(files.into_iter().take(previews));
+    let mut timings = Timings::default();
+    let mut slowest = Vec::new();
+    for file in targets {
+        let start = Instant::now();
+        PreviewGenerator::generate_preview_content(&file).await;
+        let elapsed = start.elapsed();
+        timings.push(elapsed);
+        slowest.push((elapsed, file.name));
+    }
+    row(
+        "previewed",
+        &format!("{}, {} in total", timings.0.len(), millis(timings.total())),
+    );
+    row("time", &timings.describe());
+    slowest.sort_by_key(|(elapsed, _)| std::cmp::Reverse(*elapsed));
+    for (elapsed, name) in slowest.into_iter().take(SLOWEST_PREVIEWS) {
+        row("", &format!("{:>10}  {name}", millis(elapsed)));
+    }
+    Ok(())
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn row(label: &str, value: &str) {
+    println!("  {label:<14}{value}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_summary() {
+        let mut timings = Timings::default();
+        assert_eq!(timings.summary(), None);
+        for ms in [30, 10, 20] {
+            timings.push(Duration::from_millis(ms));
+        }
+        assert_eq!(
+            timings.summary(),
+            Some((
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+                Duration::from_millis(30)
+            ))
+        );
+        assert_eq!(timings.total(), Duration::from_millis(60));
+        assert_eq!(millis(Duration::from_micros(1500)), "1.50ms");
+    }
+}
@@ -1,6 +1,8 @@
-use anyhow::Result;
-use std::{fs, path::PathBuf};
-use tracing::{debug, instrument};
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use tracing::{debug, info, instrument};
 
 /// Get the data directory for quickswitch
 ///
@@ -12,15 +14,15 @@ use tracing::{debug, instrument};
 /// The function will create the directory if it doesn't exist.
 pub fn get_data_dir() -> Result<PathBuf> {
     // First, try to read from environment variable
-    if let Ok(env_dir) = std::env::var("_QUICKSWITCH_DATA_DIR") {
-        if !env_dir.trim().is_empty() {
-            let data_dir = PathBuf::from(env_dir);
-            // Create directory if it doesn't exist
-            if !data_dir.exists() {
-                fs::create_dir_all(&data_dir)?;
-            }
-            return Ok(data_dir);
+    if let Ok(env_dir) = std::env::var("_QUICKSWITCH_DATA_DIR")
+        && !env_dir.trim().is_empty()
+    {
+        let data_dir = PathBuf::from(env_dir);
+        // Create directory if it doesn't exist
+        if !data_dir.exists() {
+            fs::create_dir_all(&data_dir)?;
         }
+        return Ok(data_dir);
     }
 
     // If environment variable is not set or empty, use default directory
@@ -65,17 +67,421 @@ fn get_default_data_dir() -> Result<PathBuf> {
     }
 }
 
+/// Get the path of the configuration file
+///
+/// This function reads the `_QUICKSWITCH_CONFIG` environment variable.
+/// If the environment variable is not set or empty, it returns the default location:
+/// - On Unix-like systems: `$XDG_CONFIG_HOME/quickswitch/config.toml` or `~/.config/quickswitch/config.toml`
+/// - On Windows: `%APPDATA%\quickswitch\config.toml`
+pub fn get_config_file_path() -> PathBuf {
+    if let Ok(env_file) = std::env::var("_QUICKSWITCH_CONFIG")
+        && !env_file.trim().is_empty()
+    {
+        return PathBuf::from(env_file);
+    }
+
+    get_default_config_dir().join("config.toml")
+}
+
+/// Get the default configuration directory based on the operating system
+fn get_default_config_dir() -> PathBuf {
+    #[cfg(windows)]
+    {
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            PathBuf::from(appdata).join("quickswitch")
+        } else {
+            std::env::temp_dir().join("quickswitch")
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME")
+            && !xdg_config_home.trim().is_empty()
+        {
+            PathBuf::from(xdg_config_home).join("quickswitch")
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".config").join("quickswitch")
+        } else {
+            std::env::temp_dir().join("quickswitch")
+        }
+    }
+}
+
+/// Application configuration read from `config.toml`
+///
+/// Every section and key is optional; missing values fall back to their defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Which directory entries are listed
+    pub filter: FilterConfig,
+    /// How search queries are matched
+    pub search: SearchConfig,
+    /// How directory entries are ordered
+    pub sort: SortConfig,
+    /// Appearance of the user interface
+    pub ui: UiConfig,
+    /// Panel sizes
+    pub layout: LayoutConfig,
+    /// List movement behavior
+    pub navigation: NavigationConfig,
+    /// Mouse click behavior
+    pub mouse: MouseConfig,
+    /// History ranking and retention
+    pub history: HistoryConfig,
+    /// How much of a file is previewed
+    pub preview: PreviewConfig,
+    /// Directories indexed for jumping anywhere
+    pub index: IndexConfig,
+    /// Key sequences replayed with `@<letter>`, in vim notation like `/src<Esc>jl`
+    pub macros: BTreeMap<String, String>,
+    /// What is written to the log file
+    pub log: LogConfig,
+    /// External commands run on events, for previews and as entry sources
+    pub plugins: PluginsConfig,
+}
+
+/// Configuration for filtering directory entries
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+    /// Hide entries matched by `.gitignore`, `.ignore` and the global git excludes file
+    pub respect_ignore: bool,
+    /// Which kinds of entries are listed on startup
+    pub show: EntryFilter,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            respect_ignore: true,
+            show: EntryFilter::default(),
+        }
+    }
+}
+
+/// Kinds of directory entries shown in the file list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryFilter {
+    /// Files and directories
+    #[default]
+    All,
+    /// Only directories, for when quickswitch is used purely to cd
+    Dirs,
+    /// Only files
+    Files,
+}
+
+impl EntryFilter {
+    /// The next filter in the All -> Dirs -> Files cycle
+    pub fn next(self) -> Self {
+        match self {
+            EntryFilter::All => EntryFilter::Dirs,
+            EntryFilter::Dirs => EntryFilter::Files,
+            EntryFilter::Files => EntryFilter::All,
+        }
+    }
+
+    /// Whether an entry of this kind passes the filter
+    pub fn allows(self, is_dir: bool) -> bool {
+        match self {
+            EntryFilter::All => true,
+            EntryFilter::Dirs => is_dir,
+            EntryFilter::Files => !is_dir,
+        }
+    }
+}
+
+/// Configuration for matching search queries
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Whether the case of letters matters when searching
+    pub case: CaseMatching,
+    /// Rank matches in the file list by how often their directory was visited
+    pub frecency_boost: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            case: CaseMatching::default(),
+            frecency_boost: true,
+        }
+    }
+}
+
+/// Whether a search query matches letters of a different case
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMatching {
+    /// Ignore case unless the query contains an uppercase letter
+    #[default]
+    Smart,
+    /// Always match case
+    Sensitive,
+    /// Never match case
+    Insensitive,
+}
+
+impl CaseMatching {
+    /// The next setting in the Smart -> Sensitive -> Insensitive cycle
+    pub fn next(self) -> Self {
+        match self {
+            CaseMatching::Smart => CaseMatching::Sensitive,
+            CaseMatching::Sensitive => CaseMatching::Insensitive,
+            CaseMatching::Insensitive => CaseMatching::Smart,
+        }
+    }
+
+    /// Whether `query` has to match with the same case
+    pub fn is_sensitive(self, query: &str) -> bool {
+        match self {
+            CaseMatching::Smart => query.chars().any(char::is_uppercase),
+            CaseMatching::Sensitive => true,
+            CaseMatching::Insensitive => false,
+        }
+    }
+
+    /// Settle smart case for `query`, to apply one case to all of its parts
+    pub fn resolve(self, query: &str) -> Self {
+        if self.is_sensitive(query) {
+            CaseMatching::Sensitive
+        } else {
+            CaseMatching::Insensitive
+        }
+    }
+
+    /// Whether `text` contains `query` under this setting
+    pub fn contains(self, text: &str, query: &str) -> bool {
+        if self.is_sensitive(query) {
+            text.contains(query)
+        } else {
+            text.to_lowercase().contains(&query.to_lowercase())
+        }
+    }
+}
+
+/// Configuration for sorting directory entries
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SortConfig {
+    /// Compare digit runs numerically so `file2` sorts before `file10`
+    pub natural: bool,
+}
+
+impl Default for SortConfig {
+    fn default() -> Self {
+        Self { natural: true }
+    }
+}
+
+/// Configuration for the appearance of the user interface
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Icon set shown in front of entries
+    pub icons: IconStyle,
+    /// Use colors; when disabled, backgrounds are rendered as reverse video instead
+    pub color: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            icons: IconStyle::default(),
+            color: true,
+        }
+    }
+}
+
+/// Icon set used in lists and preview titles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    /// Emoji icons, which need no special font
+    #[default]
+    Emoji,
+    /// Nerd Font glyphs with per-filetype icons
+    Nerd,
+    /// Plain ASCII type indicators for limited terminals
+    Ascii,
+}
+
+/// Configuration for the panel layout
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Width of the preview panel in percent of the terminal width (10-90)
+    pub preview_width: u16,
+    /// Whether the preview panel is shown on startup
+    pub show_preview: bool,
+    /// Start with two file lists side by side instead of the list and the preview
+    pub dual_pane: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            preview_width: crate::core::layout::DEFAULT_PREVIEW_WIDTH,
+            show_preview: true,
+            dual_pane: false,
+        }
+    }
+}
+
+/// Configuration for moving through lists
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NavigationConfig {
+    /// Wrap from the last item to the first (and back) when moving past the end
+    pub wrap_around: bool,
+    /// Number of lines kept visible above and below the selection while scrolling
+    pub scrolloff: usize,
+    /// Enter directories that are symlinks, instead of leaving them closed
+    pub follow_symlinks: bool,
+}
+
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        Self {
+            wrap_around: false,
+            scrolloff: 3,
+            follow_symlinks: true,
+        }
+    }
+}
+
+/// Configuration for mouse clicks and the wheel
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MouseConfig {
+    /// Enter a directory or pick a file with one click, like a GUI file manager,
+    /// instead of a double click
+    pub single_click: bool,
+    /// List items moved per wheel notch
+    ///
+    /// Fractions add up over several notches, for high-resolution wheels that
+    /// send many small events.
+    pub scroll_step: f32,
+    /// Preview lines scrolled per wheel notch, fractions add up the same way
+    pub preview_scroll_step: f32,
+}
+
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self {
+            single_click: false,
+            scroll_step: 1.0,
+            preview_scroll_step: 1.0,
+        }
+    }
+}
+
+/// Configuration for file previews
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    /// Files larger than this many bytes are not previewed, 0 for no limit
+    pub max_file_size: u64,
+    /// Which lines of a long text file are shown
+    pub truncate: PreviewTruncate,
+    /// Lines shown from the start of a file when truncating
+    pub head_lines: usize,
+    /// Lines shown from the end of a file with `head_tail` truncation
+    pub tail_lines: usize,
+    /// Milliseconds the selection has to rest before its preview is generated,
+    /// so holding a movement key doesn't generate one per passed entry
+    pub debounce_ms: u64,
+    /// Cells a tab takes up in text previews
+    pub tab_width: usize,
+    /// Whether text previews start with line numbers shown
+    pub line_numbers: bool,
+    /// Show permissions, owner and timestamps above the previewed content
+    pub metadata_header: bool,
+    /// Levels shown by the tree preview of directories
+    pub tree_depth: usize,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: 0,
+            truncate: PreviewTruncate::default(),
+            head_lines: 500,
+            tail_lines: 100,
+            debounce_ms: 80,
+            tab_width: 4,
+            line_numbers: true,
+            metadata_header: true,
+            tree_depth: 2,
+        }
+    }
+}
+
+/// Which lines of a long text file are previewed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewTruncate {
+    /// All of them, read in chunks as the preview is scrolled
+    #[default]
+    Stream,
+    /// Only the first `head_lines`
+    Head,
+    /// The first `head_lines` and the last `tail_lines`, like for a giant log
+    HeadTail,
+}
+
+impl Config {
+    /// Load the configuration file, falling back to defaults if it doesn't exist
+    #[instrument]
+    pub fn load() -> Result<Self> {
+        let path = get_config_file_path();
+        if !path.exists() {
+            debug!(path = %path.display(), "No config file found, using defaults");
+            return Ok(Self::default());
+        }
+
+        info!(path = %path.display(), "Loading config file");
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+}
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// Install the configuration used for the rest of the program
+///
+/// Only the first call has an effect; later calls are ignored.
+pub fn init_config(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// Get the active configuration, or the defaults if none was installed
+pub fn get_config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
 /// Configuration for history functionality
-#[derive(Debug)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct HistoryConfig {
     /// Maximum number of history entries to keep
     pub max_entries: usize,
     /// Sort mode for history entries
     pub sort_mode: crate::utils::HistorySortMode,
-    /// Number of days for time decay calculation
+    /// Number of days for time decay calculation (the half-life for exponential decay)
     pub time_decay_days: u32,
     /// Minimum frequency threshold for keeping entries
     pub min_frequency_threshold: u32,
+    /// How the weight of an entry falls off with the time since its last visit
+    pub decay: DecayFunction,
+    /// Lowest weight an old entry decays to, between 0 and 1
+    pub min_decay: f64,
 }
 
 impl Default for HistoryConfig {
@@ -86,14 +492,85 @@ impl Default for HistoryConfig {
             sort_mode: crate::utils::HistorySortMode::FrequencyRecent,
             time_decay_days: 30,
             min_frequency_threshold: 1,
+            decay: DecayFunction::default(),
+            min_decay: 0.1,
         };
         debug!(?config, "Created default HistoryConfig");
         config
     }
 }
 
+/// Configuration for the directory index used by Jump mode
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Directories indexed along with their subdirectories, `~` is the home directory
+    pub roots: Vec<String>,
+    /// How many levels below a root are indexed
+    pub max_depth: usize,
+    /// Directory names that are skipped with everything below them
+    pub exclude: Vec<String>,
+    /// Minutes before the index is refreshed again in the background
+    pub refresh_minutes: u64,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            roots: vec!["~".to_string()],
+            max_depth: 6,
+            exclude: vec!["node_modules".to_string(), "target".to_string()],
+            refresh_minutes: 60,
+        }
+    }
+}
+
+/// Configuration for the log written with `-v`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Per-module levels on top of the one chosen with `-v`, written like
+    /// `RUST_LOG`, e.g. `quickswitch::services=debug`
+    pub filters: Vec<String>,
+}
+
+/// External commands that extend quickswitch
+///
+/// Every command runs in the shell and gets a JSON object on stdin, see
+/// `services::plugins` for what it reads and prints.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    /// Commands run with the selection when quickswitch exits with one
+    pub on_select: Vec<String>,
+    /// Commands run in the background each time the file list enters a directory
+    pub on_enter_directory: Vec<String>,
+    /// Commands that preview files, tried before the built-in previews
+    pub previewers: Vec<PreviewerConfig>,
+    /// Commands listing the entries picked from with `--source <name>`, by name
+    pub sources: BTreeMap<String, String>,
+}
+
+/// A command previewing the files with one of its extensions
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreviewerConfig {
+    /// Extensions of the previewed files, without the dot
+    pub extensions: Vec<String>,
+    pub command: String,
+}
+
+/// Time decay applied to history entries in frecency ranking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecayFunction {
+    /// Falls linearly to the minimum weight over `time_decay_days`
+    #[default]
+    Linear,
+    /// Halves every `time_decay_days`, so old favorites fade out gradually
+    Exponential,
+}
+
 /// Get the history configuration
-pub fn get_history_config() -> HistoryConfig {
-    // In the future, this could read from a config file
-    HistoryConfig::default()
+pub fn get_history_config() -> &'static HistoryConfig {
+    &get_config().history
 }
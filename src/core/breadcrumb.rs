@@ -0,0 +1,103 @@
+use std::path::{Component, Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+use crate::services::filesystem::strip_verbatim_prefix;
+
+/// Text rendered before the breadcrumb in the file list title
+pub const BREADCRUMB_PREFIX: &str = "Files - ";
+
+/// Separator rendered between breadcrumb segments
+pub const BREADCRUMB_SEPARATOR: &str = " / ";
+
+/// A clickable segment of the breadcrumb path bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreadcrumbSegment {
+    /// Separator rendered before the segment
+    pub separator: &'static str,
+    /// Text displayed for the segment
+    pub label: String,
+    /// Directory the segment navigates to
+    pub path: PathBuf,
+    /// Column offset of the segment relative to the start of the breadcrumb
+    pub offset: u16,
+    /// Display width of the label
+    pub width: u16,
+}
+
+/// Split a path into breadcrumb segments, abbreviating the home directory as `~`
+pub fn breadcrumb_segments(path: &Path) -> Vec<BreadcrumbSegment> {
+    let path = &strip_verbatim_prefix(path.to_path_buf());
+    let home = home_dir();
+    let mut segments = Vec::new();
+    let mut current = PathBuf::new();
+    let mut components = path.components().peekable();
+
+    if let Some(home) = home.as_deref()
+        && path.starts_with(home)
+    {
+        push_segment(&mut segments, "~".to_string(), home.to_path_buf());
+        current = home.to_path_buf();
+        let home_depth = home.components().count();
+        for _ in 0..home_depth {
+            components.next();
+        }
+    }
+
+    for component in components {
+        current.push(component.as_os_str());
+        let label = match component {
+            Component::RootDir if !segments.is_empty() => continue,
+            Component::Prefix(prefix) => {
+                // Merge the drive prefix with the following root (e.g. `C:\`)
+                let label = format!("{}\\", prefix.as_os_str().to_string_lossy());
+                current = PathBuf::from(&label);
+                push_segment(&mut segments, label, current.clone());
+                continue;
+            }
+            Component::RootDir => std::path::MAIN_SEPARATOR.to_string(),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        };
+        push_segment(&mut segments, label, current.clone());
+    }
+
+    segments
+}
+
+/// Find the segment at the given column offset from the start of the breadcrumb
+pub fn segment_at(segments: &[BreadcrumbSegment], offset: u16) -> Option<&BreadcrumbSegment> {
+    segments
+        .iter()
+        .find(|segment| offset >= segment.offset && offset < segment.offset + segment.width)
+}
+
+fn push_segment(segments: &mut Vec<BreadcrumbSegment>, label: String, path: PathBuf) {
+    // Roots such as `/` or `C:\` already end in a separator, so only a space follows them
+    let separator = match segments.last() {
+        None => "",
+        Some(last) if last.label.ends_with(['/', '\\']) => " ",
+        Some(_) => BREADCRUMB_SEPARATOR,
+    };
+    let offset = segments
+        .last()
+        .map(|last| last.offset + last.width + separator.width() as u16)
+        .unwrap_or(0);
+    let width = label.width() as u16;
+    segments.push(BreadcrumbSegment {
+        separator,
+        label,
+        path,
+        offset,
+        width,
+    });
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let home = std::env::var("USERPROFILE");
+    #[cfg(not(windows))]
+    let home = std::env::var("HOME");
+
+    home.ok()
+        .filter(|home| !home.trim().is_empty())
+        .map(PathBuf::from)
+}
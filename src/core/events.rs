@@ -1,82 +1,440 @@
 use anyhow::Result;
-use crossterm::{
-    cursor::Show,
-    event::{DisableMouseCapture, KeyEvent, MouseEvent},
-    execute,
-    terminal::{LeaveAlternateScreen, disable_raw_mode},
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
 };
-use std::{env, io};
+use tracing::error;
 
 use crate::{
     App,
-    core::InputDispatcher,
-    modes::{ModeAction, history::HistoryDataProvider},
-    utils::FileItem,
+    core::{InputDispatcher, signals},
+    embed::Selection,
+    exec,
+    modes::{ModeAction, history::HistoryDataProvider, recent_files::RecentFilesDataProvider},
+    output::{self, OutputFormat},
+    services::{create_data_provider, filesystem::strip_verbatim_prefix, history_store, plugins},
+    terminal::{self, restore_terminal},
+    utils::{AppMode, FileItem},
 };
 
 /// Main entry point for keyboard event handling
 /// Now delegates to the app instead of handling directly
 pub async fn handle_key_event(app: &mut App, key: KeyEvent) -> Result<bool> {
+    // Raw mode delivers Ctrl+C as a key press instead of SIGINT
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        signals::request_shutdown(signals::SIGINT);
+        return Ok(false);
+    }
+
+    if let Some((_, keys)) = &mut app.state.recording {
+        keys.push(key);
+    }
+    if !dispatch_key(app, key).await? {
+        return Ok(false);
+    }
+
+    // Replay the keys of a macro started by this key
+    app.state.replaying_macro = true;
+    let mut keep_running = true;
+    while keep_running && let Some(key) = app.state.macro_queue.pop_front() {
+        keep_running = dispatch_key(app, key).await?;
+    }
+    app.state.replaying_macro = false;
+    app.state.macro_queue.clear();
+    Ok(keep_running)
+}
+
+/// Hand a key to the dispatcher and apply the outcome
+async fn dispatch_key(app: &mut App, key: KeyEvent) -> Result<bool> {
     let current_mode = *app.mode_manager.get_current_mode();
-    let action = InputDispatcher::handle_key_event(&mut app.state, key, &current_mode).await?;
-    handle_action(app, action)
+    let result = InputDispatcher::handle_key_event(&mut app.state, key, &current_mode).await;
+    handle_result(app, result).await
 }
 
 /// Handle mouse events
 pub async fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Result<bool> {
     let current_mode = *app.mode_manager.get_current_mode();
-    let action = InputDispatcher::handle_mouse_event(&mut app.state, mouse, &current_mode).await?;
-    handle_action(app, action)
+    let result = InputDispatcher::handle_mouse_event(&mut app.state, mouse, &current_mode).await;
+    handle_result(app, result).await
+}
+
+/// Apply the outcome of an input event, reporting failures to the user
+/// instead of tearing down the application
+async fn handle_result(app: &mut App, result: Result<ModeAction>) -> Result<bool> {
+    match result {
+        Ok(action) => handle_action(app, action).await,
+        Err(e) => {
+            error!("Action failed: {e:#}");
+            app.state.messages.error(format!("{e:#}"));
+            Ok(true)
+        }
+    }
 }
 
-fn handle_action(app: &mut App, action: ModeAction) -> Result<bool> {
+async fn handle_action(app: &mut App, action: ModeAction) -> Result<bool> {
     match action {
         ModeAction::Stay => Ok(true),
+        ModeAction::Refresh => {
+            if let Err(e) = refresh(app).await {
+                error!("Failed to refresh: {e:#}");
+                app.state.messages.error(format!("{e:#}"));
+            }
+            Ok(true)
+        }
+        ModeAction::Message(text) => {
+            app.state.messages.info(text);
+            Ok(true)
+        }
+        ModeAction::RunCommand { template, paths } => {
+            let result = match run_command(app, &template, &paths) {
+                Ok(()) => refresh(app).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error!("Failed to run `{template}`: {e:#}");
+                app.state.messages.error(format!("{e:#}"));
+            }
+            Ok(true)
+        }
         ModeAction::Switch(new_mode) => {
-            app.mode_manager.switch_mode(&mut app.state, &new_mode)?;
+            if let Err(e) = app.mode_manager.switch_mode(&mut app.state, &new_mode) {
+                error!("Failed to switch to {new_mode:?} mode: {e:#}");
+                app.state.messages.error(format!("{e:#}"));
+            }
             Ok(true)
         }
         ModeAction::Exit(file_item) => {
+            // Only embedded apps get here, the binary exits in handle_exit
             handle_exit(app, file_item.as_ref())?;
-            Ok(false) // This should never be reached due to process::exit in handle_exit
+            Ok(false)
         }
     }
 }
 
+/// Load the entries of the current mode again, keeping the selected entry
+/// selected, or the same row when it is gone
+async fn refresh(app: &mut App) -> Result<()> {
+    let selected = app.state.get_selected_item();
+    let row = app.state.file_list_state.selected();
+    let provider = create_data_provider(app.mode_manager.get_current_mode());
+    provider.load_data(&mut app.state)?;
+
+    let state = &app.state;
+    let position = selected.and_then(|selected| {
+        state.files.find_position(&state.filtered_files, |item| {
+            item.get_path() == selected.get_path()
+        })
+    });
+    if let Some(index) = position.or(row) {
+        provider.navigate_to_index(&mut app.state, index).await;
+    }
+    Ok(())
+}
+
+/// Run the command built from `template` on `paths` in the terminal the UI
+/// gives up meanwhile, reporting a failure as a notification
+fn run_command(app: &mut App, template: &str, paths: &[PathBuf]) -> Result<()> {
+    let suspended = terminal::suspend_terminal()?;
+    let code = exec::run(template, paths);
+    if suspended {
+        terminal::resume_terminal()?;
+    }
+    match code? {
+        0 => {}
+        code => app.state.messages.error(format!(
+            "`{}` exited with status {code}",
+            exec::build_command(template, paths)
+        )),
+    }
+    Ok(())
+}
+
 fn handle_exit(app: &mut App, file: Option<&FileItem>) -> Result<()> {
+    // Quitting without a selection still changes to the browsed directory
+    let browsed = (file.is_none()
+        && app.options.last_dir_on_exit
+        && !app.mode_manager.is_mode(&AppMode::Picker))
+    .then(|| InputDispatcher::browsed_dir(&app.state))
+    .flatten();
+    let file = file.or(browsed.as_ref());
+
+    if let Some(file) = file {
+        plugins::on_select(
+            file,
+            *app.mode_manager.get_current_mode(),
+            &app.state.search_input,
+        );
+    }
+
+    if app.options.embedded {
+        if let Some(file) = file
+            && !app.mode_manager.is_mode(&AppMode::Picker)
+        {
+            record_selection(&selected_paths(app, file));
+        }
+        app.selection = file.map(|file| Selection::new(app, file));
+        return Ok(());
+    }
+
+    if let (Some(file), Some(template)) = (file, app.options.exec.clone()) {
+        return exec_and_exit(app, file, &template);
+    }
+
+    if app.options.output == OutputFormat::Json {
+        return print_json_and_exit(app, file);
+    }
+
+    if app.mode_manager.is_mode(&AppMode::Picker) {
+        return print_picked_and_exit(app, file);
+    }
+
+    if let (Some(file), true) = (file, app.options.print_selection) {
+        let paths = selected_paths(app, file);
+        record_recent_files(&paths);
+        restore_terminal()?;
+        // Quoting isn't needed when NUL can't occur in the paths
+        let result = if app.options.print0 {
+            paths
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\0")
+        } else {
+            exec::build_command(exec::PLACEHOLDER, &paths)
+        };
+        print_result(app, &result);
+        exit(output::EXIT_SELECTED);
+    }
+
     if let Some(file) = file {
         let select_path = if file.is_dir {
             file.path.clone()
         } else {
-            app.state.current_dir.clone()
+            record_recent_files(std::slice::from_ref(&file.path));
+            file.path
+                .parent()
+                .map_or_else(|| app.state.current_dir.clone(), Into::into)
         };
+        let select_path = strip_verbatim_prefix(select_path);
         // Save to history using history data provider
         let history_provider: HistoryDataProvider = HistoryDataProvider;
-        history_provider
-            .add_to_history(select_path.clone())
-            .unwrap_or(());
+        history_provider.add_to_history(select_path.clone());
 
         // Properly cleanup terminal state before exit
-        disable_raw_mode()?;
-        execute!(
-            io::stdout(),
-            LeaveAlternateScreen,
-            DisableMouseCapture,
-            Show
-        )?;
+        restore_terminal()?;
 
         unsafe { env::set_var("QS_SELECT_PATH", select_path.to_string_lossy().as_ref()) };
-        eprintln!("{}", select_path.display());
+        match &app.options.cd_file {
+            Some(cd_file) => write_cd_file(cd_file, &select_path),
+            None => print_result(app, &select_path.display().to_string()),
+        }
     } else {
         // If no file is selected, just exit with proper cleanup
-        disable_raw_mode()?;
-        execute!(
-            io::stdout(),
-            LeaveAlternateScreen,
-            DisableMouseCapture,
-            Show
-        )?;
+        restore_terminal()?;
+        exit(output::EXIT_ABORTED);
     }
 
-    std::process::exit(0);
+    exit(output::EXIT_SELECTED);
+}
+
+/// Print the result where the shell integration reads it, stderr unless
+/// `--stdout` was given
+fn print_result(app: &App, result: &str) {
+    let terminator = terminator(app);
+    if app.options.stdout {
+        print!("{result}{terminator}");
+        let _ = io::stdout().flush();
+    } else {
+        eprint!("{result}{terminator}");
+    }
+}
+
+/// Hand the directory to change to over to the shell through `--cd-file`
+///
+/// The terminal is already restored, so a failure is reported on stderr.
+fn write_cd_file(cd_file: &Path, dir: &Path) {
+    if let Err(e) = fs::write(cd_file, dir.to_string_lossy().as_bytes()) {
+        error!("Failed to write {}: {e}", cd_file.display());
+        eprintln!("Error: failed to write {}: {e}", cd_file.display());
+        exit(output::EXIT_ERROR);
+    }
+}
+
+/// What ends each printed path, NUL with `--print0`
+fn terminator(app: &App) -> char {
+    if app.options.print0 { '\0' } else { '\n' }
+}
+
+/// Remember the files among `paths` for recent files mode
+fn record_recent_files(paths: &[PathBuf]) {
+    let provider = RecentFilesDataProvider;
+    for path in paths.iter().filter(|path| !path.is_dir()) {
+        provider.add_recent_file(path.clone());
+    }
+}
+
+/// Remember the directories among `paths` in history and the files for
+/// recent files mode
+fn record_selection(paths: &[PathBuf]) {
+    let history_provider = HistoryDataProvider;
+    for path in paths.iter().filter(|path| path.is_dir()) {
+        history_provider.add_to_history(path.clone());
+    }
+    record_recent_files(paths);
+}
+
+/// The marked entries, or the chosen one when nothing is marked
+fn selected_paths(app: &App, file: &FileItem) -> Vec<PathBuf> {
+    let paths = if app.state.marked.is_empty() {
+        vec![file.path.clone()]
+    } else {
+        app.state.marked.clone()
+    };
+    paths.into_iter().map(strip_verbatim_prefix).collect()
+}
+
+/// Restore the terminal, print the picked entries on stdout and exit
+///
+/// Marked entries are printed in the order they were marked, one per line.
+fn print_picked_and_exit(app: &mut App, file: Option<&FileItem>) -> Result<()> {
+    restore_terminal()?;
+
+    let Some(file) = file else {
+        exit(output::EXIT_ABORTED);
+    };
+    let terminator = terminator(app);
+    if app.state.marked.is_empty() {
+        print!("{}{terminator}", file.name);
+    } else {
+        for path in &app.state.marked {
+            print!(
+                "{}{terminator}",
+                strip_verbatim_prefix(path.clone()).display()
+            );
+        }
+    }
+    let _ = io::stdout().flush();
+    exit(output::EXIT_SELECTED);
+}
+
+/// Restore the terminal, print the selection as JSON and exit
+///
+/// The selection is recorded in history or recent files like a plain
+/// selection, except for the picker whose entries can be anything.
+fn print_json_and_exit(app: &mut App, file: Option<&FileItem>) -> Result<()> {
+    let is_picker = app.mode_manager.is_mode(&AppMode::Picker);
+    if let Some(file) = file.filter(|_| !is_picker) {
+        let path = strip_verbatim_prefix(file.path.clone());
+        if file.is_dir {
+            HistoryDataProvider.add_to_history(path);
+        } else {
+            record_recent_files(&[path]);
+        }
+    }
+    restore_terminal()?;
+
+    let json = output::selection_json(
+        file,
+        *app.mode_manager.get_current_mode(),
+        &app.state.search_input,
+    );
+    if is_picker {
+        println!("{json}");
+    } else {
+        print_result(app, &json);
+    }
+    exit(match file {
+        Some(_) => output::EXIT_SELECTED,
+        None => output::EXIT_ABORTED,
+    });
+}
+
+/// Restore the terminal, run the `--exec` command on the selection and exit
+/// with its status
+fn exec_and_exit(app: &mut App, file: &FileItem, template: &str) -> Result<()> {
+    let paths = selected_paths(app, file);
+    record_selection(&paths);
+
+    restore_terminal()?;
+
+    let code = exec::run(template, &paths)?;
+    exit(code);
+}
+
+/// Exit once the `on_select` hooks ran and the queued history writes are on disk
+fn exit(code: i32) -> ! {
+    plugins::run_on_select();
+    history_store::flush();
+    std::process::exit(code);
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+    use crate::{core::messages::MessageLevel, testing::Harness, utils::DisplayItem};
+
+    fn harness_in(dir: &Path) -> Harness {
+        let options = crate::InteractiveOptions {
+            start_dir: Some(dir.to_path_buf()),
+            ..Default::default()
+        };
+        Harness::new(AppMode::Normal, options, 60, 12).unwrap()
+    }
+
+    fn selected_name(app: &App) -> Option<String> {
+        app.state
+            .get_selected_item()
+            .map(|item| item.get_display_name())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_keeps_the_selected_entry() {
+        let root = tempfile::tempdir().unwrap();
+        for name in ["b", "c"] {
+            fs::write(root.path().join(name), "").unwrap();
+        }
+        let mut harness = harness_in(root.path());
+        harness.keys("G").await.unwrap();
+        assert_eq!(selected_name(harness.app()).as_deref(), Some("c"));
+
+        fs::write(root.path().join("a"), "").unwrap();
+        handle_action(harness.app_mut(), ModeAction::Refresh)
+            .await
+            .unwrap();
+        assert_eq!(harness.app().state.files.len(), 3);
+        assert_eq!(selected_name(harness.app()).as_deref(), Some("c"));
+    }
+
+    #[tokio::test]
+    async fn test_run_command_refreshes_and_reports_failures() {
+        let root = tempfile::tempdir().unwrap();
+        let mut harness = harness_in(root.path());
+        let created = root.path().join("created");
+        let action = ModeAction::RunCommand {
+            template: "touch {}".to_string(),
+            paths: vec![created.clone()],
+        };
+        assert!(handle_action(harness.app_mut(), action).await.unwrap());
+        assert!(created.exists());
+        assert!(matches!(
+            harness.app().state.files.get(0).as_deref(),
+            Some(DisplayItem::File(file)) if file.path == created
+        ));
+        assert!(harness.app().state.messages.is_empty());
+
+        let action = ModeAction::RunCommand {
+            template: "exit 3".to_string(),
+            paths: Vec::new(),
+        };
+        assert!(handle_action(harness.app_mut(), action).await.unwrap());
+        let message = harness.app().state.messages.current().unwrap();
+        assert_eq!(message.level, MessageLevel::Error);
+        assert!(
+            message.text.ends_with("exited with status 3"),
+            "{}",
+            message.text
+        );
+    }
 }
@@ -0,0 +1,335 @@
+use globset::GlobMatcher;
+use std::{
+    ops::Range,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+};
+use tracing::debug;
+
+use crate::{
+    config::{CaseMatching, EntryFilter},
+    core::listing::Listing,
+    services::history_store::FrecencyScores,
+    utils::{self, DisplayItem},
+};
+
+/// Listings at least this long are searched on a worker thread
+pub const BACKGROUND_FILTER_THRESHOLD: usize = 50_000;
+
+/// Entries the worker checks before handing over what matched so far
+const CHUNK_SIZE: usize = 5_000;
+
+/// Whether an entry passes the hidden file and entry type filters
+pub fn is_listed(item: &DisplayItem, show_hidden: bool, entry_filter: EntryFilter) -> bool {
+    // History entries are always shown
+    let DisplayItem::File(file) = item else {
+        return true;
+    };
+    entry_filter.allows(file.is_dir) && (show_hidden || !file.hidden)
+}
+
+/// One whitespace separated part of a search query
+#[derive(Debug, Clone)]
+struct SearchTerm {
+    text: String,
+    /// Written as `!text`, entries matching it are left out
+    excluded: bool,
+    /// The compiled term, when it is a valid glob pattern
+    glob: Option<GlobMatcher>,
+    path_query: bool,
+}
+
+impl SearchTerm {
+    fn parse(term: &str, case: CaseMatching) -> Option<Self> {
+        let (text, excluded) = match term.strip_prefix('!') {
+            Some(text) => (text, true),
+            None => (term, false),
+        };
+        if text.is_empty() {
+            return None;
+        }
+        // Invalid glob syntax like a lone `[` is searched for literally
+        let glob = utils::is_glob_query(text)
+            .then(|| utils::glob_matcher(text, case))
+            .flatten();
+        Some(Self {
+            text: text.to_string(),
+            excluded,
+            glob,
+            path_query: utils::is_path_query(text),
+        })
+    }
+
+    /// Whether `item` contains the term, no matter if it is excluded
+    fn is_found_in(&self, item: &DisplayItem, case: CaseMatching) -> bool {
+        let text = self.text.as_str();
+        match &self.glob {
+            Some(glob) if self.path_query => glob.is_match(item.get_path()),
+            Some(glob) => glob.is_match(item.get_display_name()),
+            None if self.path_query => {
+                let path = item.get_path().to_string_lossy();
+                utils::find_path_components(&path, text, case).is_some()
+            }
+            None => {
+                case.contains(&item.get_display_name(), text)
+                    || item
+                        .get_alias()
+                        .is_some_and(|alias| case.contains(alias, text))
+            }
+        }
+    }
+}
+
+/// A search query along with the list filters it is combined with
+///
+/// Entries have to match every term of the query, and none of the ones
+/// written as `!term`. It doesn't borrow the app state, so it can be moved
+/// to a worker thread.
+#[derive(Debug, Clone)]
+pub struct SearchFilter {
+    search: String,
+    terms: Vec<SearchTerm>,
+    case: CaseMatching,
+    show_hidden: bool,
+    entry_filter: EntryFilter,
+    /// Visit scores that move often visited directories up the matches
+    frecency: Option<FrecencyScores>,
+}
+
+impl SearchFilter {
+    pub fn new(
+        search: &str,
+        case: CaseMatching,
+        show_hidden: bool,
+        entry_filter: EntryFilter,
+    ) -> Self {
+        // An uppercase letter in any term makes the whole query match case
+        let case = case.resolve(search);
+        Self {
+            search: search.to_string(),
+            terms: search
+                .split_whitespace()
+                .filter_map(|term| SearchTerm::parse(term, case))
+                .collect(),
+            case,
+            show_hidden,
+            entry_filter,
+            frecency: None,
+        }
+    }
+
+    /// Rank matching directories by their frecency in `scores`
+    pub fn with_frecency(mut self, scores: FrecencyScores) -> Self {
+        self.frecency = Some(scores);
+        self
+    }
+
+    /// Whether `item` is listed and matches the query
+    pub fn matches(&self, item: &DisplayItem) -> bool {
+        is_listed(item, self.show_hidden, self.entry_filter)
+            && self
+                .terms
+                .iter()
+                .all(|term| term.is_found_in(item, self.case) != term.excluded)
+    }
+
+    /// Indices of the matching entries among `items`, in list order
+    pub fn filter(&self, items: &Listing) -> Vec<usize> {
+        self.filter_range(items, 0..items.len())
+    }
+
+    /// Indices of the matching entries among `items` in `range`
+    fn filter_range(&self, items: &Listing, range: Range<usize>) -> Vec<usize> {
+        items.positions(range, |item| self.matches(item))
+    }
+
+    /// Put the entry whose alias was typed in full first, followed by the
+    /// files with the highest frecency
+    ///
+    /// History entries keep their order, they are sorted by the mode already.
+    pub fn rank(&self, items: &Listing, indices: &mut [usize]) {
+        if self.search.is_empty() {
+            return;
+        }
+        let search_lower = self.search.to_lowercase();
+        let alias_typed = |item: &DisplayItem| {
+            item.get_alias()
+                .is_some_and(|alias| alias.to_lowercase() == search_lower)
+        };
+        let mut lookup = self.frecency.as_ref().map(FrecencyScores::lookup);
+        let mut frecency = |item: &DisplayItem| match (item, lookup.as_mut()) {
+            (DisplayItem::File(file), Some(lookup)) => lookup.score(file),
+            _ => 0.0,
+        };
+        // Packed listings make an item per lookup, so each is looked at once
+        let mut keyed: Vec<(usize, bool, f64)> = Vec::with_capacity(indices.len());
+        items.visit(indices.iter().copied(), |i, item| {
+            keyed.push((i, alias_typed(item), frecency(item)));
+        });
+        keyed.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.2.total_cmp(&a.2)));
+        for (index, (i, _, _)) in indices.iter_mut().zip(keyed) {
+            *index = i;
+        }
+    }
+}
+
+/// A search of a large listing running on a worker thread
+///
+/// The worker sends the matches in chunks as it goes, so the list fills in
+/// while keys are still handled. Dropping the job stops the worker.
+#[derive(Debug)]
+pub struct FilterJob {
+    /// The search being run, to rank the matches once they are all in
+    pub filter: SearchFilter,
+    /// The entry that was selected before, selected again once it is found
+    pub keep_selected: Option<PathBuf>,
+    receiver: Receiver<Vec<usize>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl FilterJob {
+    pub fn spawn(
+        filter: SearchFilter,
+        items: Arc<Listing>,
+        keep_selected: Option<PathBuf>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        let worker_filter = filter.clone();
+
+        std::thread::spawn(move || {
+            for start in (0..items.len()).step_by(CHUNK_SIZE) {
+                if worker_cancelled.load(Ordering::Relaxed) {
+                    debug!("Background filter cancelled");
+                    return;
+                }
+                let end = (start + CHUNK_SIZE).min(items.len());
+                let matches = worker_filter.filter_range(&items, start..end);
+                if sender.send(matches).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            filter,
+            keep_selected,
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Wait for the worker to finish and return the matches it didn't hand over yet
+    pub fn wait(&self) -> Vec<usize> {
+        self.receiver.iter().flatten().collect()
+    }
+
+    /// The matches found since the last call, and whether the worker is done
+    pub fn poll(&self) -> (Vec<usize>, bool) {
+        let mut matches = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(chunk) => matches.extend(chunk),
+                Err(TryRecvError::Empty) => return (matches, false),
+                Err(TryRecvError::Disconnected) => return (matches, true),
+            }
+        }
+    }
+}
+
+impl Drop for FilterJob {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FileItem;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_filter_job_streams_all_matches() {
+        let items: Listing = (0..CHUNK_SIZE * 3)
+            .map(|i| {
+                let name = format!("file{i}");
+                DisplayItem::File(FileItem::new(name.clone(), PathBuf::from(name)))
+            })
+            .collect();
+        let filter = SearchFilter::new("99", CaseMatching::Smart, false, EntryFilter::All);
+        let expected = filter.filter(&items);
+
+        let job = FilterJob::spawn(filter, Arc::new(items), None);
+        let (mut found, _) = job.poll();
+        found.extend(job.wait());
+        assert_eq!(found, expected);
+        assert!(found.contains(&9999));
+    }
+
+    #[test]
+    fn test_excluded_terms() {
+        let items: Listing = ["test_api.rs", "test_api.snapshot", "api.rs"]
+            .into_iter()
+            .map(|name| DisplayItem::File(FileItem::new(name.to_string(), PathBuf::from(name))))
+            .collect();
+        let search = |query| {
+            SearchFilter::new(query, CaseMatching::Smart, false, EntryFilter::All).filter(&items)
+        };
+
+        assert_eq!(search("test !snapshot"), vec![0]);
+        assert_eq!(search("api rs"), vec![0, 2]);
+        assert_eq!(search("!test"), vec![2]);
+        // A lone `!` excludes nothing
+        assert_eq!(search("api !"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_hidden_entries() {
+        let dotfile = DisplayItem::File(FileItem::new(
+            ".env".to_string(),
+            PathBuf::from("/quickswitch-missing/.env"),
+        ));
+        // Like a file with the hidden attribute on Windows
+        let mut attribute = FileItem::new("desktop.ini".to_string(), PathBuf::from("desktop.ini"));
+        attribute.hidden = true;
+        let attribute = DisplayItem::File(attribute);
+
+        for item in [&dotfile, &attribute] {
+            assert!(!is_listed(item, false, EntryFilter::All));
+            assert!(is_listed(item, true, EntryFilter::All));
+        }
+    }
+
+    #[test]
+    fn test_rank_by_frecency() {
+        // History records paths resolved, the listing may be reached differently
+        let scores = FrecencyScores::new(HashMap::from([(
+            PathBuf::from("/quickswitch-missing/src/tests"),
+            4.0,
+        )]));
+        let items: Listing = ["test_data", "tests"]
+            .into_iter()
+            .map(|name| {
+                let path = PathBuf::from("/quickswitch-missing/other/../src/./").join(name);
+                let mut file = FileItem::new(name.to_string(), path);
+                file.is_dir = true;
+                DisplayItem::File(file)
+            })
+            .collect();
+
+        let filter = SearchFilter::new("test", CaseMatching::Smart, false, EntryFilter::All);
+        let mut indices = filter.filter(&items);
+        filter.rank(&items, &mut indices);
+        assert_eq!(indices, vec![0, 1]);
+
+        let filter = filter.with_frecency(scores);
+        filter.rank(&items, &mut indices);
+        assert_eq!(indices, vec![1, 0]);
+    }
+}
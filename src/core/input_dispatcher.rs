@@ -1,13 +1,27 @@
-use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
 use crate::{
     AppState,
-    modes::ModeAction,
-    services::{PreviewManager, create_data_provider},
+    app_state::{DragTarget, PagerState},
+    config::{CaseMatching, get_config},
+    core::{
+        breadcrumb::{BREADCRUMB_PREFIX, breadcrumb_segments, segment_at},
+        keymap::{Action, KEYMAP},
+    },
+    modes::{ModeAction, history::HistoryDataProvider, normal::FileListDataProvider},
+    services::{
+        Aliases, HistoryStore, Macros, PreviewManager, create_data_provider, history_store,
+    },
     utils::{AppMode, DisplayItem, FileItem},
 };
 
+/// Percentage points the panel divider moves per `<`/`>` key press
+const PANEL_RESIZE_STEP: i16 = 5;
+
+/// Upper bound for count prefixes, large enough to reach any list entry
+const MAX_COUNT: usize = 99_999;
+
 /// Unified input dispatcher for handling all user interactions
 /// This centralizes key and mouse event handling, reducing duplication across modes
 pub struct InputDispatcher;
@@ -19,23 +33,119 @@ impl InputDispatcher {
         key: KeyEvent,
         current_mode: &AppMode,
     ) -> Result<ModeAction> {
-        // Handle exit keys first (highest priority)
-        if let Some(action) = Self::handle_exit_keys(state, key, current_mode) {
-            return Ok(action);
+        // Any key closes the help overlay
+        if state.show_help_overlay {
+            state.show_help_overlay = false;
+            return Ok(ModeAction::Stay);
+        }
+
+        // While typing an alias every key goes to the alias prompt
+        if state.alias_input.is_some() {
+            return Self::handle_alias_keys(state, key);
+        }
+
+        // The pager has its own less-like keys until it is closed
+        if state.pager.is_some() {
+            Self::handle_pager_keys(state, key);
+            return Ok(ModeAction::Stay);
+        }
+
+        // The letter after `m` or `'` names the mark
+        if let Some(action) = state.pending_letter.take() {
+            return Self::handle_letter_key(state, key, action);
         }
 
-        // Handle mode switch keys
-        if let Some(action) = Self::handle_mode_switch_keys(state, key, current_mode) {
-            return Ok(action);
+        // Esc dismisses a notification before doing anything else
+        if key.code == KeyCode::Esc && !state.messages.is_empty() {
+            state.messages.dismiss();
+            return Ok(ModeAction::Stay);
         }
 
-        // Handle navigation keys (unified for all modes)
-        if let Some(action) = Self::handle_navigation_keys(state, key, current_mode).await? {
-            return Ok(action);
+        // Every character extends an active type-ahead jump, bound letters and digits included
+        if !state.is_searching
+            && state.type_ahead_active()
+            && let KeyCode::Char(_) = key.code
+            && !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            Self::handle_type_ahead(state, key, current_mode).await;
+            return Ok(ModeAction::Stay);
         }
 
-        // Handle mode-specific keys
-        Self::handle_mode_specific_keys(state, key, current_mode)
+        if !state.is_searching {
+            // Esc cancels a half typed command first
+            if key.code == KeyCode::Esc && state.pending_input().is_some() {
+                state.clear_pending_input();
+                return Ok(ModeAction::Stay);
+            }
+
+            // Digits build up a count for the next movement, like `5j` in vim
+            if let KeyCode::Char(c @ '0'..='9') = key.code
+                && !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                && (c != '0' || state.pending_count.is_some())
+            {
+                let digit = c.to_digit(10).unwrap_or_default() as usize;
+                let count = state.pending_count.unwrap_or(0) * 10 + digit;
+                state.pending_count = Some(count.min(MAX_COUNT));
+                return Ok(ModeAction::Stay);
+            }
+        }
+
+        // Bound keys take precedence over search input
+        let previous = state.pending_key.take();
+        let count = state.pending_count.take();
+        if let Some(action) =
+            KEYMAP.resolve(&key, previous.as_ref(), state.is_searching, current_mode)
+        {
+            let result = Self::perform_action(state, action, count, current_mode).await;
+            if state.is_searching {
+                PreviewManager::preview_changed_selection(state);
+            }
+            return result;
+        }
+
+        // Wait for the second key of a sequence like `gg`, keeping the count
+        if KEYMAP.starts_sequence(&key, state.is_searching, current_mode) {
+            state.pending_key = Some(key);
+            state.pending_count = count;
+            return Ok(ModeAction::Stay);
+        }
+
+        // Unbound keys are typed into the search box when searching
+        if state.is_searching {
+            let result = Self::handle_search_keys(state, key);
+            PreviewManager::preview_changed_selection(state);
+            result
+        } else {
+            Self::handle_type_ahead(state, key, current_mode).await;
+            Ok(ModeAction::Stay)
+        }
+    }
+
+    /// Jump to the first entry whose name starts with the typed characters
+    async fn handle_type_ahead(state: &mut AppState, key: KeyEvent, current_mode: &AppMode) {
+        let KeyCode::Char(c) = key.code else {
+            return;
+        };
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return;
+        }
+
+        let prefix = state.push_type_ahead(c);
+        let target = state.files.find_position(&state.filtered_files, |item| {
+            item.get_display_name().to_lowercase().starts_with(&prefix)
+        });
+        if let Some(position) = target {
+            create_data_provider(current_mode)
+                .navigate_to_index(state, position)
+                .await;
+        }
     }
 
     /// Handle mouse input uniformly across all modes
@@ -45,219 +155,671 @@ impl InputDispatcher {
         current_mode: &AppMode,
     ) -> Result<ModeAction> {
         match mouse.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if state.pager.is_some() => {
+                let visible_height = state.layout.get_pager_content_height();
+                let step = get_config().mouse.preview_scroll_step;
+                let delta = if mouse.kind == MouseEventKind::ScrollUp {
+                    state.wheel.preview_lines(-step)
+                } else {
+                    state.wheel.preview_lines(step)
+                };
+                if delta != 0 {
+                    PreviewManager::scroll_preview_by(delta, visible_height);
+                }
+                Ok(ModeAction::Stay)
+            }
+            // Clicks would land on the list hidden behind the pager
+            _ if state.pager.is_some() => Ok(ModeAction::Stay),
             MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
                 Self::handle_scroll_navigation(state, mouse, current_mode).await
             }
-            MouseEventKind::Up(crossterm::event::MouseButton::Left) => {
+            MouseEventKind::Down(MouseButton::Left)
+                if state.layout.is_on_divider(mouse.column, mouse.row) =>
+            {
+                state.is_dragging_divider = true;
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Drag(MouseButton::Left) if state.is_dragging_divider => {
+                state.layout.drag_divider_to(mouse.column);
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Up(MouseButton::Left) if state.is_dragging_divider => {
+                state.is_dragging_divider = false;
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if state.is_point_in_left_panel(mouse.column, mouse.row)
+                    && mouse.row > state.layout.get_left_area().y =>
+            {
+                state.drag_target = Some(DragTarget::List { moved: false });
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if state.layout.is_preview_visible()
+                    && state.is_point_in_right_panel(mouse.column, mouse.row) =>
+            {
+                state.drag_target = Some(DragTarget::Preview {
+                    row: mouse.row,
+                    moved: false,
+                });
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Drag(MouseButton::Left) if state.drag_target.is_some() => {
+                Self::handle_drag(state, mouse, current_mode);
+                Ok(ModeAction::Stay)
+            }
+            // Releasing ends the drag, and after a move it isn't a click on the row
+            MouseEventKind::Up(MouseButton::Left)
+                if state.drag_target.take().is_some_and(|drag| drag.moved()) =>
+            {
+                Ok(ModeAction::Stay)
+            }
+            // Clicking the unfocused pane moves the focus there
+            MouseEventKind::Up(MouseButton::Left)
+                if state.layout.is_in_other_area(mouse.column, mouse.row) =>
+            {
+                state.swap_panes();
+                PreviewManager::preview_for_selected_item(state);
+                Ok(ModeAction::Stay)
+            }
+            // Clicking the search box starts searching with the cursor at the click
+            MouseEventKind::Up(MouseButton::Left)
+                if state.alias_input.is_none()
+                    && state.is_point_in_search_area(mouse.column, mouse.row) =>
+            {
+                let text_start = state.layout.get_search_area().x + 1;
+                state.is_searching = true;
+                state.search_cursor_at_column(mouse.column.saturating_sub(text_start));
+                Ok(ModeAction::Stay)
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
                 Self::handle_left_click(state, mouse, current_mode).await
             }
             _ => Ok(ModeAction::Stay),
         }
     }
 
-    /// Handle exit keys (Esc, Enter) - unified across all modes
-    fn handle_exit_keys(
+    /// Perform the action bound to a key
+    ///
+    /// `count` is the numeric prefix typed before the key. Movements are
+    /// repeated that many times, and `gg`/`G` jump to that item instead.
+    async fn perform_action(
         state: &mut AppState,
-        key: KeyEvent,
+        action: Action,
+        count: Option<usize>,
         current_mode: &AppMode,
-    ) -> Option<ModeAction> {
-        match key.code {
-            KeyCode::Esc => {
-                // If searching, exit search mode but keep search input and results
-                if state.is_searching {
-                    state.is_searching = false;
-                    // Don't clear search_input - keep the search results visible
-                    Some(ModeAction::Stay)
-                } else if current_mode == &AppMode::Normal {
-                    if state.get_selected_item().is_none() {
-                        // In normal mode, Esc exits the application
-                        return Some(ModeAction::Exit(None));
-                    }
-                    state.file_list_state.select(None);
-                    PreviewManager::clear_preview();
-                    Some(ModeAction::Stay)
+    ) -> Result<ModeAction> {
+        let provider = create_data_provider(current_mode);
+        let steps = count.unwrap_or(1);
+
+        match action {
+            Action::Back => Ok(Self::handle_back(state, current_mode)),
+            Action::Select => Ok(Self::handle_select(state, current_mode)),
+            Action::SelectAndStay => Self::handle_select_and_stay(state, current_mode),
+            Action::QuitInCurrentDir => Ok(ModeAction::Exit(Self::browsed_dir(state))),
+            Action::MoveUp => {
+                provider.navigate_up(state, steps).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::MoveDown => {
+                provider.navigate_down(state, steps).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::HalfPageUp => {
+                provider.navigate_half_page_up(state, steps).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::HalfPageDown => {
+                provider.navigate_half_page_down(state, steps).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::JumpToFirst | Action::JumpToLast if count.is_some() => {
+                // `5gg` and `5G` both go to the fifth item
+                provider.navigate_to_index(state, steps - 1).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::JumpToFirst => {
+                provider.navigate_to_first(state).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::JumpToLast => {
+                provider.navigate_to_last(state).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::EnterDirectory => Ok(provider
+                .navigate_into_directory(state)?
+                .unwrap_or(ModeAction::Stay)),
+            Action::ParentDirectory => Ok(provider
+                .navigate_to_parent(state)?
+                .unwrap_or(ModeAction::Stay)),
+            Action::PreviewPageUp
+            | Action::PreviewPageDown
+            | Action::PreviewPreviousPage
+            | Action::PreviewNextPage
+            | Action::ZoomImageIn
+            | Action::ZoomImageOut
+            | Action::PanImageLeft
+            | Action::PanImageRight
+            | Action::PanImageUp
+            | Action::PanImageDown => {
+                Self::handle_preview_navigation(state, action);
+                Ok(ModeAction::Stay)
+            }
+            Action::ShowDiskUsage => {
+                if PreviewManager::disk_usage_for_selected_item(state) {
+                    Ok(ModeAction::Stay)
                 } else {
-                    // In other modes, Esc returns to normal mode
-                    Some(ModeAction::Switch(AppMode::Normal))
+                    Ok(ModeAction::Message(
+                        "Select a directory to see its disk usage".to_string(),
+                    ))
                 }
             }
-            KeyCode::Enter => {
-                if key.modifiers == KeyModifiers::CONTROL {
-                    return Some(ModeAction::Stay);
+            Action::StartSearch => {
+                state.is_searching = true;
+                // Resume editing at the end of a kept query
+                state.search_cursor_end();
+                Ok(ModeAction::Stay)
+            }
+            Action::StartTypeAhead => {
+                state.start_type_ahead();
+                Ok(ModeAction::Stay)
+            }
+            Action::SwitchToHistory => Ok(ModeAction::Switch(AppMode::History)),
+            Action::SwitchToRecentFiles => Ok(ModeAction::Switch(AppMode::RecentFiles)),
+            Action::SwitchToJump => Ok(ModeAction::Switch(AppMode::Jump)),
+            Action::SwitchToRepos => Ok(ModeAction::Switch(AppMode::Repos)),
+            Action::SwitchToProjects => Ok(ModeAction::Switch(AppMode::Projects)),
+            Action::SwitchToMounts => Ok(ModeAction::Switch(AppMode::Mounts)),
+            Action::ToggleHiddenFiles => {
+                state.toggle_hidden_files();
+                Ok(ModeAction::Stay)
+            }
+            Action::ToggleMark => {
+                state.toggle_mark_selected();
+                provider.navigate_down(state, 1).await;
+                Ok(ModeAction::Stay)
+            }
+            Action::EditAlias => {
+                if let Some(DisplayItem::History(entry)) = state.get_selected_item() {
+                    state.alias_input = Some(entry.alias.unwrap_or_default());
                 }
-                // Handle selection and exit using unified data provider
-                let provider = create_data_provider(current_mode);
-                if let Some(item) = state.get_selected_item() {
-                    let _ = provider.navigate_to_selected(state);
-                    match item {
-                        DisplayItem::File(file) => Some(ModeAction::Exit(Some(file))),
-                        DisplayItem::History(entry) => {
-                            let file_item = FileItem::from_path(&entry.path);
-                            Some(ModeAction::Exit(Some(file_item)))
-                        }
-                    }
+                Ok(ModeAction::Stay)
+            }
+            Action::DeleteEntry => Self::handle_delete_entries(state),
+            Action::UndoDelete => {
+                let restored = history_store::undo_removal()?;
+                if restored == 0 {
+                    return Ok(ModeAction::Message("Nothing to undo".to_string()));
+                }
+                state.messages.info(format!("Restored {restored} entries"));
+                Ok(ModeAction::Refresh)
+            }
+            Action::CycleEntryFilter => {
+                state.cycle_entry_filter();
+                Ok(ModeAction::Stay)
+            }
+            Action::CycleCaseMatching => {
+                state.cycle_case_matching();
+                let message = match state.case_matching {
+                    CaseMatching::Smart => "Search ignores case unless the query has uppercase",
+                    CaseMatching::Sensitive => "Search matches case",
+                    CaseMatching::Insensitive => "Search ignores case",
+                };
+                Ok(ModeAction::Message(message.to_string()))
+            }
+            Action::TogglePreview => {
+                state.layout.toggle_preview();
+                Ok(ModeAction::Stay)
+            }
+            Action::ToggleTreeView => {
+                let message = if PreviewManager::toggle_tree_view(state) {
+                    "Directories previewed as a tree"
                 } else {
-                    let file_item = FileItem::from_path(&state.current_dir);
-                    Some(ModeAction::Exit(Some(file_item)))
+                    "Directories previewed as a list"
+                };
+                Ok(ModeAction::Message(message.to_string()))
+            }
+            Action::OpenPager => {
+                if state.get_selected_item().is_none() {
+                    return Ok(ModeAction::Message(
+                        "Select an entry to view it full screen".to_string(),
+                    ));
+                }
+                state.pager = Some(PagerState::default());
+                Ok(ModeAction::Stay)
+            }
+            Action::ToggleDualPane => {
+                state.toggle_dual_pane();
+                Ok(ModeAction::Stay)
+            }
+            Action::SwitchPane => {
+                if !state.swap_panes() {
+                    return Ok(ModeAction::Message(
+                        "Press W to open a second file list".to_string(),
+                    ));
                 }
+                PreviewManager::preview_for_selected_item(state);
+                Ok(ModeAction::Stay)
+            }
+            Action::RecordMacro if state.recording.is_some() => {
+                Self::finish_recording(state)?;
+                Ok(ModeAction::Stay)
+            }
+            Action::SetMark | Action::JumpToMark | Action::RecordMacro | Action::ReplayMacro => {
+                state.pending_letter = Some(action);
+                Ok(ModeAction::Stay)
+            }
+            Action::ToggleLineNumbers => {
+                let message = if PreviewManager::toggle_line_numbers(state) {
+                    "Line numbers shown"
+                } else {
+                    "Line numbers hidden"
+                };
+                Ok(ModeAction::Message(message.to_string()))
+            }
+            Action::GrowPreview => {
+                // Move the divider left, widening the preview panel
+                state.layout.resize_preview(PANEL_RESIZE_STEP);
+                Ok(ModeAction::Stay)
+            }
+            Action::ShrinkPreview => {
+                // Move the divider right, widening the list panel
+                state.layout.resize_preview(-PANEL_RESIZE_STEP);
+                Ok(ModeAction::Stay)
+            }
+            Action::ShowHelp => {
+                state.show_help_overlay = true;
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchCursorLeft => {
+                state.move_search_cursor(-1);
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchCursorRight => {
+                state.move_search_cursor(1);
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchCursorHome => {
+                state.search_cursor_home();
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchCursorEnd => {
+                state.search_cursor_end();
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchDeleteBackward => {
+                state.search_delete_backward();
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchDeleteForward => {
+                state.search_delete_forward();
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchDeleteWord => {
+                state.search_delete_word();
+                Ok(ModeAction::Stay)
+            }
+            Action::SearchClear => {
+                state.clear_search();
+                state.apply_search_filter();
+                Ok(ModeAction::Stay)
             }
-            _ => None,
         }
     }
 
-    /// Handle mode switching keys - unified across all modes
-    fn handle_mode_switch_keys(
-        state: &mut AppState,
-        key: KeyEvent,
-        current_mode: &AppMode,
-    ) -> Option<ModeAction> {
-        match key.code {
-            KeyCode::Char('/') => {
-                // Enable search functionality in normal and history modes
-                if matches!(current_mode, AppMode::Normal | AppMode::History) && !state.is_searching
-                {
-                    state.is_searching = true;
-                    Some(ModeAction::Stay)
-                } else {
-                    None
-                }
+    /// Handle Esc: leave search, clear the selection, or leave the mode
+    fn handle_back(state: &mut AppState, current_mode: &AppMode) -> ModeAction {
+        // If searching, exit search mode but keep search input and results
+        if state.is_searching {
+            state.is_searching = false;
+            // Don't clear search_input - keep the search results visible
+            ModeAction::Stay
+        } else if matches!(current_mode, AppMode::Normal | AppMode::Picker) {
+            if state.get_selected_item().is_none() {
+                // In normal mode, Esc exits the application
+                return ModeAction::Exit(None);
             }
-            KeyCode::Char('v') if !state.is_searching => {
-                if current_mode != &AppMode::History {
-                    Some(ModeAction::Switch(AppMode::History))
-                } else {
-                    None
+            state.file_list_state.select(None);
+            PreviewManager::clear_preview();
+            ModeAction::Stay
+        } else {
+            // In other modes, Esc returns to normal mode
+            ModeAction::Switch(AppMode::Normal)
+        }
+    }
+
+    /// Handle Enter: select the current item and exit
+    fn handle_select(state: &mut AppState, current_mode: &AppMode) -> ModeAction {
+        // Handle selection and exit using unified data provider
+        let provider = create_data_provider(current_mode);
+        if let Some(item) = state.get_selected_item() {
+            let _ = provider.navigate_to_selected(state);
+            match item {
+                DisplayItem::File(file) => ModeAction::Exit(Some(file)),
+                DisplayItem::History(entry) => {
+                    let file_item = FileItem::from_path(&entry.path);
+                    ModeAction::Exit(Some(file_item))
                 }
             }
-            _ => None,
+        } else if current_mode == &AppMode::Picker {
+            // Like fzf, Enter without a selection picks the first match
+            let first = state
+                .filtered_files
+                .first()
+                .and_then(|&index| state.files.get(index));
+            match first.as_deref() {
+                Some(DisplayItem::File(file)) => ModeAction::Exit(Some(file.clone())),
+                _ => ModeAction::Exit(None),
+            }
+        } else {
+            let file_item = FileItem::from_path(&state.current_dir);
+            ModeAction::Exit(Some(file_item))
         }
     }
 
-    /// Handle navigation keys - unified using data providers
-    async fn handle_navigation_keys(
-        state: &mut AppState,
-        key: KeyEvent,
-        current_mode: &AppMode,
-    ) -> Result<Option<ModeAction>> {
+    /// The directory being browsed, to change to when quitting without a selection
+    ///
+    /// `None` in the drives view, which isn't a directory.
+    pub fn browsed_dir(state: &AppState) -> Option<FileItem> {
+        (state.current_dir.to_string_lossy() != "DRIVES:")
+            .then(|| FileItem::from_path(&state.current_dir))
+    }
+
+    /// Handle Ctrl+Enter: change into the selected directory and keep browsing
+    ///
+    /// The directory is recorded in history just like a regular selection.
+    fn handle_select_and_stay(state: &mut AppState, current_mode: &AppMode) -> Result<ModeAction> {
+        let Some(item) = state.get_selected_item() else {
+            return Ok(ModeAction::Stay);
+        };
+        if !item.is_directory() {
+            return Ok(ModeAction::Stay);
+        }
+
+        // History mode records the entry itself when switching back to normal mode
         let provider = create_data_provider(current_mode);
+        let action = provider
+            .navigate_into_directory(state)?
+            .unwrap_or(ModeAction::Stay);
+
+        if current_mode == &AppMode::Normal {
+            HistoryDataProvider.add_to_history(item.get_path().clone());
+        }
+        Ok(action)
+    }
+
+    /// Delete the marked history entries, or the selected one
+    fn handle_delete_entries(state: &mut AppState) -> Result<ModeAction> {
+        let paths = if state.marked.is_empty() {
+            match state.get_selected_item() {
+                Some(item) => vec![item.get_path().clone()],
+                None => return Ok(ModeAction::Stay),
+            }
+        } else {
+            std::mem::take(&mut state.marked)
+        };
 
+        let removed = HistoryStore::DIRECTORIES.remove_entries(&paths)?;
+        state
+            .messages
+            .info(format!("Deleted {removed} entries, press u to undo"));
+        // The selection stays on the same row, now the next entry
+        Ok(ModeAction::Refresh)
+    }
+
+    /// Handle keys typed into the alias prompt of the selected history entry
+    fn handle_alias_keys(state: &mut AppState, key: KeyEvent) -> Result<ModeAction> {
+        let Some(input) = state.alias_input.as_mut() else {
+            return Ok(ModeAction::Stay);
+        };
         match key.code {
-            KeyCode::Up => {
-                provider.navigate_up(state).await;
-                Ok(Some(ModeAction::Stay))
-            }
-            KeyCode::Down => {
-                provider.navigate_down(state).await;
-                Ok(Some(ModeAction::Stay))
-            }
-            KeyCode::Right => {
-                // Use provider's navigation method
-                if let Some(action) = provider.navigate_into_directory(state)? {
-                    Ok(Some(action))
+            KeyCode::Esc => state.alias_input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                input.push(c);
+            }
+            KeyCode::Enter => {
+                let alias = state.alias_input.take().unwrap_or_default();
+                let Some(item) = state.get_selected_item() else {
+                    return Ok(ModeAction::Stay);
+                };
+                let path = item.get_path().clone();
+                let mut aliases = Aliases::load()?;
+                aliases.set(&path, &alias)?;
+                aliases.save()?;
+
+                let alias = alias.trim();
+                if alias.is_empty() {
+                    state
+                        .messages
+                        .info(format!("Removed alias of {}", path.display()));
                 } else {
-                    Ok(Some(ModeAction::Stay))
+                    state.messages.info(format!("{alias} → {}", path.display()));
                 }
+                // Show the new alias
+                return Ok(ModeAction::Refresh);
             }
-            KeyCode::Left => {
-                // Use provider's navigation method
-                if let Some(action) = provider.navigate_to_parent(state)? {
-                    Ok(Some(action))
-                } else {
-                    Ok(Some(ModeAction::Stay))
+            _ => {}
+        }
+        Ok(ModeAction::Stay)
+    }
+
+    /// Handle keys in the full-screen pager, mostly the same as in `less`
+    fn handle_pager_keys(state: &mut AppState, key: KeyEvent) {
+        let Some(pager) = state.pager.as_mut() else {
+            return;
+        };
+        let visible_height = state.layout.get_pager_content_height();
+        let half_page = (visible_height / 2).max(1) as isize;
+        let page = visible_height.max(1) as isize;
+
+        // Typing a search pattern after `/` or `?`
+        if let Some((input, forward)) = pager.search_input.as_mut() {
+            match key.code {
+                KeyCode::Esc => pager.search_input = None,
+                // Like less, backspacing over the prompt cancels the search
+                KeyCode::Backspace if input.is_empty() => pager.search_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
                 }
+                KeyCode::Enter => {
+                    let search = (std::mem::take(input), *forward);
+                    pager.search_input = None;
+                    if !search.0.is_empty() {
+                        pager.last_search = Some(search);
+                    }
+                    Self::repeat_pager_search(pager, false);
+                }
+                KeyCode::Char(c)
+                    if !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    input.push(c);
+                }
+                _ => {}
             }
-            // hjkl keys only work when not searching
-            KeyCode::Char('k') if !state.is_searching => {
-                provider.navigate_up(state).await;
-                Ok(Some(ModeAction::Stay))
+            return;
+        }
+
+        pager.status = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Tab => state.pager = None,
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => {
+                PreviewManager::scroll_preview_by(1, visible_height);
             }
-            KeyCode::Char('j') if !state.is_searching => {
-                provider.navigate_down(state).await;
-                Ok(Some(ModeAction::Stay))
+            KeyCode::Char('k') | KeyCode::Up => {
+                PreviewManager::scroll_preview_by(-1, visible_height);
             }
-            KeyCode::Char('l') if !state.is_searching => {
-                // Use provider's navigation method
-                if let Some(action) = provider.navigate_into_directory(state)? {
-                    Ok(Some(action))
-                } else {
-                    Ok(Some(ModeAction::Stay))
-                }
+            KeyCode::Char('d') => {
+                PreviewManager::scroll_preview_by(half_page, visible_height);
             }
-            KeyCode::Char('h') if !state.is_searching => {
-                // Use provider's navigation method
-                if let Some(action) = provider.navigate_to_parent(state)? {
-                    Ok(Some(action))
-                } else {
-                    Ok(Some(ModeAction::Stay))
-                }
+            KeyCode::Char('u') => {
+                PreviewManager::scroll_preview_by(-half_page, visible_height);
+            }
+            KeyCode::Char(' ' | 'f') | KeyCode::PageDown => {
+                PreviewManager::scroll_preview_by(page, visible_height);
+            }
+            KeyCode::Char('b') | KeyCode::PageUp => {
+                PreviewManager::scroll_preview_by(-page, visible_height);
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                PreviewManager::scroll_preview_to_top();
             }
-            KeyCode::PageUp | KeyCode::PageDown => {
-                Self::handle_preview_navigation(state, key);
-                Ok(Some(ModeAction::Stay))
+            KeyCode::Char('G') | KeyCode::End => {
+                PreviewManager::scroll_preview_to_bottom(visible_height);
             }
-            // Half-page navigation keys (only work when not searching)
-            KeyCode::Char('b') if !state.is_searching => {
-                provider.navigate_half_page_down(state).await;
-                Ok(Some(ModeAction::Stay))
+            KeyCode::Char('[') => {
+                PreviewManager::scroll_preview_previous_page();
             }
-            KeyCode::Char('f') if !state.is_searching => {
-                provider.navigate_half_page_up(state).await;
-                Ok(Some(ModeAction::Stay))
+            KeyCode::Char(']') => {
+                PreviewManager::scroll_preview_next_page();
             }
-            _ => Ok(None),
+            KeyCode::Char('/') => pager.search_input = Some((String::new(), true)),
+            KeyCode::Char('?') => pager.search_input = Some((String::new(), false)),
+            KeyCode::Char('n') => Self::repeat_pager_search(pager, false),
+            KeyCode::Char('N') => Self::repeat_pager_search(pager, true),
+            _ => {}
+        }
+    }
+
+    /// Jump to the next match of the last pager search, or the previous one
+    /// when `reverse` is set
+    fn repeat_pager_search(pager: &mut PagerState, reverse: bool) {
+        let Some((pattern, forward)) = &pager.last_search else {
+            pager.status = Some("No previous search".to_string());
+            return;
+        };
+        if !PreviewManager::search_preview(pattern, *forward != reverse) {
+            pager.status = Some(format!("Pattern not found: {pattern}"));
         }
     }
 
-    /// Handle mode-specific keys that don't fit into common patterns
-    fn handle_mode_specific_keys(
+    /// Handle the letter typed after `m`, `'`, `q` or `@`
+    ///
+    /// Any other key cancels.
+    fn handle_letter_key(
         state: &mut AppState,
         key: KeyEvent,
-        _current_mode: &AppMode,
+        action: Action,
     ) -> Result<ModeAction> {
-        // Handle search input when in search mode
-        if state.is_searching {
-            Self::handle_search_keys(state, key)
-        } else {
-            // Handle non-search mode keys
-            match key.code {
-                KeyCode::Char('.') => {
-                    // Toggle hidden files visibility
-                    state.toggle_hidden_files();
-                    Ok(ModeAction::Stay)
-                }
-                _ => Ok(ModeAction::Stay),
+        let KeyCode::Char(letter @ ('a'..='z' | 'A'..='Z')) = key.code else {
+            return Ok(ModeAction::Stay);
+        };
+        match action {
+            Action::RecordMacro => {
+                state.recording = Some((letter, Vec::new()));
+                return Ok(ModeAction::Stay);
             }
+            Action::ReplayMacro => {
+                Self::replay_macro(state, letter)?;
+                return Ok(ModeAction::Stay);
+            }
+            _ => {}
         }
+        if action == Action::SetMark {
+            let dir = state.current_dir.clone();
+            state.marks.set(letter, &dir)?;
+            state
+                .messages
+                .info(format!("Marked {} as '{letter}", dir.display()));
+            return Ok(ModeAction::Stay);
+        }
+
+        match state.marks.get(letter)? {
+            Some(dir) if dir.is_dir() => FileListDataProvider.change_directory(state, dir)?,
+            Some(dir) => state.messages.error(format!(
+                "Mark '{letter} points to missing {}",
+                dir.display()
+            )),
+            None => state.messages.info(format!("Mark '{letter} is not set")),
+        }
+        Ok(ModeAction::Stay)
+    }
+
+    /// Stop recording and keep the macro, without the `q` that stopped it
+    fn finish_recording(state: &mut AppState) -> Result<()> {
+        let Some((name, mut keys)) = state.recording.take() else {
+            return Ok(());
+        };
+        keys.pop();
+        Macros::save_recorded(name, &keys)?;
+        state
+            .messages
+            .info(format!("Recorded {} keys as @{name}", keys.len()));
+        Ok(())
+    }
+
+    /// Queue the keys of a macro, they are handled right after this key
+    fn replay_macro(state: &mut AppState, name: char) -> Result<()> {
+        // A macro running itself would never stop
+        if state.replaying_macro {
+            bail!("Macros can't run other macros");
+        }
+        match Macros::load()?.keys(name)? {
+            Some(keys) => state.macro_queue.extend(keys),
+            None => state.messages.info(format!("Macro @{name} is not set")),
+        }
+        Ok(())
     }
 
     /// Handle search mode specific keys
     fn handle_search_keys(state: &mut AppState, key: KeyEvent) -> Result<ModeAction> {
-        match key.code {
-            KeyCode::Char(c) => {
-                state.search_input.push(c);
-                state.apply_search_filter();
-                Ok(ModeAction::Stay)
-            }
-            KeyCode::Backspace => {
-                state.search_input.pop();
-                state.apply_search_filter();
-                Ok(ModeAction::Stay)
-            }
-            _ => Ok(ModeAction::Stay),
+        // Unbound Ctrl/Alt chords should not end up in the query
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return Ok(ModeAction::Stay);
         }
+
+        if let KeyCode::Char(c) = key.code {
+            state.search_insert_char(c);
+        }
+        Ok(ModeAction::Stay)
     }
 
     /// Handle preview navigation (Page Up/Down)
-    fn handle_preview_navigation(state: &mut AppState, key: KeyEvent) {
+    fn handle_preview_navigation(state: &mut AppState, action: Action) {
         // Use the actual right panel content height from layout manager
         let visible_height = state.layout.get_right_content_height();
-        match key.code {
-            KeyCode::PageUp => {
+        match action {
+            Action::PreviewPageUp => {
                 PreviewManager::scroll_preview_page_up(visible_height);
             }
-            KeyCode::PageDown => {
+            Action::PreviewPageDown => {
                 PreviewManager::scroll_preview_page_down(visible_height);
             }
+            Action::PreviewPreviousPage => {
+                PreviewManager::scroll_preview_previous_page();
+            }
+            Action::PreviewNextPage => {
+                PreviewManager::scroll_preview_next_page();
+            }
+            Action::ZoomImageIn => {
+                PreviewManager::zoom_preview_image(true);
+            }
+            Action::ZoomImageOut => {
+                PreviewManager::zoom_preview_image(false);
+            }
+            Action::PanImageLeft => {
+                PreviewManager::pan_preview_image(-1, 0);
+            }
+            Action::PanImageRight => {
+                PreviewManager::pan_preview_image(1, 0);
+            }
+            Action::PanImageUp => {
+                PreviewManager::pan_preview_image(0, -1);
+            }
+            Action::PanImageDown => {
+                PreviewManager::pan_preview_image(0, 1);
+            }
             _ => {}
         }
     }
@@ -268,30 +830,94 @@ impl InputDispatcher {
         mouse: MouseEvent,
         current_mode: &AppMode,
     ) -> Result<ModeAction> {
-        let is_scroll_up = matches!(mouse.kind, MouseEventKind::ScrollUp);
+        let direction = if matches!(mouse.kind, MouseEventKind::ScrollUp) {
+            -1.0
+        } else {
+            1.0
+        };
+        let config = &get_config().mouse;
 
         // Check if mouse is in left area (file/history list) or right area (preview)
         if state.is_point_in_left_panel(mouse.column, mouse.row) {
             // Mouse is in left panel - scroll list using unified provider
             let provider = create_data_provider(current_mode);
-            if is_scroll_up {
-                provider.navigate_up(state).await;
+            let lines = state.wheel.list_lines(direction * config.scroll_step);
+            if lines == 0 {
+                return Ok(ModeAction::Stay);
+            }
+            if lines < 0 {
+                provider.navigate_up(state, lines.unsigned_abs()).await;
             } else {
-                provider.navigate_down(state).await;
+                provider.navigate_down(state, lines as usize).await;
             }
             PreviewManager::preview_for_selected_item(state);
         } else if state.is_point_in_right_panel(mouse.column, mouse.row) {
             // Mouse is in right panel - scroll preview content
-            if is_scroll_up {
-                PreviewManager::scroll_preview_up();
-            } else {
-                PreviewManager::scroll_preview_down();
+            let lines = state
+                .wheel
+                .preview_lines(direction * config.preview_scroll_step);
+            for _ in 0..lines.unsigned_abs() {
+                if lines < 0 {
+                    PreviewManager::scroll_preview_up();
+                } else {
+                    PreviewManager::scroll_preview_down();
+                }
             }
         }
 
         Ok(ModeAction::Stay)
     }
 
+    /// Move the selection to the row under the pointer, or scroll the
+    /// preview along with it
+    ///
+    /// Dragging past the top or bottom of the list scrolls it one item at a time.
+    fn handle_drag(state: &mut AppState, mouse: MouseEvent, current_mode: &AppMode) {
+        match state.drag_target {
+            Some(DragTarget::List { .. }) => {
+                let provider = create_data_provider(current_mode);
+                let total = provider.get_total_count(state);
+                let left_area = state.layout.get_left_area();
+                let list_top = left_area.y + 1;
+                let list_rows = left_area.height.saturating_sub(2) as usize;
+                let offset = Self::get_scroll_offset(state, current_mode);
+                let target = if mouse.row < list_top {
+                    offset.saturating_sub(1)
+                } else {
+                    offset + ((mouse.row - list_top) as usize).min(list_rows)
+                };
+                if total == 0 {
+                    return;
+                }
+                let target = target.min(total - 1);
+                if state.file_list_state.selected() != Some(target) {
+                    provider.set_selected_index(state, Some(target));
+                    PreviewManager::preview_for_selected_item(state);
+                    state.drag_target = Some(DragTarget::List { moved: true });
+                }
+            }
+            Some(DragTarget::Preview { row, .. }) => {
+                // The content moves with the pointer, so dragging up shows what's below
+                let lines = row as isize - mouse.row as isize;
+                if lines == 0 {
+                    return;
+                }
+                for _ in 0..lines.unsigned_abs() {
+                    if lines < 0 {
+                        PreviewManager::scroll_preview_up();
+                    } else {
+                        PreviewManager::scroll_preview_down();
+                    }
+                }
+                state.drag_target = Some(DragTarget::Preview {
+                    row: mouse.row,
+                    moved: true,
+                });
+            }
+            None => {}
+        }
+    }
+
     /// Handle left mouse click using unified data providers
     async fn handle_left_click(
         state: &mut AppState,
@@ -306,6 +932,11 @@ impl InputDispatcher {
         let provider = create_data_provider(current_mode);
         let left_area = state.layout.get_left_area();
 
+        // Clicks on the top border hit the breadcrumb path bar
+        if mouse.row == left_area.y {
+            return Self::handle_breadcrumb_click(state, mouse, current_mode);
+        }
+
         // Calculate the actual clicked index considering scroll offset
         let visible_row = (mouse.row - left_area.y - 1) as usize; // Row relative to the visible area
         let scroll_offset = Self::get_scroll_offset(state, current_mode);
@@ -328,27 +959,57 @@ impl InputDispatcher {
         Self::update_double_click_state(state, mouse_position, clicked_index);
 
         // Handle double-click action
-        if is_double_click {
-            if let Some(item) = state.get_selected_item() {
-                match item {
-                    DisplayItem::File(_) => {
-                        if let Some(action) = provider.navigate_into_directory(state)? {
-                            return Ok(action);
-                        } else {
-                            return Ok(ModeAction::Stay);
-                        }
-                    }
-                    DisplayItem::History(entry) => {
-                        let file_item = FileItem::from_path(&entry.path);
-                        return Ok(ModeAction::Exit(Some(file_item)));
+        let single_click = get_config().mouse.single_click;
+        if (is_double_click || single_click)
+            && let Some(item) = state.get_selected_item()
+        {
+            match item {
+                // A file is picked like with Enter, a double click leaves it selected
+                DisplayItem::File(file) if single_click && !file.is_dir => {
+                    return Ok(Self::handle_select(state, current_mode));
+                }
+                DisplayItem::File(_) => {
+                    if let Some(action) = provider.navigate_into_directory(state)? {
+                        return Ok(action);
+                    } else {
+                        return Ok(ModeAction::Stay);
                     }
                 }
+                DisplayItem::History(entry) => {
+                    let file_item = FileItem::from_path(&entry.path);
+                    return Ok(ModeAction::Exit(Some(file_item)));
+                }
             }
         }
 
         Ok(ModeAction::Stay)
     }
 
+    /// Handle a click on the breadcrumb path bar by jumping to the clicked ancestor
+    fn handle_breadcrumb_click(
+        state: &mut AppState,
+        mouse: MouseEvent,
+        current_mode: &AppMode,
+    ) -> Result<ModeAction> {
+        if current_mode != &AppMode::Normal {
+            return Ok(ModeAction::Stay);
+        }
+
+        // The title starts one column after the top-left corner of the border
+        let breadcrumb_start = state.layout.get_left_area().x + 1 + BREADCRUMB_PREFIX.len() as u16;
+        let Some(offset) = mouse.column.checked_sub(breadcrumb_start) else {
+            return Ok(ModeAction::Stay);
+        };
+
+        let segments = breadcrumb_segments(&state.current_dir);
+        if let Some(segment) = segment_at(&segments, offset)
+            && segment.path != state.current_dir
+        {
+            FileListDataProvider.change_directory(state, segment.path.clone())?;
+        }
+        Ok(ModeAction::Stay)
+    }
+
     /// Check if current click is a double-click
     fn is_double_click(
         state: &mut AppState,
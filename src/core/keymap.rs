@@ -0,0 +1,555 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use once_cell::sync::Lazy;
+
+use crate::utils::AppMode;
+
+/// Actions that can be triggered from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    HalfPageUp,
+    HalfPageDown,
+    JumpToFirst,
+    JumpToLast,
+    EnterDirectory,
+    ParentDirectory,
+    PreviewPageUp,
+    PreviewPageDown,
+    PreviewPreviousPage,
+    PreviewNextPage,
+    ZoomImageIn,
+    ZoomImageOut,
+    PanImageLeft,
+    PanImageRight,
+    PanImageUp,
+    PanImageDown,
+    ShowDiskUsage,
+    ToggleLineNumbers,
+    ToggleTreeView,
+    OpenPager,
+    ToggleDualPane,
+    SwitchPane,
+    SetMark,
+    JumpToMark,
+    RecordMacro,
+    ReplayMacro,
+    StartSearch,
+    /// Jump to the first name starting with the characters typed next
+    StartTypeAhead,
+    SwitchToHistory,
+    SwitchToRecentFiles,
+    SwitchToJump,
+    SwitchToRepos,
+    SwitchToProjects,
+    SwitchToMounts,
+    ToggleHiddenFiles,
+    CycleEntryFilter,
+    CycleCaseMatching,
+    ToggleMark,
+    EditAlias,
+    DeleteEntry,
+    UndoDelete,
+    TogglePreview,
+    GrowPreview,
+    ShrinkPreview,
+    ShowHelp,
+    Select,
+    SelectAndStay,
+    QuitInCurrentDir,
+    Back,
+    SearchCursorLeft,
+    SearchCursorRight,
+    SearchCursorHome,
+    SearchCursorEnd,
+    SearchDeleteBackward,
+    SearchDeleteForward,
+    SearchDeleteWord,
+    SearchClear,
+}
+
+impl Action {
+    /// Human readable description shown in help
+    pub fn description(&self, mode: &AppMode) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::HalfPageUp => "Move up half page",
+            Action::HalfPageDown => "Move down half page",
+            Action::JumpToFirst => "Jump to first item",
+            Action::JumpToLast => "Jump to last item",
+            Action::EnterDirectory => match mode {
+                AppMode::Normal | AppMode::Picker => "Enter directory",
+                AppMode::RecentFiles => "Show file in its directory",
+                AppMode::History
+                | AppMode::Jump
+                | AppMode::Repos
+                | AppMode::Projects
+                | AppMode::Mounts => "Enter directory & return to normal",
+            },
+            Action::ParentDirectory => match mode {
+                AppMode::Normal | AppMode::Picker => "Go to parent directory",
+                AppMode::History
+                | AppMode::RecentFiles
+                | AppMode::Jump
+                | AppMode::Repos
+                | AppMode::Projects
+                | AppMode::Mounts => "Return to normal mode",
+            },
+            Action::PreviewPageUp => "Scroll preview up",
+            Action::PreviewPageDown => "Scroll preview down",
+            Action::PreviewPreviousPage => "Previous page of PDF preview",
+            Action::PreviewNextPage => "Next page of PDF preview",
+            Action::ZoomImageIn => "Zoom into image preview",
+            Action::ZoomImageOut => "Zoom out of image preview",
+            Action::PanImageLeft => "Pan image preview left",
+            Action::PanImageRight => "Pan image preview right",
+            Action::PanImageUp => "Pan image preview up",
+            Action::PanImageDown => "Pan image preview down",
+            Action::ShowDiskUsage => "Show disk usage of directory",
+            Action::ToggleLineNumbers => "Toggle line numbers in preview",
+            Action::ToggleTreeView => "Toggle tree preview of directories",
+            Action::OpenPager => "View preview full screen",
+            Action::ToggleDualPane => "Toggle a second file list instead of the preview",
+            Action::SwitchPane => "Switch to the other file list",
+            Action::SetMark => "Mark directory (a-z this session, A-Z kept)",
+            Action::JumpToMark => "Go to marked directory",
+            Action::RecordMacro => "Record macro (q again to stop)",
+            Action::ReplayMacro => "Run macro",
+            Action::StartSearch => "Search",
+            Action::StartTypeAhead => "Jump to the first name starting with the typed characters",
+            Action::SwitchToHistory => "Enter history mode",
+            Action::SwitchToRecentFiles => "Enter recent files mode",
+            Action::SwitchToJump => "Jump to an indexed directory",
+            Action::SwitchToRepos => "Switch to a git repository or worktree",
+            Action::SwitchToProjects => "Switch to a Cargo, npm, Go or other project",
+            Action::SwitchToMounts => "Go to a mounted filesystem or drive",
+            Action::ToggleHiddenFiles => "Toggle hidden files",
+            Action::CycleEntryFilter => "Show all / directories only / files only",
+            Action::CycleCaseMatching => "Search: smart case / match case / ignore case",
+            Action::ToggleMark => "Mark entry and move down",
+            Action::EditAlias => "Set alias for entry",
+            Action::DeleteEntry => "Delete entry (or marked entries) from history",
+            Action::UndoDelete => "Undo the last delete",
+            Action::TogglePreview => "Toggle preview panel",
+            Action::GrowPreview => "Widen preview panel",
+            Action::ShrinkPreview => "Narrow preview panel",
+            Action::ShowHelp => "Show this help",
+            Action::Select => "Select and exit",
+            Action::SelectAndStay => "Enter directory & keep browsing",
+            Action::QuitInCurrentDir => "Quit and change to the browsed directory",
+            Action::Back => match mode {
+                AppMode::Normal | AppMode::Picker => "Exit search / clear selection / quit",
+                AppMode::History
+                | AppMode::RecentFiles
+                | AppMode::Jump
+                | AppMode::Repos
+                | AppMode::Projects
+                | AppMode::Mounts => "Exit search / return to normal mode",
+            },
+            Action::SearchCursorLeft => "Search: move cursor left",
+            Action::SearchCursorRight => "Search: move cursor right",
+            Action::SearchCursorHome => "Search: jump to start",
+            Action::SearchCursorEnd => "Search: jump to end",
+            Action::SearchDeleteBackward => "Search: delete character",
+            Action::SearchDeleteForward => "Search: delete character under cursor",
+            Action::SearchDeleteWord => "Search: delete word",
+            Action::SearchClear => "Search: clear query",
+        }
+    }
+
+    /// Check if the action does anything in the given mode
+    pub fn is_available(&self, mode: &AppMode) -> bool {
+        match self {
+            Action::SwitchToHistory
+            | Action::SwitchToRecentFiles
+            | Action::SwitchToJump
+            | Action::SwitchToRepos
+            | Action::SwitchToProjects
+            | Action::SwitchToMounts
+            | Action::CycleEntryFilter
+            | Action::ToggleDualPane
+            | Action::SwitchPane
+            | Action::SetMark
+            | Action::JumpToMark => mode == &AppMode::Normal,
+            // The picker is a flat list of stdin entries without a directory to browse
+            Action::EnterDirectory
+            | Action::ParentDirectory
+            | Action::SelectAndStay
+            | Action::QuitInCurrentDir
+            | Action::ShowDiskUsage
+            | Action::ToggleHiddenFiles => mode != &AppMode::Picker,
+            Action::EditAlias | Action::DeleteEntry | Action::UndoDelete => {
+                mode == &AppMode::History
+            }
+            _ => true,
+        }
+    }
+}
+
+/// When a key binding is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyScope {
+    /// Only while browsing the list
+    Browse,
+    /// Only while typing a search query
+    Search,
+    /// In both cases
+    Always,
+}
+
+/// A single key binding
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: Action,
+    pub scope: KeyScope,
+    /// Whether the key has to be pressed twice in a row, like vim's `gg`
+    pub double: bool,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, action: Action) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+            action,
+            scope: KeyScope::Browse,
+            double: false,
+        }
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        let (code, modifiers) = normalize(key);
+        self.code == code && self.modifiers == modifiers
+    }
+
+    /// Keep this binding active while typing a search query
+    fn in_search(mut self) -> Self {
+        self.scope = KeyScope::Always;
+        self
+    }
+
+    /// Make this binding active only while typing a search query
+    fn only_in_search(mut self) -> Self {
+        self.scope = KeyScope::Search;
+        self
+    }
+
+    fn is_active(&self, is_searching: bool) -> bool {
+        match self.scope {
+            KeyScope::Browse => !is_searching,
+            KeyScope::Search => is_searching,
+            KeyScope::Always => true,
+        }
+    }
+
+    /// Require the key to be pressed twice in a row
+    fn twice(mut self) -> Self {
+        self.double = true;
+        self
+    }
+
+    /// Require modifiers such as Ctrl or Alt to be held
+    fn with(mut self, modifiers: KeyModifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+}
+
+/// Mapping from keys to actions
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: Vec<KeyBinding>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use Action::*;
+        use KeyCode::*;
+
+        Self {
+            bindings: vec![
+                KeyBinding::new(Esc, Back).in_search(),
+                KeyBinding::new(Enter, Select).in_search(),
+                KeyBinding::new(Enter, SelectAndStay)
+                    .with(KeyModifiers::CONTROL)
+                    .in_search(),
+                KeyBinding::new(Up, MoveUp).in_search(),
+                KeyBinding::new(Char('k'), MoveUp),
+                KeyBinding::new(Down, MoveDown).in_search(),
+                KeyBinding::new(Char('j'), MoveDown),
+                KeyBinding::new(Left, ParentDirectory),
+                KeyBinding::new(Char('h'), ParentDirectory),
+                KeyBinding::new(Right, EnterDirectory),
+                KeyBinding::new(Char('l'), EnterDirectory),
+                KeyBinding::new(Char('f'), HalfPageUp),
+                KeyBinding::new(Char('b'), HalfPageDown),
+                KeyBinding::new(Home, JumpToFirst),
+                KeyBinding::new(Char('g'), JumpToFirst).twice(),
+                KeyBinding::new(End, JumpToLast),
+                KeyBinding::new(Char('G'), JumpToLast),
+                KeyBinding::new(Char('u'), HalfPageUp).with(KeyModifiers::CONTROL),
+                KeyBinding::new(Char('d'), HalfPageDown).with(KeyModifiers::CONTROL),
+                KeyBinding::new(PageUp, PreviewPageUp).in_search(),
+                KeyBinding::new(PageDown, PreviewPageDown).in_search(),
+                KeyBinding::new(Char('['), PreviewPreviousPage),
+                KeyBinding::new(Char(']'), PreviewNextPage),
+                KeyBinding::new(Char('+'), ZoomImageIn),
+                KeyBinding::new(Char('='), ZoomImageIn),
+                KeyBinding::new(Char('-'), ZoomImageOut),
+                KeyBinding::new(Left, PanImageLeft).with(KeyModifiers::SHIFT),
+                KeyBinding::new(Char('H'), PanImageLeft),
+                KeyBinding::new(Right, PanImageRight).with(KeyModifiers::SHIFT),
+                KeyBinding::new(Char('L'), PanImageRight),
+                KeyBinding::new(Up, PanImageUp).with(KeyModifiers::SHIFT),
+                KeyBinding::new(Char('K'), PanImageUp),
+                KeyBinding::new(Down, PanImageDown).with(KeyModifiers::SHIFT),
+                KeyBinding::new(Char('J'), PanImageDown),
+                KeyBinding::new(Char('/'), StartSearch),
+                KeyBinding::new(Char(','), StartTypeAhead),
+                KeyBinding::new(Char('v'), SwitchToHistory),
+                KeyBinding::new(Char('F'), SwitchToRecentFiles),
+                KeyBinding::new(Char('z'), SwitchToJump),
+                KeyBinding::new(Char('R'), SwitchToRepos),
+                KeyBinding::new(Char('P'), SwitchToProjects),
+                KeyBinding::new(Char('M'), SwitchToMounts),
+                KeyBinding::new(Char('.'), ToggleHiddenFiles),
+                KeyBinding::new(Char(' '), ToggleMark),
+                KeyBinding::new(Char('a'), EditAlias),
+                KeyBinding::new(Char('d'), DeleteEntry),
+                KeyBinding::new(Char('u'), UndoDelete),
+                KeyBinding::new(Char('t'), CycleEntryFilter)
+                    .with(KeyModifiers::CONTROL)
+                    .in_search(),
+                KeyBinding::new(Char('c'), CycleCaseMatching)
+                    .with(KeyModifiers::ALT)
+                    .in_search(),
+                KeyBinding::new(Char('p'), TogglePreview),
+                KeyBinding::new(Char('D'), ShowDiskUsage),
+                KeyBinding::new(Char('#'), ToggleLineNumbers),
+                KeyBinding::new(Char('T'), ToggleTreeView),
+                KeyBinding::new(Tab, OpenPager),
+                KeyBinding::new(Char('W'), ToggleDualPane),
+                KeyBinding::new(Char('w'), SwitchPane),
+                KeyBinding::new(Char('m'), SetMark),
+                KeyBinding::new(Char('\''), JumpToMark),
+                KeyBinding::new(Char('q'), RecordMacro),
+                KeyBinding::new(Char('Q'), QuitInCurrentDir),
+                KeyBinding::new(Char('@'), ReplayMacro),
+                KeyBinding::new(Char('<'), GrowPreview),
+                KeyBinding::new(Char('>'), ShrinkPreview),
+                KeyBinding::new(Char('?'), ShowHelp),
+                // Line editing in the search box
+                KeyBinding::new(Left, SearchCursorLeft).only_in_search(),
+                KeyBinding::new(Right, SearchCursorRight).only_in_search(),
+                KeyBinding::new(Char('a'), SearchCursorHome)
+                    .with(KeyModifiers::CONTROL)
+                    .only_in_search(),
+                KeyBinding::new(Home, SearchCursorHome).only_in_search(),
+                KeyBinding::new(Char('e'), SearchCursorEnd)
+                    .with(KeyModifiers::CONTROL)
+                    .only_in_search(),
+                KeyBinding::new(End, SearchCursorEnd).only_in_search(),
+                KeyBinding::new(Backspace, SearchDeleteBackward).only_in_search(),
+                KeyBinding::new(Delete, SearchDeleteForward).only_in_search(),
+                KeyBinding::new(Char('w'), SearchDeleteWord)
+                    .with(KeyModifiers::CONTROL)
+                    .only_in_search(),
+                KeyBinding::new(Char('u'), SearchClear)
+                    .with(KeyModifiers::CONTROL)
+                    .only_in_search(),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Find the action bound to a key in the current context
+    ///
+    /// `previous` is the key pressed just before, used to complete double-press
+    /// bindings such as `gg`.
+    pub fn resolve(
+        &self,
+        key: &KeyEvent,
+        previous: Option<&KeyEvent>,
+        is_searching: bool,
+        mode: &AppMode,
+    ) -> Option<Action> {
+        self.active_bindings(key, is_searching, mode)
+            .find(|binding| !binding.double || previous.is_some_and(|prev| binding.matches(prev)))
+            .map(|binding| binding.action)
+    }
+
+    /// Check if the key is the first press of a double-press binding
+    pub fn starts_sequence(&self, key: &KeyEvent, is_searching: bool, mode: &AppMode) -> bool {
+        self.active_bindings(key, is_searching, mode)
+            .any(|binding| binding.double)
+    }
+
+    fn active_bindings<'a>(
+        &'a self,
+        key: &'a KeyEvent,
+        is_searching: bool,
+        mode: &'a AppMode,
+    ) -> impl Iterator<Item = &'a KeyBinding> {
+        self.bindings.iter().filter(move |binding| {
+            binding.matches(key)
+                && binding.is_active(is_searching)
+                && binding.action.is_available(mode)
+        })
+    }
+
+    /// List the bindings available in a mode, grouping keys bound to the same action
+    pub fn help_entries(&self, mode: &AppMode) -> Vec<(String, &'static str)> {
+        let mut entries: Vec<(Action, Vec<String>)> = Vec::new();
+        for binding in self
+            .bindings
+            .iter()
+            .filter(|binding| binding.action.is_available(mode))
+        {
+            let mut label = key_label(binding.code, binding.modifiers);
+            if binding.double {
+                label = label.repeat(2);
+            }
+            match entries
+                .iter_mut()
+                .find(|(action, _)| *action == binding.action)
+            {
+                Some((_, keys)) => keys.push(label),
+                None => entries.push((binding.action, vec![label])),
+            }
+        }
+
+        entries
+            .into_iter()
+            .map(|(action, keys)| (keys.join("/"), action.description(mode)))
+            .collect()
+    }
+}
+
+/// Reduce a key event to the code and modifiers bindings are matched against
+///
+/// Shift is already part of the character for printable keys (`?`, `V`), and
+/// terminals disagree on whether they still report it, so it is dropped there.
+fn normalize(key: &KeyEvent) -> (KeyCode, KeyModifiers) {
+    let modifiers =
+        key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Char(c) => (KeyCode::Char(c), modifiers - KeyModifiers::SHIFT),
+        KeyCode::BackTab => (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT),
+        code => (code, modifiers),
+    }
+}
+
+/// Format a key combination for display in help texts
+pub fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let mut label = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("Alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        label.push_str("Shift+");
+    }
+    label.push_str(&code_label(code));
+    label
+}
+
+fn code_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// The active key map
+pub static KEYMAP: Lazy<KeyMap> = Lazy::new(KeyMap::default);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_with_modifiers() {
+        let keymap = KeyMap::default();
+        let mode = AppMode::Normal;
+
+        // Shift is implied by the character itself
+        let help = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT);
+        assert_eq!(
+            keymap.resolve(&help, None, false, &mode),
+            Some(Action::ShowHelp)
+        );
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let ctrl_enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL);
+        assert_eq!(
+            keymap.resolve(&enter, None, true, &mode),
+            Some(Action::Select)
+        );
+        assert_eq!(
+            keymap.resolve(&ctrl_enter, None, true, &mode),
+            Some(Action::SelectAndStay)
+        );
+
+        // Plain letters are typed into the search box, chords are not
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(
+            keymap.resolve(&ctrl_d, None, false, &mode),
+            Some(Action::HalfPageDown)
+        );
+        assert_eq!(keymap.resolve(&j, None, true, &mode), None);
+        assert_eq!(
+            key_label(KeyCode::Enter, KeyModifiers::CONTROL),
+            "Ctrl+Enter"
+        );
+    }
+
+    #[test]
+    fn test_resolve_double_press() {
+        let keymap = KeyMap::default();
+        let mode = AppMode::Normal;
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+
+        assert_eq!(keymap.resolve(&g, None, false, &mode), None);
+        assert!(keymap.starts_sequence(&g, false, &mode));
+        assert_eq!(
+            keymap.resolve(&g, Some(&g), false, &mode),
+            Some(Action::JumpToFirst)
+        );
+        assert_eq!(keymap.resolve(&g, Some(&j), false, &mode), None);
+    }
+
+    #[test]
+    fn test_help_entries_follow_mode() {
+        let keymap = KeyMap::default();
+        let history = keymap.help_entries(&AppMode::History);
+        let normal = keymap.help_entries(&AppMode::Normal);
+
+        assert!(history.contains(&("a".to_string(), "Set alias for entry")));
+        assert!(!history.iter().any(|(keys, _)| keys == "v"));
+        assert!(normal.contains(&("v".to_string(), "Enter history mode")));
+        assert!(normal.contains(&("↑/k".to_string(), "Move up")));
+    }
+}
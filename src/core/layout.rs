@@ -1,7 +1,12 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
+/// Default width of the preview panel in percent of the main area
+pub const DEFAULT_PREVIEW_WIDTH: u16 = 50;
+const MIN_PREVIEW_WIDTH: u16 = 10;
+const MAX_PREVIEW_WIDTH: u16 = 90;
+
 /// Layout manager for handling UI area calculations and management
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct LayoutManager {
     /// The entire terminal area
     pub terminal_area: Rect,
@@ -13,39 +18,78 @@ pub struct LayoutManager {
     pub left_area: Rect,
     /// Right panel area (preview or help)
     pub right_area: Rect,
+    /// List of the unfocused pane in dual-pane mode, empty otherwise
+    pub other_area: Rect,
+    /// Width of the right panel in percent of the main area
+    preview_width: u16,
+    /// Whether the right panel is shown at all
+    preview_visible: bool,
+    /// Whether two file lists are shown side by side instead of list and preview
+    dual_pane: bool,
+    /// Whether the focused pane is the one on the right in dual-pane mode
+    focus_right: bool,
     /// Whether the layout has been initialized
     initialized: bool,
 }
 
+impl Default for LayoutManager {
+    fn default() -> Self {
+        Self {
+            terminal_area: Rect::default(),
+            search_area: Rect::default(),
+            main_area: Rect::default(),
+            left_area: Rect::default(),
+            right_area: Rect::default(),
+            other_area: Rect::default(),
+            preview_width: DEFAULT_PREVIEW_WIDTH,
+            preview_visible: true,
+            dual_pane: false,
+            focus_right: false,
+            initialized: false,
+        }
+    }
+}
+
 impl LayoutManager {
     /// Create a new layout manager
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a new layout manager with the given preview panel width in percent
+    pub fn with_preview_width(preview_width: u16) -> Self {
+        Self {
+            preview_width: preview_width.clamp(MIN_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH),
+            ..Self::default()
+        }
+    }
+
     /// Initialize or update the layout based on terminal size
     pub fn update_layout(&mut self, terminal_size: Rect) {
-        self.terminal_area = terminal_size;
-
-        // Split vertically: search box (3 lines) + main content
-        let vertical_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)])
-            .split(self.terminal_area);
-
-        self.search_area = vertical_chunks[0];
-        self.main_area = vertical_chunks[1];
-
-        // Split main area horizontally: left panel (50%) + right panel (50%)
-        let horizontal_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(self.main_area);
-
-        self.left_area = horizontal_chunks[0];
-        self.right_area = horizontal_chunks[1];
-
-        self.initialized = true;
+        if self.dual_pane {
+            self.update_layout_with_constraints(
+                terminal_size,
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            );
+            // The focused list is always `left_area`, whichever side it is on
+            self.other_area = std::mem::take(&mut self.right_area);
+            if self.focus_right {
+                std::mem::swap(&mut self.left_area, &mut self.other_area);
+            }
+            return;
+        }
+
+        // Split main area horizontally: left panel + right panel (preview_width%)
+        let (left_constraint, right_constraint) = if self.preview_visible {
+            (
+                Constraint::Percentage(100 - self.preview_width),
+                Constraint::Percentage(self.preview_width),
+            )
+        } else {
+            (Constraint::Percentage(100), Constraint::Length(0))
+        };
+        self.update_layout_with_constraints(terminal_size, left_constraint, right_constraint);
     }
 
     /// Update layout with custom constraints for left/right panels
@@ -74,10 +118,83 @@ impl LayoutManager {
 
         self.left_area = horizontal_chunks[0];
         self.right_area = horizontal_chunks[1];
+        self.other_area = Rect::default();
 
         self.initialized = true;
     }
 
+    /// Check if the preview panel is shown, which it isn't in dual-pane mode
+    pub fn is_preview_visible(&self) -> bool {
+        self.preview_visible && !self.dual_pane
+    }
+
+    /// Check if two file lists are shown side by side
+    pub fn is_dual_pane(&self) -> bool {
+        self.dual_pane
+    }
+
+    /// Show two file lists instead of the list and the preview, or go back
+    pub fn set_dual_pane(&mut self, dual_pane: bool) {
+        self.dual_pane = dual_pane;
+        self.focus_right = false;
+        if self.initialized {
+            self.update_layout(self.terminal_area);
+        }
+    }
+
+    /// Move the focus to the other pane in dual-pane mode
+    pub fn swap_focus(&mut self) {
+        self.focus_right = !self.focus_right;
+        if self.initialized {
+            self.update_layout(self.terminal_area);
+        }
+    }
+
+    /// Show or hide the preview panel, giving the list the full width when hidden
+    pub fn toggle_preview(&mut self) {
+        self.preview_visible = !self.preview_visible;
+        if self.initialized {
+            self.update_layout(self.terminal_area);
+        }
+    }
+
+    /// Get the width of the preview panel in percent
+    pub fn get_preview_width(&self) -> u16 {
+        self.preview_width
+    }
+
+    /// Set the width of the preview panel in percent and recalculate the layout
+    pub fn set_preview_width(&mut self, preview_width: u16) {
+        self.preview_width = preview_width.clamp(MIN_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH);
+        if self.initialized {
+            self.update_layout(self.terminal_area);
+        }
+    }
+
+    /// Grow (positive delta) or shrink (negative delta) the preview panel
+    pub fn resize_preview(&mut self, delta: i16) {
+        let new_width = (self.preview_width as i16 + delta).max(0) as u16;
+        self.set_preview_width(new_width);
+    }
+
+    /// Check if a point is on the border between the left and right panels
+    pub fn is_on_divider(&self, x: u16, y: u16) -> bool {
+        self.is_preview_visible()
+            && y >= self.main_area.y
+            && y < self.main_area.y + self.main_area.height
+            && (x + 1 == self.right_area.x || x == self.right_area.x)
+    }
+
+    /// Move the panel divider to the given terminal column
+    pub fn drag_divider_to(&mut self, x: u16) {
+        if self.main_area.width == 0 {
+            return;
+        }
+        let right_width = (self.main_area.x + self.main_area.width).saturating_sub(x);
+        let percent = (right_width as u32 * 100 / self.main_area.width as u32) as u16;
+        self.set_preview_width(percent);
+    }
+
     /// Check if the layout has been initialized
     pub fn is_initialized(&self) -> bool {
         self.initialized
@@ -108,6 +225,11 @@ impl LayoutManager {
         self.right_area
     }
 
+    /// Get the area of the unfocused pane in dual-pane mode
+    pub fn get_other_area(&self) -> Rect {
+        self.other_area
+    }
+
     /// Check if a point (x, y) is within the left area
     pub fn is_in_left_area(&self, x: u16, y: u16) -> bool {
         x >= self.left_area.x
@@ -124,6 +246,14 @@ impl LayoutManager {
             && y < self.right_area.y + self.right_area.height
     }
 
+    /// Check if a point (x, y) is within the unfocused pane
+    pub fn is_in_other_area(&self, x: u16, y: u16) -> bool {
+        x >= self.other_area.x
+            && x < self.other_area.x + self.other_area.width
+            && y >= self.other_area.y
+            && y < self.other_area.y + self.other_area.height
+    }
+
     /// Check if a point (x, y) is within the search area
     pub fn is_in_search_area(&self, x: u16, y: u16) -> bool {
         x >= self.search_area.x
@@ -167,6 +297,12 @@ impl LayoutManager {
         self.get_content_height(self.right_area)
     }
 
+    /// Get the visible content height of the full-screen pager, which keeps
+    /// the bottom line for its status
+    pub fn get_pager_content_height(&self) -> usize {
+        self.terminal_area.height.saturating_sub(3) as usize
+    }
+
     /// Get the visible content width for the left panel
     pub fn get_left_content_width(&self) -> usize {
         self.get_content_width(self.left_area)
@@ -230,4 +366,58 @@ mod tests {
         assert!(right_content_height > 0);
         assert_eq!(left_content_height, right_content_height);
     }
+
+    #[test]
+    fn test_preview_width_and_toggle() {
+        let mut layout = LayoutManager::with_preview_width(60);
+        layout.update_layout(Rect::new(0, 0, 100, 50));
+
+        assert_eq!(layout.get_left_area().width, 40);
+        assert_eq!(layout.get_right_area().width, 60);
+
+        layout.toggle_preview();
+        assert!(!layout.is_preview_visible());
+        assert_eq!(layout.get_left_area().width, 100);
+        assert_eq!(layout.get_right_area().width, 0);
+
+        layout.toggle_preview();
+        assert_eq!(layout.get_right_area().width, 60);
+    }
+
+    #[test]
+    fn test_resize_preview() {
+        let mut layout = LayoutManager::new();
+        layout.update_layout(Rect::new(0, 0, 100, 50));
+
+        layout.resize_preview(10);
+        assert_eq!(layout.get_right_area().width, 60);
+
+        layout.resize_preview(-100);
+        assert_eq!(layout.get_preview_width(), 10);
+
+        layout.drag_divider_to(30);
+        assert_eq!(layout.get_preview_width(), 70);
+        assert!(layout.is_on_divider(layout.get_right_area().x, 10));
+    }
+
+    #[test]
+    fn test_dual_pane() {
+        let mut layout = LayoutManager::with_preview_width(70);
+        layout.update_layout(Rect::new(0, 0, 100, 50));
+
+        layout.set_dual_pane(true);
+        assert!(!layout.is_preview_visible());
+        assert_eq!(layout.get_left_area().x, 0);
+        assert_eq!(layout.get_other_area().x, 50);
+        assert_eq!(layout.get_right_area().width, 0);
+
+        layout.swap_focus();
+        assert_eq!(layout.get_left_area().x, 50);
+        assert!(layout.is_in_other_area(10, 10));
+
+        layout.set_dual_pane(false);
+        assert!(layout.is_preview_visible());
+        assert_eq!(layout.get_right_area().width, 70);
+        assert_eq!(layout.get_other_area(), Rect::default());
+    }
 }
@@ -0,0 +1,288 @@
+use std::{borrow::Cow, ffi::OsStr, ops::Range, path::PathBuf};
+
+use crate::utils::{DisplayItem, FileItem, FileKind};
+
+/// Directory listings at least this long are packed, see [`Listing::from_files`]
+pub const COMPACT_LISTING_ENTRIES: usize = 10_000;
+
+/// The entries a mode lists
+///
+/// Entries are handed out by index. Short listings keep their items, while
+/// huge directories only keep the names and flags of their entries and make
+/// an item for each entry that is asked for, like the rows on screen.
+#[derive(Debug, Default)]
+pub struct Listing(Store);
+
+#[derive(Debug)]
+enum Store {
+    Items(Vec<DisplayItem>),
+    Directory(CompactDirectory),
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Store::Items(Vec::new())
+    }
+}
+
+/// The entries of one directory, packed into a few allocations
+///
+/// An entry takes the length of its name and 7 bytes, where a [`FileItem`]
+/// takes about 60 bytes plus two allocations for its name and path.
+#[derive(Debug)]
+struct CompactDirectory {
+    dir: PathBuf,
+    /// The names of all entries, one after another
+    names: String,
+    /// Where each name ends in `names`
+    ends: Vec<u32>,
+    flags: Vec<EntryFlags>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EntryFlags {
+    kind: FileKind,
+    is_dir: bool,
+    hidden: bool,
+}
+
+impl CompactDirectory {
+    /// Pack `files`, unless one of them isn't `dir/name` for a shared `dir`
+    fn pack(files: &[FileItem]) -> Option<Self> {
+        let dir = files.first()?.path.parent()?.to_path_buf();
+        let mut names = String::new();
+        let mut ends = Vec::with_capacity(files.len());
+        let mut flags = Vec::with_capacity(files.len());
+        for file in files {
+            // Names that aren't valid UTF-8 are listed lossily and can't be joined back
+            if file.path.parent() != Some(dir.as_path())
+                || file.path.file_name() != Some(OsStr::new(&file.name))
+            {
+                return None;
+            }
+            names.push_str(&file.name);
+            ends.push(u32::try_from(names.len()).ok()?);
+            flags.push(EntryFlags {
+                kind: file.kind,
+                is_dir: file.is_dir,
+                hidden: file.hidden,
+            });
+        }
+        names.shrink_to_fit();
+        Some(Self {
+            dir,
+            names,
+            ends,
+            flags,
+        })
+    }
+
+    fn name(&self, index: usize) -> &str {
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        };
+        &self.names[start..self.ends[index] as usize]
+    }
+
+    fn item(&self, index: usize) -> Option<DisplayItem> {
+        let mut item = blank_file();
+        self.fill(index, &mut item)
+            .then_some(DisplayItem::File(item))
+    }
+
+    /// Turn `item` into the entry at `index`, reusing its buffers
+    fn fill(&self, index: usize, item: &mut FileItem) -> bool {
+        let Some(flags) = self.flags.get(index) else {
+            return false;
+        };
+        let name = self.name(index);
+        item.name.clear();
+        item.name.push_str(name);
+        item.path.as_mut_os_string().clear();
+        item.path.push(&self.dir);
+        item.path.push(name);
+        item.is_dir = flags.is_dir;
+        item.kind = flags.kind;
+        item.hidden = flags.hidden;
+        true
+    }
+}
+
+/// A file item to fill in with [`CompactDirectory::fill`]
+fn blank_file() -> FileItem {
+    FileItem {
+        name: String::new(),
+        path: PathBuf::new(),
+        is_dir: false,
+        kind: FileKind::default(),
+        hidden: false,
+    }
+}
+
+impl Listing {
+    /// The entries of a directory, packed when there are at least
+    /// [`COMPACT_LISTING_ENTRIES`] of them
+    pub fn from_files(files: Vec<FileItem>) -> Self {
+        if files.len() >= COMPACT_LISTING_ENTRIES
+            && let Some(directory) = CompactDirectory::pack(&files)
+        {
+            return Self(Store::Directory(directory));
+        }
+        files.into_iter().map(DisplayItem::File).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Store::Items(items) => items.len(),
+            Store::Directory(directory) => directory.flags.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the entries are packed rather than kept as items
+    pub fn is_compact(&self) -> bool {
+        matches!(self.0, Store::Directory(_))
+    }
+
+    /// The entry at `index`, made on the spot when the listing is packed
+    pub fn get(&self, index: usize) -> Option<Cow<'_, DisplayItem>> {
+        match &self.0 {
+            Store::Items(items) => items.get(index).map(Cow::Borrowed),
+            Store::Directory(directory) => directory.item(index).map(Cow::Owned),
+        }
+    }
+
+    /// Call `f` with each of the entries at `indices` that exist
+    ///
+    /// Packed entries are made in one reused item, so going over a huge
+    /// directory allocates nothing per entry.
+    pub fn visit(
+        &self,
+        indices: impl IntoIterator<Item = usize>,
+        mut f: impl FnMut(usize, &DisplayItem),
+    ) {
+        match &self.0 {
+            Store::Items(items) => {
+                for index in indices {
+                    if let Some(item) = items.get(index) {
+                        f(index, item);
+                    }
+                }
+            }
+            Store::Directory(directory) => {
+                let mut item = DisplayItem::File(blank_file());
+                for index in indices {
+                    let DisplayItem::File(file) = &mut item else {
+                        unreachable!("the reused item is a file");
+                    };
+                    if directory.fill(index, file) {
+                        f(index, &item);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Position in `indices` of the first entry `matches` is true for
+    ///
+    /// Packed entries are checked in one reused item like with [`Listing::visit`].
+    pub fn find_position(
+        &self,
+        indices: &[usize],
+        mut matches: impl FnMut(&DisplayItem) -> bool,
+    ) -> Option<usize> {
+        match &self.0 {
+            Store::Items(items) => indices
+                .iter()
+                .position(|&index| items.get(index).is_some_and(&mut matches)),
+            Store::Directory(directory) => {
+                let mut item = DisplayItem::File(blank_file());
+                indices.iter().position(|&index| {
+                    let DisplayItem::File(file) = &mut item else {
+                        unreachable!("the reused item is a file");
+                    };
+                    directory.fill(index, file) && matches(&item)
+                })
+            }
+        }
+    }
+
+    /// Indices of the entries in `range` that `keep` is true for
+    pub fn positions(
+        &self,
+        range: Range<usize>,
+        mut keep: impl FnMut(&DisplayItem) -> bool,
+    ) -> Vec<usize> {
+        let mut kept = Vec::new();
+        self.visit(range, |index, item| {
+            if keep(item) {
+                kept.push(index);
+            }
+        });
+        kept
+    }
+}
+
+impl FromIterator<DisplayItem> for Listing {
+    fn from_iter<I: IntoIterator<Item = DisplayItem>>(items: I) -> Self {
+        Self(Store::Items(items.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(dir: &str, count: usize) -> Vec<FileItem> {
+        (0..count)
+            .map(|i| {
+                let name = format!("entry{i}");
+                FileItem {
+                    path: PathBuf::from(dir).join(&name),
+                    name,
+                    is_dir: i % 3 == 0,
+                    kind: if i % 3 == 0 {
+                        FileKind::Directory
+                    } else {
+                        FileKind::Regular
+                    },
+                    hidden: i % 5 == 0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_packed_entries_match_the_files() {
+        let files = files("/data/huge", COMPACT_LISTING_ENTRIES);
+        let listing = Listing::from_files(files.clone());
+        assert!(listing.is_compact());
+        assert_eq!(listing.len(), files.len());
+        for index in [0, 1, 4_999, COMPACT_LISTING_ENTRIES - 1] {
+            match listing.get(index).as_deref() {
+                Some(DisplayItem::File(file)) => assert_eq!(file, &files[index]),
+                other => panic!("unexpected entry {other:?}"),
+            }
+        }
+        assert!(listing.get(COMPACT_LISTING_ENTRIES).is_none());
+        let wanted = PathBuf::from("/data/huge/entry42");
+        assert_eq!(
+            listing.find_position(&[7, 42, 3], |item| item.get_path() == &wanted),
+            Some(1)
+        );
+        assert_eq!(
+            listing.find_position(&[7, 3], |item| item.get_path() == &wanted),
+            None
+        );
+
+        // Entries of other directories, like in the drives view, stay items
+        let mut mixed = files;
+        mixed[7].path = PathBuf::from("/elsewhere/entry7");
+        assert!(!Listing::from_files(mixed).is_compact());
+        assert!(!Listing::from_files(self::files("/data/small", 10)).is_compact());
+    }
+}
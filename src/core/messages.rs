@@ -0,0 +1,98 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How long a message stays on screen before it expires
+const INFO_TIMEOUT: Duration = Duration::from_secs(3);
+const ERROR_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Maximum number of queued messages, older ones are dropped first
+const MAX_MESSAGES: usize = 8;
+
+/// Severity of a user facing message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Error,
+}
+
+/// A notification shown to the user
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub level: MessageLevel,
+    pub text: String,
+    shown_at: Option<Instant>,
+}
+
+impl Message {
+    fn timeout(&self) -> Duration {
+        match self.level {
+            MessageLevel::Info => INFO_TIMEOUT,
+            MessageLevel::Error => ERROR_TIMEOUT,
+        }
+    }
+}
+
+/// Queue of pending notifications, displayed one at a time
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    messages: VecDeque<Message>,
+}
+
+impl MessageQueue {
+    /// Queue an informational message
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(MessageLevel::Info, text.into());
+    }
+
+    /// Queue an error message
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(MessageLevel::Error, text.into());
+    }
+
+    fn push(&mut self, level: MessageLevel, text: String) {
+        // Repeating the same action should not stack identical messages
+        if self.messages.back().is_some_and(|last| last.text == text) {
+            return;
+        }
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            level,
+            text,
+            shown_at: None,
+        });
+    }
+
+    /// The message currently on screen
+    pub fn current(&self) -> Option<&Message> {
+        self.messages.front()
+    }
+
+    /// Number of messages waiting behind the current one
+    pub fn pending(&self) -> usize {
+        self.messages.len().saturating_sub(1)
+    }
+
+    /// Dismiss the message currently on screen
+    pub fn dismiss(&mut self) {
+        self.messages.pop_front();
+    }
+
+    /// Start the timer of the current message and drop it once it expired
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(message) = self.messages.front_mut() {
+            let shown_at = *message.shown_at.get_or_insert(now);
+            if now.duration_since(shown_at) >= message.timeout() {
+                self.messages.pop_front();
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
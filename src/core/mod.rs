@@ -1,6 +1,12 @@
+pub mod breadcrumb;
 pub mod events;
+pub mod filter;
 pub mod input_dispatcher;
+pub mod keymap;
 pub mod layout;
+pub mod listing;
+pub mod messages;
+pub mod signals;
 
 // Re-export commonly used types
 pub use input_dispatcher::InputDispatcher;
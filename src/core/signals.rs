@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+use tracing::{info, warn};
+
+/// Conventional signal numbers, used for the exit status after a shutdown
+pub const SIGHUP: i32 = 1;
+pub const SIGINT: i32 = 2;
+pub const SIGTERM: i32 = 15;
+
+/// Signal that asked the application to quit, 0 while running
+static RECEIVED_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Listen for termination signals in the background
+///
+/// The handlers only record the signal. The event loop polls
+/// [`shutdown_requested`] so the terminal is restored on the normal exit path.
+pub fn install_signal_handlers() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        for kind in [
+            SignalKind::terminate(),
+            SignalKind::hangup(),
+            SignalKind::interrupt(),
+        ] {
+            match signal(kind) {
+                Ok(mut stream) => {
+                    tokio::spawn(async move {
+                        if stream.recv().await.is_some() {
+                            request_shutdown(kind.as_raw_value());
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to install handler for signal {kind:?}: {e}"),
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_break, ctrl_close};
+
+        match ctrl_close() {
+            Ok(mut stream) => {
+                tokio::spawn(async move {
+                    if stream.recv().await.is_some() {
+                        request_shutdown(SIGHUP);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to install console close handler: {e}"),
+        }
+        match ctrl_break() {
+            Ok(mut stream) => {
+                tokio::spawn(async move {
+                    if stream.recv().await.is_some() {
+                        request_shutdown(SIGINT);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to install Ctrl+Break handler: {e}"),
+        }
+    }
+}
+
+/// Ask the event loop to shut down as if `signal` had been received
+pub fn request_shutdown(signal: i32) {
+    info!(signal, "Shutdown requested");
+    // Keep the first signal if several arrive
+    let _ = RECEIVED_SIGNAL.compare_exchange(0, signal, Ordering::SeqCst, Ordering::SeqCst);
+}
+
+/// Forget a shutdown request, once an embedded picker was left with Ctrl+C
+pub fn clear_shutdown_request() {
+    RECEIVED_SIGNAL.store(0, Ordering::SeqCst);
+}
+
+pub fn shutdown_requested() -> bool {
+    RECEIVED_SIGNAL.load(Ordering::SeqCst) != 0
+}
+
+/// Exit status to report after a signal, following the `128 + n` shell convention
+pub fn exit_code() -> Option<i32> {
+    match RECEIVED_SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(128 + signal),
+    }
+}
@@ -0,0 +1,207 @@
+use crossterm::terminal;
+use ratatui_image::picker::{Capability, Picker, ProtocolType};
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    Result,
+    config::{self, Config},
+    services::HistoryStore,
+};
+
+/// Colors the terminal can show, as far as its environment tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Colors256,
+    Colors16,
+    /// `TERM=dumb`, which can't show colors or move the cursor
+    None,
+}
+
+impl ColorDepth {
+    /// Guess the depth from `COLORTERM` and `TERM`, the way most programs do
+    pub fn detect(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let term = term.unwrap_or_default();
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            ColorDepth::TrueColor
+        } else if term == "dumb" {
+            ColorDepth::None
+        } else if term.contains("256color") || term.contains("direct") {
+            ColorDepth::Colors256
+        } else {
+            ColorDepth::Colors16
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ColorDepth::TrueColor => "24-bit (truecolor)",
+            ColorDepth::Colors256 => "256 colors",
+            ColorDepth::Colors16 => "16 colors",
+            ColorDepth::None => "none",
+        }
+    }
+}
+
+/// Print what quickswitch detects about the terminal and its data files
+///
+/// Meant to be pasted into bug reports, so it never fails on what it checks.
+pub fn run_doctor() -> Result<()> {
+    println!("quickswitch {}", env!("CARGO_PKG_VERSION"));
+
+    println!("\nTerminal");
+    let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    let term = var("TERM");
+    let colorterm = var("COLORTERM");
+    row("TERM", term.as_deref().unwrap_or("(not set)"));
+    row(
+        "TERM_PROGRAM",
+        var("TERM_PROGRAM").as_deref().unwrap_or("(not set)"),
+    );
+    if let Some(multiplexer) = ["TMUX", "STY", "ZELLIJ"]
+        .iter()
+        .find(|name| var(name).is_some())
+    {
+        row("multiplexer", multiplexer);
+    }
+    let tty = io::stdin().is_terminal() && io::stdout().is_terminal();
+    row(
+        "interactive",
+        if tty {
+            "yes"
+        } else {
+            "no, stdin or stdout is redirected"
+        },
+    );
+    match terminal::size() {
+        Ok((columns, rows)) => row("size", &format!("{columns}x{rows}")),
+        Err(e) => row("size", &format!("unknown ({e})")),
+    }
+    let depth = ColorDepth::detect(colorterm.as_deref(), term.as_deref());
+    row("colors", depth.label());
+    if var("NO_COLOR").is_some() {
+        row("", "NO_COLOR is set, quickswitch draws without colors");
+    }
+    row(
+        "mouse",
+        match term.as_deref() {
+            Some("dumb") => "not supported",
+            Some("linux") => "only with gpm on the Linux console",
+            _ => "yes, if the terminal reports mouse events",
+        },
+    );
+
+    println!("\nImage preview");
+    // The query is answered on stdin, so it only works in an interactive terminal
+    if tty {
+        match Picker::from_query_stdio() {
+            Ok(picker) => {
+                row("protocol", protocol_label(picker.protocol_type()));
+                let (width, height) = picker.font_size();
+                row("cell size", &format!("{width}x{height} px"));
+                let reported: Vec<&str> = picker
+                    .capabilities()
+                    .iter()
+                    .filter_map(|capability| match capability {
+                        Capability::Kitty => Some("kitty"),
+                        Capability::Sixel => Some("sixel"),
+                        _ => None,
+                    })
+                    .collect();
+                if !reported.is_empty() {
+                    row("reported", &reported.join(", "));
+                }
+            }
+            Err(e) => row(
+                "protocol",
+                &format!("halfblocks, the terminal didn't answer the query ({e})"),
+            ),
+        }
+    } else {
+        row(
+            "protocol",
+            "not queried, run doctor in an interactive terminal",
+        );
+    }
+
+    println!("\nFiles");
+    let config_path = config::get_config_file_path();
+    let config_state = if !config_path.exists() {
+        "not found, using defaults".to_string()
+    } else {
+        match Config::load() {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("{e:#}"),
+        }
+    };
+    row(
+        "config",
+        &format!("{} ({config_state})", config_path.display()),
+    );
+    match config::get_data_dir() {
+        Ok(data_dir) => {
+            let writable = tempfile::tempfile_in(&data_dir)
+                .map_or_else(|e| format!("not writable: {e}"), |_| "writable".to_string());
+            row("data dir", &format!("{} ({writable})", data_dir.display()));
+        }
+        Err(e) => row("data dir", &format!("unavailable: {e:#}")),
+    }
+    for (label, store) in [
+        ("history", HistoryStore::DIRECTORIES),
+        ("recent files", HistoryStore::RECENT_FILES),
+    ] {
+        let health = store.health();
+        let state = match (&health.entries, health.size) {
+            (Err(e), _) => format!("damaged: {e}"),
+            (Ok(_), None) => "not created yet".to_string(),
+            (Ok(entries), Some(size)) => format!("{entries} entries, {size} bytes"),
+        };
+        row(label, &format!("{} ({state})", health.path.display()));
+        if health.journal_visits > 0 {
+            row(
+                "",
+                &format!("{} visits in the journal", health.journal_visits),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn protocol_label(protocol: ProtocolType) -> &'static str {
+    match protocol {
+        ProtocolType::Kitty => "kitty",
+        ProtocolType::Sixel => "sixel",
+        ProtocolType::Iterm2 => "iTerm2",
+        ProtocolType::Halfblocks => "halfblocks, no image protocol detected",
+    }
+}
+
+fn row(label: &str, value: &str) {
+    println!("  {label:<14}{value}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_depth_detection() {
+        assert_eq!(
+            ColorDepth::detect(Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("screen-256color")),
+            ColorDepth::Colors256
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("xterm")),
+            ColorDepth::Colors16
+        );
+        assert_eq!(ColorDepth::detect(None, Some("dumb")), ColorDepth::None);
+        assert_eq!(ColorDepth::detect(None, None), ColorDepth::Colors16);
+    }
+}
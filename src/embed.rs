@@ -0,0 +1,136 @@
+//! Run quickswitch from other programs
+//!
+//! A [`Picker`] shows the same UI as the binary and hands the selection back
+//! instead of printing it and exiting:
+//!
+//! ```no_run
+//! use quickswitch::{Picker, PickerOptions};
+//!
+//! # async fn pick() -> quickswitch::Result<()> {
+//! let picked = Picker::new(PickerOptions::default()).run().await?;
+//! if let Some(selection) = picked {
+//!     println!("{}", selection.path.display());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use std::path::PathBuf;
+
+use crate::{
+    App, Result,
+    core::signals,
+    modes::picker,
+    services::{filesystem::strip_verbatim_prefix, plugins},
+    terminal::{self, Height, InteractiveOptions},
+    utils::{AppMode, FileItem},
+};
+
+/// What a [`Picker`] starts with
+#[derive(Debug, Clone, Default)]
+pub struct PickerOptions {
+    /// Mode to start in, ignored when `entries` are given
+    pub mode: AppMode,
+    /// Directory to start browsing in instead of the current directory
+    pub start_dir: Option<PathBuf>,
+    /// Initial search query, applied before the first frame
+    pub query: Option<String>,
+    /// Draw inline below the cursor with this height instead of on the alternate screen
+    pub height: Option<Height>,
+    /// Entries to pick from, which start picker mode like entries piped to the binary
+    pub entries: Option<Vec<String>>,
+    /// Draw on stderr instead of stdout, picker mode always draws there
+    pub draw_on_stderr: bool,
+}
+
+/// What was picked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// The chosen entry, as given for the entries of picker mode
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Entries marked with Space in the order they were marked, empty when none were
+    pub marked: Vec<PathBuf>,
+    /// Mode the selection was made in
+    pub mode: AppMode,
+    /// Search query when the selection was made
+    pub query: String,
+}
+
+impl Selection {
+    pub(crate) fn new(app: &App, file: &FileItem) -> Self {
+        Self {
+            path: strip_verbatim_prefix(file.path.clone()),
+            is_dir: file.is_dir,
+            marked: app
+                .state
+                .marked
+                .iter()
+                .cloned()
+                .map(strip_verbatim_prefix)
+                .collect(),
+            mode: *app.mode_manager.get_current_mode(),
+            query: app.state.search_input.clone(),
+        }
+    }
+}
+
+/// The quickswitch UI, run from another program
+///
+/// The picker takes over the terminal while it runs and puts it back the way
+/// it found it, so a host TUI suspends itself around [`Picker::run`] like it
+/// would for an editor. It reads the configuration installed with
+/// [`crate::config::init_config`], or the defaults. Selections are remembered
+/// in the history and recent files like with the binary.
+#[derive(Debug, Clone, Default)]
+pub struct Picker {
+    options: PickerOptions,
+}
+
+impl Picker {
+    pub fn new(options: PickerOptions) -> Self {
+        Self { options }
+    }
+
+    /// Show the UI until something is picked or it is left with Esc or
+    /// Ctrl+C, which give `None`
+    pub async fn run(self) -> Result<Option<Selection>> {
+        let PickerOptions {
+            mode,
+            start_dir,
+            query,
+            height,
+            entries,
+            draw_on_stderr,
+        } = self.options;
+        let start_dir = start_dir
+            .map(|dir| {
+                dir.canonicalize()
+                    .with_context(|| format!("Failed to resolve {}", dir.display()))
+            })
+            .transpose()?;
+        let mode = match entries {
+            Some(entries) => {
+                picker::set_entries(entries);
+                AppMode::Picker
+            }
+            None => mode,
+        };
+        let options = InteractiveOptions {
+            query,
+            height,
+            stdout: draw_on_stderr,
+            start_dir,
+            embedded: true,
+            ..InteractiveOptions::default()
+        };
+
+        let app = terminal::run_session(mode, options).await;
+        // Ctrl+C only leaves the picker, the host keeps running
+        signals::clear_shutdown_request();
+        let selection = app?.selection;
+        plugins::run_on_select();
+        Ok(selection)
+    }
+}
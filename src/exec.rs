@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::{path::PathBuf, process::Command};
+use tracing::{info, instrument};
+
+/// Placeholder in `--exec` templates replaced by the selected paths
+pub const PLACEHOLDER: &str = "{}";
+
+/// Build the shell command for `template` with the selected paths filled in
+///
+/// Every `{}` is replaced by all paths, each quoted for the platform shell. If
+/// the template has no placeholder, the paths are appended at the end.
+pub fn build_command(template: &str, paths: &[PathBuf]) -> String {
+    let arguments = paths
+        .iter()
+        .map(|path| shell_quote(&path.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if template.contains(PLACEHOLDER) {
+        template.replace(PLACEHOLDER, &arguments)
+    } else {
+        format!("{template} {arguments}")
+    }
+}
+
+/// Quote a single argument so the shell passes it through unchanged
+#[cfg(not(windows))]
+pub fn shell_quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', r"'\''"))
+}
+
+/// Quote a single argument so the shell passes it through unchanged
+#[cfg(windows)]
+pub fn shell_quote(argument: &str) -> String {
+    format!("\"{}\"", argument.replace('"', "\"\""))
+}
+
+/// Run the command built from `template` and return its exit code
+#[instrument]
+pub fn run(template: &str, paths: &[PathBuf]) -> Result<i32> {
+    let command = build_command(template, paths);
+    info!(command, "Running --exec command");
+
+    let status = shell(&command)
+        .status()
+        .with_context(|| format!("Failed to run `{command}`"))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// A process running `command` in the platform shell
+#[cfg(not(windows))]
+pub fn shell(command: &str) -> Command {
+    let mut process = Command::new("sh");
+    process.arg("-c").arg(command);
+    process
+}
+
+/// A process running `command` in the platform shell
+#[cfg(windows)]
+pub fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut process = Command::new("cmd");
+    // cmd.exe doesn't undo the quoting `arg` adds, so quotes in the command
+    // would reach it escaped
+    process.arg("/C").raw_arg(command);
+    process
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_command_quotes_paths() {
+        let paths = vec![PathBuf::from("/tmp/a b"), PathBuf::from("/tmp/it's")];
+        assert_eq!(
+            build_command("code {}", &paths),
+            r"code '/tmp/a b' '/tmp/it'\''s'"
+        );
+        assert_eq!(build_command("ls -l", &paths[..1]), "ls -l '/tmp/a b'");
+    }
+}
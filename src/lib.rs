@@ -1,19 +1,27 @@
 pub mod app;
 pub mod app_state;
+pub mod bench;
 pub mod config;
 pub mod core;
+pub mod doctor;
+pub mod embed;
+pub mod exec;
 pub mod logging;
+pub mod manual;
 pub mod modes;
+pub mod output;
 pub mod services;
 pub mod terminal;
+pub mod testing;
 pub mod utils;
 
 pub use app::App;
 pub use app_state::AppState;
 pub use config::get_data_dir;
+pub use embed::{Picker, PickerOptions, Selection};
 pub use modes::ModeHandler;
 pub use services::FilesystemService;
-pub use terminal::run_interactive_mode;
-pub use utils::{AppMode, ShellType, is_tty, qs_init, run_non_interactive};
+pub use terminal::{InteractiveOptions, run_interactive_mode};
+pub use utils::{AppMode, ShellType, is_tty, qs_init, run_non_interactive, run_query};
 
 pub type Result<T> = anyhow::Result<T>;
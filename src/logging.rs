@@ -1,25 +1,46 @@
-use anyhow::{Ok, Result};
-use chrono::Local;
-use std::{env, fs::OpenOptions};
-use tracing::{instrument, warn};
+use anyhow::{Context, Ok, Result};
+use chrono::{Local, SecondsFormat, Utc};
+use clap::ValueEnum;
+use std::{env, fmt, fs::OpenOptions};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    instrument, warn,
+};
 use tracing_appender::non_blocking;
-use tracing_subscriber::{EnvFilter, fmt::time::Uptime};
+use tracing_subscriber::{
+    EnvFilter,
+    field::RecordFields,
+    fmt::{
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+        format::{self, Writer},
+        time::Uptime,
+    },
+    registry::LookupSpan,
+};
+
+use crate::output::json_string;
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the event and its spans
+    Json,
+}
 
 #[instrument]
-pub fn init_logging(verbose_level: u8, log_file: Option<&std::path::Path>) -> Result<()> {
-    // set the default log level based on verbosity
+pub fn init_logging(
+    verbose_level: u8,
+    log_file: Option<&std::path::Path>,
+    filters: &[String],
+    format: LogFormat,
+) -> Result<()> {
     let warn_tag = verbose_level > 3;
-    let filter = match verbose_level {
-        0 => {
-            if env::var("RUST_LOG").is_ok() {
-                EnvFilter::from_default_env()
-            } else {
-                return Ok(());
-            }
-        }
-        1 => EnvFilter::new("INFO"),
-        2 => EnvFilter::new("DEBUG"),
-        _ => EnvFilter::new("TRACE"),
+    let Some(filter) = build_filter(verbose_level, filters)? else {
+        return Ok(());
     };
 
     // Initialize log file
@@ -43,16 +64,24 @@ pub fn init_logging(verbose_level: u8, log_file: Option<&std::path::Path>) -> Re
     };
     let (appender, _guard) = non_blocking(writer);
 
-    tracing_subscriber::fmt()
-        .with_thread_ids(false)
-        .with_thread_names(false)
-        .with_target(false)
-        .with_file(true)
-        .with_line_number(true)
-        .with_timer(Uptime::default())
-        .with_writer(appender)
-        .with_env_filter(filter)
-        .init();
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt()
+            .with_thread_ids(false)
+            .with_thread_names(false)
+            .with_target(false)
+            .with_file(true)
+            .with_line_number(true)
+            .with_timer(Uptime::default())
+            .with_writer(appender)
+            .with_env_filter(filter)
+            .init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat)
+            .with_writer(appender)
+            .with_env_filter(filter)
+            .init(),
+    }
 
     // Keep the guard alive for the duration of the program
     std::mem::forget(_guard);
@@ -63,3 +92,215 @@ pub fn init_logging(verbose_level: u8, log_file: Option<&std::path::Path>) -> Re
 
     Ok(())
 }
+
+/// The levels to log: the one chosen with `-v`, or `RUST_LOG` without it,
+/// refined by the per-module `filters` from the config
+///
+/// `None` when logging is off.
+fn build_filter(verbose_level: u8, filters: &[String]) -> Result<Option<EnvFilter>> {
+    // set the default log level based on verbosity
+    let mut filter = match verbose_level {
+        0 => {
+            if env::var("RUST_LOG").is_ok() {
+                EnvFilter::from_default_env()
+            } else {
+                return Ok(None);
+            }
+        }
+        1 => EnvFilter::new("INFO"),
+        2 => EnvFilter::new("DEBUG"),
+        _ => EnvFilter::new("TRACE"),
+    };
+    for directive in filters {
+        let directive = directive
+            .parse()
+            .with_context(|| format!("Invalid log filter '{directive}' in the config"))?;
+        filter = filter.add_directive(directive);
+    }
+    Ok(Some(filter))
+}
+
+/// Collects fields as the members of a JSON object
+#[derive(Default)]
+struct JsonVisitor {
+    members: Vec<String>,
+}
+
+impl JsonVisitor {
+    fn push(&mut self, field: &Field, value: String) {
+        self.members
+            .push(format!("{}:{value}", json_string(field.name())));
+    }
+
+    fn object(&self) -> String {
+        format!("{{{}}}", self.members.join(","))
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, json_string(&format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, json_string(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        // JSON has no NaN or infinity
+        if value.is_finite() {
+            self.push(field, value.to_string());
+        } else {
+            self.push(field, json_string(&value.to_string()));
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, value.to_string());
+    }
+}
+
+/// Formats the fields of spans as JSON members, kept with each span for [`JsonFormat`]
+struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        writer.write_str(&visitor.members.join(","))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &tracing::span::Record<'_>,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        if !current.fields.is_empty() && !visitor.members.is_empty() {
+            current.fields.push(',');
+        }
+        current.fields.push_str(&visitor.members.join(","));
+        fmt::Result::Ok(())
+    }
+}
+
+/// Writes each event as a line of JSON with its level, location, fields
+/// and the spans it happened in, outermost first
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut spans = Vec::new();
+        for span in ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            let extensions = span.extensions();
+            let span_fields = extensions
+                .get::<FormattedFields<N>>()
+                .map(|formatted| formatted.fields.as_str())
+                .unwrap_or_default();
+            let separator = if span_fields.is_empty() { "" } else { "," };
+            spans.push(format!(
+                "{{\"name\":{}{separator}{span_fields}}}",
+                json_string(span.name())
+            ));
+        }
+
+        write!(
+            writer,
+            "{{\"timestamp\":{},\"level\":{},\"target\":{}",
+            json_string(&Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)),
+            json_string(metadata.level().as_str()),
+            json_string(metadata.target())
+        )?;
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            write!(writer, ",\"file\":{},\"line\":{line}", json_string(file))?;
+        }
+        writeln!(
+            writer,
+            ",\"fields\":{},\"spans\":[{}]}}",
+            fields.object(),
+            spans.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_filters_refine_the_verbosity() {
+        let filters = vec!["quickswitch::services=debug".to_string()];
+        let filter = build_filter(1, &filters).unwrap().unwrap().to_string();
+        assert!(filter.contains("quickswitch::services=debug"));
+        assert!(filter.contains("info"));
+        assert!(build_filter(1, &["quickswitch=loud".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_json_lines() {
+        use std::sync::{Arc, Mutex};
+        use tracing::info_span;
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buffer {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(data);
+                std::io::Result::Ok(data.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                std::io::Result::Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("load", dir = "/tmp/\"x\"").entered();
+            warn!(count = 3, "Slow {}", "preview");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with("{\"timestamp\":\""));
+        assert!(output.contains(r#""level":"WARN","target":"quickswitch::logging::tests""#));
+        assert!(output.ends_with(
+            r#""fields":{"message":"Slow preview","count":3},"spans":[{"name":"load","dir":"/tmp/\"x\""}]}
+"#
+        ));
+    }
+}
@@ -1,9 +1,22 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
 use quickswitch::{
-    Result, ShellType, logging::init_logging, qs_init, run_interactive_mode, run_non_interactive,
-    utils::AppMode,
+    InteractiveOptions, Result, ShellType,
+    bench::run_bench,
+    config::{self, Config, EntryFilter},
+    doctor::run_doctor,
+    logging::{LogFormat, init_logging},
+    manual,
+    modes::picker,
+    output::{self, OutputFormat},
+    qs_init, run_interactive_mode, run_non_interactive, run_query,
+    services::plugins,
+    terminal::Height,
+    utils::{AppMode, WidgetKey},
+};
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
 };
-use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -13,11 +26,43 @@ use std::path::PathBuf;
     long_about = None
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Set the startup mode
     #[arg(long, value_enum, default_value_t = AppMode::Normal)]
     mode: AppMode,
 
-    /// Run in non-interactive mode
+    /// Start with the search box filled in and the filter applied
+    #[arg(long, short = 'q')]
+    query: Option<String>,
+
+    /// Run a command on the selection after exiting, e.g. --exec 'code {}'
+    ///
+    /// `{}` is replaced by the quoted path, or by all marked paths (Space marks
+    /// entries). Without `{}` the paths are appended to the command.
+    #[arg(long, value_name = "CMD")]
+    exec: Option<String>,
+
+    /// Draw inline below the prompt instead of full screen, e.g. --height 40% or --height 20
+    #[arg(long, value_name = "HEIGHT")]
+    height: Option<Height>,
+
+    /// Query for --non-interactive: print the best matching history entry
+    ///
+    /// Names of subcommands like `query` are read as the subcommand.
+    #[arg(value_name = "QUERY", requires = "non_interactive")]
+    pattern: Option<String>,
+
+    /// Read NUL-separated instead of newline-separated entries from stdin
+    #[arg(long)]
+    read0: bool,
+
+    /// Pick from the entries listed by the command configured under [plugins.sources]
+    #[arg(long, value_name = "NAME")]
+    source: Option<String>,
+
+    /// Print the best matching history entry for QUERY or --query instead of starting the UI
     #[arg(long)]
     non_interactive: bool,
 
@@ -25,32 +70,217 @@ struct Cli {
     #[arg(long, value_enum)]
     init: Option<ShellType>,
 
+    /// With --init, also bind this key (e.g. ctrl-g) to insert the selected path at the cursor
+    #[arg(long, value_name = "KEY", requires = "init")]
+    bind_key: Option<WidgetKey>,
+
+    /// Print the selected paths shell-quoted instead of their directory
+    #[arg(long)]
+    print_selection: bool,
+
+    /// Print the result on stdout and draw the UI on stderr instead
+    #[arg(long)]
+    stdout: bool,
+
+    /// Print the selection as JSON with its path, is_dir, mode and query fields
+    #[arg(long, value_enum, default_value_t = OutputFormat::Path)]
+    output: OutputFormat,
+
+    /// Separate the printed paths with NUL instead of newlines, for `xargs -0`
+    #[arg(long)]
+    print0: bool,
+
+    /// Write the directory to change to into FILE instead of printing it,
+    /// which is how the shell functions from --init read it
+    #[arg(long, value_name = "FILE")]
+    cd_file: Option<PathBuf>,
+
+    /// Output the directory being browsed when quitting with Esc, like Q does
+    #[arg(long)]
+    last_dir_on_exit: bool,
+
+    /// Print this help followed by the modes, default key bindings,
+    /// environment variables and config keys
+    #[arg(long)]
+    help_all: bool,
+
     /// Enable verbose logging (-v=INFO, -vv=DEBUG, -vvv=TRACE)
     #[arg(short = 'v', action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// Show files ignored by .gitignore, .ignore and the global git excludes file
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Only list directories
+    #[arg(long, conflicts_with = "files_only")]
+    dirs_only: bool,
+
+    /// Only list files
+    #[arg(long)]
+    files_only: bool,
+
+    /// Disable colors (also enabled by setting the NO_COLOR environment variable)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Write the log as JSON lines, e.g. for log collectors
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
     /// Log file path (creates temp file `qw-[date]-[pid].log` if not specified)
     #[arg(long)]
     log_file: Option<PathBuf>,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Print the directory an alias stands for, or else the best matching history entry
+    Query {
+        /// Alias set in history mode with `a`, e.g. `cd $(quickswitch query qw)`
+        alias: String,
+    },
+    /// Report the detected terminal, image protocol, colors and the state of
+    /// the data files, for bug reports
+    Doctor,
+    /// Print the man page in roff format, e.g. `quickswitch man > quickswitch.1`
+    Man,
+    /// Time loading, filtering and previewing a directory, to compare releases
+    Bench {
+        /// Directory to measure
+        dir: PathBuf,
+        /// How often loading and each filter query are repeated
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+        /// Query to filter with, can be given several times
+        #[arg(long = "query", value_name = "QUERY")]
+        queries: Vec<String>,
+        /// How many entries are previewed
+        #[arg(long, default_value_t = 20)]
+        previews: usize,
+    },
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cli = Cli::parse();
+    // Scripts tell errors apart from a selection (0) and an abort (1) by the status
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {e:?}");
+        std::process::exit(output::EXIT_ERROR);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    if cli.help_all {
+        print!("{}", manual::help_all(&mut Cli::command()));
+        return Ok(());
+    }
+    if let Some(Command::Man) = &cli.command {
+        print!("{}", manual::man_page(&Cli::command()));
+        return Ok(());
+    }
+
+    // The config is read first for its log filters
+    let config = Config::load();
 
     // Initialize logging if verbose flag is set
-    init_logging(cli.verbose, cli.log_file.as_deref())?;
+    let filters = config
+        .as_ref()
+        .map(|config| config.log.filters.as_slice())
+        .unwrap_or_default();
+    init_logging(
+        cli.verbose,
+        cli.log_file.as_deref(),
+        filters,
+        cli.log_format,
+    )?;
+
+    // Runs before a broken config is an error, it is one of the things it reports
+    if let Some(Command::Doctor) = &cli.command {
+        return run_doctor();
+    }
+
+    // Apply command line overrides to the config
+    let mut config = config?;
+    if cli.no_ignore {
+        config.filter.respect_ignore = false;
+    }
+    if cli.dirs_only {
+        config.filter.show = EntryFilter::Dirs;
+    } else if cli.files_only {
+        config.filter.show = EntryFilter::Files;
+    }
+    if cli.no_color || std::env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+        config.ui.color = false;
+    }
+    config::init_config(config);
 
     // Handle init option
     if let Some(shell) = cli.init {
-        return qs_init(shell);
+        return qs_init(shell, cli.bind_key);
+    }
+
+    match &cli.command {
+        Some(Command::Query { alias }) => return run_query(alias),
+        Some(Command::Bench {
+            dir,
+            runs,
+            queries,
+            previews,
+        }) => return run_bench(dir, *runs, queries, *previews).await,
+        _ => {}
     }
 
     // Handle non-interactive mode
     if cli.non_interactive {
-        return run_non_interactive();
+        let query = cli.pattern.as_deref().or(cli.query.as_deref());
+        return run_non_interactive(query);
+    }
+
+    // Entries piped on stdin turn quickswitch into a generic picker over them
+    let mut mode = cli.mode;
+    if let Some(name) = &cli.source {
+        let cwd = std::env::current_dir()?;
+        picker::set_entries(plugins::source_entries(name, &cwd)?);
+        mode = AppMode::Picker;
+    } else if !io::stdin().is_terminal() {
+        let separator = if cli.read0 { b'\0' } else { b'\n' };
+        picker::set_entries(picker::read_entries(io::stdin().lock(), separator)?);
+        mode = AppMode::Picker;
     }
 
     // Run interactive mode with specified mode
-    run_interactive_mode(cli.mode).await
+    let options = InteractiveOptions {
+        query: cli.query,
+        exec: cli.exec,
+        height: cli.height,
+        print_selection: cli.print_selection,
+        stdout: cli.stdout,
+        output: cli.output,
+        print0: cli.print0,
+        cd_file: cli.cd_file,
+        last_dir_on_exit: cli.last_dir_on_exit,
+        ..InteractiveOptions::default()
+    };
+    run_interactive_mode(mode, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_interactive_takes_a_positional_query() {
+        let cli = Cli::try_parse_from(["quickswitch", "--non-interactive", "proj"]).unwrap();
+        assert!(cli.non_interactive);
+        assert_eq!(cli.pattern.as_deref(), Some("proj"));
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["quickswitch", "-v", "query", "qw"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Query { alias }) if alias == "qw"));
+        assert!(cli.pattern.is_none());
+
+        assert!(Cli::try_parse_from(["quickswitch", "proj"]).is_err());
+    }
 }
@@ -0,0 +1,493 @@
+use clap::{Arg, Command, ValueEnum};
+use std::fmt::Write;
+
+use crate::{core::keymap::KEYMAP, output, utils::AppMode};
+
+/// Environment variables read by quickswitch
+pub const ENVIRONMENT: &[(&str, &str)] = &[
+    (
+        "_QUICKSWITCH_CONFIG",
+        "Path of the config file, instead of $XDG_CONFIG_HOME/quickswitch/config.toml (%APPDATA%\\quickswitch\\config.toml on Windows)",
+    ),
+    (
+        "_QUICKSWITCH_DATA_DIR",
+        "Directory for the history, aliases, marks, macros and the directory index, instead of ~/.local/share/quickswitch (%APPDATA%\\quickswitch on Windows)",
+    ),
+    (
+        "NO_COLOR",
+        "Draw without colors when set to anything but an empty value, like --no-color",
+    ),
+    (
+        "LS_COLORS",
+        "Colors of the entries by type and extension, in the format of dircolors",
+    ),
+    (
+        "RUST_LOG",
+        "Log filter directives like `quickswitch=debug`, used when no -v is given",
+    ),
+];
+
+/// Every key of the config file with its default and what it does
+pub const CONFIG_KEYS: &[(&str, &str, &str)] = &[
+    (
+        "filter.respect_ignore",
+        "true",
+        "Hide files ignored by .gitignore, .ignore and the global git excludes file",
+    ),
+    (
+        "filter.show",
+        "\"all\"",
+        "Entries to list: all, dirs or files",
+    ),
+    (
+        "search.case",
+        "\"smart\"",
+        "Case matching: smart (sensitive when the query has an uppercase letter), sensitive or insensitive",
+    ),
+    (
+        "search.frecency_boost",
+        "true",
+        "Rank often visited directories first among the matches",
+    ),
+    (
+        "sort.natural",
+        "true",
+        "Compare numbers in names by value, so file2 comes before file10",
+    ),
+    ("ui.icons", "\"emoji\"", "Icon set: emoji, nerd or ascii"),
+    ("ui.color", "true", "Draw with colors"),
+    (
+        "layout.preview_width",
+        "50",
+        "Width of the preview panel in percent of the terminal, 10 to 90",
+    ),
+    (
+        "layout.show_preview",
+        "true",
+        "Show the preview panel on startup",
+    ),
+    (
+        "layout.dual_pane",
+        "false",
+        "Show two file lists instead of a list and a preview on startup",
+    ),
+    (
+        "navigation.wrap_around",
+        "false",
+        "Move from the last entry to the first and back",
+    ),
+    (
+        "navigation.scrolloff",
+        "3",
+        "Entries kept visible above and below the selection",
+    ),
+    (
+        "navigation.follow_symlinks",
+        "true",
+        "Enter symlinks to directories",
+    ),
+    (
+        "mouse.single_click",
+        "false",
+        "Enter directories and pick files with a single click",
+    ),
+    (
+        "mouse.scroll_step",
+        "1.0",
+        "List entries moved per wheel notch, fractions add up",
+    ),
+    (
+        "mouse.preview_scroll_step",
+        "1.0",
+        "Preview lines scrolled per wheel notch, fractions add up",
+    ),
+    ("history.max_entries", "100", "Most history entries kept"),
+    (
+        "history.sort_mode",
+        "\"frequency_recent\"",
+        "History order: frequency_recent, frequency, recent or alphabetical",
+    ),
+    (
+        "history.time_decay_days",
+        "30",
+        "Days over which the weight of a visit decays",
+    ),
+    (
+        "history.decay",
+        "\"linear\"",
+        "How the weight decays: linear or exponential",
+    ),
+    (
+        "history.min_decay",
+        "0.1",
+        "Lowest weight an old visit decays to, between 0 and 1",
+    ),
+    (
+        "history.min_frequency_threshold",
+        "1",
+        "Fewest visits an entry needs to be kept",
+    ),
+    (
+        "index.roots",
+        "[\"~\"]",
+        "Directories indexed for the jump, repos and projects modes",
+    ),
+    ("index.max_depth", "6", "Levels indexed below each root"),
+    (
+        "index.exclude",
+        "[\"node_modules\", \"target\"]",
+        "Directory names skipped while indexing",
+    ),
+    (
+        "index.refresh_minutes",
+        "60",
+        "Age after which the index is refreshed in the background",
+    ),
+    (
+        "preview.max_file_size",
+        "0",
+        "Files larger than this many bytes aren't previewed, 0 for no limit",
+    ),
+    (
+        "preview.truncate",
+        "\"stream\"",
+        "Long text files: stream (read while scrolling), head or head_tail",
+    ),
+    (
+        "preview.head_lines",
+        "500",
+        "Lines shown from the start with head and head_tail",
+    ),
+    (
+        "preview.tail_lines",
+        "100",
+        "Lines shown from the end with head_tail",
+    ),
+    (
+        "preview.debounce_ms",
+        "80",
+        "Milliseconds the selection has to rest before it is previewed",
+    ),
+    (
+        "preview.tab_width",
+        "4",
+        "Columns a tab takes in the preview",
+    ),
+    (
+        "preview.line_numbers",
+        "true",
+        "Show line numbers in text previews",
+    ),
+    (
+        "preview.metadata_header",
+        "true",
+        "Show permissions, owner and timestamps above the preview",
+    ),
+    (
+        "preview.tree_depth",
+        "2",
+        "Levels shown by the directory tree preview",
+    ),
+    (
+        "log.filters",
+        "[]",
+        "Per-module log levels on top of the one chosen with -v, like RUST_LOG, e.g. [\"quickswitch::services=debug\"]",
+    ),
+    (
+        "macros.<letter>",
+        "",
+        "Key sequence replayed with @<letter>, written like vim keys, e.g. \"/src<Esc>jl\"",
+    ),
+    (
+        "plugins.on_select",
+        "[]",
+        "Commands run with the selection as JSON on stdin when quickswitch exits with one",
+    ),
+    (
+        "plugins.on_enter_directory",
+        "[]",
+        "Commands run in the background with {\"path\": ...} on stdin on entering a directory",
+    ),
+    (
+        "plugins.previewers",
+        "[]",
+        "Commands previewing files by extension, printing {\"title\": ..., \"lines\": [...]}, e.g. [{ extensions = [\"csv\"], command = \"csv-preview\" }]",
+    ),
+    (
+        "plugins.sources.<name>",
+        "",
+        "Command printing {\"entries\": [...]} to pick from with --source <name>",
+    ),
+];
+
+/// What each `--mode` lists
+fn modes() -> Vec<(String, String)> {
+    AppMode::value_variants()
+        .iter()
+        .filter_map(|mode| mode.to_possible_value())
+        .map(|value| {
+            let help = value
+                .get_help()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            (value.get_name().to_string(), help)
+        })
+        .collect()
+}
+
+/// The default key bindings of normal mode, followed by those only
+/// available in the other modes
+fn key_bindings() -> Vec<(String, Vec<(String, &'static str)>)> {
+    let normal = KEYMAP.help_entries(&AppMode::Normal);
+    let mut sections = vec![("normal".to_string(), normal.clone())];
+    for (name, mode) in [("history", AppMode::History), ("picker", AppMode::Picker)] {
+        let extra: Vec<_> = KEYMAP
+            .help_entries(&mode)
+            .into_iter()
+            .filter(|entry| !normal.contains(entry))
+            .collect();
+        if !extra.is_empty() {
+            sections.push((name.to_string(), extra));
+        }
+    }
+    sections
+}
+
+fn exit_codes() -> [(i32, &'static str); 3] {
+    [
+        (output::EXIT_SELECTED, "Something was selected"),
+        (output::EXIT_ABORTED, "Quit with Esc without a selection"),
+        (output::EXIT_ERROR, "An error occurred"),
+    ]
+}
+
+/// `--help` followed by the modes, key bindings, environment variables
+/// and config keys
+pub fn help_all(cli: &mut Command) -> String {
+    let mut text = cli.render_long_help().to_string();
+
+    text.push_str("\nModes:\n");
+    for (name, help) in modes() {
+        let _ = writeln!(text, "  {name:<14}{help}");
+    }
+
+    for (mode, entries) in key_bindings() {
+        let _ = writeln!(text, "\nKey bindings ({mode} mode):");
+        for (keys, description) in entries {
+            let _ = writeln!(text, "  {keys:<14}{description}");
+        }
+    }
+
+    text.push_str("\nEnvironment:\n");
+    for (name, description) in ENVIRONMENT {
+        let _ = writeln!(text, "  {name}\n          {description}");
+    }
+
+    text.push_str("\nConfig keys (config.toml):\n");
+    for (key, default, description) in CONFIG_KEYS {
+        let default = if default.is_empty() {
+            String::new()
+        } else {
+            format!(" = {default}")
+        };
+        let _ = writeln!(text, "  {key}{default}\n          {description}");
+    }
+
+    text.push_str("\nExit status:\n");
+    for (code, description) in exit_codes() {
+        let _ = writeln!(text, "  {code:<14}{description}");
+    }
+    text
+}
+
+/// The man page, in roff
+pub fn man_page(cli: &Command) -> String {
+    let name = cli.get_name();
+    let mut page = String::new();
+    let _ = writeln!(
+        page,
+        ".TH {} 1 \"\" \"{name} {}\"",
+        name.to_uppercase(),
+        cli.get_version().unwrap_or_default()
+    );
+    page.push_str(".SH NAME\n");
+    let _ = writeln!(
+        page,
+        "{name} \\- {}",
+        roff(&cli.get_about().map(ToString::to_string).unwrap_or_default())
+    );
+
+    page.push_str(".SH SYNOPSIS\n");
+    let _ = writeln!(page, "\\fB{name}\\fR [\\fIOPTIONS\\fR] [\\fIQUERY\\fR]");
+    let _ = writeln!(page, ".br\n\\fB{name}\\fR \\fICOMMAND\\fR");
+
+    page.push_str(".SH OPTIONS\n");
+    for arg in cli.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        let _ = writeln!(page, ".TP\n{}", arg_synopsis(arg));
+        let help = arg
+            .get_long_help()
+            .or(arg.get_help())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let _ = writeln!(page, "{}", roff(&help));
+        // Flags have the possible values `true` and `false`, which aren't worth listing
+        let values: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| format!("\\fB{}\\fR", roff(value.get_name())))
+            .collect();
+        if arg.get_action().takes_values() && !values.is_empty() {
+            let _ = writeln!(page, ".br\nOne of {}.", values.join(", "));
+        }
+    }
+
+    page.push_str(".SH COMMANDS\n");
+    for command in cli
+        .get_subcommands()
+        .filter(|command| !command.is_hide_set())
+    {
+        let args: Vec<String> = command
+            .get_positionals()
+            .map(|arg| format!("\\fI{}\\fR", roff(&arg_value_name(arg))))
+            .collect();
+        let mut synopsis = format!("\\fB{}\\fR", command.get_name());
+        for arg in args {
+            let _ = write!(synopsis, " {arg}");
+        }
+        let _ = writeln!(page, ".TP\n{synopsis}");
+        let about = command
+            .get_long_about()
+            .or(command.get_about())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let _ = writeln!(page, "{}", roff(&about));
+        if command.get_opts().next().is_some() {
+            page.push_str(".RS\n");
+            for arg in command.get_opts() {
+                let help = arg.get_help().map(ToString::to_string).unwrap_or_default();
+                let _ = writeln!(page, ".TP\n{}\n{}", arg_synopsis(arg), roff(&help));
+            }
+            page.push_str(".RE\n");
+        }
+    }
+
+    page.push_str(".SH MODES\n");
+    for (name, help) in modes() {
+        let _ = writeln!(page, ".TP\n\\fB{name}\\fR\n{}", roff(&help));
+    }
+
+    page.push_str(".SH KEY BINDINGS\n");
+    for (mode, entries) in key_bindings() {
+        let _ = writeln!(page, ".SS {} mode", capitalize(&mode));
+        for (keys, description) in entries {
+            let _ = writeln!(page, ".TP\n\\fB{}\\fR\n{}", roff(&keys), roff(description));
+        }
+    }
+
+    page.push_str(".SH ENVIRONMENT\n");
+    for (name, description) in ENVIRONMENT {
+        let _ = writeln!(page, ".TP\n\\fB{}\\fR\n{}", roff(name), roff(description));
+    }
+
+    page.push_str(".SH CONFIGURATION\n");
+    page.push_str(
+        "Keys of the TOML config file, written as \\fIsection\\fR.\\fIkey\\fR with their defaults. \
+         All of them are optional.\n",
+    );
+    for (key, default, description) in CONFIG_KEYS {
+        let default = if default.is_empty() {
+            String::new()
+        } else {
+            format!(" = {}", roff(default))
+        };
+        let _ = writeln!(
+            page,
+            ".TP\n\\fB{}\\fR{default}\n{}",
+            roff(key),
+            roff(description)
+        );
+    }
+
+    page.push_str(".SH EXIT STATUS\n");
+    for (code, description) in exit_codes() {
+        let _ = writeln!(page, ".TP\n\\fB{code}\\fR\n{description}");
+    }
+    page
+}
+
+/// `-v`, `--mode <MODE>` or `<QUERY>` in bold and italics
+fn arg_synopsis(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{short}\\fR"));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", roff(long)));
+    }
+    let takes_value = arg.get_action().takes_values();
+    if names.is_empty() {
+        return format!("\\fI{}\\fR", roff(&arg_value_name(arg)));
+    }
+    let mut synopsis = names.join(", ");
+    if takes_value {
+        let _ = write!(synopsis, " \\fI{}\\fR", roff(&arg_value_name(arg)));
+    }
+    synopsis
+}
+
+fn arg_value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map(ToString::to_string)
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase())
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Escape text for roff, so backslashes, dashes and leading dots come out as written
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    escaped
+        .lines()
+        .map(|line| {
+            // A blank line would end the paragraph of a `.TP` item
+            if line.is_empty() {
+                ".sp".to_string()
+            } else if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_config_keys_parse_with_their_defaults() {
+        for (key, default, _) in CONFIG_KEYS
+            .iter()
+            .filter(|(_, default, _)| !default.is_empty())
+        {
+            let (section, name) = key.split_once('.').unwrap();
+            let toml = format!("[{section}]\n{name} = {default}\n");
+            assert!(
+                toml::from_str::<Config>(&toml).is_ok(),
+                "{key} = {default} doesn't parse"
+            );
+        }
+        assert_eq!(roff(".hidden -v\\n"), "\\&.hidden \\-v\\en");
+    }
+}
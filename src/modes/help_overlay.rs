@@ -0,0 +1,99 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem},
+};
+
+use crate::{AppState, core::keymap::KEYMAP, modes::Renderer, utils::AppMode};
+
+/// Popup listing every keybinding available in the current mode
+pub struct HelpOverlayRenderer {
+    mode: AppMode,
+}
+
+impl HelpOverlayRenderer {
+    pub fn new(mode: AppMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl Renderer for HelpOverlayRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
+        let entries = KEYMAP.help_entries(&self.mode);
+        let key_width = entries
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|(keys, description)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(" {keys:<key_width$}  "),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(*description),
+                ]))
+            })
+            .collect();
+
+        let content_width = entries
+            .iter()
+            .map(|(_, description)| key_width + description.chars().count() + 3)
+            .max()
+            .unwrap_or(0) as u16;
+        let popup = centered_rect(area, content_width + 2, entries.len() as u16 + 2);
+
+        let title = match self.mode {
+            AppMode::Normal => "Keys - Normal Mode (any key to close)",
+            AppMode::History => "Keys - History Mode (any key to close)",
+            AppMode::Picker => "Keys - Picker Mode (any key to close)",
+            AppMode::RecentFiles => "Keys - Recent Files Mode (any key to close)",
+            AppMode::Jump => "Keys - Jump Mode (any key to close)",
+            AppMode::Repos => "Keys - Repos Mode (any key to close)",
+            AppMode::Projects => "Keys - Projects Mode (any key to close)",
+            AppMode::Mounts => "Keys - Mounts Mode (any key to close)",
+        };
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+        f.render_widget(Clear, popup);
+        f.render_widget(list, popup);
+    }
+}
+
+/// One `keys - description` line per binding available in `mode`, so the
+/// help panels can't drift from the keymap
+pub fn keybinding_lines(mode: &AppMode) -> Vec<Line<'static>> {
+    let entries = KEYMAP.help_entries(mode);
+    let key_width = entries
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    entries
+        .into_iter()
+        .map(|(keys, description)| Line::from(format!("{keys:<key_width$} - {description}")))
+        .collect()
+}
+
+/// Center a rectangle of the given size inside `area`, clamped to fit
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::horizontal([Constraint::Length(width.min(area.width))])
+        .flex(Flex::Center)
+        .areas(row);
+    popup
+}
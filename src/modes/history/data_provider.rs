@@ -1,13 +1,15 @@
 use anyhow::Result;
-use bincode::config;
-use std::{fs, path::PathBuf};
-use tracing::{error, info, instrument};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::instrument;
 
 use crate::{
     app_state::AppState,
-    config::{get_data_dir, get_history_config},
+    config::get_history_config,
     modes::ModeAction,
-    services::DataProvider,
+    services::{Aliases, DataProvider, FilesystemService, HistoryStore},
     utils::{AppMode, DisplayItem, HistoryEntry, HistorySortMode},
 };
 
@@ -16,226 +18,111 @@ use crate::{
 pub struct HistoryDataProvider;
 
 impl HistoryDataProvider {
-    /// Get the path to the history data file
-    fn get_history_file_path(&self) -> PathBuf {
-        if let Ok(data_dir) = get_data_dir() {
-            data_dir.join("quickswitch.history.bin")
-        } else {
-            // Fallback to temp directory if data_dir cannot be created
-            std::env::temp_dir().join("quickswitch.history.bin")
-        }
+    /// Add a path to history or update its frequency if it already exists
+    pub fn add_to_history(&self, path: PathBuf) {
+        HistoryStore::DIRECTORIES.add_to_history(path)
     }
 
-    /// Get the path to the legacy history file (for migration)
-    fn get_legacy_history_file_path(&self) -> PathBuf {
-        if let Ok(data_dir) = get_data_dir() {
-            data_dir.join("quickswitch.history")
-        } else {
-            std::env::temp_dir().join("quickswitch.history")
-        }
+    /// Get sorted history entries based on the configured sort mode
+    pub fn get_sorted_entries(&self, sort_mode: &HistorySortMode) -> Result<Vec<HistoryEntry>> {
+        HistoryStore::DIRECTORIES.get_sorted_entries(sort_mode)
     }
 
-    /// Load history entries from file
-    #[instrument(skip(self))]
-    fn load_history_entries(&self) -> Result<Vec<HistoryEntry>> {
-        let file_path = self.get_history_file_path();
-
-        // If the binary file exists, load from it
-        if file_path.exists() {
-            let data = fs::read(&file_path)?;
-            let config = config::standard();
-            info!(path = %file_path.display(), "Loading history data from file");
-            match bincode::serde::decode_from_slice(&data, config) {
-                Ok((entries, _)) => return Ok(entries),
-                Err(e) => {
-                    // If deserialization fails, try to migrate from legacy format
-                    error!("Error loading history data: {e}");
-                    if let Ok(entries) = self.migrate_from_legacy() {
-                        return Ok(entries);
-                    }
-                    return Ok(Vec::new());
-                }
-            }
-        }
-
-        // If binary file doesn't exist, try to migrate from legacy format
-        if self.get_legacy_history_file_path().exists() {
-            info!("Legacy history file found, migrating to new format");
-            if let Ok(entries) = self.migrate_from_legacy() {
-                return Ok(entries);
-            }
-        }
-
-        // If all else fails, return empty list
-        info!("No history data found, returning empty list");
-        Ok(Vec::new())
+    /// Clean up old or low-frequency entries
+    pub fn cleanup_old_entries(&self) -> Result<()> {
+        HistoryStore::DIRECTORIES.cleanup_old_entries()
     }
 
-    /// Migrate from legacy text-based history format
+    /// Find the highest ranked history entry matching `query`
+    ///
+    /// Every whitespace separated term has to appear in the path, in order and
+    /// ignoring case, and the last term has to match the final path component,
+    /// so `proj api` finds `~/projects/api` before `~/api/projects`. Entries that
+    /// no longer exist are skipped.
     #[instrument(skip(self))]
-    fn migrate_from_legacy(&self) -> Result<Vec<HistoryEntry>> {
-        let legacy_path = self.get_legacy_history_file_path();
-        if let Ok(content) = fs::read_to_string(&legacy_path) {
-            let mut entries = Vec::new();
-
-            for line in content.lines() {
-                let path = PathBuf::from(line.trim());
-                if path.exists() {
-                    entries.push(HistoryEntry::new(path));
-                }
-            }
-
-            // Save in new format
-            self.save_history_entries(&entries)?;
-
-            // Backup the legacy file
-            if legacy_path.exists() {
-                info!("Backing up legacy history file to .bak");
-                let backup_path = legacy_path.with_extension("history.bak");
-                let _ = fs::rename(&legacy_path, backup_path);
-            }
-
-            return Ok(entries);
-        }
+    pub fn best_match(&self, query: &str) -> Result<Option<HistoryEntry>> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let entries = self.get_sorted_entries(&HistorySortMode::FrequencyRecent)?;
 
-        Ok(Vec::new())
+        Ok(entries.into_iter().find(|entry| {
+            Self::matches_terms(&entry.path, &terms) && FilesystemService::exists(&entry.path)
+        }))
     }
 
-    /// Save history entries to file
-    #[instrument(skip(self, entries))]
-    fn save_history_entries(&self, entries: &[HistoryEntry]) -> Result<()> {
-        let config = config::standard();
-        let data = bincode::serde::encode_to_vec(entries, config)?;
-        let file_path = self.get_history_file_path();
-
-        // Ensure directory exists
-        if let Some(parent) = file_path.parent() {
-            if !parent.exists() {
-                info!(path = %parent.display(), "Creating directory for history file");
-                fs::create_dir_all(parent)?;
-            }
-        }
-        info!(path = %file_path.display(), "Saving history data to file");
-        fs::write(file_path, data)?;
-        Ok(())
-    }
-
-    /// Add a path to history or update its frequency if it already exists
-    #[instrument(skip(self), fields(path = %path.display()))]
-    pub fn add_to_history(&self, path: PathBuf) -> Result<()> {
-        let mut entries = self.load_history_entries()?;
-        let config = get_history_config();
-
-        // Check if path already exists in history
-        let existing_index = entries.iter().position(|entry| entry.path == path);
-
-        if let Some(index) = existing_index {
-            info!(path = %path.display(), "Updating frequency for existing history entry: {}", path.display());
-            let mut entry = entries.remove(index);
-            entry.increment_frequency();
-            entries.insert(0, entry); // Move to top
-        } else {
-            // Add new entry
-            info!(path = %path.display(), "Adding new history entry: {}", path.display());
-            entries.insert(0, HistoryEntry::new(path));
-        }
-
-        // Apply max entries limit
-        if entries.len() > config.max_entries {
-            info!(
-                "Trimming history entries to max limit: {}",
-                config.max_entries
-            );
-            entries.truncate(config.max_entries);
+    /// Whether every query term appears in `path`, see [`Self::best_match`]
+    pub fn matches_terms(path: &Path, terms: &[String]) -> bool {
+        let Some(last_term) = terms.last() else {
+            return true;
+        };
+        let last_component = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !last_component.contains(last_term.as_str()) {
+            return false;
         }
 
-        // Save updated entries
-        self.save_history_entries(&entries)?;
-        Ok(())
-    }
-
-    /// Get sorted history entries based on the configured sort mode
-    #[instrument(skip(self))]
-    pub fn get_sorted_entries(&self, sort_mode: &HistorySortMode) -> Result<Vec<HistoryEntry>> {
-        let mut entries = self.load_history_entries()?;
-        let config = get_history_config();
-
-        // Sort entries based on the specified mode
-        match sort_mode {
-            HistorySortMode::Frequency => {
-                entries.sort_by(|a, b| b.frequency.cmp(&a.frequency));
-            }
-            HistorySortMode::Recent => {
-                entries.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
-            }
-            HistorySortMode::FrequencyRecent => {
-                entries.sort_by(|a, b| {
-                    let a_score = a.calculate_score(config.time_decay_days);
-                    let b_score = b.calculate_score(config.time_decay_days);
-                    b_score
-                        .partial_cmp(&a_score)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                });
-            }
-            HistorySortMode::Alphabetical => {
-                entries.sort_by(|a, b| {
-                    let a_name = a
-                        .path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or_default();
-                    let b_name = b
-                        .path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or_default();
-                    a_name.cmp(b_name)
-                });
+        let full_path = path.to_string_lossy().to_lowercase();
+        let mut rest = full_path.as_str();
+        for term in terms {
+            match rest.find(term.as_str()) {
+                Some(index) => rest = &rest[index + term.len()..],
+                None => return false,
             }
         }
-
-        info!("Filtering out non-existent history entries");
-        entries.retain(|entry| entry.path.exists());
-
-        Ok(entries)
-    }
-
-    /// Clean up old or low-frequency entries
-    pub fn cleanup_old_entries(&self) -> Result<()> {
-        let mut entries = self.load_history_entries()?;
-        let config = get_history_config();
-
-        // Remove entries with frequency below threshold
-        entries.retain(|entry| entry.frequency >= config.min_frequency_threshold);
-
-        // Save cleaned up entries
-        self.save_history_entries(&entries)?;
-        Ok(())
+        true
     }
 }
 
 impl DataProvider for HistoryDataProvider {
     fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
         // In history mode, navigate to the selected directory and switch to normal mode
-        if let Some(item) = state.get_selected_item() {
-            if item.is_directory() {
-                // Add to history and change directory
-                self.add_to_history(item.get_path().clone())?;
-                state.current_dir = item.get_path().clone();
-                return Ok(Some(ModeAction::Switch(AppMode::Normal)));
-            }
+        if let Some(item) = state.get_selected_item()
+            && item.is_directory()
+        {
+            // Refuse to leave history mode for a directory that can't be listed
+            let path = item.get_path();
+            FilesystemService::check_readable(path)?;
+
+            // Add to history and change directory
+            self.add_to_history(path.clone());
+            state.current_dir = item.get_path().clone();
+            return Ok(Some(ModeAction::Switch(AppMode::Normal)));
         }
         Ok(Some(ModeAction::Switch(AppMode::Normal)))
     }
 
     fn load_data(&self, state: &mut AppState) -> Result<()> {
         let config = get_history_config();
-        let history_entries = self.get_sorted_entries(&config.sort_mode)?;
+        // Entries read in the background when the mode was entered come first
+        let history_entries = match HistoryStore::DIRECTORIES.take_loaded() {
+            Some(entries) => entries?,
+            None => self.get_sorted_entries(&config.sort_mode)?,
+        };
+
+        // Entries still show up when the aliases can't be read
+        let aliases = Aliases::load().unwrap_or_else(|e| {
+            state
+                .messages
+                .error(format!("Failed to load aliases: {e:#}"));
+            Aliases::default()
+        });
+        state.files = Arc::new(
+            history_entries
+                .into_iter()
+                .map(|mut entry| {
+                    entry.alias = aliases.alias_of(&entry.path).map(str::to_string);
+                    DisplayItem::History(entry)
+                })
+                .collect(),
+        );
+        state.apply_search_filter();
+        Ok(())
+    }
 
-        state.files = history_entries
-            .into_iter()
-            .map(DisplayItem::History)
-            .collect();
+    fn start_loading(&self, state: &mut AppState) -> Result<()> {
+        HistoryStore::DIRECTORIES.load_in_background(get_history_config().sort_mode.clone());
+        state.files = Arc::default();
         state.apply_search_filter();
         Ok(())
     }
@@ -51,6 +51,14 @@ impl ModeHandler for HistoryModeHandler {
     }
 
     fn get_search_box_config(&self, state: &AppState) -> (String, String, Style) {
+        if let Some(alias) = &state.alias_input {
+            return (
+                "ALIAS - Type an alias for the entry, Enter to save (empty removes it), ESC to cancel"
+                    .to_string(),
+                alias.clone(),
+                Style::default().fg(Color::Black).bg(Color::Magenta),
+            );
+        }
         let (info, style) = if state.is_searching {
             if state.search_input.is_empty() {
                 (
@@ -5,7 +5,11 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
 };
 
-use crate::{AppState, modes::Renderer};
+use crate::{
+    AppState,
+    modes::{Renderer, help_overlay::keybinding_lines},
+    utils::AppMode,
+};
 
 /// Renderer for History mode help
 #[derive(Default)]
@@ -19,21 +23,16 @@ impl HistoryHelpRenderer {
 
 impl Renderer for HistoryHelpRenderer {
     fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
-        let help_content = vec![
+        let mut help_content = vec![
             Line::from("History Mode Navigation:"),
             Line::from(""),
-            Line::from("j/k or ↑↓  - Navigate history"),
-            Line::from("l/→        - Enter directory & return to normal"),
-            Line::from("b          - Move up half page"),
-            Line::from("f          - Move down half page"),
-            Line::from("/f          - Search history"),
-            Line::from("ESC        - Exit search (when searching)"),
-            Line::from("Enter      - Select directory & exit app"),
-            Line::from("ESC        - Return to normal mode"),
+            Line::from("5j, 10k    - Repeat a movement (5G jumps to item 5)"),
+            Line::from("other keys - Jump to the first name starting with them"),
+            Line::from(",          - Jump to a name starting with a bound key"),
+            Line::from("Note: Selected directory will be moved to top of history"),
             Line::from(""),
-            Line::from("Note: Selected directory will be"),
-            Line::from("      moved to top of history"),
         ];
+        help_content.extend(keybinding_lines(&AppMode::History));
 
         let help_items: Vec<ListItem> = help_content.into_iter().map(ListItem::new).collect();
 
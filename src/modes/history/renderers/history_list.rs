@@ -6,35 +6,31 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
 };
 
-use crate::{AppState, modes::Renderer, utils::DisplayItem};
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::{
+        Renderer,
+        list_lines::{ListContent, ListLines},
+    },
+    services::{HistoryStore, icons},
+    utils::{self, DisplayItem},
+};
 
 /// Renderer for history list in History mode
 #[derive(Default)]
-pub struct HistoryListRenderer;
+pub struct HistoryListRenderer {
+    lines: ListLines,
+}
 
 impl HistoryListRenderer {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 }
 
 impl Renderer for HistoryListRenderer {
     fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
-        let history_items: Vec<ListItem> = if state.filtered_files.is_empty() {
-            if state.files.is_empty() {
-                vec![ListItem::new("No history available")]
-            } else {
-                vec![ListItem::new("No matching history entries")]
-            }
-        } else {
-            state
-                .filtered_files
-                .iter()
-                .filter_map(|&i| state.files.get(i))
-                .map(|item| create_history_list_item(item, &state.search_input))
-                .collect()
-        };
-
         let history_title = if state.is_searching && !state.search_input.is_empty() {
             format!(
                 "History - {} matches ({}/{})",
@@ -46,53 +42,88 @@ impl Renderer for HistoryListRenderer {
             format!("History - {} entries", state.files.len())
         };
 
-        let history_list = List::new(history_items)
-            .block(Block::default().borders(Borders::ALL).title(history_title))
-            .highlight_style(Style::default().bg(Color::DarkGray));
+        let block = Block::default().borders(Borders::ALL).title(history_title);
+        if state.filtered_files.is_empty() {
+            let placeholder = if state.files.is_empty() && HistoryStore::DIRECTORIES.is_loading() {
+                "Loading history…"
+            } else if state.files.is_empty() {
+                "No history available"
+            } else {
+                "No matching history entries"
+            };
+            f.render_widget(List::new([ListItem::new(placeholder)]).block(block), area);
+            return;
+        }
 
-        f.render_stateful_widget(history_list, area, &mut state.file_list_state.clone());
+        let content = ListContent {
+            files: &state.files,
+            filtered_files: &state.filtered_files,
+            list_state: &state.file_list_state,
+            search: &state.search_input,
+            case: state.case_matching,
+        };
+        self.lines.render(
+            f,
+            area,
+            block,
+            content,
+            |item| state.is_marked(item.get_path()),
+            |item, marked| {
+                create_history_line(item, &state.search_input, state.case_matching, marked)
+            },
+        );
     }
 }
 
-/// Create a list item for a history entry with directory name and full path
-fn create_history_list_item<'a>(item: &'a DisplayItem, search_input: &'a str) -> ListItem<'a> {
+/// Create the line of a history entry with directory name and full path
+fn create_history_line<'a>(
+    item: &'a DisplayItem,
+    search_input: &str,
+    case: CaseMatching,
+    marked: bool,
+) -> Line<'a> {
     match item {
         DisplayItem::History(entry) => {
-            let icon = "📁";
             let dir_name = entry
                 .path
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or_default();
-            let full_path = entry.path.to_string_lossy();
+            let icon = icons::directory_icon(dir_name);
 
             // Create spans for the display
             let mut spans = vec![
+                Span::styled(
+                    if marked { "* " } else { "" },
+                    Style::default().fg(Color::Magenta),
+                ),
                 Span::styled(icon, Style::default().fg(Color::Cyan)),
                 Span::raw(" "),
             ];
 
             // Add directory name with highlighting if searching
-            if !search_input.is_empty() {
-                let search_lower = search_input.to_lowercase();
-                let name_lower = dir_name.to_lowercase();
-                if let Some(pos) = name_lower.find(&search_lower) {
-                    // Highlight the search term in directory name
-                    let before = &dir_name[..pos];
-                    let matched = &dir_name[pos..pos + search_input.len()];
-                    let after = &dir_name[pos + search_input.len()..];
+            let mut last_end = 0;
+            for range in utils::name_matches(dir_name, &entry.path, search_input, case) {
+                spans.push(Span::styled(
+                    &dir_name[last_end..range.start],
+                    Style::default().fg(Color::Cyan),
+                ));
+                last_end = range.end;
+                spans.push(Span::styled(
+                    &dir_name[range],
+                    Style::default().fg(Color::Yellow).bg(Color::DarkGray),
+                ));
+            }
+            spans.push(Span::styled(
+                &dir_name[last_end..],
+                Style::default().fg(Color::Cyan),
+            ));
 
-                    spans.push(Span::styled(before, Style::default().fg(Color::Cyan)));
-                    spans.push(Span::styled(
-                        matched,
-                        Style::default().fg(Color::Yellow).bg(Color::DarkGray),
-                    ));
-                    spans.push(Span::styled(after, Style::default().fg(Color::Cyan)));
-                } else {
-                    spans.push(Span::styled(dir_name, Style::default().fg(Color::Cyan)));
-                }
-            } else {
-                spans.push(Span::styled(dir_name, Style::default().fg(Color::Cyan)));
+            if let Some(alias) = &entry.alias {
+                spans.push(Span::styled(
+                    format!(" [{alias}]"),
+                    Style::default().fg(Color::Magenta),
+                ));
             }
 
             // Add frequency indicator
@@ -103,16 +134,13 @@ fn create_history_list_item<'a>(item: &'a DisplayItem, search_input: &'a str) ->
 
             // Add full path in darker color
             spans.push(Span::raw(" "));
-            spans.push(Span::styled(
-                format!("({full_path})"),
-                Style::default().fg(Color::DarkGray),
-            ));
+            spans.extend(utils::path_spans(&entry.path, search_input, case));
 
-            ListItem::new(Line::from(spans))
+            Line::from(spans)
         }
         DisplayItem::File(_) => {
             // This shouldn't happen in history mode, but handle it gracefully
-            ListItem::new("Invalid history entry")
+            Line::from("Invalid history entry")
         }
     }
 }
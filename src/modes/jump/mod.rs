@@ -0,0 +1,30 @@
+pub mod data_provider;
+pub mod renderers;
+
+use crate::{
+    modes::list_mode::{ListModeHandler, ListModeSpec},
+    utils::AppMode,
+};
+
+pub use data_provider::JumpDataProvider;
+pub use renderers::JumpListRenderer;
+
+/// Jump mode (go to any indexed directory)
+pub static JUMP_MODE: ListModeSpec = ListModeSpec {
+    mode: AppMode::Jump,
+    title: "Jump",
+    entries: "directories",
+    search_hint: "indexed directories",
+    heading: "Jump Navigation:",
+    description: &[
+        "Lists the directories under the [index] roots,",
+        "refreshed in the background.",
+    ],
+    opens_search: true,
+    reads_index: true,
+};
+
+/// Handler for Jump mode
+pub fn handler() -> ListModeHandler {
+    ListModeHandler::new(&JUMP_MODE, Box::new(JumpListRenderer::new()))
+}
@@ -0,0 +1,93 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::Renderer,
+    services::{dir_index, icons},
+    utils::{self, DisplayItem},
+};
+
+/// Renderer for the directory list in Jump mode
+#[derive(Default)]
+pub struct JumpListRenderer;
+
+impl JumpListRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for JumpListRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        // Items of packed listings are made on the spot, so they are kept for the lines
+        let entries: Vec<_> = state
+            .filtered_files
+            .iter()
+            .filter_map(|&i| state.files.get(i))
+            .collect();
+        let items: Vec<ListItem> = if state.filtered_files.is_empty() {
+            if state.files.is_empty() && dir_index::is_refreshing() {
+                vec![ListItem::new("Indexing directories…")]
+            } else if state.files.is_empty() {
+                vec![ListItem::new("No indexed directories")]
+            } else {
+                vec![ListItem::new("No matching directories")]
+            }
+        } else {
+            entries
+                .iter()
+                .map(|item| create_jump_list_item(item, &state.search_input, state.case_matching))
+                .collect()
+        };
+
+        let mut title = if state.is_searching && !state.search_input.is_empty() {
+            format!(
+                "Jump - {} matches ({}/{})",
+                state.filtered_files.len(),
+                state.filtered_files.len(),
+                state.files.len()
+            )
+        } else {
+            format!("Jump - {} directories", state.files.len())
+        };
+        if dir_index::is_refreshing() {
+            title.push_str(" indexing…");
+        } else if state.is_filtering() {
+            title.push_str(" searching…");
+        }
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        f.render_stateful_widget(list, area, &mut state.file_list_state.clone());
+    }
+}
+
+/// Create a list item for an indexed directory with its name and full path
+fn create_jump_list_item<'a>(
+    item: &'a DisplayItem,
+    search_input: &'a str,
+    case: CaseMatching,
+) -> ListItem<'a> {
+    let DisplayItem::File(dir) = item else {
+        return ListItem::new("Invalid index entry");
+    };
+
+    let mut spans = vec![Span::raw(icons::icon_for(dir)), Span::raw(" ")];
+    spans.extend(utils::highlight_matches(
+        &dir.name,
+        utils::name_matches(&dir.name, &dir.path, search_input, case),
+    ));
+    spans.push(Span::raw(" "));
+    spans.extend(utils::path_spans(&dir.path, search_input, case));
+
+    ListItem::new(Line::from(spans))
+}
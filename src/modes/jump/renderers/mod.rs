@@ -0,0 +1,3 @@
+pub mod jump_list;
+
+pub use jump_list::JumpListRenderer;
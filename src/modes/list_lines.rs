@@ -0,0 +1,239 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListState},
+};
+use std::{cell::RefCell, collections::HashMap, ops::Range, sync::Arc};
+
+use crate::{config::CaseMatching, core::listing::Listing, utils::DisplayItem};
+
+/// Lines kept for rows that scrolled off screen, beyond this the kept
+/// lines are dropped except for the rows on screen
+const MAX_KEPT_LINES: usize = 4_096;
+
+/// Everything the lines of a listing's rows are built from, besides marks
+struct LinesKey {
+    /// Listings are replaced rather than changed, so the same `Arc` means the same entries
+    files: Arc<Listing>,
+    search: String,
+    case: CaseMatching,
+}
+
+impl PartialEq for LinesKey {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.files, &other.files)
+            && self.search == other.search
+            && self.case == other.case
+    }
+}
+
+/// The styled lines of the rows that were on screen, by entry index and
+/// whether the entry was marked
+type KeptLines = HashMap<usize, (bool, Line<'static>)>;
+
+/// The styled lines of list rows, kept between draws
+///
+/// The main loop redraws every 100ms even when nothing happens. With the
+/// lines kept, such a redraw copies the visible rows instead of styling
+/// every entry of the listing again, and scrolling only styles the rows
+/// that came into view. Icons, highlighted matches and name widths are
+/// worked out once for each row until the listing or the query changes.
+#[derive(Default)]
+pub struct ListLines {
+    cached: RefCell<Option<(LinesKey, KeptLines)>>,
+}
+
+/// What a list is drawn from
+pub struct ListContent<'a> {
+    pub files: &'a Arc<Listing>,
+    pub filtered_files: &'a [usize],
+    pub list_state: &'a ListState,
+    pub search: &'a str,
+    pub case: CaseMatching,
+}
+
+impl ListLines {
+    /// Draw the rows of `content` that fit in `area`, building the lines of
+    /// rows that weren't drawn before with `build`
+    ///
+    /// The selected row is highlighted like in every other list.
+    pub fn render(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        block: Block,
+        content: ListContent,
+        is_marked: impl Fn(&DisplayItem) -> bool,
+        build: impl Fn(&DisplayItem, bool) -> Line<'_>,
+    ) {
+        let height = block.inner(area).height as usize;
+        let window = visible_window(
+            content.filtered_files.len(),
+            content.list_state.offset(),
+            content.list_state.selected(),
+            height,
+        );
+        let rows = &content.filtered_files[window.clone()];
+        let key = LinesKey {
+            files: content.files.clone(),
+            search: content.search.to_string(),
+            case: content.case,
+        };
+
+        let mut cached = self.cached.borrow_mut();
+        let kept = match &mut *cached {
+            Some((known, kept)) if *known == key => kept,
+            cached => &mut cached.insert((key, HashMap::new())).1,
+        };
+        if kept.len() > MAX_KEPT_LINES {
+            kept.retain(|index, _| rows.contains(index));
+        }
+        // Only the rows on screen are made into items when the listing is packed
+        let mut lines = Vec::with_capacity(rows.len());
+        content.files.visit(rows.iter().copied(), |index, item| {
+            let marked = is_marked(item);
+            let line = match kept.get(&index) {
+                Some((was_marked, line)) if *was_marked == marked => line.clone(),
+                _ => {
+                    let line = into_owned(build(item, marked));
+                    kept.insert(index, (marked, line.clone()));
+                    line
+                }
+            };
+            lines.push(line);
+        });
+
+        let list = List::new(lines)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        let mut window_state = ListState::default().with_selected(
+            content
+                .list_state
+                .selected()
+                .filter(|selected| window.contains(selected))
+                .map(|selected| selected - window.start),
+        );
+        f.render_stateful_widget(list, area, &mut window_state);
+    }
+
+    /// Build the lines again on the next draw, for rows showing something
+    /// the key doesn't cover, like mount details loaded in the background
+    pub fn invalidate(&self) {
+        self.cached.borrow_mut().take();
+    }
+}
+
+/// The rows of a list of `total` entries that fit in `height` lines
+///
+/// Starts at `offset`, moved like ratatui's `List` would to keep `selected`
+/// on screen.
+pub fn visible_window(
+    total: usize,
+    offset: usize,
+    selected: Option<usize>,
+    height: usize,
+) -> Range<usize> {
+    let mut start = offset.min(total);
+    if let Some(selected) = selected.filter(|&selected| selected < total) {
+        if selected < start {
+            start = selected;
+        } else if height > 0 && selected >= start + height {
+            start = selected + 1 - height;
+        }
+    }
+    start..(start + height).min(total)
+}
+
+fn into_owned(line: Line<'_>) -> Line<'static> {
+    Line {
+        spans: line
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect(),
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FileItem;
+    use ratatui::{Terminal, backend::TestBackend, widgets::Borders};
+    use std::{cell::Cell, path::PathBuf};
+
+    #[test]
+    fn test_visible_window() {
+        assert_eq!(visible_window(100, 0, Some(3), 10), 0..10);
+        assert_eq!(visible_window(100, 0, Some(15), 10), 6..16);
+        assert_eq!(visible_window(100, 20, Some(5), 10), 5..15);
+        assert_eq!(visible_window(4, 0, Some(2), 10), 0..4);
+        assert_eq!(visible_window(0, 0, None, 10), 0..0);
+    }
+
+    #[test]
+    fn test_lines_built_again_only_after_a_change() {
+        let files: Arc<Listing> = Arc::new(
+            (0..1000)
+                .map(|i| {
+                    DisplayItem::File(FileItem {
+                        name: format!("file{i}"),
+                        path: PathBuf::from(format!("/tmp/file{i}")),
+                        is_dir: false,
+                        kind: crate::utils::FileKind::Regular,
+                        hidden: false,
+                    })
+                })
+                .collect(),
+        );
+        let filtered: Vec<usize> = (0..files.len()).collect();
+        let mut list_state = ListState::default().with_selected(Some(0));
+        let lines = ListLines::default();
+        let built = Cell::new(0);
+        let mut terminal = Terminal::new(TestBackend::new(20, 12)).unwrap();
+        let mut draw = |list_state: &ListState, search: &str| {
+            terminal
+                .draw(|f| {
+                    let content = ListContent {
+                        files: &files,
+                        filtered_files: &filtered,
+                        list_state,
+                        search,
+                        case: CaseMatching::Smart,
+                    };
+                    lines.render(
+                        f,
+                        f.area(),
+                        Block::default().borders(Borders::ALL),
+                        content,
+                        |_| false,
+                        |item, _| {
+                            built.set(built.get() + 1);
+                            Line::from(item.get_display_name())
+                        },
+                    );
+                })
+                .unwrap();
+        };
+
+        draw(&list_state, "");
+        assert_eq!(built.get(), 10);
+        draw(&list_state, "");
+        assert_eq!(built.get(), 10);
+        list_state.select(Some(1));
+        draw(&list_state, "");
+        assert_eq!(built.get(), 10);
+        // Scrolling down a row only builds the row that came into view
+        list_state.select(Some(10));
+        draw(&list_state, "");
+        assert_eq!(built.get(), 11);
+        list_state.select(Some(0));
+        draw(&list_state, "");
+        assert_eq!(built.get(), 11);
+        draw(&list_state, "file");
+        assert_eq!(built.get(), 21);
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    modes::{ModeHandler, Renderer, help_overlay::keybinding_lines, preview::PreviewRenderer},
+    services::dir_index,
+    utils::AppMode,
+};
+
+/// What sets apart the modes that list directories to go to, like Jump or
+/// Mounts mode
+pub struct ListModeSpec {
+    pub mode: AppMode,
+    /// Name of the mode, like `Jump`
+    pub title: &'static str,
+    /// What the entries are, like `directories`
+    pub entries: &'static str,
+    /// What the search goes through, like `indexed directories`
+    pub search_hint: &'static str,
+    /// First line of the help, like `Jump Navigation:`
+    pub heading: &'static str,
+    /// Lines describing the listed entries, shown in the help
+    pub description: &'static [&'static str],
+    /// Whether entering the mode opens the search, when picking starts with typing
+    pub opens_search: bool,
+    /// Whether the entries come from the directory index, refreshed on entering when stale
+    pub reads_index: bool,
+}
+
+/// Handler for a mode listing directories, see [`ListModeSpec`]
+pub struct ListModeHandler {
+    spec: &'static ListModeSpec,
+    list_renderer: Box<dyn Renderer>,
+    preview_renderer: Box<dyn Renderer>,
+    help_renderer: Box<dyn Renderer>,
+}
+
+impl ListModeHandler {
+    pub fn new(spec: &'static ListModeSpec, list_renderer: Box<dyn Renderer>) -> Self {
+        Self {
+            spec,
+            list_renderer,
+            preview_renderer: Box::new(PreviewRenderer::new()),
+            help_renderer: Box::new(ListHelpRenderer::new(spec)),
+        }
+    }
+}
+
+impl ModeHandler for ListModeHandler {
+    fn render_left_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        self.list_renderer.render(f, area, state);
+    }
+
+    fn render_right_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        if self.should_show_help(state) {
+            self.help_renderer.render(f, area, state);
+        } else {
+            self.preview_renderer.render(f, area, state);
+        }
+    }
+
+    fn get_search_box_config(&self, state: &AppState) -> (String, String, Style) {
+        let name = self.spec.title.to_uppercase();
+        let (info, style) = if state.is_searching {
+            if state.search_input.is_empty() {
+                (
+                    format!(
+                        "SEARCH - Type to search {}, ESC to exit search",
+                        self.spec.search_hint
+                    ),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                )
+            } else {
+                (
+                    format!(
+                        "SEARCH - '{}' - {} matches (ESC to exit)",
+                        state.search_input,
+                        state.filtered_files.len()
+                    ),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                )
+            }
+        } else if !state.search_input.is_empty() {
+            (
+                format!(
+                    "FILTERED {name} - '{}' - {} matches (l/→ enter, / to search again, ESC to normal)",
+                    state.search_input,
+                    state.filtered_files.len()
+                ),
+                Style::default().fg(Color::Black).bg(Color::Green),
+            )
+        } else {
+            (
+                format!(
+                    "{name} - {} {} (jk navigate, l/→ enter, / search, Enter select, ESC to normal)",
+                    state.files.len(),
+                    self.spec.entries
+                ),
+                Style::default().fg(Color::Cyan),
+            )
+        };
+        (info, state.search_input.clone(), style)
+    }
+
+    fn should_show_help(&self, state: &AppState) -> bool {
+        if state.is_searching {
+            state.search_input.is_empty() || state.filtered_files.is_empty()
+        } else {
+            state.file_list_state.selected().is_none()
+        }
+    }
+
+    fn on_enter(&mut self, state: &mut AppState) -> Result<()> {
+        state.file_list_state.select(None);
+        if self.spec.opens_search {
+            state.is_searching = true;
+        }
+        if self.spec.reads_index {
+            dir_index::refresh_if_stale();
+        }
+        Ok(())
+    }
+}
+
+/// Renderer for the help of a mode listing directories
+pub struct ListHelpRenderer {
+    spec: &'static ListModeSpec,
+}
+
+impl ListHelpRenderer {
+    pub fn new(spec: &'static ListModeSpec) -> Self {
+        Self { spec }
+    }
+}
+
+impl Renderer for ListHelpRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
+        let mut help_content = vec![Line::from(self.spec.heading), Line::from("")];
+        help_content.extend(self.spec.description.iter().map(|&line| Line::from(line)));
+        help_content.push(Line::from(""));
+        help_content.extend(keybinding_lines(&self.spec.mode));
+
+        let help_items: Vec<ListItem> = help_content.into_iter().map(ListItem::new).collect();
+
+        let help_widget = List::new(help_items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Help - {} Mode", self.spec.title)),
+        );
+
+        f.render_widget(help_widget, area);
+    }
+}
@@ -0,0 +1,65 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{AppState, core::messages::MessageLevel, modes::Renderer};
+
+/// Popup showing the oldest pending notification at the bottom of the screen
+#[derive(Default)]
+pub struct MessageOverlayRenderer;
+
+impl MessageOverlayRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for MessageOverlayRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        let Some(message) = state.messages.current() else {
+            return;
+        };
+
+        let (label, color) = match message.level {
+            MessageLevel::Info => ("Info", Color::Cyan),
+            MessageLevel::Error => ("Error", Color::Red),
+        };
+        let title = match state.messages.pending() {
+            0 => format!("{label} (Esc to dismiss)"),
+            n => format!("{label} (+{n} more, Esc to dismiss)"),
+        };
+
+        // Size the popup to the message, wrapping long ones
+        let max_width = area.width.saturating_sub(4).max(1);
+        let text_width = message.text.width() as u16 + 2;
+        let width = text_width
+            .max(title.chars().count() as u16 + 4)
+            .min(max_width);
+        let inner_width = width.saturating_sub(2).max(1);
+        let lines = text_width.saturating_sub(2).div_ceil(inner_width).max(1);
+        let height = (lines + 2).min(area.height);
+
+        let popup = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + area.height.saturating_sub(height + 1),
+            width,
+            height,
+        );
+
+        let paragraph = Paragraph::new(message.text.as_str())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(color)),
+            );
+
+        f.render_widget(Clear, popup);
+        f.render_widget(paragraph, popup);
+    }
+}
@@ -1,24 +1,47 @@
 use anyhow::Result;
-use ratatui::{Frame, layout::Rect, style::Style};
+use ratatui::{Frame, layout::Rect, style::Style, widgets::ListState};
+use std::path::PathBuf;
 
 use crate::{
     app_state::AppState,
     utils::{AppMode, FileItem},
 };
 
+pub mod help_overlay;
 pub mod history;
+pub mod jump;
+pub mod list_lines;
+pub mod list_mode;
+pub mod message_overlay;
+pub mod mounts;
 pub mod normal;
+pub mod pager;
+pub mod picker;
 pub mod preview;
+pub mod projects;
+pub mod recent_files;
+pub mod repos;
+pub mod scroll_indicator;
 
 pub trait Renderer {
     /// Render the component in the given area
     fn render(&self, f: &mut Frame, area: Rect, state: &AppState);
 }
 
-/// Represents a mode switch request
+/// What the app does after a key, a click or a mode's own handling
 #[derive(Debug, Clone, PartialEq)]
 pub enum ModeAction {
     Stay,
+    /// Stay and load the entries of the mode again, keeping the selected one
+    Refresh,
+    /// Stay and show a notification
+    Message(String),
+    /// Hand the terminal to a shell command built like `--exec` does, then
+    /// come back and refresh
+    RunCommand {
+        template: String,
+        paths: Vec<PathBuf>,
+    },
     Switch(AppMode),
     Exit(Option<FileItem>),
 }
@@ -55,6 +78,12 @@ pub fn create_mode_handler(mode: &AppMode) -> Box<dyn ModeHandler> {
     match mode {
         AppMode::Normal => Box::new(normal::NormalModeHandler::new()),
         AppMode::History => Box::new(history::HistoryModeHandler::new()),
+        AppMode::Picker => Box::new(picker::PickerModeHandler::new()),
+        AppMode::RecentFiles => Box::new(recent_files::RecentFilesModeHandler::new()),
+        AppMode::Jump => Box::new(jump::handler()),
+        AppMode::Repos => Box::new(repos::handler()),
+        AppMode::Projects => Box::new(projects::handler()),
+        AppMode::Mounts => Box::new(mounts::handler()),
     }
 }
 
@@ -62,6 +91,8 @@ pub fn create_mode_handler(mode: &AppMode) -> Box<dyn ModeHandler> {
 pub struct ModeManager {
     pub current_handler: Box<dyn ModeHandler>,
     pub current_mode: AppMode,
+    /// Draws the unfocused pane of dual-pane mode, keeping its lines between draws
+    other_pane_renderer: normal::FileListRenderer,
 }
 
 impl ModeManager {
@@ -69,19 +100,25 @@ impl ModeManager {
         Self {
             current_handler: create_mode_handler(initial_mode),
             current_mode: *initial_mode,
+            other_pane_renderer: normal::FileListRenderer::new(),
         }
     }
 
     pub fn switch_mode(&mut self, state: &mut AppState, new_mode: &AppMode) -> Result<()> {
         self.current_handler.on_exit(state)?;
 
+        // The second pane is a file list, which only normal mode shows
+        if *new_mode != AppMode::Normal {
+            state.close_dual_pane();
+        }
+
         // Clear search when switching modes
-        state.search_input.clear();
+        state.clear_search();
         state.is_searching = false;
 
         // Load appropriate data for the new mode using data provider
         let data_provider = crate::services::create_data_provider(new_mode);
-        data_provider.load_data(state)?;
+        data_provider.start_loading(state)?;
 
         self.current_handler = create_mode_handler(new_mode);
         self.current_mode = *new_mode;
@@ -91,6 +128,16 @@ impl ModeManager {
 
     pub fn render_left_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
         self.current_handler.render_left_panel(f, area, state);
+        render_list_position(f, area, state.filtered_files.len(), &state.file_list_state);
+    }
+
+    /// Render the unfocused pane of dual-pane mode, if it is open
+    pub fn render_other_pane(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        let Some(pane) = &state.other_pane else {
+            return;
+        };
+        self.other_pane_renderer.render_pane(f, area, state, pane);
+        render_list_position(f, area, pane.filtered_files.len(), &pane.file_list_state);
     }
 
     pub fn render_right_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
@@ -109,3 +156,14 @@ impl ModeManager {
         self.current_mode == *mode
     }
 }
+
+/// Show where a list is scrolled to, the same way in every mode
+fn render_list_position(f: &mut Frame, area: Rect, total: usize, list_state: &ListState) {
+    let visible = area.height.saturating_sub(2) as usize;
+    scroll_indicator::render_scrollbar(f, area, total, list_state.offset(), visible);
+    if let Some(selected) = list_state.selected()
+        && total > 0
+    {
+        scroll_indicator::render_position(f, area, &format!("{}/{total}", selected + 1));
+    }
+}
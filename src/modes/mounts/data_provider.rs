@@ -0,0 +1,48 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::{
+    app_state::AppState,
+    modes::{ModeAction, history::HistoryDataProvider},
+    services::{DataProvider, FilesystemService, mounts},
+    utils::{AppMode, DisplayItem, FileItem, FileKind},
+};
+
+/// Data provider for mounted filesystems (Mounts mode)
+#[derive(Debug)]
+pub struct MountsDataProvider;
+
+impl DataProvider for MountsDataProvider {
+    fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
+        if let Some(item) = state.get_selected_item() {
+            // Media can be ejected while the list is open
+            let path = item.get_path().clone();
+            FilesystemService::check_readable(&path)?;
+
+            HistoryDataProvider.add_to_history(path.clone());
+            state.current_dir = path;
+        }
+        Ok(Some(ModeAction::Switch(AppMode::Normal)))
+    }
+
+    fn load_data(&self, state: &mut AppState) -> Result<()> {
+        let mounts = mounts::load_mounts()?;
+
+        state.files = Arc::new(
+            mounts
+                .into_iter()
+                .map(|mount| {
+                    DisplayItem::File(FileItem {
+                        name: mount.mount_point.display().to_string(),
+                        path: mount.mount_point,
+                        is_dir: true,
+                        kind: FileKind::Directory,
+                        hidden: false,
+                    })
+                })
+                .collect(),
+        );
+        state.apply_search_filter();
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+pub mod data_provider;
+pub mod renderers;
+
+use crate::{
+    modes::list_mode::{ListModeHandler, ListModeSpec},
+    utils::AppMode,
+};
+
+pub use data_provider::MountsDataProvider;
+pub use renderers::MountsListRenderer;
+
+/// Mounts mode (go to a mounted filesystem or drive)
+pub static MOUNTS_MODE: ListModeSpec = ListModeSpec {
+    mode: AppMode::Mounts,
+    title: "Mounts",
+    entries: "mounts",
+    search_hint: "mounts",
+    heading: "Mount Navigation:",
+    description: &[
+        "Lists mounted filesystems with their device,",
+        "type and free space.",
+    ],
+    opens_search: false,
+    reads_index: false,
+};
+
+/// Handler for Mounts mode
+pub fn handler() -> ListModeHandler {
+    ListModeHandler::new(&MOUNTS_MODE, Box::new(MountsListRenderer::new()))
+}
@@ -0,0 +1,3 @@
+pub mod mounts_list;
+
+pub use mounts_list::{MountsListRenderer, mount_detail_spans};
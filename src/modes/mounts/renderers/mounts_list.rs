@@ -0,0 +1,133 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::Renderer,
+    services::{
+        icons,
+        mounts::{self, Mount},
+    },
+    utils::{self, DisplayItem},
+};
+
+/// Renderer for the mount list in Mounts mode
+#[derive(Default)]
+pub struct MountsListRenderer;
+
+impl MountsListRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for MountsListRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        // Items of packed listings are made on the spot, so they are kept for the lines
+        let entries: Vec<_> = state
+            .filtered_files
+            .iter()
+            .filter_map(|&i| state.files.get(i))
+            .collect();
+        let items: Vec<ListItem> = if state.filtered_files.is_empty() {
+            if state.files.is_empty() {
+                vec![ListItem::new("No mounted filesystems")]
+            } else {
+                vec![ListItem::new("No matching mounts")]
+            }
+        } else {
+            entries
+                .iter()
+                .map(|item| create_mount_list_item(item, &state.search_input, state.case_matching))
+                .collect()
+        };
+
+        let title = if state.is_searching && !state.search_input.is_empty() {
+            format!(
+                "Mounts - {} matches ({}/{})",
+                state.filtered_files.len(),
+                state.filtered_files.len(),
+                state.files.len()
+            )
+        } else {
+            format!("Mounts - {} mounts", state.files.len())
+        };
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        f.render_stateful_widget(list, area, &mut state.file_list_state.clone());
+    }
+}
+
+/// Create a list item for a mount with its device, filesystem type and free space
+fn create_mount_list_item<'a>(
+    item: &'a DisplayItem,
+    search_input: &'a str,
+    case: CaseMatching,
+) -> ListItem<'a> {
+    let DisplayItem::File(mount_point) = item else {
+        return ListItem::new("Invalid mount entry");
+    };
+    let mount = mounts::info(&mount_point.path).unwrap_or_default();
+
+    let mut spans = vec![Span::raw(icons::drive_icon()), Span::raw(" ")];
+    spans.extend(utils::highlight_matches(
+        &mount_point.name,
+        utils::name_matches(&mount_point.name, &mount_point.path, search_input, case),
+    ));
+    spans.extend(mount_detail_spans(&mount));
+
+    ListItem::new(Line::from(spans))
+}
+
+/// The label, filesystem, free space and device of a mount, as shown after its name
+pub fn mount_detail_spans(mount: &Mount) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if let Some(label) = &mount.label {
+        spans.push(Span::styled(
+            format!(" {label}"),
+            Style::default().fg(Color::White),
+        ));
+    }
+    if !mount.fs_type.is_empty() {
+        spans.push(Span::styled(
+            format!(" [{}]", mount.fs_type),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if mount.removable {
+        spans.push(Span::styled(
+            " removable",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if let Some(space) = mount.space {
+        spans.push(Span::styled(
+            format!(" {}", space.bar(10)),
+            Style::default().fg(Color::Blue),
+        ));
+        spans.push(Span::styled(
+            format!(
+                " {} free of {}",
+                utils::format_size(space.free),
+                utils::format_size(space.total)
+            ),
+            Style::default().fg(Color::Green),
+        ));
+    }
+    if !mount.device.is_empty() && mount.device != mount.mount_point.display().to_string() {
+        spans.push(Span::styled(
+            format!(" ({})", mount.device),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    spans
+}
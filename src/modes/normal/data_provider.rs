@@ -3,8 +3,9 @@ use std::path::{Path, PathBuf};
 
 use crate::{
     app_state::AppState,
+    config::get_config,
     modes::ModeAction,
-    services::{DataProvider, FilesystemService, PreviewManager},
+    services::{DataProvider, FilesystemService, PreviewManager, plugins},
     utils::DisplayItem,
 };
 
@@ -22,19 +23,15 @@ impl DataProvider for FileListDataProvider {
     }
 
     fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
-        if let Some(file) = state.get_selected_item() {
-            if file.is_directory() {
-                // Save current position before changing directory
-                self.save_position(state);
-
-                // Change directory
-                state.current_dir = file.get_path().to_path_buf();
-
-                // Handle directory change
-                self.on_directory_changed(state, &state.current_dir.clone())?;
-
-                return Ok(None); // Stay in current mode
-            }
+        if let Some(file) = state.get_selected_item()
+            && file.is_directory()
+        {
+            let path = match Self::symlink_destination(state, file.get_path()) {
+                Some(path) => path,
+                None => return Ok(None),
+            };
+            self.change_directory(state, path)?;
+            return Ok(None); // Stay in current mode
         }
         Ok(None)
     }
@@ -46,34 +43,13 @@ impl DataProvider for FileListDataProvider {
         }
 
         if let Some(parent) = state.current_dir.parent() {
-            let parent_path = parent.to_path_buf();
-
-            // Save current position before changing directory
-            self.save_position(state);
-
-            // Change directory
-            state.current_dir = parent_path.clone();
-
-            // Handle directory change
-            self.on_directory_changed(state, &parent_path)?;
-
+            self.change_directory(state, parent.to_path_buf())?;
             Ok(None) // Stay in current mode
         } else {
-            // On Windows, if we're at a drive root (like C:\), show drives
-            #[cfg(windows)]
-            {
-                if self.is_windows_drive_root(&state.current_dir) {
-                    // Save current position before changing to drives view
-                    self.save_position(state);
-
-                    // Set to special drives path
-                    state.current_dir = PathBuf::from("DRIVES:");
-
-                    // Handle directory change
-                    self.on_directory_changed(state, &state.current_dir.clone())?;
-
-                    return Ok(None);
-                }
+            // At a drive root like C:\ or at /, show the drives and media
+            if self.is_filesystem_root(&state.current_dir) {
+                // Switch to the special drives view
+                self.change_directory(state, PathBuf::from("DRIVES:"))?;
             }
 
             Ok(None)
@@ -115,9 +91,9 @@ impl DataProvider for FileListDataProvider {
         }
     }
 
-    fn on_directory_changed(&self, state: &mut AppState, _new_dir: &Path) -> Result<()> {
+    fn on_directory_changed(&self, state: &mut AppState, new_dir: &Path) -> Result<()> {
         // Clear search and exit search mode when changing directory
-        state.search_input.clear();
+        state.clear_search();
         state.is_searching = false;
 
         // Load new directory contents
@@ -129,17 +105,71 @@ impl DataProvider for FileListDataProvider {
         // Clear preview
         PreviewManager::clear_preview();
 
+        if new_dir.to_string_lossy() != "DRIVES:" {
+            plugins::on_enter_directory(new_dir);
+        }
+
         Ok(())
     }
 }
 
 impl FileListDataProvider {
+    /// Where entering `path` leads, `None` if it's a link that isn't followed
+    ///
+    /// A link back to the current directory or one above it would make the
+    /// path grow with every step through the loop, so it jumps to the target
+    /// itself instead.
+    fn symlink_destination(state: &mut AppState, path: &Path) -> Option<PathBuf> {
+        if !path.is_symlink() {
+            return Some(path.to_path_buf());
+        }
+        if !get_config().navigation.follow_symlinks {
+            state
+                .messages
+                .info("Symlinked directories are not followed (navigation.follow_symlinks)");
+            return None;
+        }
+        if let (Ok(target), Ok(current)) = (path.canonicalize(), state.current_dir.canonicalize())
+            && current.starts_with(&target)
+        {
+            state
+                .messages
+                .info(format!("Link leads back to {}", target.display()));
+            return Some(target);
+        }
+        Some(path.to_path_buf())
+    }
+
+    /// Change to an arbitrary directory, remembering the position in the current one
+    ///
+    /// If the new directory can't be loaded the previous one is restored, so a
+    /// failed navigation leaves the view untouched.
+    pub fn change_directory(&self, state: &mut AppState, path: PathBuf) -> Result<()> {
+        self.save_position(state);
+        let previous_dir = std::mem::replace(&mut state.current_dir, path.clone());
+
+        if let Err(e) = self.on_directory_changed(state, &path) {
+            state.current_dir = previous_dir.clone();
+            self.on_directory_changed(state, &previous_dir)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
     #[cfg(windows)]
-    fn is_windows_drive_root(&self, path: &PathBuf) -> bool {
+    fn is_filesystem_root(&self, path: &Path) -> bool {
+        // Long directories are listed as `\\?\C:\...`, so going up ends at `\\?\C:\`
+        let path = crate::services::filesystem::strip_verbatim_prefix(path.to_path_buf());
         let path_str = path.to_string_lossy();
-        // Check if it's a drive root like "C:\" or "D:\"
-        path_str.len() == 3
+        // Check if it's a drive root like "C:\" or "D:\", or a share like "\\server\share\"
+        (path_str.len() == 3
             && path_str.ends_with(":\\")
-            && path_str.chars().next().unwrap().is_ascii_alphabetic()
+            && path_str.chars().next().unwrap().is_ascii_alphabetic())
+            || crate::services::filesystem::is_unc_share_root(&path)
+    }
+
+    #[cfg(not(windows))]
+    fn is_filesystem_root(&self, path: &Path) -> bool {
+        path == Path::new("/")
     }
 }
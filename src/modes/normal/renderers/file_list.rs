@@ -1,95 +1,265 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{Block, Borders, ListState},
+};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use crate::{
     AppState,
-    modes::Renderer,
+    app_state::Pane,
+    config::{CaseMatching, EntryFilter},
+    core::{
+        breadcrumb::{BREADCRUMB_PREFIX, BreadcrumbSegment, breadcrumb_segments},
+        listing::Listing,
+    },
+    modes::{
+        Renderer,
+        list_lines::{ListContent, ListLines},
+        mounts::renderers::mount_detail_spans,
+    },
+    services::{LS_COLORS, icons, mounts},
     utils::{self, DisplayItem, FileItem},
 };
 
 /// Renderer for file list in Normal mode
 #[derive(Default)]
-pub struct FileListRenderer;
+pub struct FileListRenderer {
+    focused: PaneLines,
+    /// Both panes are drawn every frame in dual-pane mode, so each keeps its own lines
+    unfocused: PaneLines,
+}
+
+/// What a pane's list keeps between draws
+#[derive(Default)]
+struct PaneLines {
+    rows: ListLines,
+    /// The breadcrumb of the directory listed last, with its measured widths
+    breadcrumb: RefCell<Option<(PathBuf, Vec<BreadcrumbSegment>)>>,
+}
+
+impl PaneLines {
+    fn breadcrumb(&self, dir: &Path) -> Vec<BreadcrumbSegment> {
+        let mut breadcrumb = self.breadcrumb.borrow_mut();
+        match &*breadcrumb {
+            Some((known, segments)) if known == dir => segments.clone(),
+            _ => {
+                let segments = breadcrumb_segments(dir);
+                *breadcrumb = Some((dir.to_path_buf(), segments.clone()));
+                segments
+            }
+        }
+    }
+}
 
 impl FileListRenderer {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 }
 
 impl Renderer for FileListRenderer {
     fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
-        let files: Vec<ListItem> = state
-            .filtered_files
-            .iter()
-            .filter_map(|&i| state.files.get(i))
-            .map(|item| create_display_item_list_item(item, &state.search_input))
-            .collect();
-
-        let files_title = format!(
-            "Files - {} ({}/{})",
-            state.current_dir.display(),
-            state.filtered_files.len(),
-            state.files.len()
-        );
-
-        let files_list = List::new(files)
-            .block(Block::default().borders(Borders::ALL).title(files_title))
-            .highlight_style(Style::default().bg(Color::DarkGray));
-
-        f.render_stateful_widget(files_list, area, &mut state.file_list_state.clone());
+        let view = ListView {
+            current_dir: &state.current_dir,
+            files: &state.files,
+            filtered_files: &state.filtered_files,
+            list_state: &state.file_list_state,
+            search_input: &state.search_input,
+            focused: true,
+        };
+        render_list(f, area, state, view, &self.focused);
     }
 }
 
-/// Create a list item for a file with optional search highlighting
-fn create_file_list_item<'a>(file: &'a FileItem, search_input: &'a str) -> ListItem<'a> {
-    let icon = if file.is_dir { "📁" } else { "📄" };
-    let style = if file.is_dir {
-        Style::default().fg(Color::Cyan)
-    } else {
+impl FileListRenderer {
+    /// Render the unfocused pane of dual-pane mode, with a dimmed border
+    pub fn render_pane(&self, f: &mut Frame, area: Rect, state: &AppState, pane: &Pane) {
+        let view = ListView {
+            current_dir: &pane.current_dir,
+            files: &pane.files,
+            filtered_files: &pane.filtered_files,
+            list_state: &pane.file_list_state,
+            search_input: &pane.search_input,
+            focused: false,
+        };
+        render_list(f, area, state, view, &self.unfocused);
+    }
+}
+
+/// The parts of a pane a file list is drawn from
+struct ListView<'a> {
+    current_dir: &'a Path,
+    files: &'a Arc<Listing>,
+    filtered_files: &'a [usize],
+    list_state: &'a ListState,
+    search_input: &'a str,
+    focused: bool,
+}
+
+/// Draw a file list, marks and the entry filter are shared by both panes
+fn render_list(f: &mut Frame, area: Rect, state: &AppState, view: ListView, pane: &PaneLines) {
+    let drives_view = view.current_dir == Path::new("DRIVES:");
+    // Drives show mount details that are read in the background
+    if drives_view {
+        pane.rows.invalidate();
+    }
+
+    let mut title_spans = vec![Span::raw(BREADCRUMB_PREFIX)];
+    let segments = pane.breadcrumb(view.current_dir);
+    let last_index = segments.len().saturating_sub(1);
+    for (i, segment) in segments.into_iter().enumerate() {
+        let style = if i == last_index {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+        title_spans.push(Span::raw(segment.separator));
+        title_spans.push(Span::styled(segment.label, style));
+    }
+    title_spans.push(Span::raw(format!(
+        " ({}/{})",
+        view.filtered_files.len(),
+        view.files.len()
+    )));
+    if view.focused && state.is_filtering() {
+        title_spans.push(Span::styled(
+            " searching…",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    match state.entry_filter {
+        EntryFilter::All => {}
+        EntryFilter::Dirs => {
+            title_spans.push(Span::styled(" [dirs]", Style::default().fg(Color::Yellow)))
+        }
+        EntryFilter::Files => {
+            title_spans.push(Span::styled(" [files]", Style::default().fg(Color::Yellow)))
+        }
+    }
+    let files_title = Line::from(title_spans);
+
+    let border_style = if view.focused {
         Style::default()
+    } else {
+        Style::default().fg(Color::DarkGray)
     };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border_style)
+        .title(files_title);
+    let content = ListContent {
+        files: view.files,
+        filtered_files: view.filtered_files,
+        list_state: view.list_state,
+        search: view.search_input,
+        case: state.case_matching,
+    };
+    pane.rows.render(
+        f,
+        area,
+        block,
+        content,
+        |item| state.is_marked(item.get_path()),
+        |item, marked| match item {
+            DisplayItem::File(drive) if drives_view => {
+                create_drive_line(drive, view.search_input, state.case_matching)
+            }
+            _ => create_display_item_line(item, view.search_input, state.case_matching, marked),
+        },
+    );
+}
+
+/// Create the line of a file with optional search highlighting
+fn create_file_line<'a>(
+    file: &'a FileItem,
+    search_input: &str,
+    case: CaseMatching,
+    marked: bool,
+) -> Line<'a> {
+    let icon = icons::icon_for(file);
+    let style = LS_COLORS.style_for(file);
 
     let display_name = if !search_input.is_empty() {
-        utils::highlight_search_term(&file.name, search_input)
+        utils::highlight_matches(
+            &file.name,
+            utils::name_matches(&file.name, &file.path, search_input, case),
+        )
+        .into_iter()
+        .map(|span| {
+            let span_style = style.patch(span.style);
+            span.style(span_style)
+        })
+        .collect()
     } else {
         vec![Span::styled(&file.name, style)]
     };
 
-    let mut spans = vec![Span::raw(icon), Span::raw(" ")];
+    let mut spans = vec![mark_span(marked), Span::raw(icon), Span::raw(" ")];
     spans.extend(display_name);
 
-    ListItem::new(Line::from(spans))
+    Line::from(spans)
+}
+
+/// Create the line of a drive with its label, filesystem and free space
+fn create_drive_line<'a>(drive: &'a FileItem, search_input: &str, case: CaseMatching) -> Line<'a> {
+    let mut spans = vec![Span::raw(icons::drive_icon()), Span::raw(" ")];
+    spans.extend(utils::highlight_matches(
+        &drive.name,
+        utils::name_matches(&drive.name, &drive.path, search_input, case),
+    ));
+    if let Some(mount) = mounts::info(&drive.path) {
+        spans.extend(mount_detail_spans(&mount));
+    }
+    Line::from(spans)
+}
+
+/// Leading marker showing whether an entry is part of the multi-selection
+fn mark_span(marked: bool) -> Span<'static> {
+    if marked {
+        Span::styled("* ", Style::default().fg(Color::Magenta))
+    } else {
+        Span::raw("")
+    }
 }
 
-/// Create a list item for a DisplayItem with optional search highlighting
-fn create_display_item_list_item<'a>(item: &'a DisplayItem, search_input: &'a str) -> ListItem<'a> {
+/// Create the line of a DisplayItem with optional search highlighting
+fn create_display_item_line<'a>(
+    item: &'a DisplayItem,
+    search_input: &str,
+    case: CaseMatching,
+    marked: bool,
+) -> Line<'a> {
     match item {
-        DisplayItem::File(file) => create_file_list_item(file, search_input),
+        DisplayItem::File(file) => create_file_line(file, search_input, case, marked),
         DisplayItem::History(entry) => {
-            let icon = "📁";
             let style = Style::default().fg(Color::Cyan);
             let name = entry
                 .path
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or_default();
+            let icon = icons::directory_icon(name);
 
             let display_name = if !search_input.is_empty() {
-                utils::highlight_search_term(name, search_input)
+                utils::highlight_matches(
+                    name,
+                    utils::name_matches(name, &entry.path, search_input, case),
+                )
             } else {
                 vec![Span::styled(name, style)]
             };
 
-            let mut spans = vec![Span::raw(icon), Span::raw(" ")];
+            let mut spans = vec![mark_span(marked), Span::raw(icon), Span::raw(" ")];
             spans.extend(display_name);
 
-            ListItem::new(Line::from(spans))
+            Line::from(spans)
         }
     }
 }
@@ -5,7 +5,11 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
 };
 
-use crate::{AppState, modes::Renderer};
+use crate::{
+    AppState,
+    modes::{Renderer, help_overlay::keybinding_lines},
+    utils::AppMode,
+};
 
 /// Renderer for Normal mode help
 #[derive(Default)]
@@ -19,22 +23,14 @@ impl NormalHelpRenderer {
 
 impl Renderer for NormalHelpRenderer {
     fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
-        let help_content = vec![
+        let mut help_content = vec![
             Line::from("Normal Mode Navigation:"),
             Line::from(""),
-            Line::from("h/←        - Go to parent directory"),
-            Line::from("j/↓        - Move down"),
-            Line::from("k/↑        - Move up"),
-            Line::from("l/→        - Enter directory"),
-            Line::from("b          - Move up half page"),
-            Line::from("f          - Move down half page"),
+            Line::from("5j, 10k    - Repeat a movement (5G jumps to item 5)"),
+            Line::from("other keys - Jump to the first name starting with them"),
             Line::from(""),
-            Line::from("/          - Search files"),
-            Line::from("ESC        - Exit search (when searching)"),
-            Line::from("V          - Enter history mode"),
-            Line::from("Enter      - Select and exit"),
-            Line::from("ESC        - Quit application (when not searching)"),
         ];
+        help_content.extend(keybinding_lines(&AppMode::Normal));
 
         let help_items: Vec<ListItem> = help_content.into_iter().map(ListItem::new).collect();
 
@@ -0,0 +1,52 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Clear, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::{Renderer, preview::PreviewRenderer};
+use crate::AppState;
+
+/// Key hints shown in the status line while nothing else is to be reported
+const PAGER_HINTS: &str =
+    "j/k line, Space/b page, d/u half page, g/G top/bottom, / ? search, n/N next/previous, q quit";
+
+/// Full-screen view of the preview with a `less`-like status line at the bottom
+#[derive(Default)]
+pub struct PagerRenderer;
+
+impl PagerRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for PagerRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        let Some(pager) = &state.pager else {
+            return;
+        };
+        let [content_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+        f.render_widget(Clear, area);
+        PreviewRenderer::new().render(f, content_area, state);
+
+        let (status, style) = match (&pager.search_input, &pager.status) {
+            (Some((input, forward)), _) => {
+                let prompt = if *forward { '/' } else { '?' };
+                let status = format!("{prompt}{input}");
+                f.set_cursor_position((status_area.x + status.width() as u16, status_area.y));
+                (status, Style::default())
+            }
+            (None, Some(status)) => (status.clone(), Style::default().fg(Color::Yellow)),
+            (None, None) => (
+                PAGER_HINTS.to_string(),
+                Style::default().add_modifier(Modifier::REVERSED),
+            ),
+        };
+        f.render_widget(Paragraph::new(status).style(style), status_area);
+    }
+}
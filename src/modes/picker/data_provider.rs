@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use std::{io::BufRead, path::PathBuf, sync::RwLock};
+
+use crate::{
+    app_state::AppState, config::EntryFilter, modes::ModeAction, services::DataProvider,
+    utils::FileItem,
+};
+
+/// Entries read from stdin or handed to an embedded picker, set before the picker starts
+static ENTRIES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Set the entries shown by the picker
+pub fn set_entries(entries: Vec<String>) {
+    *ENTRIES.write().unwrap() = entries;
+}
+
+/// Read `separator`-terminated entries, skipping empty ones
+///
+/// With a newline separator a trailing `\r` is dropped as well, so input
+/// produced on Windows works unchanged.
+pub fn read_entries(mut reader: impl BufRead, separator: u8) -> Result<Vec<String>> {
+    let mut entries = Vec::new();
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let read = reader
+            .read_until(separator, &mut buffer)
+            .context("Failed to read entries from stdin")?;
+        if read == 0 {
+            break;
+        }
+
+        if buffer.last() == Some(&separator) {
+            buffer.pop();
+        }
+        if separator == b'\n' && buffer.last() == Some(&b'\r') {
+            buffer.pop();
+        }
+        if !buffer.is_empty() {
+            entries.push(String::from_utf8_lossy(&buffer).into_owned());
+        }
+    }
+    Ok(entries)
+}
+
+/// Data provider for the stdin list picker (Picker mode)
+pub struct PickerDataProvider;
+
+impl DataProvider for PickerDataProvider {
+    fn get_preview_path(&self, state: &AppState) -> Option<PathBuf> {
+        // Entries don't have to be paths, only preview the ones that exist
+        state
+            .get_selected_item()
+            .map(|item| item.get_path().clone())
+            .filter(|path| path.exists())
+    }
+
+    fn navigate_into_directory(&self, _state: &mut AppState) -> Result<Option<ModeAction>> {
+        Ok(None)
+    }
+
+    fn navigate_to_parent(&self, _state: &mut AppState) -> Result<Option<ModeAction>> {
+        Ok(None)
+    }
+
+    fn load_data(&self, state: &mut AppState) -> Result<()> {
+        // Every entry is shown as given, the file filters don't apply here
+        state.show_hidden_files = true;
+        state.entry_filter = EntryFilter::All;
+
+        let items = ENTRIES
+            .read()
+            .unwrap()
+            .iter()
+            .map(|entry| FileItem::new(entry.clone(), PathBuf::from(entry)))
+            .collect();
+        state.load_file_items(items);
+        state.apply_search_filter();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_entries() {
+        let input = b"src/main.rs\r\n\nREADME.md\nlast";
+        assert_eq!(
+            read_entries(&input[..], b'\n').unwrap(),
+            vec!["src/main.rs", "README.md", "last"]
+        );
+
+        let input = b"a b\nc\0d\0";
+        assert_eq!(read_entries(&input[..], 0).unwrap(), vec!["a b\nc", "d"]);
+    }
+}
@@ -0,0 +1,88 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+};
+
+use crate::{
+    AppState,
+    modes::{
+        ModeHandler, Renderer,
+        picker::{PickerHelpRenderer, PickerListRenderer},
+        preview::PreviewRenderer,
+    },
+};
+
+/// Handler for Picker mode (choose from entries read on stdin)
+pub struct PickerModeHandler {
+    picker_list_renderer: Box<dyn Renderer>,
+    preview_renderer: Box<dyn Renderer>,
+    help_renderer: Box<dyn Renderer>,
+}
+
+impl Default for PickerModeHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PickerModeHandler {
+    pub fn new() -> Self {
+        Self {
+            picker_list_renderer: Box::new(PickerListRenderer::new()),
+            preview_renderer: Box::new(PreviewRenderer::new()),
+            help_renderer: Box::new(PickerHelpRenderer::new()),
+        }
+    }
+}
+
+impl ModeHandler for PickerModeHandler {
+    fn render_left_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        self.picker_list_renderer.render(f, area, state);
+    }
+
+    fn render_right_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        if self.should_show_help(state) {
+            self.help_renderer.render(f, area, state);
+        } else {
+            self.preview_renderer.render(f, area, state);
+        }
+    }
+
+    fn get_search_box_config(&self, state: &AppState) -> (String, String, Style) {
+        let (info, style) = if state.is_searching {
+            (
+                format!(
+                    "SEARCH - {} matches (Enter pick, ESC to exit search)",
+                    state.filtered_files.len()
+                ),
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            )
+        } else if !state.search_input.is_empty() {
+            (
+                format!(
+                    "FILTERED - '{}' - {} matches (/ to search again, Enter pick, ESC to quit)",
+                    state.search_input,
+                    state.filtered_files.len()
+                ),
+                Style::default().fg(Color::Black).bg(Color::Green),
+            )
+        } else {
+            (
+                format!(
+                    "PICK - {} entries (jk navigate, / search, Space mark, Enter pick, ESC to quit)",
+                    state.files.len()
+                ),
+                Style::default().fg(Color::Cyan),
+            )
+        };
+        (info, state.search_input.clone(), style)
+    }
+
+    fn should_show_help(&self, state: &AppState) -> bool {
+        // Entries that aren't paths have nothing to preview
+        state
+            .get_selected_item()
+            .is_none_or(|item| !item.get_path().exists())
+    }
+}
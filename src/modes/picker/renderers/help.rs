@@ -0,0 +1,44 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    modes::{Renderer, help_overlay::keybinding_lines},
+    utils::AppMode,
+};
+
+/// Renderer for Picker mode help
+#[derive(Default)]
+pub struct PickerHelpRenderer;
+
+impl PickerHelpRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for PickerHelpRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
+        let mut help_content = vec![
+            Line::from("Picker Mode:"),
+            Line::from(""),
+            Line::from("Note: Marked entries are printed one per line"),
+            Line::from(""),
+        ];
+        help_content.extend(keybinding_lines(&AppMode::Picker));
+
+        let help_items: Vec<ListItem> = help_content.into_iter().map(ListItem::new).collect();
+
+        let help_widget = List::new(help_items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Help - Picker Mode"),
+        );
+
+        f.render_widget(help_widget, area);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod help;
+pub mod picker_list;
+
+pub use help::PickerHelpRenderer;
+pub use picker_list::PickerListRenderer;
@@ -0,0 +1,89 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::Renderer,
+    utils::{self, DisplayItem},
+};
+
+/// Renderer for the entry list in Picker mode
+#[derive(Default)]
+pub struct PickerListRenderer;
+
+impl PickerListRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for PickerListRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        // Items of packed listings are made on the spot, so they are kept for the lines
+        let entries: Vec<_> = state
+            .filtered_files
+            .iter()
+            .filter_map(|&i| state.files.get(i))
+            .collect();
+        let items: Vec<ListItem> = if state.filtered_files.is_empty() {
+            if state.files.is_empty() {
+                vec![ListItem::new("No entries on stdin")]
+            } else {
+                vec![ListItem::new("No matching entries")]
+            }
+        } else {
+            entries
+                .iter()
+                .map(|item| {
+                    let marked = state.is_marked(item.get_path());
+                    create_picker_list_item(item, &state.search_input, state.case_matching, marked)
+                })
+                .collect()
+        };
+
+        let mut title = format!(
+            "Pick - {}/{} entries",
+            state.filtered_files.len(),
+            state.files.len()
+        );
+        if !state.marked.is_empty() {
+            title.push_str(&format!(" ({} marked)", state.marked.len()));
+        }
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        f.render_stateful_widget(list, area, &mut state.file_list_state.clone());
+    }
+}
+
+/// Create a list item for an entry with optional search highlighting
+fn create_picker_list_item<'a>(
+    item: &'a DisplayItem,
+    search_input: &'a str,
+    case: CaseMatching,
+    marked: bool,
+) -> ListItem<'a> {
+    let text = match item {
+        DisplayItem::File(file) => file.name.as_str(),
+        DisplayItem::History(entry) => entry.path.to_str().unwrap_or_default(),
+    };
+
+    let mut spans = vec![Span::styled(
+        if marked { "* " } else { "" },
+        Style::default().fg(Color::Magenta),
+    )];
+    spans.extend(utils::highlight_matches(
+        text,
+        utils::name_matches(text, item.get_path(), search_input, case),
+    ));
+
+    ListItem::new(Line::from(spans))
+}
@@ -1,14 +1,19 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    widgets::{Block, Borders, List, ListItem},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
 };
-use ratatui_image::{StatefulImage, protocol::StatefulProtocol};
+use ratatui_image::StatefulImage;
 
-use super::Renderer;
+use super::{Renderer, scroll_indicator};
 use crate::{
     AppState,
-    services::{GlobalPreviewState, global_preview_state::PreviewState, preview::PreviewContent},
+    services::{
+        GlobalPreviewState,
+        global_preview_state::PreviewState,
+        preview::{ImagePreview, PreviewContent},
+    },
 };
 
 /// Renderer for preview panel showing file/directory content
@@ -23,19 +28,16 @@ impl PreviewRenderer {
 
 impl Renderer for PreviewRenderer {
     fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
-        let global_state = GlobalPreviewState::instance();
-        let preview_state = global_state.get_state();
-
-        match &preview_state.content {
-            PreviewContent::Text(lines) => {
-                self.render_text_preview(f, area, &preview_state, lines);
+        GlobalPreviewState::instance().with_state(|preview_state| match &preview_state.content {
+            PreviewContent::Text(lines)
+            | PreviewContent::PartialText(lines, _)
+            | PreviewContent::Pages(lines, _) => {
+                self.render_text_preview(f, area, preview_state, lines);
             }
-            PreviewContent::Image(protocol) => {
-                if let Ok(mut protocol_guard) = protocol.try_lock() {
-                    self.render_image_preview(f, area, &preview_state, &mut protocol_guard);
-                }
+            PreviewContent::Image(image) => {
+                self.render_image_preview(f, area, preview_state, image);
             }
-        }
+        });
     }
 }
 
@@ -67,13 +69,33 @@ impl PreviewRenderer {
             vec![]
         };
 
-        let preview_list = List::new(visible_content).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(preview_state.title.as_str()),
-        );
+        let mut title = preview_state.title.clone();
+        let page_starts = preview_state.content.page_starts();
+        if !page_starts.is_empty() {
+            let page = page_starts
+                .partition_point(|&start| start <= start_line)
+                .max(1);
+            title.push_str(&format!(" - page {page}/{}", page_starts.len()));
+        }
+
+        // Position like `less`, the rest of a partially read file is still unknown
+        if total_lines > visible_height {
+            let more = if matches!(preview_state.content, PreviewContent::PartialText(..)) {
+                "+"
+            } else {
+                ""
+            };
+            title.push_str(&format!(
+                " - line {} of {total_lines}{more} ({}%)",
+                start_line + 1,
+                end_line * 100 / total_lines
+            ));
+        }
+        let preview_list =
+            List::new(visible_content).block(Block::default().borders(Borders::ALL).title(title));
 
         f.render_widget(preview_list, area);
+        scroll_indicator::render_scrollbar(f, area, total_lines, start_line, visible_height);
     }
 
     /// Render image preview content
@@ -82,20 +104,34 @@ impl PreviewRenderer {
         f: &mut Frame,
         area: Rect,
         preview_state: &PreviewState,
-        protocol: &mut StatefulProtocol,
+        image: &ImagePreview,
     ) {
         // Create the StatefulImage widget with a border
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title(preview_state.title.as_str());
+        let zoom = image.zoom();
+        let title = if zoom > 1 {
+            format!("{} - {zoom}x", preview_state.title)
+        } else {
+            preview_state.title.clone()
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
         let inner_area = block.inner(area);
 
         // Render the block first
         f.render_widget(block, area);
 
-        // Create and render the StatefulImage widget
-        let image_widget = StatefulImage::default();
-        f.render_stateful_widget(image_widget, inner_area, protocol);
+        if !image.prepare(inner_area) {
+            let placeholder =
+                Paragraph::new("Rendering image...").style(Style::default().fg(Color::Yellow));
+            f.render_widget(placeholder, inner_area);
+            return;
+        }
+        let Ok(mut protocol) = image.protocol.try_lock() else {
+            return;
+        };
+
+        // Create and render the StatefulImage widget, already encoded for the area
+        let image_widget = StatefulImage::default().resize(ImagePreview::RESIZE);
+        f.render_stateful_widget(image_widget, inner_area, &mut *protocol);
 
         // Handle encoding result (important for ratatui-image 8.0)
         if let Some(Err(_e)) = protocol.last_encoding_result() {
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::{
+    app_state::AppState,
+    modes::{ModeAction, history::HistoryDataProvider},
+    services::{DataProvider, DirIndex, FilesystemService, projects},
+    utils::{AppMode, DisplayItem, FileItem, FileKind},
+};
+
+/// Data provider for directories with a project manifest (Projects mode)
+#[derive(Debug)]
+pub struct ProjectsDataProvider;
+
+impl DataProvider for ProjectsDataProvider {
+    fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
+        if let Some(item) = state.get_selected_item() {
+            // The index can be older than the project it lists
+            let path = item.get_path().clone();
+            FilesystemService::check_readable(&path)?;
+
+            HistoryDataProvider.add_to_history(path.clone());
+            state.current_dir = path;
+        }
+        Ok(Some(ModeAction::Switch(AppMode::Normal)))
+    }
+
+    fn load_data(&self, state: &mut AppState) -> Result<()> {
+        let projects = projects::load_projects(&DirIndex::load()?);
+
+        // Listed under the project name, so searching finds it by name too
+        state.files = Arc::new(
+            projects
+                .into_iter()
+                .map(|project| {
+                    DisplayItem::File(FileItem {
+                        name: project.name,
+                        path: project.path,
+                        is_dir: true,
+                        kind: FileKind::Directory,
+                        hidden: false,
+                    })
+                })
+                .collect(),
+        );
+        state.apply_search_filter();
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+pub mod data_provider;
+pub mod renderers;
+
+use crate::{
+    modes::list_mode::{ListModeHandler, ListModeSpec},
+    utils::AppMode,
+};
+
+pub use data_provider::ProjectsDataProvider;
+pub use renderers::ProjectsListRenderer;
+
+/// Projects mode (hop between Cargo, npm, Go and other projects)
+pub static PROJECTS_MODE: ListModeSpec = ListModeSpec {
+    mode: AppMode::Projects,
+    title: "Projects",
+    entries: "projects",
+    search_hint: "projects",
+    heading: "Project Navigation:",
+    description: &[
+        "Lists directories with a Cargo.toml, package.json,",
+        "go.mod or other manifest under the [index] roots.",
+    ],
+    opens_search: true,
+    reads_index: true,
+};
+
+/// Handler for Projects mode
+pub fn handler() -> ListModeHandler {
+    ListModeHandler::new(&PROJECTS_MODE, Box::new(ProjectsListRenderer::new()))
+}
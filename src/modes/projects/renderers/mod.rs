@@ -0,0 +1,3 @@
+pub mod projects_list;
+
+pub use projects_list::ProjectsListRenderer;
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    app_state::AppState,
+    config::get_history_config,
+    modes::ModeAction,
+    services::{DataProvider, HistoryStore},
+    utils::{AppMode, DisplayItem},
+};
+
+/// Data provider for recently opened files (RecentFiles mode)
+#[derive(Debug)]
+pub struct RecentFilesDataProvider;
+
+impl RecentFilesDataProvider {
+    /// Record a file picked by the user
+    pub fn add_recent_file(&self, path: PathBuf) {
+        HistoryStore::RECENT_FILES.add_to_history(path)
+    }
+}
+
+impl DataProvider for RecentFilesDataProvider {
+    fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
+        // Show the selected file in its directory
+        if let Some(item) = state.get_selected_item()
+            && let Some(parent) = item.get_path().parent()
+        {
+            state.current_dir = parent.to_path_buf();
+        }
+        Ok(Some(ModeAction::Switch(AppMode::Normal)))
+    }
+
+    fn load_data(&self, state: &mut AppState) -> Result<()> {
+        let config = get_history_config();
+        // Entries read in the background when the mode was entered come first
+        let entries = match HistoryStore::RECENT_FILES.take_loaded() {
+            Some(entries) => entries?,
+            None => HistoryStore::RECENT_FILES.get_sorted_entries(&config.sort_mode)?,
+        };
+
+        state.files = Arc::new(entries.into_iter().map(DisplayItem::History).collect());
+        state.apply_search_filter();
+        Ok(())
+    }
+
+    fn start_loading(&self, state: &mut AppState) -> Result<()> {
+        HistoryStore::RECENT_FILES.load_in_background(get_history_config().sort_mode.clone());
+        state.files = Arc::default();
+        state.apply_search_filter();
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+};
+
+use crate::{
+    AppState,
+    modes::{
+        ModeHandler, Renderer,
+        preview::PreviewRenderer,
+        recent_files::{RecentFilesHelpRenderer, RecentFilesListRenderer},
+    },
+};
+
+/// Handler for RecentFiles mode (reopen previously picked files)
+pub struct RecentFilesModeHandler {
+    recent_files_list_renderer: Box<dyn Renderer>,
+    preview_renderer: Box<dyn Renderer>,
+    help_renderer: Box<dyn Renderer>,
+}
+
+impl Default for RecentFilesModeHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecentFilesModeHandler {
+    pub fn new() -> Self {
+        Self {
+            recent_files_list_renderer: Box::new(RecentFilesListRenderer::new()),
+            preview_renderer: Box::new(PreviewRenderer::new()),
+            help_renderer: Box::new(RecentFilesHelpRenderer::new()),
+        }
+    }
+}
+
+impl ModeHandler for RecentFilesModeHandler {
+    fn render_left_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        self.recent_files_list_renderer.render(f, area, state);
+    }
+
+    fn render_right_panel(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        if self.should_show_help(state) {
+            self.help_renderer.render(f, area, state);
+        } else {
+            self.preview_renderer.render(f, area, state);
+        }
+    }
+
+    fn get_search_box_config(&self, state: &AppState) -> (String, String, Style) {
+        let (info, style) = if state.is_searching {
+            if state.search_input.is_empty() {
+                (
+                    "SEARCH - Type to search recent files, ESC to exit search".to_string(),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                )
+            } else {
+                (
+                    format!(
+                        "SEARCH - '{}' - {} matches (ESC to exit)",
+                        state.search_input,
+                        state.filtered_files.len()
+                    ),
+                    Style::default().fg(Color::Black).bg(Color::Yellow),
+                )
+            }
+        } else if !state.search_input.is_empty() {
+            // Show search results even when not actively searching
+            (
+                format!(
+                    "FILTERED RECENT FILES - '{}' - {} matches (l/→ show in directory, / to search again, ESC to normal)",
+                    state.search_input,
+                    state.filtered_files.len()
+                ),
+                Style::default().fg(Color::Black).bg(Color::Green),
+            )
+        } else {
+            (
+                format!(
+                    "RECENT FILES - {} entries (jk navigate, l/→ show in directory, / search, Enter select, ESC to normal)",
+                    state.files.len()
+                ),
+                Style::default().fg(Color::Cyan),
+            )
+        };
+        (info, state.search_input.clone(), style)
+    }
+
+    fn should_show_help(&self, state: &AppState) -> bool {
+        // Show help if no selection or if searching with no results
+        if state.is_searching {
+            state.search_input.is_empty() || state.filtered_files.is_empty()
+        } else {
+            state.file_list_state.selected().is_none()
+        }
+    }
+
+    fn on_enter(&mut self, state: &mut AppState) -> Result<()> {
+        // Initialize recent files mode selection
+        state.file_list_state.select(None);
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+pub mod data_provider;
+pub mod handler;
+pub mod renderers;
+
+// Re-export the handler for easy access
+pub use data_provider::RecentFilesDataProvider;
+pub use handler::RecentFilesModeHandler;
+pub use renderers::{RecentFilesHelpRenderer, RecentFilesListRenderer};
@@ -0,0 +1,45 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    modes::{Renderer, help_overlay::keybinding_lines},
+    utils::AppMode,
+};
+
+/// Renderer for RecentFiles mode help
+#[derive(Default)]
+pub struct RecentFilesHelpRenderer;
+
+impl RecentFilesHelpRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for RecentFilesHelpRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, _state: &AppState) {
+        let mut help_content = vec![
+            Line::from("Recent Files Navigation:"),
+            Line::from(""),
+            Line::from("Tip: quickswitch --mode recent-files"),
+            Line::from("     --exec 'vim {}' reopens a file"),
+            Line::from(""),
+        ];
+        help_content.extend(keybinding_lines(&AppMode::RecentFiles));
+
+        let help_items: Vec<ListItem> = help_content.into_iter().map(ListItem::new).collect();
+
+        let help_widget = List::new(help_items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Help - Recent Files Mode"),
+        );
+
+        f.render_widget(help_widget, area);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod help;
+pub mod recent_files_list;
+
+pub use help::RecentFilesHelpRenderer;
+pub use recent_files_list::RecentFilesListRenderer;
@@ -0,0 +1,123 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::Renderer,
+    services::{HistoryStore, icons},
+    utils::{self, DisplayItem, FileItem, FileKind},
+};
+
+/// Renderer for the file list in RecentFiles mode
+#[derive(Default)]
+pub struct RecentFilesListRenderer;
+
+impl RecentFilesListRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for RecentFilesListRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        // Items of packed listings are made on the spot, so they are kept for the lines
+        let entries: Vec<_> = state
+            .filtered_files
+            .iter()
+            .filter_map(|&i| state.files.get(i))
+            .collect();
+        let items: Vec<ListItem> = if state.filtered_files.is_empty() {
+            if state.files.is_empty() && HistoryStore::RECENT_FILES.is_loading() {
+                vec![ListItem::new("Loading recent files…")]
+            } else if state.files.is_empty() {
+                vec![ListItem::new("No recent files")]
+            } else {
+                vec![ListItem::new("No matching recent files")]
+            }
+        } else {
+            entries
+                .iter()
+                .map(|item| {
+                    let marked = state.is_marked(item.get_path());
+                    create_recent_file_list_item(
+                        item,
+                        &state.search_input,
+                        state.case_matching,
+                        marked,
+                    )
+                })
+                .collect()
+        };
+
+        let title = if state.is_searching && !state.search_input.is_empty() {
+            format!(
+                "Recent Files - {} matches ({}/{})",
+                state.filtered_files.len(),
+                state.filtered_files.len(),
+                state.files.len()
+            )
+        } else {
+            format!("Recent Files - {} entries", state.files.len())
+        };
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        f.render_stateful_widget(list, area, &mut state.file_list_state.clone());
+    }
+}
+
+/// Create a list item for a recent file with its name and full path
+fn create_recent_file_list_item<'a>(
+    item: &'a DisplayItem,
+    search_input: &'a str,
+    case: CaseMatching,
+    marked: bool,
+) -> ListItem<'a> {
+    let DisplayItem::History(entry) = item else {
+        return ListItem::new("Invalid recent file entry");
+    };
+
+    let file_name = entry
+        .path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    // Only the extension matters for the icon, so don't touch the disk
+    let icon = icons::icon_for(&FileItem {
+        name: file_name.to_string(),
+        path: entry.path.clone(),
+        is_dir: false,
+        kind: FileKind::Regular,
+        hidden: false,
+    });
+
+    let mut spans = vec![
+        Span::styled(
+            if marked { "* " } else { "" },
+            Style::default().fg(Color::Magenta),
+        ),
+        Span::raw(icon),
+        Span::raw(" "),
+    ];
+
+    spans.extend(utils::highlight_matches(
+        file_name,
+        utils::name_matches(file_name, &entry.path, search_input, case),
+    ));
+    spans.push(Span::styled(
+        format!(" ({}×)", entry.frequency),
+        Style::default().fg(Color::Yellow),
+    ));
+    spans.push(Span::raw(" "));
+    spans.extend(utils::path_spans(&entry.path, search_input, case));
+
+    ListItem::new(Line::from(spans))
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::{
+    app_state::AppState,
+    modes::{ModeAction, history::HistoryDataProvider},
+    services::{DataProvider, DirIndex, FilesystemService, repos},
+    utils::{AppMode, DisplayItem, FileItem, FileKind},
+};
+
+/// Data provider for git repositories and worktrees (Repos mode)
+#[derive(Debug)]
+pub struct ReposDataProvider;
+
+impl DataProvider for ReposDataProvider {
+    fn navigate_into_directory(&self, state: &mut AppState) -> Result<Option<ModeAction>> {
+        if let Some(item) = state.get_selected_item() {
+            // The index can be older than the repository it lists
+            let path = item.get_path().clone();
+            FilesystemService::check_readable(&path)?;
+
+            HistoryDataProvider.add_to_history(path.clone());
+            state.current_dir = path;
+        }
+        Ok(Some(ModeAction::Switch(AppMode::Normal)))
+    }
+
+    fn load_data(&self, state: &mut AppState) -> Result<()> {
+        let repo_paths = repos::find_repos(&DirIndex::load()?);
+        repos::load_info(&repo_paths);
+
+        state.files = Arc::new(
+            repo_paths
+                .into_iter()
+                .map(|path| {
+                    DisplayItem::File(FileItem {
+                        name: path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| path.display().to_string()),
+                        path,
+                        is_dir: true,
+                        kind: FileKind::Directory,
+                        hidden: false,
+                    })
+                })
+                .collect(),
+        );
+        state.apply_search_filter();
+        Ok(())
+    }
+}
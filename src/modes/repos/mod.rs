@@ -0,0 +1,30 @@
+pub mod data_provider;
+pub mod renderers;
+
+use crate::{
+    modes::list_mode::{ListModeHandler, ListModeSpec},
+    utils::AppMode,
+};
+
+pub use data_provider::ReposDataProvider;
+pub use renderers::ReposListRenderer;
+
+/// Repos mode (switch between git repositories and worktrees)
+pub static REPOS_MODE: ListModeSpec = ListModeSpec {
+    mode: AppMode::Repos,
+    title: "Repos",
+    entries: "repositories",
+    search_hint: "repositories",
+    heading: "Repository Navigation:",
+    description: &[
+        "Lists the git repositories and worktrees under",
+        "the [index] roots, * marks uncommitted changes.",
+    ],
+    opens_search: true,
+    reads_index: true,
+};
+
+/// Handler for Repos mode
+pub fn handler() -> ListModeHandler {
+    ListModeHandler::new(&REPOS_MODE, Box::new(ReposListRenderer::new()))
+}
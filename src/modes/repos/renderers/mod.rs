@@ -0,0 +1,3 @@
+pub mod repos_list;
+
+pub use repos_list::ReposListRenderer;
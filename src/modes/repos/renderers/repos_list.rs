@@ -0,0 +1,111 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+};
+
+use crate::{
+    AppState,
+    config::CaseMatching,
+    modes::Renderer,
+    services::{dir_index, icons, repos},
+    utils::{self, DisplayItem},
+};
+
+/// Renderer for the repository list in Repos mode
+#[derive(Default)]
+pub struct ReposListRenderer;
+
+impl ReposListRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for ReposListRenderer {
+    fn render(&self, f: &mut Frame, area: Rect, state: &AppState) {
+        // Items of packed listings are made on the spot, so they are kept for the lines
+        let entries: Vec<_> = state
+            .filtered_files
+            .iter()
+            .filter_map(|&i| state.files.get(i))
+            .collect();
+        let items: Vec<ListItem> = if state.filtered_files.is_empty() {
+            if state.files.is_empty() && dir_index::is_refreshing() {
+                vec![ListItem::new("Indexing directories…")]
+            } else if state.files.is_empty() {
+                vec![ListItem::new("No repositories under the index roots")]
+            } else {
+                vec![ListItem::new("No matching repositories")]
+            }
+        } else {
+            entries
+                .iter()
+                .map(|item| create_repo_list_item(item, &state.search_input, state.case_matching))
+                .collect()
+        };
+
+        let mut title = if state.is_searching && !state.search_input.is_empty() {
+            format!(
+                "Repos - {} matches ({}/{})",
+                state.filtered_files.len(),
+                state.filtered_files.len(),
+                state.files.len()
+            )
+        } else {
+            format!("Repos - {} repositories", state.files.len())
+        };
+        if dir_index::is_refreshing() {
+            title.push_str(" indexing…");
+        } else if state.is_filtering() {
+            title.push_str(" searching…");
+        }
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        f.render_stateful_widget(list, area, &mut state.file_list_state.clone());
+    }
+}
+
+/// Create a list item for a repository with its branch, state and full path
+fn create_repo_list_item<'a>(
+    item: &'a DisplayItem,
+    search_input: &'a str,
+    case: CaseMatching,
+) -> ListItem<'a> {
+    let DisplayItem::File(repo) = item else {
+        return ListItem::new("Invalid repository entry");
+    };
+    let info = repos::info(&repo.path).unwrap_or_default();
+
+    let mut spans = vec![Span::raw(icons::icon_for(repo)), Span::raw(" ")];
+    spans.extend(utils::highlight_matches(
+        &repo.name,
+        utils::name_matches(&repo.name, &repo.path, search_input, case),
+    ));
+    if let Some(branch) = info.branch {
+        spans.push(Span::styled(
+            format!(" [{branch}]"),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    match info.dirty {
+        Some(true) => spans.push(Span::styled(" *", Style::default().fg(Color::Red))),
+        Some(false) => {}
+        None => spans.push(Span::styled(" …", Style::default().fg(Color::DarkGray))),
+    }
+    if info.worktree {
+        spans.push(Span::styled(
+            " worktree",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    spans.push(Span::raw(" "));
+    spans.extend(utils::path_spans(&repo.path, search_input, case));
+
+    ListItem::new(Line::from(spans))
+}
@@ -0,0 +1,45 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Margin, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+};
+
+/// Draw a scrollbar over the right border of a bordered panel
+///
+/// Nothing is drawn when all `total` lines fit in the `visible` ones.
+pub fn render_scrollbar(f: &mut Frame, area: Rect, total: usize, offset: usize, visible: usize) {
+    if total <= visible {
+        return;
+    }
+    // The thumb reaches the bottom once the last line is visible
+    let mut scrollbar_state = ScrollbarState::new(total.saturating_sub(visible))
+        .position(offset)
+        .viewport_content_length(visible);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .thumb_style(Style::default().fg(Color::Cyan))
+        .track_style(Style::default().fg(Color::DarkGray));
+    f.render_stateful_widget(
+        scrollbar,
+        area.inner(Margin {
+            vertical: 1,
+            horizontal: 0,
+        }),
+        &mut scrollbar_state,
+    );
+}
+
+/// Write `text` into the bottom right corner of a bordered panel's border
+pub fn render_position(f: &mut Frame, area: Rect, text: &str) {
+    if area.height < 2 || area.width < 4 {
+        return;
+    }
+    let bottom = Rect::new(area.x + 1, area.y + area.height - 1, area.width - 2, 1);
+    let position = Paragraph::new(Line::from(format!(" {text} ")))
+        .alignment(Alignment::Right)
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(position, bottom);
+}
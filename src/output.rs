@@ -0,0 +1,94 @@
+use clap::ValueEnum;
+
+use crate::{
+    services::filesystem::strip_verbatim_prefix,
+    utils::{AppMode, FileItem},
+};
+
+/// Exit status after something was selected
+pub const EXIT_SELECTED: i32 = 0;
+
+/// Exit status after leaving with Esc without a selection
+pub const EXIT_ABORTED: i32 = 1;
+
+/// Exit status after an error
+pub const EXIT_ERROR: i32 = 2;
+
+/// How the result is printed when quickswitch exits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The directory to change to, or the selected paths with --print-selection
+    #[default]
+    Path,
+    /// A JSON object with the selected path, whether it is a directory, the mode and the query
+    Json,
+}
+
+/// The object printed with `--output json`
+///
+/// `path` and `is_dir` are `null` when nothing was selected, like after Esc.
+pub fn selection_json(file: Option<&FileItem>, mode: AppMode, query: &str) -> String {
+    let (path, is_dir) = match file {
+        Some(file) => (
+            json_string(&strip_verbatim_prefix(file.path.clone()).to_string_lossy()),
+            file.is_dir.to_string(),
+        ),
+        None => ("null".to_string(), "null".to_string()),
+    };
+    // The picker isn't a `--mode` value, it starts when entries are piped in
+    let mode = mode.to_possible_value().map_or_else(
+        || format!("{mode:?}").to_lowercase(),
+        |value| value.get_name().to_string(),
+    );
+    format!(
+        r#"{{"path":{path},"is_dir":{is_dir},"mode":{},"query":{}}}"#,
+        json_string(&mode),
+        json_string(query)
+    )
+}
+
+/// Quote `text` as a JSON string
+pub(crate) fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str(r#"\""#),
+            '\\' => quoted.push_str(r"\\"),
+            '\n' => quoted.push_str(r"\n"),
+            '\r' => quoted.push_str(r"\r"),
+            '\t' => quoted.push_str(r"\t"),
+            c if c.is_control() => quoted.push_str(&format!(r"\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FileKind;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_selection_json() {
+        let file = FileItem {
+            name: "notes \"draft\".txt".to_string(),
+            path: PathBuf::from("/tmp/notes \"draft\".txt"),
+            is_dir: false,
+            kind: FileKind::Regular,
+            hidden: false,
+        };
+        assert_eq!(
+            selection_json(Some(&file), AppMode::RecentFiles, "not\\es"),
+            r#"{"path":"/tmp/notes \"draft\".txt","is_dir":false,"mode":"recent-files","query":"not\\es"}"#
+        );
+        assert_eq!(
+            selection_json(None, AppMode::Picker, ""),
+            r#"{"path":null,"is_dir":null,"mode":"picker","query":""}"#
+        );
+        assert_eq!(json_string("a\nb\u{1}"), r#""a\nb\u0001""#);
+    }
+}
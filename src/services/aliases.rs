@@ -0,0 +1,104 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::{info, instrument};
+
+use crate::config::get_data_dir;
+
+/// Short names for history entries, like `qw` for `~/work/quickswitch`
+///
+/// Kept in `quickswitch.aliases.toml` in the data directory rather than in
+/// the history file, so the history format stays unchanged and the aliases can
+/// be edited by hand.
+#[derive(Debug, Default)]
+pub struct Aliases {
+    by_name: BTreeMap<String, PathBuf>,
+}
+
+impl Aliases {
+    fn file_path() -> Result<PathBuf> {
+        Ok(get_data_dir()?.join("quickswitch.aliases.toml"))
+    }
+
+    /// Load the aliases, empty if none were set yet
+    #[instrument]
+    pub fn load() -> Result<Self> {
+        let path = Self::file_path()?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let by_name = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Self { by_name })
+    }
+
+    /// Write the aliases back to disk
+    #[instrument(skip(self))]
+    pub fn save(&self) -> Result<()> {
+        let path = Self::file_path()?;
+        info!(path = %path.display(), "Saving aliases");
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, toml::to_string(&self.by_name)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// The path an alias stands for
+    pub fn resolve(&self, alias: &str) -> Option<&PathBuf> {
+        self.by_name.get(alias)
+    }
+
+    /// The alias of a path, if it has one
+    pub fn alias_of(&self, path: &Path) -> Option<&str> {
+        self.by_name
+            .iter()
+            .find(|(_, target)| target.as_path() == path)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// Give `path` the alias `alias`, replacing its previous alias
+    ///
+    /// An empty alias removes the alias of `path`. An alias used by another
+    /// path moves over to this one.
+    pub fn set(&mut self, path: &Path, alias: &str) -> Result<()> {
+        let alias = alias.trim();
+        if alias.chars().any(char::is_whitespace) {
+            bail!("Aliases can't contain spaces");
+        }
+        self.by_name.retain(|_, target| target.as_path() != path);
+        if !alias.is_empty() {
+            self.by_name.insert(alias.to_string(), path.to_path_buf());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_alias() {
+        let mut aliases = Aliases::default();
+        let project = Path::new("/home/me/work/quickswitch");
+        let other = Path::new("/home/me/other");
+
+        aliases.set(project, "qs").unwrap();
+        aliases.set(project, " qw ").unwrap();
+        assert_eq!(aliases.resolve("qs"), None);
+        assert_eq!(aliases.resolve("qw").map(PathBuf::as_path), Some(project));
+        assert_eq!(aliases.alias_of(project), Some("qw"));
+
+        aliases.set(other, "qw").unwrap();
+        assert_eq!(aliases.alias_of(project), None);
+        assert!(aliases.set(other, "a b").is_err());
+
+        aliases.set(other, "").unwrap();
+        assert_eq!(aliases.resolve("qw"), None);
+    }
+}
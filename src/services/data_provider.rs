@@ -1,9 +1,13 @@
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     app_state::AppState,
-    modes::{ModeAction, history, normal},
+    config::get_config,
+    modes::{ModeAction, history, jump, mounts, normal, picker, projects, recent_files, repos},
     services::PreviewManager,
     utils::{AppMode, DisplayItem},
 };
@@ -17,7 +21,7 @@ pub trait DataProvider {
             .filtered_files
             .iter()
             .filter_map(|&index| state.files.get(index))
-            .cloned()
+            .map(Cow::into_owned)
             .collect()
     }
 
@@ -36,14 +40,23 @@ pub trait DataProvider {
         state.filtered_files.len()
     }
 
-    /// Navigate up in the list
+    /// Navigate up `count` items in the list
     #[allow(async_fn_in_trait)]
-    async fn navigate_up(&self, state: &mut AppState) -> bool {
-        let visible_height = state.layout.get_left_content_height() / 2;
+    async fn navigate_up(&self, state: &mut AppState, count: usize) -> bool {
         if let Some(selected) = state.file_list_state.selected() {
             if selected > 0 {
-                state.file_list_state.select(Some(selected - 1));
-                self.update_scroll_offset(state, visible_height);
+                state
+                    .file_list_state
+                    .select(Some(selected.saturating_sub(count)));
+                self.update_scroll_offset(state);
+                PreviewManager::preview_for_selected_item(state);
+                return true;
+            } else if get_config().navigation.wrap_around && state.filtered_files.len() > 1 {
+                // Wrap from the first item to the last
+                state
+                    .file_list_state
+                    .select(Some(state.filtered_files.len() - 1));
+                self.update_scroll_offset(state);
                 PreviewManager::preview_for_selected_item(state);
                 return true;
             }
@@ -51,86 +64,93 @@ pub trait DataProvider {
             state
                 .file_list_state
                 .select(Some(state.filtered_files.len() - 1));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             return true;
         }
         false
     }
 
-    /// Navigate down in the list
+    /// Navigate down `count` items in the list
     #[allow(async_fn_in_trait)]
-    async fn navigate_down(&self, state: &mut AppState) -> bool {
+    async fn navigate_down(&self, state: &mut AppState, count: usize) -> bool {
         let total = state.filtered_files.len();
         if total == 0 {
             return false;
         }
 
-        let visible_height = state.layout.get_left_content_height() / 2;
         if let Some(selected) = state.file_list_state.selected() {
             if selected + 1 < total {
-                state.file_list_state.select(Some(selected + 1));
-                self.update_scroll_offset(state, visible_height);
+                state
+                    .file_list_state
+                    .select(Some((selected + count).min(total - 1)));
+                self.update_scroll_offset(state);
+                PreviewManager::preview_for_selected_item(state);
+                return true;
+            } else if get_config().navigation.wrap_around && total > 1 {
+                // Wrap from the last item to the first
+                state.file_list_state.select(Some(0));
+                self.update_scroll_offset(state);
                 PreviewManager::preview_for_selected_item(state);
                 return true;
             }
         } else {
             state.file_list_state.select(Some(0));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             return true;
         }
         false
     }
 
-    /// Navigate half page up in the list
+    /// Navigate `count` half pages up in the list
     #[allow(async_fn_in_trait)]
-    async fn navigate_half_page_up(&self, state: &mut AppState) -> bool {
+    async fn navigate_half_page_up(&self, state: &mut AppState, count: usize) -> bool {
         let total = state.filtered_files.len();
         if total == 0 {
             return false;
         }
 
         let visible_height = state.layout.get_left_content_height();
-        let half_page = (visible_height / 2).max(1);
+        let half_page = (visible_height / 2).max(1).saturating_mul(count);
 
         if let Some(selected) = state.file_list_state.selected() {
             let new_selected = selected.saturating_sub(half_page);
             state.file_list_state.select(Some(new_selected));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             return true;
         } else if !state.filtered_files.is_empty() {
             state
                 .file_list_state
                 .select(Some(state.filtered_files.len() - 1));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             return true;
         }
         false
     }
 
-    /// Navigate half page down in the list
+    /// Navigate `count` half pages down in the list
     #[allow(async_fn_in_trait)]
-    async fn navigate_half_page_down(&self, state: &mut AppState) -> bool {
+    async fn navigate_half_page_down(&self, state: &mut AppState, count: usize) -> bool {
         let total = state.filtered_files.len();
         if total == 0 {
             return false;
         }
 
         let visible_height = state.layout.get_left_content_height();
-        let half_page = (visible_height / 2).max(1);
+        let half_page = (visible_height / 2).max(1).saturating_mul(count);
 
         if let Some(selected) = state.file_list_state.selected() {
             let new_selected = (selected + half_page).min(total - 1);
             state.file_list_state.select(Some(new_selected));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             true
         } else if !state.filtered_files.is_empty() {
             state.file_list_state.select(Some(0));
-            self.update_scroll_offset(state, visible_height);
+            self.update_scroll_offset(state);
             PreviewManager::preview_for_selected_item(state);
             true
         } else {
@@ -138,6 +158,47 @@ pub trait DataProvider {
         }
     }
 
+    /// Select the item at `index` in the filtered list, clamped to the last item
+    #[allow(async_fn_in_trait)]
+    async fn navigate_to_index(&self, state: &mut AppState, index: usize) -> bool {
+        let total = state.filtered_files.len();
+        if total == 0 {
+            return false;
+        }
+
+        state.file_list_state.select(Some(index.min(total - 1)));
+        self.update_scroll_offset(state);
+        PreviewManager::preview_for_selected_item(state);
+        true
+    }
+
+    /// Jump to the first item in the list
+    #[allow(async_fn_in_trait)]
+    async fn navigate_to_first(&self, state: &mut AppState) -> bool {
+        if state.filtered_files.is_empty() {
+            return false;
+        }
+
+        state.file_list_state.select(Some(0));
+        self.update_scroll_offset(state);
+        PreviewManager::preview_for_selected_item(state);
+        true
+    }
+
+    /// Jump to the last item in the list
+    #[allow(async_fn_in_trait)]
+    async fn navigate_to_last(&self, state: &mut AppState) -> bool {
+        let total = state.filtered_files.len();
+        if total == 0 {
+            return false;
+        }
+
+        state.file_list_state.select(Some(total - 1));
+        self.update_scroll_offset(state);
+        PreviewManager::preview_for_selected_item(state);
+        true
+    }
+
     /// Get the file path for preview (unified interface)
     fn get_preview_path(&self, state: &AppState) -> Option<PathBuf> {
         state
@@ -145,27 +206,31 @@ pub trait DataProvider {
             .map(|item| item.get_path().clone())
     }
 
-    /// Update scroll offset for automatic scrolling
-    fn update_scroll_offset(&self, state: &mut AppState, visible_height: usize) {
+    /// Scroll the list so the selection stays visible
+    ///
+    /// The list only scrolls once the selection comes closer than
+    /// `navigation.scrolloff` lines to the top or bottom edge, like vim's
+    /// `scrolloff`, and never scrolls past the end of the list.
+    fn update_scroll_offset(&self, state: &mut AppState) {
+        let visible_height = state.layout.get_left_content_height();
         if visible_height == 0 {
-            return; // Avoid division by zero and overflow
+            return;
         }
 
         if let Some(selected) = state.file_list_state.selected() {
-            let current_offset = state.file_list_state.offset();
-            let new_offset = if selected < current_offset {
-                selected
-            } else if selected >= current_offset + visible_height
-                || selected < current_offset + visible_height - 1
-            {
-                selected.saturating_sub(visible_height - 1)
-            } else {
-                current_offset
-            };
-
-            if new_offset != current_offset {
-                *state.file_list_state.offset_mut() = new_offset;
+            let total = state.filtered_files.len();
+            let margin = get_config()
+                .navigation
+                .scrolloff
+                .min(visible_height.saturating_sub(1) / 2);
+            let mut offset = state.file_list_state.offset();
+
+            if selected < offset + margin {
+                offset = selected.saturating_sub(margin);
+            } else if selected + margin >= offset + visible_height {
+                offset = selected + margin + 1 - visible_height;
             }
+            *state.file_list_state.offset_mut() = offset.min(total.saturating_sub(visible_height));
         }
     }
 
@@ -190,6 +255,14 @@ pub trait DataProvider {
     /// Load initial data for this mode
     fn load_data(&self, state: &mut AppState) -> Result<()>;
 
+    /// Load the data when the mode is entered
+    ///
+    /// Modes whose data is slow to read start reading it on a background
+    /// thread instead, and show a placeholder until it is there.
+    fn start_loading(&self, state: &mut AppState) -> Result<()> {
+        self.load_data(state)
+    }
+
     /// Save current position before navigation
     fn save_position(&self, _state: &mut AppState) {}
 
@@ -207,6 +280,12 @@ pub trait DataProvider {
 pub enum DataProviderType {
     Normal(normal::FileListDataProvider),
     History(history::HistoryDataProvider),
+    Picker(picker::PickerDataProvider),
+    RecentFiles(recent_files::RecentFilesDataProvider),
+    Jump(jump::JumpDataProvider),
+    Repos(repos::ReposDataProvider),
+    Projects(projects::ProjectsDataProvider),
+    Mounts(mounts::MountsDataProvider),
 }
 
 impl DataProviderType {
@@ -215,6 +294,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.get_items(state),
             DataProviderType::History(provider) => provider.get_items(state),
+            DataProviderType::Picker(provider) => provider.get_items(state),
+            DataProviderType::RecentFiles(provider) => provider.get_items(state),
+            DataProviderType::Jump(provider) => provider.get_items(state),
+            DataProviderType::Repos(provider) => provider.get_items(state),
+            DataProviderType::Projects(provider) => provider.get_items(state),
+            DataProviderType::Mounts(provider) => provider.get_items(state),
         }
     }
 
@@ -223,6 +308,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.get_selected_index(state),
             DataProviderType::History(provider) => provider.get_selected_index(state),
+            DataProviderType::Picker(provider) => provider.get_selected_index(state),
+            DataProviderType::RecentFiles(provider) => provider.get_selected_index(state),
+            DataProviderType::Jump(provider) => provider.get_selected_index(state),
+            DataProviderType::Repos(provider) => provider.get_selected_index(state),
+            DataProviderType::Projects(provider) => provider.get_selected_index(state),
+            DataProviderType::Mounts(provider) => provider.get_selected_index(state),
         }
     }
 
@@ -231,6 +322,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.set_selected_index(state, index),
             DataProviderType::History(provider) => provider.set_selected_index(state, index),
+            DataProviderType::Picker(provider) => provider.set_selected_index(state, index),
+            DataProviderType::RecentFiles(provider) => provider.set_selected_index(state, index),
+            DataProviderType::Jump(provider) => provider.set_selected_index(state, index),
+            DataProviderType::Repos(provider) => provider.set_selected_index(state, index),
+            DataProviderType::Projects(provider) => provider.set_selected_index(state, index),
+            DataProviderType::Mounts(provider) => provider.set_selected_index(state, index),
         }
     }
 
@@ -239,38 +336,140 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.get_total_count(state),
             DataProviderType::History(provider) => provider.get_total_count(state),
+            DataProviderType::Picker(provider) => provider.get_total_count(state),
+            DataProviderType::RecentFiles(provider) => provider.get_total_count(state),
+            DataProviderType::Jump(provider) => provider.get_total_count(state),
+            DataProviderType::Repos(provider) => provider.get_total_count(state),
+            DataProviderType::Projects(provider) => provider.get_total_count(state),
+            DataProviderType::Mounts(provider) => provider.get_total_count(state),
+        }
+    }
+
+    /// Navigate up `count` items in the list
+    pub async fn navigate_up(&self, state: &mut AppState, count: usize) -> bool {
+        match self {
+            DataProviderType::Normal(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::History(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::Picker(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::RecentFiles(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::Jump(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::Repos(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::Projects(provider) => provider.navigate_up(state, count).await,
+            DataProviderType::Mounts(provider) => provider.navigate_up(state, count).await,
         }
     }
 
-    /// Navigate up in the list
-    pub async fn navigate_up(&self, state: &mut AppState) -> bool {
+    /// Navigate down `count` items in the list
+    pub async fn navigate_down(&self, state: &mut AppState, count: usize) -> bool {
         match self {
-            DataProviderType::Normal(provider) => provider.navigate_up(state).await,
-            DataProviderType::History(provider) => provider.navigate_up(state).await,
+            DataProviderType::Normal(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::History(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::Picker(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::RecentFiles(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::Jump(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::Repos(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::Projects(provider) => provider.navigate_down(state, count).await,
+            DataProviderType::Mounts(provider) => provider.navigate_down(state, count).await,
         }
     }
 
-    /// Navigate down in the list
-    pub async fn navigate_down(&self, state: &mut AppState) -> bool {
+    /// Navigate `count` half pages up in the list
+    pub async fn navigate_half_page_up(&self, state: &mut AppState, count: usize) -> bool {
         match self {
-            DataProviderType::Normal(provider) => provider.navigate_down(state).await,
-            DataProviderType::History(provider) => provider.navigate_down(state).await,
+            DataProviderType::Normal(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
+            DataProviderType::History(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
+            DataProviderType::Picker(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
+            DataProviderType::RecentFiles(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
+            DataProviderType::Jump(provider) => provider.navigate_half_page_up(state, count).await,
+            DataProviderType::Repos(provider) => provider.navigate_half_page_up(state, count).await,
+            DataProviderType::Projects(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
+            DataProviderType::Mounts(provider) => {
+                provider.navigate_half_page_up(state, count).await
+            }
         }
     }
 
-    /// Navigate half page up in the list
-    pub async fn navigate_half_page_up(&self, state: &mut AppState) -> bool {
+    /// Navigate `count` half pages down in the list
+    pub async fn navigate_half_page_down(&self, state: &mut AppState, count: usize) -> bool {
         match self {
-            DataProviderType::Normal(provider) => provider.navigate_half_page_up(state).await,
-            DataProviderType::History(provider) => provider.navigate_half_page_up(state).await,
+            DataProviderType::Normal(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::History(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::Picker(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::RecentFiles(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::Jump(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::Repos(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::Projects(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+            DataProviderType::Mounts(provider) => {
+                provider.navigate_half_page_down(state, count).await
+            }
+        }
+    }
+
+    /// Select the item at `index` in the filtered list
+    pub async fn navigate_to_index(&self, state: &mut AppState, index: usize) -> bool {
+        match self {
+            DataProviderType::Normal(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::History(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::Picker(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::RecentFiles(provider) => {
+                provider.navigate_to_index(state, index).await
+            }
+            DataProviderType::Jump(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::Repos(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::Projects(provider) => provider.navigate_to_index(state, index).await,
+            DataProviderType::Mounts(provider) => provider.navigate_to_index(state, index).await,
         }
     }
 
-    /// Navigate half page down in the list
-    pub async fn navigate_half_page_down(&self, state: &mut AppState) -> bool {
+    /// Jump to the first item in the list
+    pub async fn navigate_to_first(&self, state: &mut AppState) -> bool {
         match self {
-            DataProviderType::Normal(provider) => provider.navigate_half_page_down(state).await,
-            DataProviderType::History(provider) => provider.navigate_half_page_down(state).await,
+            DataProviderType::Normal(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::History(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::Picker(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::RecentFiles(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::Jump(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::Repos(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::Projects(provider) => provider.navigate_to_first(state).await,
+            DataProviderType::Mounts(provider) => provider.navigate_to_first(state).await,
+        }
+    }
+
+    /// Jump to the last item in the list
+    pub async fn navigate_to_last(&self, state: &mut AppState) -> bool {
+        match self {
+            DataProviderType::Normal(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::History(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::Picker(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::RecentFiles(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::Jump(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::Repos(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::Projects(provider) => provider.navigate_to_last(state).await,
+            DataProviderType::Mounts(provider) => provider.navigate_to_last(state).await,
         }
     }
 
@@ -279,6 +478,26 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.load_data(state),
             DataProviderType::History(provider) => provider.load_data(state),
+            DataProviderType::Picker(provider) => provider.load_data(state),
+            DataProviderType::RecentFiles(provider) => provider.load_data(state),
+            DataProviderType::Jump(provider) => provider.load_data(state),
+            DataProviderType::Repos(provider) => provider.load_data(state),
+            DataProviderType::Projects(provider) => provider.load_data(state),
+            DataProviderType::Mounts(provider) => provider.load_data(state),
+        }
+    }
+
+    /// Load the data when the mode is entered, see [`DataProvider::start_loading`]
+    pub fn start_loading(&self, state: &mut AppState) -> Result<()> {
+        match self {
+            DataProviderType::Normal(provider) => provider.start_loading(state),
+            DataProviderType::History(provider) => provider.start_loading(state),
+            DataProviderType::Picker(provider) => provider.start_loading(state),
+            DataProviderType::RecentFiles(provider) => provider.start_loading(state),
+            DataProviderType::Jump(provider) => provider.start_loading(state),
+            DataProviderType::Repos(provider) => provider.start_loading(state),
+            DataProviderType::Projects(provider) => provider.start_loading(state),
+            DataProviderType::Mounts(provider) => provider.start_loading(state),
         }
     }
 
@@ -287,6 +506,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.navigate_into_directory(state),
             DataProviderType::History(provider) => provider.navigate_into_directory(state),
+            DataProviderType::Picker(provider) => provider.navigate_into_directory(state),
+            DataProviderType::RecentFiles(provider) => provider.navigate_into_directory(state),
+            DataProviderType::Jump(provider) => provider.navigate_into_directory(state),
+            DataProviderType::Repos(provider) => provider.navigate_into_directory(state),
+            DataProviderType::Projects(provider) => provider.navigate_into_directory(state),
+            DataProviderType::Mounts(provider) => provider.navigate_into_directory(state),
         }
     }
 
@@ -295,6 +520,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.navigate_to_parent(state),
             DataProviderType::History(provider) => provider.navigate_to_parent(state),
+            DataProviderType::Picker(provider) => provider.navigate_to_parent(state),
+            DataProviderType::RecentFiles(provider) => provider.navigate_to_parent(state),
+            DataProviderType::Jump(provider) => provider.navigate_to_parent(state),
+            DataProviderType::Repos(provider) => provider.navigate_to_parent(state),
+            DataProviderType::Projects(provider) => provider.navigate_to_parent(state),
+            DataProviderType::Mounts(provider) => provider.navigate_to_parent(state),
         }
     }
 
@@ -303,6 +534,12 @@ impl DataProviderType {
         match self {
             DataProviderType::Normal(provider) => provider.navigate_to_selected(state),
             DataProviderType::History(provider) => provider.navigate_to_selected(state),
+            DataProviderType::Picker(provider) => provider.navigate_to_selected(state),
+            DataProviderType::RecentFiles(provider) => provider.navigate_to_selected(state),
+            DataProviderType::Jump(provider) => provider.navigate_to_selected(state),
+            DataProviderType::Repos(provider) => provider.navigate_to_selected(state),
+            DataProviderType::Projects(provider) => provider.navigate_to_selected(state),
+            DataProviderType::Mounts(provider) => provider.navigate_to_selected(state),
         }
     }
 }
@@ -312,5 +549,13 @@ pub fn create_data_provider(mode: &AppMode) -> DataProviderType {
     match mode {
         AppMode::Normal => DataProviderType::Normal(normal::FileListDataProvider),
         AppMode::History => DataProviderType::History(history::HistoryDataProvider),
+        AppMode::Picker => DataProviderType::Picker(picker::PickerDataProvider),
+        AppMode::RecentFiles => {
+            DataProviderType::RecentFiles(recent_files::RecentFilesDataProvider)
+        }
+        AppMode::Jump => DataProviderType::Jump(jump::JumpDataProvider),
+        AppMode::Repos => DataProviderType::Repos(repos::ReposDataProvider),
+        AppMode::Projects => DataProviderType::Projects(projects::ProjectsDataProvider),
+        AppMode::Mounts => DataProviderType::Mounts(mounts::MountsDataProvider),
     }
 }
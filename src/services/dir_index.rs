@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use bincode::config;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    config::{IndexConfig, get_config, get_data_dir},
+    core::breadcrumb,
+    modes::history::HistoryDataProvider,
+};
+
+/// Whether a refresh is running on the background thread
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+/// Set when a refresh finished, until [`take_update`] picks it up
+static UPDATED: AtomicBool = AtomicBool::new(false);
+
+/// Entries noted when present in a directory, to find repositories and
+/// the manifests of [`crate::services::projects::ProjectKind`]
+pub const MARKERS: &[&str] = &[
+    ".git",
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "pom.xml",
+    "build.gradle",
+    "Gemfile",
+    "composer.json",
+];
+
+/// A directory as the indexer last saw it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexedDir {
+    /// Modification time in seconds, which changes when entries are added or removed
+    modified: u64,
+    /// Names of the subdirectories
+    subdirs: Vec<String>,
+    /// The [`MARKERS`] found in the directory
+    markers: Vec<String>,
+}
+
+/// Directories under the configured roots, for jumping anywhere without
+/// walking the filesystem at query time
+///
+/// The index is kept in `quickswitch.index.bin` in the data directory. A
+/// refresh only lists directories whose modification time changed since.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirIndex {
+    dirs: BTreeMap<PathBuf, IndexedDir>,
+    /// The [`MARKERS`] looked for when the index was built
+    markers: Vec<String>,
+}
+
+impl DirIndex {
+    fn file_path() -> Result<PathBuf> {
+        Ok(get_data_dir()?.join("quickswitch.index.bin"))
+    }
+
+    /// Read the index, empty if it wasn't built yet
+    #[instrument]
+    pub fn load() -> Result<Self> {
+        let path = Self::file_path()?;
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        match bincode::serde::decode_from_slice(&data, config::standard()) {
+            Ok((index, _)) => Ok(index),
+            // A corrupt index is rebuilt by the next refresh
+            Err(e) => {
+                warn!("Discarding unreadable index {}: {e}", path.display());
+                Ok(Self::default())
+            }
+        }
+    }
+
+    #[instrument(skip(self), fields(dirs = self.dirs.len()))]
+    fn save(&self) -> Result<()> {
+        let path = Self::file_path()?;
+        info!(path = %path.display(), "Saving directory index");
+        let tmp_path = path.with_extension("tmp");
+        fs::write(
+            &tmp_path,
+            bincode::serde::encode_to_vec(self, config::standard())?,
+        )?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// The indexed directories, sorted by path
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.dirs.keys().map(PathBuf::as_path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.dirs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty()
+    }
+
+    /// The indexed directories containing `marker`, one of [`MARKERS`]
+    pub fn paths_with_marker<'a>(&'a self, marker: &'a str) -> impl Iterator<Item = &'a Path> {
+        self.dirs
+            .iter()
+            .filter(move |(_, dir)| dir.markers.iter().any(|known| known == marker))
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Walk the configured roots again, reusing the entries of directories
+    /// that didn't change since this index was built
+    #[instrument(skip(self, config))]
+    pub fn refresh(&self, config: &IndexConfig) -> Self {
+        let mut refreshed = Self {
+            dirs: BTreeMap::new(),
+            markers: MARKERS.iter().map(|marker| marker.to_string()).collect(),
+        };
+        // Directories have to be listed again to find markers added since
+        let unchanged = if self.markers == refreshed.markers {
+            self
+        } else {
+            &Self::default()
+        };
+        for root in config.roots.iter().filter_map(|root| expand_home(root)) {
+            let mut pending = vec![(root, 0)];
+            while let Some((dir, depth)) = pending.pop() {
+                let Some(entry) = unchanged.scan(&dir, config) else {
+                    continue;
+                };
+                if depth < config.max_depth {
+                    pending.extend(entry.subdirs.iter().map(|name| (dir.join(name), depth + 1)));
+                }
+                refreshed.dirs.insert(dir, entry);
+            }
+        }
+        refreshed
+    }
+
+    /// The subdirectories of `dir`, listed only if it changed since the last scan
+    fn scan(&self, dir: &Path, config: &IndexConfig) -> Option<IndexedDir> {
+        let modified = fs::metadata(dir)
+            .and_then(|metadata| metadata.modified())
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |age| age.as_secs());
+        if let Some(known) = self
+            .dirs
+            .get(dir)
+            .filter(|known| known.modified == modified)
+        {
+            return Some(known.clone());
+        }
+
+        let mut subdirs = Vec::new();
+        let mut markers = Vec::new();
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if MARKERS.contains(&name.as_str()) {
+                markers.push(name.clone());
+            }
+            // Symlinks aren't followed, they could lead out of the root or in circles
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            if is_dir && !name.starts_with('.') && !config.exclude.contains(&name) {
+                subdirs.push(name);
+            }
+        }
+        subdirs.sort();
+        markers.sort();
+        Some(IndexedDir {
+            modified,
+            subdirs,
+            markers,
+        })
+    }
+
+    /// The best indexed directory for a query like `proj api`
+    ///
+    /// Uses the same rules as history queries, and prefers the shallowest match.
+    pub fn best_match(&self, query: &str) -> Option<&Path> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.paths()
+            .filter(|path| HistoryDataProvider::matches_terms(path, &terms))
+            .min_by_key(|path| path.components().count())
+    }
+}
+
+/// Resolve a leading `~` to the home directory
+fn expand_home(root: &str) -> Option<PathBuf> {
+    match root.strip_prefix('~') {
+        Some(rest) => {
+            let home = breadcrumb::home_dir()?;
+            Some(home.join(rest.trim_start_matches(['/', '\\'])))
+        }
+        None => Some(PathBuf::from(root)),
+    }
+}
+
+/// Whether a refresh is running in the background
+pub fn is_refreshing() -> bool {
+    REFRESHING.load(Ordering::Relaxed)
+}
+
+/// Whether a refresh finished since the last call
+pub fn take_update() -> bool {
+    UPDATED.swap(false, Ordering::Relaxed)
+}
+
+/// Refresh the index on a background thread, unless one is running already
+pub fn spawn_refresh() {
+    if get_config().index.roots.is_empty() || REFRESHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    thread::spawn(|| {
+        let result = DirIndex::load().and_then(|index| index.refresh(&get_config().index).save());
+        match result {
+            Ok(()) => UPDATED.store(true, Ordering::Relaxed),
+            Err(e) => warn!("Failed to refresh the directory index: {e:#}"),
+        }
+        REFRESHING.store(false, Ordering::Relaxed);
+    });
+}
+
+/// Refresh the index in the background once it is older than `refresh_minutes`
+pub fn refresh_if_stale() {
+    let max_age = Duration::from_secs(get_config().index.refresh_minutes * 60);
+    let age = DirIndex::file_path()
+        .and_then(|path| Ok(fs::metadata(path)?.modified()?))
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if age.is_none_or(|age| age >= max_age) {
+        spawn_refresh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_reuses_unchanged_directories() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [
+            "projects/api",
+            "projects/web",
+            ".cache/big",
+            "node_modules/dep",
+        ] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        let config = IndexConfig {
+            roots: vec![root.path().display().to_string()],
+            max_depth: 3,
+            exclude: vec!["node_modules".to_string()],
+            refresh_minutes: 60,
+        };
+
+        let index = DirIndex::default().refresh(&config);
+        let paths: Vec<&Path> = index.paths().collect();
+        assert_eq!(
+            paths,
+            vec![
+                root.path(),
+                &root.path().join("projects"),
+                &root.path().join("projects/api"),
+                &root.path().join("projects/web"),
+            ]
+        );
+        assert_eq!(
+            index.best_match("proj api"),
+            Some(root.path().join("projects/api").as_path())
+        );
+
+        // An unchanged directory is taken from the old index without listing it
+        let mut stale = DirIndex::default().refresh(&config);
+        let projects = stale.dirs.get_mut(&root.path().join("projects")).unwrap();
+        projects.subdirs.push("gone".to_string());
+        let refreshed = stale.refresh(&config);
+        assert!(
+            refreshed.dirs[&root.path().join("projects")]
+                .subdirs
+                .contains(&"gone".to_string())
+        );
+    }
+}
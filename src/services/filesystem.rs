@@ -1,83 +1,377 @@
-use anyhow::Result;
-use std::{fs, path::PathBuf};
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::warn;
 
-use crate::utils::FileItem;
+use crate::{
+    config::get_config,
+    services::mounts,
+    utils::{FileItem, natural_cmp},
+};
+
+/// Listings with more entries than this are stat'ed on several threads
+const PARALLEL_STAT_ENTRIES: usize = 256;
+
+/// An entry as listed: its name, path and the type the listing reported
+type ListedEntry = (String, PathBuf, Option<fs::FileType>);
+
+/// How long a network share gets to answer before it is given up on
+pub const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a share that didn't answer is skipped without trying again
+const UNREACHABLE_RETRY: Duration = Duration::from_secs(30);
+
+/// Network roots that timed out, and when
+static UNREACHABLE: Lazy<Mutex<HashMap<PathBuf, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The share a UNC path is on, like `\\server\share` for `\\server\share\docs`
+///
+/// Works on the text of the path, so it also recognizes the `\\?\UNC\` form
+/// and doesn't depend on the platform the path is parsed on.
+pub fn unc_share_root(path: &Path) -> Option<String> {
+    let text = path.to_string_lossy();
+    let (prefix, rest) = match text.strip_prefix(r"\\?\UNC\") {
+        Some(rest) => (r"\\?\UNC\", rest),
+        // `\\?\C:\` and `\\.\pipe` are local despite the leading backslashes
+        None if text.starts_with(r"\\?\") || text.starts_with(r"\\.\") => return None,
+        None => (r"\\", text.strip_prefix(r"\\")?),
+    };
+    let mut parts = rest.splitn(3, '\\');
+    let server = parts.next().filter(|server| !server.is_empty())?;
+    let share = parts.next().filter(|share| !share.is_empty())?;
+    Some(format!("{prefix}{server}\\{share}"))
+}
+
+/// Whether `path` is the top of a share, like `\\server\share\`
+pub fn is_unc_share_root(path: &Path) -> bool {
+    unc_share_root(path).is_some_and(|root| path.to_string_lossy().trim_end_matches('\\') == root)
+}
+
+/// The share or mapped drive `path` is on, `None` for local paths
+fn network_root(path: &Path) -> Option<PathBuf> {
+    unc_share_root(path)
+        .map(PathBuf::from)
+        .or_else(|| mounts::network_drive_root(path))
+}
+
+/// Paths this long need the `\\?\` prefix for some Windows APIs
+const MAX_PATH: usize = 260;
+
+/// `path` in the extended-length `\\?\` form on Windows when it is too long
+/// for the regular APIs, unchanged otherwise
+pub fn to_extended_length(path: &Path) -> PathBuf {
+    if !cfg!(windows) || !path.is_absolute() || path.as_os_str().len() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    PathBuf::from(extended_length_text(&path.to_string_lossy()))
+}
+
+/// Turn `\\?\C:\dir` or `\\?\UNC\server\share` back into the form users type,
+/// for display and output
+pub fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    match strip_verbatim_text(&path.to_string_lossy()) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path,
+    }
+}
+
+/// Prefix an absolute Windows path with `\\?\`, which also turns off the
+/// parsing of `/` as a separator
+fn extended_length_text(text: &str) -> String {
+    if text.starts_with(r"\\?\") {
+        return text.to_string();
+    }
+    let text = text.replace('/', r"\");
+    match text.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{share}"),
+        None => format!(r"\\?\{text}"),
+    }
+}
+
+/// `text` without a `\\?\` prefix, `None` for verbatim paths that have no
+/// other form like `\\?\Volume{...}`
+fn strip_verbatim_text(text: &str) -> Option<String> {
+    let rest = text.strip_prefix(r"\\?\")?;
+    if let Some(share) = rest.strip_prefix(r"UNC\") {
+        return Some(format!(r"\\{share}"));
+    }
+    let mut chars = rest.chars();
+    let is_drive =
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic()) && chars.next() == Some(':');
+    is_drive.then(|| rest.to_string())
+}
 
 /// Service for filesystem operations
 pub struct FilesystemService;
 
 impl FilesystemService {
     /// Load directory contents and return sorted file list
-    pub fn load_directory(current_dir: &PathBuf) -> Result<Vec<FileItem>> {
+    pub fn load_directory(current_dir: &Path) -> Result<Vec<FileItem>> {
         let mut files = Vec::new();
 
-        // Check if we're at Windows drives view and should show drives
+        // Check if we're at the drives view and should show drives
         if Self::should_show_drives(current_dir) {
             return Self::load_drives();
         }
 
-        let entries = fs::read_dir(current_dir)?;
-        let mut items: Vec<FileItem> = entries
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                let path = entry.path();
-                let name = entry.file_name().to_string_lossy().to_string();
-                let is_dir = path.is_dir();
-
-                Some(FileItem { name, path, is_dir })
-            })
-            .collect();
+        // A share that went away would block the listing below for a long time
+        if Self::is_network_path(current_dir) {
+            Self::check_readable(current_dir)?;
+        }
+        // The entries are listed with long paths, so they can be opened in turn
+        let dir = to_extended_length(current_dir);
+        let listing = if get_config().filter.respect_ignore {
+            Self::list_unignored_entries(&dir)
+        } else {
+            Self::list_entries(&dir)
+        }
+        .with_context(|| format!("Cannot open {}", current_dir.display()))?;
+        let parallel = listing.len() > PARALLEL_STAT_ENTRIES || Self::is_network_path(current_dir);
+        let mut items: Vec<FileItem> = if parallel {
+            listing
+                .into_par_iter()
+                .map(|(name, path, file_type)| FileItem::from_listing(name, path, file_type))
+                .collect()
+        } else {
+            listing
+                .into_iter()
+                .map(|(name, path, file_type)| FileItem::from_listing(name, path, file_type))
+                .collect()
+        };
 
         items.sort_by(|a, b| match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.cmp(&b.name),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => Self::compare_names(&a.name, &b.name),
         });
 
         files.extend(items);
         Ok(files)
     }
 
-    /// Check if we should show drives instead of directory contents
-    fn should_show_drives(current_dir: &PathBuf) -> bool {
-        #[cfg(windows)]
+    /// Whether `path` is on a UNC share or a mapped network drive
+    pub fn is_network_path(path: &Path) -> bool {
+        network_root(path).is_some()
+    }
+
+    /// Run `f` on a worker thread when `path` is on the network, giving up
+    /// after [`NETWORK_TIMEOUT`]
+    ///
+    /// A share that timed out fails right away for a while, so a list of
+    /// paths on it doesn't wait for each of them in turn.
+    pub fn with_network_timeout<T: Send + 'static>(
+        path: &Path,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> io::Result<T> {
+        let Some(root) = network_root(path) else {
+            return Ok(f());
+        };
+        let timed_out = || {
+            io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{} did not respond", root.display()),
+            )
+        };
+        if let Ok(unreachable) = UNREACHABLE.lock()
+            && unreachable
+                .get(&root)
+                .is_some_and(|since| since.elapsed() < UNREACHABLE_RETRY)
         {
-            // On Windows, only show drives when we're at the special "DRIVES:" path
-            current_dir.to_string_lossy() == "DRIVES:"
+            return Err(timed_out());
         }
-        #[cfg(not(windows))]
-        {
-            let _ = current_dir; // Suppress unused variable warning
-            false
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(f());
+        });
+        let result = receiver.recv_timeout(NETWORK_TIMEOUT);
+        if let Ok(mut unreachable) = UNREACHABLE.lock() {
+            match result {
+                Ok(_) => unreachable.remove(&root),
+                Err(_) => {
+                    warn!(
+                        "{} did not respond within {NETWORK_TIMEOUT:?}",
+                        root.display()
+                    );
+                    unreachable.insert(root.clone(), Instant::now())
+                }
+            };
         }
+        result.map_err(|_| timed_out())
     }
 
-    /// Load available drives on Windows
-    pub fn load_drives() -> Result<Vec<FileItem>> {
-        #[cfg(windows)]
-        {
-            let mut drives = Vec::new();
-
-            // Try common drive letters and check if they exist
-            for letter in 'A'..='Z' {
-                let drive_path = format!("{}:\\", letter);
-                let path = PathBuf::from(&drive_path);
-
-                // Check if the drive is accessible by trying to read its metadata
-                if path.exists() && path.is_dir() {
-                    drives.push(FileItem {
-                        name: drive_path.clone(),
-                        path,
-                        is_dir: true,
-                    });
+    /// Check that `path` is a directory that can be listed, without hanging
+    /// on a share that doesn't answer
+    pub fn check_readable(path: &Path) -> Result<()> {
+        let dir = to_extended_length(path);
+        Self::with_network_timeout(path, move || fs::read_dir(dir).map(drop))
+            .and_then(|result| result)
+            .with_context(|| format!("Cannot open {}", path.display()))
+    }
+
+    /// [`Path::is_dir`], `false` for a share that doesn't answer
+    pub fn is_dir(path: &Path) -> bool {
+        let owned = path.to_path_buf();
+        Self::with_network_timeout(path, move || owned.is_dir()).unwrap_or(false)
+    }
+
+    /// [`Path::exists`], `false` for a share that doesn't answer
+    pub fn exists(path: &Path) -> bool {
+        let owned = path.to_path_buf();
+        Self::with_network_timeout(path, move || owned.exists()).unwrap_or(false)
+    }
+
+    /// Compare two entry names according to the configured sort settings
+    pub fn compare_names(a: &str, b: &str) -> Ordering {
+        if get_config().sort.natural {
+            natural_cmp(a, b)
+        } else {
+            a.cmp(b)
+        }
+    }
+
+    /// List the immediate children of a directory
+    fn list_entries(dir: &Path) -> Result<Vec<ListedEntry>> {
+        let listing = fs::read_dir(dir)?
+            .filter_map(|entry| match entry {
+                Ok(entry) => {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    Some((name, entry.path(), entry.file_type().ok()))
                 }
-            }
+                Err(e) => {
+                    warn!("Skipping an entry of {}: {e}", dir.display());
+                    None
+                }
+            })
+            .collect();
+        Ok(listing)
+    }
 
-            Ok(drives)
+    /// List the immediate children of a directory, skipping entries matched by
+    /// `.gitignore`, `.ignore` or the global git excludes file
+    fn list_unignored_entries(dir: &Path) -> Result<Vec<ListedEntry>> {
+        let mut listing = Vec::new();
+        let walk = WalkBuilder::new(dir)
+            .max_depth(Some(1))
+            // Hidden files are filtered separately by the show_hidden_files toggle
+            .hidden(false)
+            .build();
+        for entry in walk {
+            match entry {
+                Ok(entry) if entry.depth() == 0 => {}
+                Ok(entry) => {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    listing.push((name, entry.path().to_path_buf(), entry.file_type()));
+                }
+                // The directory itself can't be read
+                Err(e) if e.depth() == Some(0) => return Err(e.into()),
+                Err(e) => warn!("Skipping an entry of {}: {e}", dir.display()),
+            }
         }
-        #[cfg(not(windows))]
-        {
-            Ok(Vec::new())
+        Ok(listing)
+    }
+
+    /// Check if we should show drives instead of directory contents
+    fn should_show_drives(current_dir: &Path) -> bool {
+        current_dir.to_string_lossy() == "DRIVES:"
+    }
+
+    /// Load the entries of the drives view, see [`mounts::drive_roots`]
+    pub fn load_drives() -> Result<Vec<FileItem>> {
+        // Looks up the labels and free space shown next to the drives
+        mounts::load_mounts()?;
+        let drives = mounts::drive_roots()?
+            .into_iter()
+            .map(|path| FileItem {
+                name: path.display().to_string(),
+                path,
+                is_dir: true,
+                kind: crate::utils::FileKind::Directory,
+                hidden: false,
+            })
+            .collect();
+        Ok(drives)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unc_share_root() {
+        let root = |path: &str| unc_share_root(Path::new(path));
+        assert_eq!(root(r"\\nas\media\films").as_deref(), Some(r"\\nas\media"));
+        assert_eq!(
+            root(r"\\?\UNC\nas\media\films").as_deref(),
+            Some(r"\\?\UNC\nas\media")
+        );
+        assert_eq!(root(r"\\nas"), None);
+        assert_eq!(root(r"\\?\C:\Users"), None);
+        assert_eq!(root(r"C:\Users"), None);
+        assert_eq!(root("/home/user"), None);
+
+        assert!(is_unc_share_root(Path::new(r"\\nas\media\")));
+        assert!(!is_unc_share_root(Path::new(r"\\nas\media\films")));
+        assert!(!FilesystemService::is_network_path(Path::new("/tmp")));
+    }
+
+    #[test]
+    fn test_extended_length_round_trip() {
+        let long = format!(r"C:\Users\me\{}", "x".repeat(300));
+        let extended = extended_length_text(&long);
+        assert_eq!(extended, format!(r"\\?\{long}"));
+        assert_eq!(strip_verbatim_text(&extended), Some(long));
+        assert_eq!(extended_length_text(&extended), extended);
+
+        let share = extended_length_text(r"\\nas\media/films");
+        assert_eq!(share, r"\\?\UNC\nas\media\films");
+        assert_eq!(
+            strip_verbatim_text(&share).as_deref(),
+            Some(r"\\nas\media\films")
+        );
+        assert_eq!(strip_verbatim_text(r"\\?\Volume{1234}\"), None);
+        assert_eq!(strip_verbatim_text(r"C:\Users"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listing_matches_stats() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        fs::write(dir.path().join("run.sh"), "").unwrap();
+        fs::set_permissions(dir.path().join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        symlink(dir.path().join("sub"), dir.path().join("link")).unwrap();
+        symlink(dir.path().join("gone"), dir.path().join(".broken")).unwrap();
+
+        for entry in fs::read_dir(dir.path()).unwrap() {
+            let entry = entry.unwrap();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let listed = FileItem::from_listing(name.clone(), entry.path(), entry.file_type().ok());
+            let stated = FileItem::new(name, entry.path());
+            assert_eq!(
+                (listed.is_dir, listed.kind, listed.hidden),
+                (stated.is_dir, stated.kind, stated.hidden),
+                "{}",
+                listed.name
+            );
         }
     }
 }
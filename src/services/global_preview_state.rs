@@ -1,12 +1,13 @@
 use crate::utils::FileItem;
 
-use super::preview::PreviewContent;
+use super::preview::{PreviewContent, TEXT_CHUNK_LINES};
 use once_cell::sync::Lazy;
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
 use std::sync::{Arc, RwLock};
+use tracing::warn;
 
 /// Global preview state that can be safely accessed from multiple threads
 #[derive(Debug, Clone)]
@@ -17,6 +18,32 @@ pub struct PreviewState {
     pub current_file_item: Option<FileItem>,
 }
 
+impl PreviewState {
+    /// Read more of a partially loaded text file until it has `needed` lines
+    fn ensure_lines(&mut self, needed: usize) {
+        while self.content.len() < needed {
+            let PreviewContent::PartialText(mut lines, source) = std::mem::take(&mut self.content)
+            else {
+                return;
+            };
+            self.content = match source.clone().read_lines(TEXT_CHUNK_LINES) {
+                Ok((more, Some(rest))) => {
+                    lines.extend(more);
+                    PreviewContent::PartialText(lines, rest).capped()
+                }
+                Ok((more, None)) => {
+                    lines.extend(more);
+                    PreviewContent::Text(lines).capped()
+                }
+                Err(e) => {
+                    warn!("Failed to read more of the preview: {e}");
+                    PreviewContent::Text(lines)
+                }
+            };
+        }
+    }
+}
+
 impl Default for PreviewState {
     fn default() -> Self {
         Self {
@@ -53,9 +80,14 @@ impl GlobalPreviewState {
         self.state.read().unwrap().current_file_item.clone()
     }
 
-    /// Get a copy of the current preview state
-    pub fn get_state(&self) -> PreviewState {
-        self.state.read().unwrap().clone()
+    /// Check if `file_item` is still the one being previewed
+    pub fn is_current_file_item(&self, file_item: &FileItem) -> bool {
+        self.state.read().unwrap().current_file_item.as_ref() == Some(file_item)
+    }
+
+    /// Look at the current preview state without copying its lines
+    pub fn with_state<T>(&self, f: impl FnOnce(&PreviewState) -> T) -> T {
+        f(&self.state.read().unwrap())
     }
 
     /// Update the preview content and title
@@ -70,7 +102,7 @@ impl GlobalPreviewState {
         }
         let mut state = self.state.write().unwrap();
         state.title = title;
-        state.content = content;
+        state.content = content.capped();
         state.scroll_offset = 0; // Reset scroll when content changes
     }
 
@@ -120,6 +152,8 @@ impl GlobalPreviewState {
     /// Scroll preview content down by one line
     pub fn scroll_down(&self) -> bool {
         let mut state = self.state.write().unwrap();
+        let needed = state.scroll_offset + TEXT_CHUNK_LINES / 2;
+        state.ensure_lines(needed);
         if state.scroll_offset + 1 < state.content.len() {
             state.scroll_offset += 1;
             true
@@ -145,6 +179,9 @@ impl GlobalPreviewState {
     pub fn scroll_page_down(&self, visible_height: usize) -> bool {
         let mut state = self.state.write().unwrap();
         let half_screen = (visible_height / 2).max(1);
+        // Keep a chunk beyond the visible lines loaded
+        let needed = state.scroll_offset + half_screen + visible_height + TEXT_CHUNK_LINES / 2;
+        state.ensure_lines(needed);
         let max_offset = state.content.len().saturating_sub(visible_height);
         let new_offset = (state.scroll_offset + half_screen).min(max_offset);
         if new_offset != state.scroll_offset {
@@ -155,6 +192,130 @@ impl GlobalPreviewState {
         }
     }
 
+    /// Scroll by `delta` lines, stopping once the last line is at the bottom
+    pub fn scroll_by(&self, delta: isize, visible_height: usize) -> bool {
+        let mut state = self.state.write().unwrap();
+        let offset = state.scroll_offset;
+        let new_offset = if delta < 0 {
+            offset.saturating_sub(delta.unsigned_abs())
+        } else {
+            let needed = offset + delta as usize + visible_height + TEXT_CHUNK_LINES / 2;
+            state.ensure_lines(needed);
+            let max_offset = state.content.len().saturating_sub(visible_height);
+            (offset + delta as usize).min(max_offset).max(offset)
+        };
+        state.scroll_offset = new_offset;
+        new_offset != offset
+    }
+
+    /// Scroll to the first line
+    pub fn scroll_to_top(&self) -> bool {
+        let mut state = self.state.write().unwrap();
+        std::mem::replace(&mut state.scroll_offset, 0) != 0
+    }
+
+    /// Scroll so the last line is at the bottom, reading all of a partially
+    /// loaded file
+    pub fn scroll_to_bottom(&self, visible_height: usize) -> bool {
+        let mut state = self.state.write().unwrap();
+        state.ensure_lines(usize::MAX);
+        let max_offset = state.content.len().saturating_sub(visible_height);
+        std::mem::replace(&mut state.scroll_offset, max_offset) != max_offset
+    }
+
+    /// Scroll to the next line after (or before) the top one containing `pattern`
+    ///
+    /// Searching forward reads more of a partially loaded file until a match
+    /// turns up. Returns `false` when there is none.
+    pub fn search(&self, pattern: &str, forward: bool) -> bool {
+        let mut state = self.state.write().unwrap();
+        let offset = state.scroll_offset;
+        loop {
+            let Some(lines) = state.content.as_text() else {
+                return false;
+            };
+            if let Some(index) = find_line(lines, pattern, offset, forward) {
+                state.scroll_offset = index;
+                return true;
+            }
+            let loaded = lines.len();
+            if !forward {
+                return false;
+            }
+            state.ensure_lines(loaded + TEXT_CHUNK_LINES);
+            if state.content.len() == loaded {
+                return false;
+            }
+        }
+    }
+
+    /// Scroll to the start of the next page of paged content
+    pub fn scroll_next_page(&self) -> bool {
+        let mut state = self.state.write().unwrap();
+        let offset = state.scroll_offset;
+        match state
+            .content
+            .page_starts()
+            .iter()
+            .find(|&&start| start > offset)
+        {
+            Some(&start) => {
+                state.scroll_offset = start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll to the start of the current page, or the previous one when
+    /// already there
+    pub fn scroll_previous_page(&self) -> bool {
+        let mut state = self.state.write().unwrap();
+        let offset = state.scroll_offset;
+        match state
+            .content
+            .page_starts()
+            .iter()
+            .rfind(|&&start| start < offset)
+        {
+            Some(&start) => {
+                state.scroll_offset = start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Zoom the previewed image in or out
+    pub fn zoom_image(&self, zoom_in: bool) -> bool {
+        let mut state = self.state.write().unwrap();
+        let PreviewContent::Image(image) = &state.content else {
+            return false;
+        };
+        match image.zoomed(zoom_in) {
+            Some(image) => {
+                state.content = PreviewContent::Image(image);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the zoomed in part of the previewed image
+    pub fn pan_image(&self, dx: i8, dy: i8) -> bool {
+        let mut state = self.state.write().unwrap();
+        let PreviewContent::Image(image) = &state.content else {
+            return false;
+        };
+        match image.panned(dx, dy) {
+            Some(image) => {
+                state.content = PreviewContent::Image(image);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Reset scroll position to top
     pub fn reset_scroll(&self) {
         let mut state = self.state.write().unwrap();
@@ -168,6 +329,32 @@ impl Default for GlobalPreviewState {
     }
 }
 
+/// Index of the first line after `from` (or before it, going backward) that
+/// contains `pattern`
+///
+/// Like vim's smartcase, the search ignores case unless the pattern has capitals.
+fn find_line(lines: &[Line<'static>], pattern: &str, from: usize, forward: bool) -> Option<usize> {
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    let pattern = if ignore_case {
+        pattern.to_lowercase()
+    } else {
+        pattern.to_string()
+    };
+    let matches = |index: &usize| {
+        let text = lines[*index].to_string();
+        if ignore_case {
+            text.to_lowercase().contains(&pattern)
+        } else {
+            text.contains(&pattern)
+        }
+    };
+    if forward {
+        (from + 1..lines.len()).find(matches)
+    } else {
+        (0..from.min(lines.len())).rev().find(matches)
+    }
+}
+
 /// Global instance of the preview state
 pub static GLOBAL_PREVIEW_STATE: Lazy<GlobalPreviewState> = Lazy::new(GlobalPreviewState::new);
 
@@ -178,3 +365,22 @@ impl GlobalPreviewState {
         &GLOBAL_PREVIEW_STATE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_line() {
+        let lines: Vec<Line<'static>> = ["fn main() {", "    Foo::new();", "}", "fn foo() {}"]
+            .into_iter()
+            .map(Line::from)
+            .collect();
+
+        assert_eq!(find_line(&lines, "fn", 0, true), Some(3));
+        assert_eq!(find_line(&lines, "foo", 0, true), Some(1));
+        assert_eq!(find_line(&lines, "Foo", 1, true), None);
+        assert_eq!(find_line(&lines, "fn", 3, false), Some(0));
+        assert_eq!(find_line(&lines, "fn", 0, false), None);
+    }
+}
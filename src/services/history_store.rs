@@ -0,0 +1,949 @@
+use anyhow::{Context, Result};
+use bincode::config;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    config::{get_data_dir, get_history_config},
+    services::filesystem::strip_verbatim_prefix,
+    utils::{FileItem, FileKind, HistoryEntry, HistorySortMode},
+};
+
+/// Size at which the journal is folded back into the history file
+const JOURNAL_COMPACT_SIZE: u64 = 16 * 1024;
+
+/// How long checking whether one history entry still exists may take
+///
+/// Stale entries on unmounted network drives can block for much longer.
+const PRUNE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Paths the background prune found to be gone, hidden until they're visited again
+static MISSING_PATHS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(Default::default);
+
+/// Entries taken out by the last delete or prune, for a one-step undo
+static LAST_REMOVAL: Mutex<Option<(HistoryStore, Vec<HistoryEntry>)>> = Mutex::new(None);
+
+/// Frecency of the visited directories, see [`frecency_scores`]
+static FRECENCY: Mutex<FrecencyLoad> = Mutex::new(FrecencyLoad {
+    scores: None,
+    loading: false,
+});
+
+/// Bumped whenever the directory history changes, so the scores are read again
+static HISTORY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The last frecency scores read and the history generation they were read at
+struct FrecencyLoad {
+    scores: Option<(u64, FrecencyScores)>,
+    loading: bool,
+}
+
+/// Frecency of the visited directories, by the path their visits were recorded under
+#[derive(Debug, Clone, Default)]
+pub struct FrecencyScores(Arc<HashMap<PathBuf, f64>>);
+
+impl FrecencyScores {
+    pub fn new(scores: HashMap<PathBuf, f64>) -> Self {
+        Self(Arc::new(scores))
+    }
+
+    /// The scores of the directories in the history file
+    fn read() -> Self {
+        let config = get_history_config();
+        match HistoryStore::DIRECTORIES.load_history_entries() {
+            Ok(entries) => Self::new(
+                entries
+                    .into_iter()
+                    .map(|entry| {
+                        let score = entry.calculate_score(config);
+                        (entry.path, score)
+                    })
+                    .collect(),
+            ),
+            Err(e) => {
+                warn!("Failed to load history for ranking: {e:#}");
+                Self::default()
+            }
+        }
+    }
+
+    /// A lookup of scores for the entries of a listing
+    pub fn lookup(&self) -> FrecencyLookup<'_> {
+        FrecencyLookup {
+            scores: &self.0,
+            resolved_dirs: HashMap::new(),
+        }
+    }
+}
+
+/// Looks up the score of files, resolving their paths like visits are recorded
+///
+/// The directory a file is in is resolved once for all of its entries, only
+/// symlinks are resolved one by one.
+pub struct FrecencyLookup<'a> {
+    scores: &'a HashMap<PathBuf, f64>,
+    resolved_dirs: HashMap<PathBuf, PathBuf>,
+}
+
+impl FrecencyLookup<'_> {
+    /// Frecency of `file`, `0` for files and directories that weren't visited
+    pub fn score(&mut self, file: &FileItem) -> f64 {
+        if !file.is_dir || self.scores.is_empty() {
+            return 0.0;
+        }
+        let path = match (file.kind, file.path.parent(), file.path.file_name()) {
+            (FileKind::Symlink, ..) | (_, None, _) | (_, _, None) => normalize_path(&file.path),
+            (_, Some(dir), Some(name)) => self
+                .resolved_dirs
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| normalize_path(dir))
+                .join(name),
+        };
+        self.scores.get(&path).copied().unwrap_or_default()
+    }
+}
+
+/// A background load of a store, `None` until it is done
+type BackgroundLoad = Option<Result<Vec<HistoryEntry>>>;
+
+/// Stores being read on a background thread by name, with their entries once read
+static BACKGROUND_LOADS: Lazy<Mutex<HashMap<&'static str, BackgroundLoad>>> =
+    Lazy::new(Default::default);
+
+/// Stores that were already pruned in this process
+static PRUNED_STORES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Find the paths that no longer exist, giving up on paths that take too long
+///
+/// The checks run on a helper thread. When one hangs past [`PRUNE_TIMEOUT`]
+/// the helper is abandoned and a new one takes over the remaining paths.
+fn find_missing_paths(paths: Vec<PathBuf>) -> HashSet<PathBuf> {
+    let mut missing = HashSet::new();
+    let mut checker = None;
+    for path in paths {
+        let (requests, results) = checker.get_or_insert_with(|| {
+            let (request_sender, request_receiver) = mpsc::channel::<PathBuf>();
+            let (result_sender, result_receiver) = mpsc::channel();
+            thread::spawn(move || {
+                for path in request_receiver {
+                    // Errors like permission denied don't prove the path is gone
+                    let exists = !matches!(path.try_exists(), Ok(false));
+                    if result_sender.send(exists).is_err() {
+                        break;
+                    }
+                }
+            });
+            (request_sender, result_receiver)
+        });
+
+        if requests.send(path.clone()).is_err() {
+            checker = None;
+            continue;
+        }
+        match results.recv_timeout(PRUNE_TIMEOUT) {
+            Ok(true) => {}
+            Ok(false) => {
+                missing.insert(path);
+            }
+            Err(_) => {
+                warn!(path = %path.display(), "Timed out checking history entry");
+                checker = None;
+            }
+        }
+    }
+    missing
+}
+
+/// A single visit recorded in the history journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Visit {
+    path: PathBuf,
+    at: DateTime<Utc>,
+}
+
+/// Decode the visits in a journal, along with the length of the intact part
+///
+/// A crash in the middle of an append leaves a partial record at the end,
+/// which is dropped along with anything after it.
+fn decode_journal(data: &[u8]) -> (Vec<Visit>, usize) {
+    let mut visits = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        match bincode::serde::decode_from_slice::<Visit, _>(rest, config::standard()) {
+            Ok((visit, read)) => {
+                visits.push(visit);
+                rest = &rest[read..];
+            }
+            Err(e) => {
+                warn!("Ignoring damaged end of history journal: {e}");
+                break;
+            }
+        }
+    }
+    (visits, data.len() - rest.len())
+}
+
+/// Append `record` to the journal at `path` and return the new journal size
+///
+/// A damaged end is cut off first, the records appended after it couldn't be
+/// read back otherwise.
+fn append_to_journal(path: &Path, record: &[u8]) -> Result<u64> {
+    let mut journal = fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut data = Vec::new();
+    journal.read_to_end(&mut data)?;
+    let (_, intact) = decode_journal(&data);
+    if intact < data.len() {
+        warn!(
+            "Cutting {} damaged bytes off the history journal",
+            data.len() - intact
+        );
+        journal.set_len(intact as u64)?;
+    }
+    // A single write keeps records from interleaving
+    journal.write_all(record)?;
+    Ok(journal.metadata()?.len())
+}
+
+/// The form a path is recorded in, so one directory has a single entry
+///
+/// Symlinks, `.`, `..` and trailing separators are resolved, and on Windows the
+/// spelling on disk is used so case differences don't matter. Paths that can't be
+/// resolved (e.g. already deleted) are only cleaned up lexically.
+fn normalize_path(path: &Path) -> PathBuf {
+    match fs::canonicalize(path) {
+        Ok(canonical) => strip_verbatim_prefix(canonical),
+        Err(_) => {
+            let mut normalized = PathBuf::new();
+            for component in path.components() {
+                match component {
+                    Component::CurDir => {}
+                    Component::ParentDir => {
+                        normalized.pop();
+                    }
+                    component => normalized.push(component),
+                }
+            }
+            normalized
+        }
+    }
+}
+
+/// Apply a visit to the entries, moving the visited path to the top
+fn apply_visit(entries: &mut Vec<HistoryEntry>, visit: Visit) {
+    let mut entry = match entries.iter().position(|entry| entry.path == visit.path) {
+        Some(index) => {
+            let mut entry = entries.remove(index);
+            entry.frequency += 1;
+            entry
+        }
+        None => {
+            let mut entry = HistoryEntry::new(visit.path);
+            entry.first_accessed = visit.at;
+            entry
+        }
+    };
+    entry.last_accessed = visit.at;
+    entries.insert(0, entry);
+}
+
+/// Work for the background history writer
+enum WriterRequest {
+    Visit(HistoryStore, Visit),
+    /// Answered once every request queued before it is done
+    Flush(mpsc::Sender<()>),
+}
+
+/// Queue of the background history writer, started on first use
+///
+/// `None` when the thread couldn't be started, visits are then written right away.
+static WRITER: Lazy<Option<mpsc::Sender<WriterRequest>>> = Lazy::new(|| {
+    let (sender, receiver) = mpsc::channel();
+    match thread::Builder::new()
+        .name("history-writer".to_string())
+        .spawn(move || run_writer(receiver))
+    {
+        Ok(_) => Some(sender),
+        Err(e) => {
+            error!("Failed to start history writer: {e}");
+            None
+        }
+    }
+});
+
+fn run_writer(receiver: mpsc::Receiver<WriterRequest>) {
+    for request in receiver {
+        match request {
+            WriterRequest::Visit(store, visit) => store.write_visit(visit),
+            WriterRequest::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Frecency score of every directory in the history, once they were read
+///
+/// Search results in normal mode are boosted by them. They are read on a
+/// background thread, again after the history changed, and until then the
+/// last scores read are handed out.
+pub fn frecency_scores() -> Option<FrecencyScores> {
+    let generation = HISTORY_GENERATION.load(Ordering::Relaxed);
+    let mut frecency = FRECENCY.lock().unwrap();
+    let current = matches!(&frecency.scores, Some((read_at, _)) if *read_at == generation);
+    if !current && !frecency.loading {
+        frecency.loading = true;
+        thread::spawn(move || {
+            let scores = FrecencyScores::read();
+            let mut frecency = FRECENCY.lock().unwrap();
+            frecency.scores = Some((generation, scores));
+            frecency.loading = false;
+        });
+    }
+    frecency.scores.as_ref().map(|(_, scores)| scores.clone())
+}
+
+/// Read the frecency scores on a background thread, so the first search
+/// doesn't wait for them
+pub fn warm_frecency_scores() {
+    frecency_scores();
+}
+
+/// Wait until the visits queued by [`HistoryStore::add_to_history`] are on disk
+///
+/// Call this before exiting, `std::process::exit` doesn't wait for the writer.
+pub fn flush() {
+    let Some(Some(sender)) = Lazy::get(&WRITER) else {
+        return;
+    };
+    let (done, wait) = mpsc::channel();
+    if sender.send(WriterRequest::Flush(done)).is_ok() {
+        let _ = wait.recv();
+    }
+}
+
+/// What [`HistoryStore::health`] found
+#[derive(Debug)]
+pub struct StoreHealth {
+    pub path: PathBuf,
+    /// Size of the history file, `None` until the first visit is compacted into it
+    pub size: Option<u64>,
+    /// Entries in the history file, or why it can't be read
+    pub entries: Result<usize, String>,
+    /// Visits in the journal that aren't folded into the history file yet
+    pub journal_visits: usize,
+}
+
+/// A persisted list of visited paths ranked by frecency
+///
+/// Each store is a compacted `<name>.bin` file plus a `<name>.journal` of the
+/// visits recorded since, guarded by a `<name>.lock` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryStore {
+    name: &'static str,
+}
+
+impl HistoryStore {
+    /// Directories the user changed to
+    pub const DIRECTORIES: Self = Self {
+        name: "quickswitch.history",
+    };
+
+    /// Files the user picked, for reopening them later
+    pub const RECENT_FILES: Self = Self {
+        name: "quickswitch.recent_files",
+    };
+
+    /// Get the path to the history data file
+    fn get_history_file_path(&self) -> PathBuf {
+        let file_name = format!("{}.bin", self.name);
+        if let Ok(data_dir) = get_data_dir() {
+            data_dir.join(file_name)
+        } else {
+            // Fallback to temp directory if data_dir cannot be created
+            std::env::temp_dir().join(file_name)
+        }
+    }
+
+    /// Run `f` while holding an exclusive lock on the history
+    ///
+    /// Every read-modify-write of the history goes through here, so concurrent
+    /// shells wait for each other instead of overwriting each other's visits.
+    /// The lock lives in a separate file because saving replaces the history file.
+    pub fn with_history_lock<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let lock_path = self.get_history_file_path().with_extension("lock");
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+        lock_file
+            .lock()
+            .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+
+        // The lock is released when `lock_file` is dropped
+        f()
+    }
+
+    /// Get the path to the legacy history file (for migration)
+    fn get_legacy_history_file_path(&self) -> PathBuf {
+        if let Ok(data_dir) = get_data_dir() {
+            data_dir.join(self.name)
+        } else {
+            std::env::temp_dir().join(self.name)
+        }
+    }
+
+    /// Get the path to the journal of visits not yet folded into the history file
+    fn get_journal_file_path(&self) -> PathBuf {
+        self.get_history_file_path().with_extension("journal")
+    }
+
+    /// Load history entries: the history file with the journal replayed on top
+    #[instrument(skip(self))]
+    pub fn load_history_entries(&self) -> Result<Vec<HistoryEntry>> {
+        let mut entries = self.load_snapshot()?;
+
+        let journal_path = self.get_journal_file_path();
+        let journal = match fs::read(&journal_path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read {}", journal_path.display()));
+            }
+        };
+        for visit in decode_journal(&journal).0 {
+            apply_visit(&mut entries, visit);
+        }
+
+        entries.truncate(get_history_config().max_entries);
+        Ok(entries)
+    }
+
+    /// Load the compacted history file
+    #[instrument(skip(self))]
+    fn load_snapshot(&self) -> Result<Vec<HistoryEntry>> {
+        let file_path = self.get_history_file_path();
+
+        // If the binary file exists, load from it
+        if file_path.exists() {
+            let data = fs::read(&file_path)?;
+            let config = config::standard();
+            info!(path = %file_path.display(), "Loading history data from file");
+            match bincode::serde::decode_from_slice(&data, config) {
+                Ok((entries, _)) => return Ok(entries),
+                Err(e) => {
+                    // If deserialization fails, try to migrate from legacy format
+                    error!("Error loading history data: {e}");
+                    if let Ok(entries) = self.migrate_from_legacy() {
+                        return Ok(entries);
+                    }
+                    return Ok(Vec::new());
+                }
+            }
+        }
+
+        // If binary file doesn't exist, try to migrate from legacy format
+        if self.get_legacy_history_file_path().exists() {
+            info!("Legacy history file found, migrating to new format");
+            if let Ok(entries) = self.migrate_from_legacy() {
+                return Ok(entries);
+            }
+        }
+
+        // If all else fails, return empty list
+        info!("No history data found, returning empty list");
+        Ok(Vec::new())
+    }
+
+    /// The state of the files of this store, for `quickswitch doctor`
+    ///
+    /// Unlike loading, a history file that can't be decoded is reported
+    /// instead of being replaced by an empty history.
+    pub fn health(&self) -> StoreHealth {
+        let path = self.get_history_file_path();
+        let (size, entries) = match fs::read(&path) {
+            Ok(data) => (
+                Some(data.len() as u64),
+                bincode::serde::decode_from_slice::<Vec<HistoryEntry>, _>(
+                    &data,
+                    config::standard(),
+                )
+                .map(|(entries, _)| entries.len())
+                .map_err(|e| e.to_string()),
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (None, Ok(0)),
+            Err(e) => (None, Err(e.to_string())),
+        };
+        let journal_visits = fs::read(self.get_journal_file_path())
+            .map_or(0, |journal| decode_journal(&journal).0.len());
+        StoreHealth {
+            path,
+            size,
+            entries,
+            journal_visits,
+        }
+    }
+
+    /// Migrate from legacy text-based history format
+    #[instrument(skip(self))]
+    fn migrate_from_legacy(&self) -> Result<Vec<HistoryEntry>> {
+        let legacy_path = self.get_legacy_history_file_path();
+        if let Ok(content) = fs::read_to_string(&legacy_path) {
+            let mut entries = Vec::new();
+
+            for line in content.lines() {
+                let path = PathBuf::from(line.trim());
+                if path.exists() {
+                    entries.push(HistoryEntry::new(path));
+                }
+            }
+
+            // Save in new format
+            self.save_history_entries(&entries)?;
+
+            // Backup the legacy file
+            if legacy_path.exists() {
+                info!("Backing up legacy history file to .bak");
+                let backup_path = legacy_path.with_extension("history.bak");
+                let _ = fs::rename(&legacy_path, backup_path);
+            }
+
+            return Ok(entries);
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Save history entries to file, replacing the journal
+    #[instrument(skip(self, entries))]
+    pub fn save_history_entries(&self, entries: &[HistoryEntry]) -> Result<()> {
+        let config = config::standard();
+        let data = bincode::serde::encode_to_vec(entries, config)?;
+        let file_path = self.get_history_file_path();
+
+        // Ensure directory exists
+        if let Some(parent) = file_path.parent()
+            && !parent.exists()
+        {
+            info!(path = %parent.display(), "Creating directory for history file");
+            fs::create_dir_all(parent)?;
+        }
+        info!(path = %file_path.display(), "Saving history data to file");
+        // Write to a temporary file first so an interrupted save never leaves
+        // a truncated history behind
+        let tmp_path = file_path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &file_path)?;
+
+        self.changed();
+
+        // The saved entries already include every journaled visit
+        match fs::remove_file(self.get_journal_file_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Have the frecency scores read again after the directory history changed
+    fn changed(&self) {
+        if *self == Self::DIRECTORIES {
+            HISTORY_GENERATION.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Add a path to history or update its frequency if it already exists
+    ///
+    /// The visit is written by a background thread so navigation never waits
+    /// on the disk, failures are only logged. See [`flush`].
+    #[instrument(skip(self), fields(path = %path.display()))]
+    pub fn add_to_history(&self, path: PathBuf) {
+        MISSING_PATHS.lock().unwrap().remove(&path);
+        let visit = Visit {
+            path,
+            at: Utc::now(),
+        };
+        match &*WRITER {
+            Some(sender) => {
+                if let Err(mpsc::SendError(WriterRequest::Visit(_, visit))) =
+                    sender.send(WriterRequest::Visit(*self, visit))
+                {
+                    self.write_visit(visit);
+                }
+            }
+            None => self.write_visit(visit),
+        }
+    }
+
+    /// Write a visit to disk, logging failures
+    fn write_visit(&self, mut visit: Visit) {
+        visit.path = normalize_path(&visit.path);
+        if let Err(e) = self.with_history_lock(|| self.append_visit(visit)) {
+            error!("Failed to save history: {e:#}");
+        }
+    }
+
+    /// Record a visit by appending it to the journal
+    ///
+    /// This doesn't touch the history file, so it stays cheap regardless of the
+    /// history size. Once the journal grows past [`JOURNAL_COMPACT_SIZE`] it is
+    /// folded into the history file, which bounds what an append reads to
+    /// check the journal for damage.
+    fn append_visit(&self, visit: Visit) -> Result<()> {
+        info!(path = %visit.path.display(), "Recording visit in history journal");
+        let record = bincode::serde::encode_to_vec(&visit, config::standard())?;
+
+        let journal_path = self.get_journal_file_path();
+        if let Some(parent) = journal_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let journal_size = append_to_journal(&journal_path, &record)?;
+        self.changed();
+        if journal_size >= JOURNAL_COMPACT_SIZE {
+            self.compact_history()?;
+        }
+        Ok(())
+    }
+
+    /// Fold the journal into the history file
+    #[instrument(skip(self))]
+    fn compact_history(&self) -> Result<()> {
+        info!("Compacting history journal");
+        let entries = self.load_history_entries()?;
+        self.save_history_entries(&entries)
+    }
+
+    /// Sort the entries on a background thread, unless that runs already
+    ///
+    /// The entries are picked up with [`HistoryStore::take_loaded`] once
+    /// [`HistoryStore::is_loaded`] says the load finished.
+    pub fn load_in_background(self, sort_mode: HistorySortMode) {
+        let mut loads = BACKGROUND_LOADS.lock().unwrap();
+        if matches!(loads.get(self.name), Some(None)) {
+            return;
+        }
+        loads.insert(self.name, None);
+        thread::spawn(move || {
+            let entries = self.get_sorted_entries(&sort_mode);
+            BACKGROUND_LOADS
+                .lock()
+                .unwrap()
+                .insert(self.name, Some(entries));
+        });
+    }
+
+    /// Whether the entries are being read on a background thread, or were
+    /// read and wait to be picked up
+    pub fn is_loading(self) -> bool {
+        BACKGROUND_LOADS.lock().unwrap().contains_key(self.name)
+    }
+
+    /// Whether a background load finished and its entries wait to be picked up
+    pub fn is_loaded(self) -> bool {
+        matches!(
+            BACKGROUND_LOADS.lock().unwrap().get(self.name),
+            Some(Some(_))
+        )
+    }
+
+    /// The entries a background load read, once it finished
+    pub fn take_loaded(self) -> Option<Result<Vec<HistoryEntry>>> {
+        let mut loads = BACKGROUND_LOADS.lock().unwrap();
+        match loads.get(self.name) {
+            Some(Some(_)) => loads.remove(self.name).flatten(),
+            _ => None,
+        }
+    }
+
+    /// Get sorted history entries based on the configured sort mode
+    #[instrument(skip(self))]
+    pub fn get_sorted_entries(&self, sort_mode: &HistorySortMode) -> Result<Vec<HistoryEntry>> {
+        // Include the visits still queued for the writer
+        flush();
+        let mut entries = self.load_history_entries()?;
+        let config = get_history_config();
+
+        // Sort entries based on the specified mode
+        match sort_mode {
+            HistorySortMode::Frequency => {
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.frequency));
+            }
+            HistorySortMode::Recent => {
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_accessed));
+            }
+            HistorySortMode::FrequencyRecent => {
+                entries.sort_by(|a, b| {
+                    let a_score = a.calculate_score(config);
+                    let b_score = b.calculate_score(config);
+                    b_score
+                        .partial_cmp(&a_score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            HistorySortMode::Alphabetical => {
+                entries.sort_by(|a, b| {
+                    let a_name = a
+                        .path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or_default();
+                    let b_name = b
+                        .path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or_default();
+                    a_name.cmp(b_name)
+                });
+            }
+        }
+
+        // Checking every entry here would hang on unreachable drives, so only
+        // drop what the background prune already found missing
+        self.prune_in_background();
+        let missing = MISSING_PATHS.lock().unwrap();
+        entries.retain(|entry| !missing.contains(&entry.path));
+        drop(missing);
+
+        Ok(entries)
+    }
+
+    /// Remove entries that no longer exist from the history file, once per process
+    ///
+    /// Runs on its own thread and records what it finds in [`MISSING_PATHS`],
+    /// so they are hidden the next time the entries are listed.
+    fn prune_in_background(&self) {
+        {
+            let mut pruned = PRUNED_STORES.lock().unwrap();
+            if pruned.contains(&self.name) {
+                return;
+            }
+            pruned.push(self.name);
+        }
+
+        let store = *self;
+        let spawned = thread::Builder::new()
+            .name("history-prune".to_string())
+            .spawn(move || {
+                if let Err(e) = store.prune() {
+                    error!("Failed to prune history: {e:#}");
+                }
+            });
+        if let Err(e) = spawned {
+            error!("Failed to start history prune: {e}");
+        }
+    }
+
+    #[instrument(skip(self))]
+    fn prune(&self) -> Result<()> {
+        let paths = self
+            .load_history_entries()?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        let missing = find_missing_paths(paths);
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        info!(
+            count = missing.len(),
+            "Pruning non-existent history entries"
+        );
+        MISSING_PATHS
+            .lock()
+            .unwrap()
+            .extend(missing.iter().cloned());
+        self.remove_where(|entry| missing.contains(&entry.path))?;
+        Ok(())
+    }
+
+    /// Delete the entries for `paths`, returning how many were removed
+    ///
+    /// They can be brought back with [`undo_removal`] until the next removal.
+    #[instrument(skip(self))]
+    pub fn remove_entries(&self, paths: &[PathBuf]) -> Result<usize> {
+        // Visits still queued could otherwise bring a deleted entry back
+        flush();
+        self.remove_where(|entry| paths.contains(&entry.path))
+    }
+
+    /// Remove the matching entries and remember them for [`undo_removal`]
+    fn remove_where(&self, matches: impl Fn(&HistoryEntry) -> bool) -> Result<usize> {
+        let removed = self.with_history_lock(|| {
+            let (removed, kept) = self
+                .load_history_entries()?
+                .into_iter()
+                .partition::<Vec<_>, _>(|entry| matches(entry));
+            if !removed.is_empty() {
+                self.save_history_entries(&kept)?;
+            }
+            Ok(removed)
+        })?;
+
+        let count = removed.len();
+        if count > 0 {
+            *LAST_REMOVAL.lock().unwrap() = Some((*self, removed));
+        }
+        Ok(count)
+    }
+
+    /// Clean up old or low-frequency entries
+    pub fn cleanup_old_entries(&self) -> Result<()> {
+        self.with_history_lock(|| {
+            let mut entries = self.load_history_entries()?;
+            let config = get_history_config();
+
+            // Remove entries with frequency below threshold
+            entries.retain(|entry| entry.frequency >= config.min_frequency_threshold);
+
+            // Save cleaned up entries
+            self.save_history_entries(&entries)
+        })
+    }
+}
+
+/// Put back the entries taken out by the last delete or prune
+///
+/// Returns how many entries were restored, `0` when there is nothing to undo.
+/// Entries visited again in the meantime keep their newer state.
+pub fn undo_removal() -> Result<usize> {
+    let Some((store, removed)) = LAST_REMOVAL.lock().unwrap().take() else {
+        return Ok(0);
+    };
+
+    flush();
+    let count = removed.len();
+    store.with_history_lock(|| {
+        let mut entries = store.load_history_entries()?;
+        let mut missing = MISSING_PATHS.lock().unwrap();
+        for entry in removed.into_iter().rev() {
+            missing.remove(&entry.path);
+            if !entries.iter().any(|existing| existing.path == entry.path) {
+                entries.insert(0, entry);
+            }
+        }
+        drop(missing);
+        store.save_history_entries(&entries)
+    })?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_journal_drops_partial_record() {
+        let visits = vec![
+            Visit {
+                path: PathBuf::from("/tmp/a"),
+                at: Utc::now(),
+            },
+            Visit {
+                path: PathBuf::from("/tmp/b"),
+                at: Utc::now(),
+            },
+        ];
+        let mut journal = Vec::new();
+        for visit in &visits {
+            journal.extend(bincode::serde::encode_to_vec(visit, config::standard()).unwrap());
+        }
+        assert_eq!(decode_journal(&journal), (visits.clone(), journal.len()));
+
+        let first_len = bincode::serde::encode_to_vec(&visits[0], config::standard())
+            .unwrap()
+            .len();
+        journal.truncate(journal.len() - 3);
+        assert_eq!(decode_journal(&journal), (visits[..1].to_vec(), first_len));
+    }
+
+    #[test]
+    fn test_append_after_damaged_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.journal");
+        let record = |path: &str| {
+            let visit = Visit {
+                path: PathBuf::from(path),
+                at: Utc::now(),
+            };
+            (
+                bincode::serde::encode_to_vec(&visit, config::standard()).unwrap(),
+                visit,
+            )
+        };
+        let (first, first_visit) = record("/tmp/a");
+        let (second, second_visit) = record("/tmp/b");
+
+        append_to_journal(&path, &first).unwrap();
+        // A crash in the middle of the next append
+        append_to_journal(&path, &second[..second.len() - 3]).unwrap();
+        append_to_journal(&path, &second).unwrap();
+
+        let (visits, intact) = decode_journal(&fs::read(&path).unwrap());
+        assert_eq!(visits, [first_visit, second_visit]);
+        assert_eq!(intact, first.len() + second.len());
+    }
+
+    #[test]
+    fn test_loaded_entries_belong_to_their_store() {
+        let store = HistoryStore::RECENT_FILES;
+        store.load_in_background(HistorySortMode::Recent);
+        let start = std::time::Instant::now();
+        while !store.is_loaded() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!HistoryStore::DIRECTORIES.is_loaded());
+        assert!(store.take_loaded().is_some());
+        assert!(!store.is_loaded() && !store.is_loading());
+    }
+
+    #[test]
+    fn test_find_missing_paths() {
+        let existing = std::env::temp_dir();
+        let missing = PathBuf::from("/quickswitch-missing/dir");
+        assert_eq!(
+            find_missing_paths(vec![existing, missing.clone()]),
+            HashSet::from([missing])
+        );
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        let missing = Path::new("/quickswitch-missing/a/./b/../c/");
+        assert_eq!(
+            normalize_path(missing),
+            PathBuf::from("/quickswitch-missing/a/c")
+        );
+
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            normalize_path(&dir.join(".")),
+            strip_verbatim_prefix(fs::canonicalize(&dir).unwrap())
+        );
+    }
+}
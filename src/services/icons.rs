@@ -0,0 +1,114 @@
+use crate::{
+    config::{IconStyle, get_config},
+    utils::{FileItem, FileKind},
+};
+
+/// Get the icon for a file item according to the configured icon style
+pub fn icon_for(file: &FileItem) -> &'static str {
+    match get_config().ui.icons {
+        IconStyle::Nerd => nerd_icon(file),
+        IconStyle::Emoji => emoji_icon(file),
+        IconStyle::Ascii => ascii_icon(file),
+    }
+}
+
+/// Get the icon for a directory known only by its name (e.g. a history entry)
+pub fn directory_icon(name: &str) -> &'static str {
+    match get_config().ui.icons {
+        IconStyle::Nerd => nerd_directory_icon(name),
+        IconStyle::Emoji => "📁",
+        IconStyle::Ascii => "d",
+    }
+}
+
+/// Get the icon for a drive or mount point
+pub fn drive_icon() -> &'static str {
+    match get_config().ui.icons {
+        IconStyle::Nerd => "\u{f0a0}",
+        IconStyle::Emoji => "💾",
+        IconStyle::Ascii => "#",
+    }
+}
+
+fn emoji_icon(file: &FileItem) -> &'static str {
+    if file.is_dir {
+        "📁"
+    } else if matches!(file.kind, FileKind::Symlink | FileKind::BrokenSymlink) {
+        "🔗"
+    } else if file.is_image() {
+        "🖼️"
+    } else {
+        "📄"
+    }
+}
+
+fn ascii_icon(file: &FileItem) -> &'static str {
+    match file.kind {
+        FileKind::Symlink | FileKind::BrokenSymlink => "l",
+        _ if file.is_dir => "d",
+        FileKind::Executable => "x",
+        _ => "-",
+    }
+}
+
+fn nerd_icon(file: &FileItem) -> &'static str {
+    match file.kind {
+        FileKind::Symlink if file.is_dir => return "\u{f482}",
+        FileKind::Symlink | FileKind::BrokenSymlink => return "\u{f481}",
+        _ => {}
+    }
+    if file.is_dir {
+        return nerd_directory_icon(&file.name);
+    }
+
+    match file.name.to_lowercase().as_str() {
+        "cargo.lock" | "package-lock.json" | "yarn.lock" | "pnpm-lock.yaml" => return "\u{f023}",
+        "dockerfile" => return "\u{f308}",
+        "makefile" => return "\u{e779}",
+        "license" | "license.md" | "license.txt" => return "\u{f0219}",
+        ".gitignore" | ".gitattributes" | ".gitmodules" => return "\u{e702}",
+        _ => {}
+    }
+
+    let extension = file
+        .path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    match extension.as_deref() {
+        Some("rs") => "\u{e7a8}",
+        Some("py") => "\u{e606}",
+        Some("js" | "mjs" | "cjs") => "\u{e74e}",
+        Some("ts" | "tsx") => "\u{e628}",
+        Some("go") => "\u{e627}",
+        Some("c") => "\u{e61e}",
+        Some("cpp" | "cc" | "cxx" | "hpp") => "\u{e61d}",
+        Some("h") => "\u{f0fd}",
+        Some("java") => "\u{e738}",
+        Some("html" | "htm") => "\u{e736}",
+        Some("css" | "scss") => "\u{e749}",
+        Some("md" | "markdown") => "\u{e609}",
+        Some("json") => "\u{e60b}",
+        Some("toml" | "yaml" | "yml" | "ini" | "conf") => "\u{e615}",
+        Some("sh" | "bash" | "zsh" | "fish" | "ps1") => "\u{f489}",
+        Some("txt" | "log") => "\u{f15c}",
+        Some("pdf") => "\u{f1c1}",
+        Some("zip" | "tar" | "gz" | "tgz" | "xz" | "bz2" | "zst" | "7z" | "rar") => "\u{f410}",
+        Some("mp3" | "flac" | "ogg" | "wav") => "\u{f1c7}",
+        Some("mp4" | "mkv" | "mov" | "avi" | "webm") => "\u{f1c8}",
+        _ if file.is_image() => "\u{f1c5}",
+        _ if file.kind == FileKind::Executable => "\u{f489}",
+        _ => "\u{f15b}",
+    }
+}
+
+fn nerd_directory_icon(name: &str) -> &'static str {
+    match name {
+        ".git" => "\u{e5fb}",
+        ".github" => "\u{e5fd}",
+        ".config" => "\u{e5fc}",
+        "node_modules" => "\u{e5fa}",
+        _ => "\u{f07b}",
+    }
+}
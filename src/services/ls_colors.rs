@@ -0,0 +1,257 @@
+use once_cell::sync::Lazy;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::utils::{FileItem, FileKind};
+
+/// File coloring rules in the format of the `LS_COLORS` environment variable
+#[derive(Debug, Clone, Default)]
+pub struct LsColors {
+    directory: Option<Style>,
+    symlink: Option<Style>,
+    broken_symlink: Option<Style>,
+    executable: Option<Style>,
+    special: Option<Style>,
+    file: Option<Style>,
+    /// Lowercased filename suffixes (`.tar.gz`) with their styles
+    extensions: Vec<(String, Style)>,
+}
+
+/// Built-in rules used when `LS_COLORS` is not set, loosely following the `dircolors` defaults
+const DEFAULT_LS_COLORS: &str = "di=36:ln=35:or=31:ex=32:pi=33:so=35:bd=33:cd=33:\
+*.tar=31:*.tgz=31:*.gz=31:*.xz=31:*.bz2=31:*.zst=31:*.zip=31:*.7z=31:*.rar=31:*.deb=31:*.rpm=31:\
+*.jpg=35:*.jpeg=35:*.png=35:*.gif=35:*.bmp=35:*.webp=35:*.svg=35:*.ico=35:*.tif=35:*.tiff=35:\
+*.mp3=36:*.flac=36:*.ogg=36:*.wav=36:*.mp4=35:*.mkv=35:*.mov=35:*.avi=35:*.webm=35";
+
+impl LsColors {
+    /// Read rules from `$LS_COLORS`, falling back to the built-in rules
+    pub fn from_env() -> Self {
+        match std::env::var("LS_COLORS") {
+            Ok(value) if !value.trim().is_empty() => Self::parse(&value),
+            _ => Self::parse(DEFAULT_LS_COLORS),
+        }
+    }
+
+    /// Parse a `LS_COLORS` string such as `di=01;34:ln=01;36:*.tar=01;31`
+    pub fn parse(value: &str) -> Self {
+        let mut colors = Self::default();
+
+        for entry in value.split(':') {
+            let Some((key, codes)) = entry.split_once('=') else {
+                continue;
+            };
+            let style = apply_sgr(Style::default(), codes);
+
+            if let Some(pattern) = key.strip_prefix('*') {
+                colors.extensions.push((pattern.to_lowercase(), style));
+                continue;
+            }
+
+            match key {
+                "di" => colors.directory = Some(style),
+                "ln" => colors.symlink = Some(style),
+                "or" => colors.broken_symlink = Some(style),
+                "ex" => colors.executable = Some(style),
+                "pi" | "so" | "bd" | "cd" | "do" => {
+                    colors.special.get_or_insert(style);
+                }
+                "fi" => colors.file = Some(style),
+                _ => {}
+            }
+        }
+
+        colors
+    }
+
+    /// Get the style for a file item
+    pub fn style_for(&self, file: &FileItem) -> Style {
+        let kind_style = match file.kind {
+            FileKind::Directory => self.directory,
+            FileKind::Symlink => self.symlink,
+            FileKind::BrokenSymlink => self.broken_symlink.or(self.symlink),
+            FileKind::Executable => self.executable,
+            FileKind::Special => self.special,
+            FileKind::Regular => None,
+        };
+
+        kind_style
+            .or_else(|| self.extension_style(&file.name))
+            .or(self.file)
+            .unwrap_or_default()
+    }
+
+    /// Find the style of the longest matching filename suffix
+    fn extension_style(&self, name: &str) -> Option<Style> {
+        let name = name.to_lowercase();
+        self.extensions
+            .iter()
+            .filter(|(suffix, _)| name.ends_with(suffix.as_str()))
+            .max_by_key(|(suffix, _)| suffix.len())
+            .map(|(_, style)| *style)
+    }
+}
+
+/// Global coloring rules, read from the environment on first use
+pub static LS_COLORS: Lazy<LsColors> = Lazy::new(LsColors::from_env);
+
+/// Apply `;`-separated SGR parameters (as used in ANSI escape sequences) to a style
+pub fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params
+        .split(';')
+        .map(|code| code.trim().parse().unwrap_or(0))
+        .collect();
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 | 6 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            code @ 30..=37 => style = style.fg(ansi_color(code - 30)),
+            code @ 40..=47 => style = style.bg(ansi_color(code - 40)),
+            code @ 90..=97 => style = style.fg(ansi_color(code - 90 + 8)),
+            code @ 100..=107 => style = style.bg(ansi_color(code - 100 + 8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let (color, consumed) = parse_extended_color(&codes[i + 1..]);
+                if let Some(color) = color {
+                    style = if code == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+                i += consumed;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    style
+}
+
+/// Map one of the 16 basic ANSI color numbers to a ratatui color
+fn ansi_color(index: u16) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// Parse the arguments of a `38`/`48` extended color code, returning the color
+/// and the number of parameters consumed
+fn parse_extended_color(args: &[u16]) -> (Option<Color>, usize) {
+    match args {
+        [5, index, ..] => (Some(Color::Indexed(*index as u8)), 2),
+        [2, r, g, b, ..] => (Some(Color::Rgb(*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, args.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn item(name: &str, kind: FileKind) -> FileItem {
+        FileItem {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            is_dir: kind == FileKind::Directory,
+            kind,
+            hidden: false,
+        }
+    }
+
+    #[test]
+    fn test_compound_codes() {
+        let colors = LsColors::parse("di=01;34:ln=38;5;208:ex=1;38;2;10;20;30;4");
+        assert_eq!(
+            colors.style_for(&item("src", FileKind::Directory)),
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            colors.style_for(&item("link", FileKind::Symlink)),
+            Style::default().fg(Color::Indexed(208))
+        );
+        assert_eq!(
+            colors.style_for(&item("run", FileKind::Executable)),
+            Style::default()
+                .fg(Color::Rgb(10, 20, 30))
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        );
+    }
+
+    #[test]
+    fn test_extension_globs() {
+        let colors = LsColors::parse("fi=37:*.gz=31:*.tar.gz=33:*README=32");
+        let style = |name| colors.style_for(&item(name, FileKind::Regular)).fg;
+        // The longest suffix wins, regardless of case
+        assert_eq!(style("backup.tar.gz"), Some(Color::Yellow));
+        assert_eq!(style("LOG.GZ"), Some(Color::Red));
+        assert_eq!(style("README"), Some(Color::Green));
+        assert_eq!(style("notes.txt"), Some(Color::Gray));
+    }
+
+    #[test]
+    fn test_kind_takes_precedence_over_extension() {
+        let colors = LsColors::parse("di=34:ln=36:ex=32:*.png=35:*.sh=33");
+        let style = |name, kind| colors.style_for(&item(name, kind)).fg;
+        assert_eq!(style("icons.png", FileKind::Directory), Some(Color::Blue));
+        assert_eq!(style("photo.png", FileKind::Symlink), Some(Color::Cyan));
+        assert_eq!(style("build.sh", FileKind::Executable), Some(Color::Green));
+        assert_eq!(style("build.sh", FileKind::Regular), Some(Color::Yellow));
+        // Without `or`, broken symlinks look like the others
+        assert_eq!(style("gone", FileKind::BrokenSymlink), Some(Color::Cyan));
+    }
+
+    #[test]
+    fn test_malformed_entries_are_skipped() {
+        // Unknown keys and entries without `=` are ignored, unreadable codes reset
+        let colors = LsColors::parse("::di:xx=31:di=zz;34:ex=:*.md=32=1:*.rs=33");
+        assert_eq!(
+            colors.style_for(&item("src", FileKind::Directory)).fg,
+            Some(Color::Blue)
+        );
+        assert_eq!(
+            colors.style_for(&item("run", FileKind::Executable)),
+            Style::default()
+        );
+        assert_eq!(
+            colors.style_for(&item("notes.md", FileKind::Regular)),
+            Style::default()
+        );
+        assert_eq!(
+            colors.style_for(&item("main.rs", FileKind::Regular)).fg,
+            Some(Color::Yellow)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+use tracing::{info, instrument};
+
+use crate::config::{get_config, get_data_dir};
+
+/// Key sequences recorded with `q<letter>` and replayed with `@<letter>`
+///
+/// Macros come from the `[macros]` section of the config file, and recorded
+/// ones are kept in `quickswitch.macros.toml` in the data directory, which
+/// takes precedence. Both use vim's key notation, like `/src<Esc>jl`.
+#[derive(Debug, Default)]
+pub struct Macros {
+    by_name: BTreeMap<String, String>,
+}
+
+impl Macros {
+    fn file_path() -> Result<PathBuf> {
+        Ok(get_data_dir()?.join("quickswitch.macros.toml"))
+    }
+
+    /// Read the recorded macros, empty if none were recorded yet
+    #[instrument]
+    fn load_recorded() -> Result<BTreeMap<String, String>> {
+        let path = Self::file_path()?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Load the configured macros along with the recorded ones
+    pub fn load() -> Result<Self> {
+        let mut by_name = get_config().macros.clone();
+        by_name.extend(Self::load_recorded()?);
+        Ok(Self { by_name })
+    }
+
+    /// Keep `keys` as the macro `name`, replacing an earlier recording
+    #[instrument(skip(keys))]
+    pub fn save_recorded(name: char, keys: &[KeyEvent]) -> Result<()> {
+        let mut recorded = Self::load_recorded()?;
+        recorded.insert(name.to_string(), format_keys(keys));
+
+        let path = Self::file_path()?;
+        info!(path = %path.display(), "Saving macros");
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, toml::to_string(&recorded)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// The keys of the macro `name`, if there is one
+    pub fn keys(&self, name: char) -> Result<Option<Vec<KeyEvent>>> {
+        self.by_name
+            .get(&name.to_string())
+            .map(|notation| {
+                parse_keys(notation).with_context(|| format!("Invalid macro @{name}: {notation}"))
+            })
+            .transpose()
+    }
+}
+
+/// Special keys written as `<Name>`, and the name used for them
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "S-Tab"),
+    (KeyCode::Backspace, "BS"),
+    (KeyCode::Delete, "Del"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Char(' '), "Space"),
+    (KeyCode::Char('<'), "lt"),
+];
+
+/// Write keys in vim's notation, e.g. `gg/src<Enter><C-d>`
+pub fn format_keys(keys: &[KeyEvent]) -> String {
+    let mut notation = String::new();
+    for key in keys {
+        let mut prefix = String::new();
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            prefix.push_str("C-");
+        }
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            prefix.push_str("A-");
+        }
+        // Shift is part of the character for printable keys, and of `S-Tab`
+        if key.modifiers.contains(KeyModifiers::SHIFT)
+            && !matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab)
+        {
+            prefix.push_str("S-");
+        }
+        let name = KEY_NAMES
+            .iter()
+            .find(|(code, _)| *code == key.code)
+            .map(|(_, name)| *name);
+        match (name, key.code) {
+            (Some(name), _) => notation.push_str(&format!("<{prefix}{name}>")),
+            (None, KeyCode::Char(c)) if prefix.is_empty() => notation.push(c),
+            (None, KeyCode::Char(c)) => notation.push_str(&format!("<{prefix}{c}>")),
+            // Keys without a name, like function keys, can't be replayed
+            (None, _) => {}
+        }
+    }
+    notation
+}
+
+/// Read keys written in vim's notation, the reverse of [`format_keys`]
+pub fn parse_keys(notation: &str) -> Result<Vec<KeyEvent>> {
+    let mut keys = Vec::new();
+    let mut rest = notation;
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            keys.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            bail!("Unclosed < in {notation}");
+        };
+        keys.push(parse_key_name(&rest[1..end])?);
+        rest = &rest[end + 1..];
+    }
+    Ok(keys)
+}
+
+/// Parse the inside of a `<...>` key like `C-d` or `Enter`
+fn parse_key_name(name: &str) -> Result<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    loop {
+        let modifier = match rest.get(..2) {
+            Some("C-") => KeyModifiers::CONTROL,
+            Some("A-") => KeyModifiers::ALT,
+            Some("S-") if rest != "S-Tab" => KeyModifiers::SHIFT,
+            _ => break,
+        };
+        modifiers |= modifier;
+        rest = &rest[2..];
+    }
+
+    if let Some((code, _)) = KEY_NAMES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(rest))
+    {
+        return Ok(KeyEvent::new(*code, modifiers));
+    }
+    let mut chars = rest.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(KeyEvent::new(KeyCode::Char(c), modifiers)),
+        _ => bail!("Unknown key <{name}>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_notation_round_trip() {
+        let keys = vec![
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE),
+        ];
+        let notation = format_keys(&keys);
+        assert_eq!(notation, "g/<lt><Space><Enter><C-d><S-Up><S-Tab>");
+        assert_eq!(parse_keys(&notation).unwrap(), keys);
+        // Terminals report Shift+Tab as BackTab with Shift held
+        let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(format_keys(&[back_tab]), "<S-Tab>");
+
+        assert_eq!(
+            parse_keys("<esc>G").unwrap(),
+            vec![
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE),
+            ]
+        );
+        assert!(parse_keys("<Enter").is_err());
+        assert!(parse_keys("<Nope>").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::{info, instrument};
+
+use crate::config::get_data_dir;
+
+/// Directories marked with `m<letter>` and jumped back to with `'<letter>`
+///
+/// Like vim's file marks, lowercase marks only last for the session while
+/// uppercase ones are kept in `quickswitch.marks.toml` in the data directory.
+#[derive(Debug, Default)]
+pub struct Marks {
+    session: BTreeMap<char, PathBuf>,
+}
+
+impl Marks {
+    fn file_path() -> Result<PathBuf> {
+        Ok(get_data_dir()?.join("quickswitch.marks.toml"))
+    }
+
+    /// Read the uppercase marks, empty if none were set yet
+    #[instrument]
+    fn load_global() -> Result<BTreeMap<String, PathBuf>> {
+        let path = Self::file_path()?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    #[instrument(skip(marks))]
+    fn save_global(marks: &BTreeMap<String, PathBuf>) -> Result<()> {
+        let path = Self::file_path()?;
+        info!(path = %path.display(), "Saving marks");
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, toml::to_string(marks)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Mark `path` with `letter`, saving it to disk if the letter is uppercase
+    pub fn set(&mut self, letter: char, path: &Path) -> Result<()> {
+        if !letter.is_ascii_alphabetic() {
+            bail!("Marks are letters, a-z for this session or A-Z to keep them");
+        }
+        if letter.is_ascii_uppercase() {
+            let mut global = Self::load_global()?;
+            global.insert(letter.to_string(), path.to_path_buf());
+            Self::save_global(&global)
+        } else {
+            self.session.insert(letter, path.to_path_buf());
+            Ok(())
+        }
+    }
+
+    /// The directory marked with `letter`, if any
+    pub fn get(&self, letter: char) -> Result<Option<PathBuf>> {
+        if letter.is_ascii_uppercase() {
+            Ok(Self::load_global()?.remove(&letter.to_string()))
+        } else {
+            Ok(self.session.get(&letter).cloned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_marks() {
+        let mut marks = Marks::default();
+        let project = Path::new("/home/me/work/quickswitch");
+
+        marks.set('a', project).unwrap();
+        assert_eq!(marks.get('a').unwrap().as_deref(), Some(project));
+        assert_eq!(marks.get('b').unwrap(), None);
+        assert!(marks.set('1', project).is_err());
+    }
+}
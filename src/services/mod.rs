@@ -1,12 +1,29 @@
+pub mod aliases;
 pub mod data_provider;
+pub mod dir_index;
 pub mod filesystem;
 pub mod global_preview_state;
+pub mod history_store;
+pub mod icons;
+pub mod ls_colors;
+pub mod macros;
+pub mod marks;
+pub mod mounts;
+pub mod plugins;
 pub mod preview;
 pub mod preview_manager;
+pub mod projects;
+pub mod repos;
 
 // Re-export commonly used types
+pub use aliases::Aliases;
 pub use data_provider::{DataProvider, create_data_provider};
+pub use dir_index::DirIndex;
 pub use filesystem::FilesystemService;
 pub use global_preview_state::GlobalPreviewState;
+pub use history_store::HistoryStore;
+pub use ls_colors::LS_COLORS;
+pub use macros::Macros;
+pub use marks::Marks;
 pub use preview::PreviewGenerator;
 pub use preview_manager::PreviewManager;
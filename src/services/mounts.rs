@@ -0,0 +1,466 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    thread,
+};
+use tracing::{debug, instrument};
+
+/// What is known about the mounts listed in Mounts mode, by mount point
+static MOUNT_INFO: Lazy<RwLock<HashMap<PathBuf, Mount>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Filesystems on the network, listed even though their device isn't a path
+const NETWORK_FS_TYPES: &[&str] = &["nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs", "9p"];
+
+/// A mounted filesystem, or a drive on Windows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mount {
+    /// The device or share it is mounted from, like `/dev/sda1`
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    /// The volume label, only known on Windows
+    pub label: Option<String>,
+    /// Whether it is on removable media like a USB stick
+    pub removable: bool,
+    /// Total and available bytes, unknown until they were looked up
+    pub space: Option<Space>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Space {
+    pub total: u64,
+    pub free: u64,
+}
+
+impl Space {
+    /// A bar of `width` cells filled in by the used share, like `[███░░░░░]`
+    pub fn bar(&self, width: usize) -> String {
+        let used = self.total.saturating_sub(self.free);
+        let filled = match self.total {
+            0 => 0,
+            total => ((used as f64 / total as f64) * width as f64).round() as usize,
+        };
+        format!(
+            "[{}{}]",
+            "█".repeat(filled),
+            "░".repeat(width - filled.min(width))
+        )
+    }
+}
+
+/// The mounted filesystems, skipping pseudo filesystems like `proc`
+#[instrument]
+pub fn list_mounts() -> Result<Vec<Mount>> {
+    let mounts = platform::list_mounts()?;
+    debug!(count = mounts.len(), "Found mounts");
+    Ok(mounts)
+}
+
+/// List the mounts and look up their free space and labels on a background
+/// thread
+///
+/// A network mount that stopped responding can block for a long time.
+pub fn load_mounts() -> Result<Vec<Mount>> {
+    let mounts = list_mounts()?;
+    if let Ok(mut known) = MOUNT_INFO.write() {
+        *known = mounts
+            .iter()
+            .map(|mount| (mount.mount_point.clone(), mount.clone()))
+            .collect();
+    }
+
+    let mut pending = mounts.clone();
+    thread::spawn(move || {
+        for mount in &mut pending {
+            platform::load_details(mount);
+            if let Ok(mut known) = MOUNT_INFO.write()
+                && let Some(known_mount) = known.get_mut(&mount.mount_point)
+            {
+                *known_mount = mount.clone();
+            }
+        }
+    });
+    Ok(mounts)
+}
+
+/// The entries of the drives view shown when going up from the top of the filesystem
+///
+/// These are the drives on Windows, and `/` followed by the external volumes
+/// or removable media elsewhere.
+pub fn drive_roots() -> Result<Vec<PathBuf>> {
+    platform::drive_roots()
+}
+
+/// The root of the mapped network drive `path` is on, like `Z:\`
+pub fn network_drive_root(path: &Path) -> Option<PathBuf> {
+    platform::network_drive_root(path)
+}
+
+/// The known info of the mount at `mount_point`
+pub fn info(mount_point: &Path) -> Option<Mount> {
+    MOUNT_INFO.read().ok()?.get(mount_point).cloned()
+}
+
+/// Parse the lines of `/proc/self/mounts` or `/etc/mtab`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_mounts(content: &str) -> Vec<Mount> {
+    let mut mounts: Vec<Mount> = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(device), Some(mount_point), Some(fs_type)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let is_device = device.starts_with('/') && fs_type != "squashfs";
+        if !is_device && !NETWORK_FS_TYPES.contains(&fs_type) {
+            continue;
+        }
+        let mount_point = PathBuf::from(unescape_mount_field(mount_point));
+        // A later mount over the same point hides the earlier one
+        mounts.retain(|mount| mount.mount_point != mount_point);
+        mounts.push(Mount {
+            device: unescape_mount_field(device),
+            mount_point,
+            fs_type: fs_type.to_string(),
+            ..Mount::default()
+        });
+    }
+    mounts
+}
+
+/// Undo the octal escapes of spaces and tabs, like `\040`
+fn unescape_mount_field(field: &str) -> String {
+    let mut text = String::new();
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        text.push_str(&rest[..index]);
+        let escaped = rest.get(index + 1..index + 4);
+        match escaped.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                text.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                text.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use anyhow::{Context, Result};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use super::Mount;
+
+    pub fn list_mounts() -> Result<Vec<Mount>> {
+        let content =
+            fs::read_to_string("/proc/self/mounts").context("Failed to read /proc/self/mounts")?;
+        let mut mounts = super::parse_proc_mounts(&content);
+        for mount in &mut mounts {
+            mount.removable = is_removable(mount);
+        }
+        Ok(mounts)
+    }
+
+    /// Whether the block device is removable, or mounted where udisks puts media
+    fn is_removable(mount: &Mount) -> bool {
+        if mount.mount_point.starts_with("/media") || mount.mount_point.starts_with("/run/media") {
+            return true;
+        }
+        let Some(name) = mount.device.strip_prefix("/dev/") else {
+            return false;
+        };
+        // A partition like sdb1 has the flag on its disk, one level up
+        let block = Path::new("/sys/class/block").join(name);
+        [block.join("removable"), block.join("../removable")]
+            .iter()
+            .find_map(|flag| fs::read_to_string(flag).ok())
+            .is_some_and(|flag| flag.trim() == "1")
+    }
+
+    /// `/` and the media mounted by udisks, which go below /media or /run/media
+    pub fn drive_roots() -> Result<Vec<PathBuf>> {
+        let mut roots = vec![PathBuf::from("/")];
+        roots.extend(
+            list_mounts()?
+                .into_iter()
+                .filter(|mount| mount.removable && mount.mount_point != Path::new("/"))
+                .map(|mount| mount.mount_point),
+        );
+        Ok(roots)
+    }
+
+    pub fn load_details(mount: &mut Mount) {
+        mount.space = super::statvfs_space(&mount.mount_point);
+    }
+
+    /// Network filesystems are mounted into the tree instead of on drive letters
+    pub fn network_drive_root(_path: &Path) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+mod platform {
+    use anyhow::{Context, Result};
+    use std::{fs, path::Path, path::PathBuf, process::Command};
+
+    use super::Mount;
+
+    /// Parse the output of `mount`, like `/dev/disk3s1 on /Volumes/USB (msdos, local, nodev)`
+    pub fn list_mounts() -> Result<Vec<Mount>> {
+        let output = Command::new("mount")
+            .output()
+            .context("Failed to run mount")?;
+        let mounts = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (device, rest) = line.split_once(" on ")?;
+                let (mount_point, options) = rest.rsplit_once(" (")?;
+                let fs_type = options.split([',', ')']).next()?.trim().to_string();
+                let is_device = device.starts_with('/') || device.starts_with("//");
+                if !is_device && !super::NETWORK_FS_TYPES.contains(&fs_type.as_str()) {
+                    return None;
+                }
+                let mount_point = PathBuf::from(mount_point);
+                Some(Mount {
+                    device: device.to_string(),
+                    // External disks are mounted below /Volumes on macOS
+                    removable: mount_point.starts_with("/Volumes"),
+                    mount_point,
+                    fs_type,
+                    ..Mount::default()
+                })
+            })
+            .collect();
+        Ok(mounts)
+    }
+
+    /// `/` and the volumes in /Volumes, leaving out the link back to `/`
+    pub fn drive_roots() -> Result<Vec<PathBuf>> {
+        let mut roots = vec![PathBuf::from("/")];
+        let mut volumes: Vec<PathBuf> = fs::read_dir("/Volumes")
+            .context("Failed to read /Volumes")?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && !path.is_symlink())
+            .collect();
+        volumes.sort();
+        roots.extend(volumes);
+        Ok(roots)
+    }
+
+    pub fn load_details(mount: &mut Mount) {
+        mount.space = super::statvfs_space(&mount.mount_point);
+    }
+
+    /// Network filesystems are mounted into the tree instead of on drive letters
+    pub fn network_drive_root(_path: &Path) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use anyhow::Result;
+    use std::{
+        ffi::OsString,
+        os::windows::ffi::{OsStrExt, OsStringExt},
+        path::{Component, Path, PathBuf, Prefix},
+        ptr,
+    };
+    use winapi::{
+        shared::ntdef::ULARGE_INTEGER,
+        um::{
+            errhandlingapi::SetThreadErrorMode,
+            fileapi::{
+                GetDiskFreeSpaceExW, GetDriveTypeW, GetLogicalDrives, GetVolumeInformationW,
+            },
+            winbase::{DRIVE_CDROM, DRIVE_REMOTE, DRIVE_REMOVABLE, SEM_FAILCRITICALERRORS},
+        },
+    };
+
+    use super::{Mount, Space};
+
+    /// Longest volume label or filesystem name, including the NUL
+    const NAME_LEN: usize = 261;
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    fn from_wide(buffer: &[u16]) -> String {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        OsString::from_wide(&buffer[..len])
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The drive letters in use, without touching the drives
+    ///
+    /// Probing `A:` to `Z:` with `exists()` wakes up sleeping disks and hangs
+    /// on disconnected network drives.
+    pub fn list_mounts() -> Result<Vec<Mount>> {
+        // SAFETY: takes no arguments and only returns a bit mask
+        let mask = unsafe { GetLogicalDrives() };
+        let drives = (0..26u8)
+            .filter(|bit| mask & (1 << bit) != 0)
+            .map(|bit| {
+                let letter = (b'A' + bit) as char;
+                let mount_point = PathBuf::from(format!("{letter}:\\"));
+                // SAFETY: the root path is NUL terminated
+                let drive_type = unsafe { GetDriveTypeW(wide(&mount_point).as_ptr()) };
+                Mount {
+                    device: format!("{letter}:"),
+                    mount_point,
+                    fs_type: if drive_type == DRIVE_REMOTE {
+                        "network".to_string()
+                    } else {
+                        String::new()
+                    },
+                    removable: drive_type == DRIVE_REMOVABLE || drive_type == DRIVE_CDROM,
+                    ..Mount::default()
+                }
+            })
+            .collect();
+        Ok(drives)
+    }
+
+    pub fn network_drive_root(path: &Path) -> Option<PathBuf> {
+        let Some(Component::Prefix(prefix)) = path.components().next() else {
+            return None;
+        };
+        let (Prefix::Disk(letter) | Prefix::VerbatimDisk(letter)) = prefix.kind() else {
+            return None;
+        };
+        let root = PathBuf::from(format!("{}:\\", letter as char));
+        // SAFETY: the root path is NUL terminated
+        let drive_type = unsafe { GetDriveTypeW(wide(&root).as_ptr()) };
+        (drive_type == DRIVE_REMOTE).then_some(root)
+    }
+
+    pub fn drive_roots() -> Result<Vec<PathBuf>> {
+        Ok(list_mounts()?
+            .into_iter()
+            .map(|mount| mount.mount_point)
+            .collect())
+    }
+
+    /// Read the volume label, filesystem and free space of a drive
+    pub fn load_details(mount: &mut Mount) {
+        let root = wide(&mount.mount_point);
+        // An empty card reader or DVD drive would open an error dialog
+        // SAFETY: only changes the error mode of this thread
+        unsafe { SetThreadErrorMode(SEM_FAILCRITICALERRORS, ptr::null_mut()) };
+
+        let mut label = [0u16; NAME_LEN];
+        let mut fs_name = [0u16; NAME_LEN];
+        // SAFETY: the buffers are as long as the lengths passed with them
+        let found = unsafe {
+            GetVolumeInformationW(
+                root.as_ptr(),
+                label.as_mut_ptr(),
+                NAME_LEN as u32,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                fs_name.as_mut_ptr(),
+                NAME_LEN as u32,
+            )
+        };
+        if found != 0 {
+            mount.label = Some(from_wide(&label)).filter(|label| !label.is_empty());
+            mount.fs_type = from_wide(&fs_name);
+        }
+
+        // SAFETY: ULARGE_INTEGER is plain data, and every pointer is valid or null
+        unsafe {
+            let mut free: ULARGE_INTEGER = std::mem::zeroed();
+            let mut total: ULARGE_INTEGER = std::mem::zeroed();
+            if GetDiskFreeSpaceExW(root.as_ptr(), &mut free, &mut total, ptr::null_mut()) != 0 {
+                mount.space = Some(Space {
+                    total: *total.QuadPart(),
+                    free: *free.QuadPart(),
+                });
+            }
+        }
+    }
+}
+
+/// Total and available space of the filesystem at `path`
+#[cfg(unix)]
+fn statvfs_space(path: &Path) -> Option<Space> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs only writes to the struct it is given, and the path is NUL terminated
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block_size = stat.f_frsize as u64;
+    Some(Space {
+        total: stat.f_blocks as u64 * block_size,
+        free: stat.f_bavail as u64 * block_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_bar() {
+        let space = Space {
+            total: 100,
+            free: 25,
+        };
+        assert_eq!(space.bar(8), "[██████░░]");
+        assert_eq!(Space::default().bar(4), "[░░░░]");
+    }
+
+    #[test]
+    fn test_parse_proc_mounts() {
+        let content = "\
+proc /proc proc rw,relatime 0 0
+/dev/sda2 / ext4 rw,relatime 0 0
+tmpfs /run tmpfs rw,nosuid 0 0
+/dev/loop3 /snap/core/123 squashfs ro 0 0
+/dev/sdb1 /run/media/me/My\\040Stick vfat rw 0 0
+server:/export /mnt/share nfs4 rw 0 0
+/dev/sda3 / ext4 rw 0 0
+";
+        let mounts = parse_proc_mounts(content);
+        let points: Vec<&Path> = mounts.iter().map(|m| m.mount_point.as_path()).collect();
+        assert_eq!(
+            points,
+            vec![
+                Path::new("/run/media/me/My Stick"),
+                Path::new("/mnt/share"),
+                Path::new("/"),
+            ]
+        );
+        assert_eq!(mounts[2].device, "/dev/sda3");
+        assert_eq!(mounts[1].fs_type, "nfs4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_drive_roots_start_at_root() {
+        let roots = drive_roots().unwrap_or_default();
+        assert!(roots.is_empty() || roots[0] == Path::new("/"));
+    }
+}
@@ -0,0 +1,532 @@
+//! External commands configured under `[plugins]`
+//!
+//! Each command runs in the shell with a JSON object on stdin:
+//!
+//! - `on_select` hooks get the selection like `--output json` prints it once
+//!   the terminal is restored and the result printed, and their output is
+//!   ignored
+//! - `on_enter_directory` hooks get `{"path": ...}` and run in the background
+//! - previewers get `{"path": ...}` and print
+//!   `{"title": ..., "lines": [...]}`, where the title is optional
+//! - sources get `{"source": ..., "cwd": ...}` and print `{"entries": [...]}`
+//!
+//! A command that fails, prints something else or runs longer than
+//! [`PLUGIN_TIMEOUT`] is reported in the log, or in the preview for
+//! previewers.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    path::Path,
+    process::{Child, Stdio},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    config::get_config,
+    exec,
+    output::{json_string, selection_json},
+    services::filesystem::strip_verbatim_prefix,
+    utils::{AppMode, FileItem},
+};
+
+/// How long a plugin command may run before it is killed
+pub const PLUGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a running plugin command is checked on
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long the output of a finished command is read for, a process it left
+/// running in the background may keep its pipes open
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+/// The selection the `on_select` hooks run with, once the terminal is restored
+static PENDING_SELECTION: Mutex<Option<String>> = Mutex::new(None);
+
+/// Remember what was selected for [`run_on_select`]
+///
+/// The hooks don't run right away, a slow one would leave the UI frozen on
+/// screen.
+pub fn on_select(file: &FileItem, mode: AppMode, query: &str) {
+    if get_config().plugins.on_select.is_empty() {
+        return;
+    }
+    *PENDING_SELECTION.lock().unwrap() = Some(selection_json(Some(file), mode, query));
+}
+
+/// Run the `on_select` hooks one after another, if something was selected
+pub fn run_on_select() {
+    let Some(input) = PENDING_SELECTION.lock().unwrap().take() else {
+        return;
+    };
+    for command in &get_config().plugins.on_select {
+        if let Err(e) = run(command, &input) {
+            warn!("on_select hook failed: {e:#}");
+        }
+    }
+}
+
+/// Run the `on_enter_directory` hooks with `dir` in the background
+pub fn on_enter_directory(dir: &Path) {
+    let hooks = &get_config().plugins.on_enter_directory;
+    if hooks.is_empty() {
+        return;
+    }
+    let input = path_json(dir);
+    thread::spawn(move || {
+        for command in hooks {
+            if let Err(e) = run(command, &input) {
+                warn!("on_enter_directory hook failed: {e:#}");
+            }
+        }
+    });
+}
+
+/// The previewer configured for the extension of `file`, if any
+pub fn previewer_for(file: &FileItem) -> Option<&'static str> {
+    let extension = file.path.extension()?.to_str()?;
+    get_config()
+        .plugins
+        .previewers
+        .iter()
+        .find(|previewer| {
+            previewer.extensions.iter().any(|known| {
+                known
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(extension)
+            })
+        })
+        .map(|previewer| previewer.command.as_str())
+}
+
+/// What a previewer made of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginPreview {
+    pub title: Option<String>,
+    pub lines: Vec<String>,
+}
+
+/// Run the previewer `command` on `path`
+pub fn preview(command: &str, path: &Path) -> Result<PluginPreview> {
+    let output = parse(&run(command, &path_json(path))?)?;
+    let title = match output.get("title") {
+        None | Some(Json::Null) => None,
+        Some(title) => Some(
+            title
+                .as_str()
+                .context("\"title\" isn't a string")?
+                .to_string(),
+        ),
+    };
+    Ok(PluginPreview {
+        title,
+        lines: strings(&output, "lines")?,
+    })
+}
+
+/// The entries listed by the source called `name`, run in `cwd`
+#[instrument]
+pub fn source_entries(name: &str, cwd: &Path) -> Result<Vec<String>> {
+    let sources = &get_config().plugins.sources;
+    let Some(command) = sources.get(name) else {
+        let known = sources.keys().cloned().collect::<Vec<_>>();
+        bail!(
+            "No source called {name:?} under [plugins.sources], known sources: {}",
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        );
+    };
+    let input = format!(
+        r#"{{"source":{},"cwd":{}}}"#,
+        json_string(name),
+        json_string(&strip_verbatim_prefix(cwd.to_path_buf()).to_string_lossy())
+    );
+    let entries = strings(&parse(&run(command, &input)?)?, "entries")?;
+    debug!(count = entries.len(), "Listed source entries");
+    Ok(entries)
+}
+
+/// Run `command` in the shell with `input` on stdin and hand back its stdout
+///
+/// Fails when the command exits with an error, with its stderr in the error,
+/// or when it runs longer than [`PLUGIN_TIMEOUT`].
+pub fn run(command: &str, input: &str) -> Result<String> {
+    debug!(command, "Running plugin command");
+    let mut process = exec::shell(command);
+    process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // In a group of its own, so what it started is killed with it on timeout
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut process, 0);
+    let mut child = process
+        .spawn()
+        .with_context(|| format!("Failed to run `{command}`"))?;
+
+    // Written and read on threads so a command that doesn't read its input,
+    // or fills a pipe before exiting, can't block us
+    let mut stdin = child.stdin.take().context("stdin isn't piped")?;
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status =
+        wait_with_timeout(&mut child).with_context(|| format!("`{command}` didn't finish"))?;
+    let until = Instant::now() + OUTPUT_GRACE;
+    let stdout = collect_output(&stdout, until);
+    if !status.success() {
+        let stderr = collect_output(&stderr, until);
+        bail!(
+            "`{command}` exited with {status}: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        );
+    }
+    String::from_utf8(stdout).with_context(|| format!("`{command}` printed invalid UTF-8"))
+}
+
+/// Send what comes out of `pipe` in chunks until it is closed
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        thread::spawn(move || {
+            let mut chunk = [0; 8192];
+            while let Ok(read @ 1..) = pipe.read(&mut chunk) {
+                if sender.send(chunk[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    receiver
+}
+
+/// What came out of a pipe until it was closed, or until `until`
+fn collect_output(chunks: &Receiver<Vec<u8>>, until: Instant) -> Vec<u8> {
+    let mut output = Vec::new();
+    while let Ok(chunk) = chunks.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        output.extend_from_slice(&chunk);
+    }
+    output
+}
+
+fn wait_with_timeout(child: &mut Child) -> Result<std::process::ExitStatus> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if start.elapsed() > PLUGIN_TIMEOUT {
+            #[cfg(unix)]
+            if let Ok(group) = libc::pid_t::try_from(child.id()) {
+                // SAFETY: only signals the group the command was started in
+                unsafe { libc::kill(-group, libc::SIGKILL) };
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("killed after {PLUGIN_TIMEOUT:?}");
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn path_json(path: &Path) -> String {
+    format!(
+        r#"{{"path":{}}}"#,
+        json_string(&strip_verbatim_prefix(path.to_path_buf()).to_string_lossy())
+    )
+}
+
+/// The strings in the array under `key` of `object`
+fn strings(object: &Json, key: &str) -> Result<Vec<String>> {
+    let Some(Json::Array(items)) = object.get(key) else {
+        bail!("Expected an array of strings under {key:?}");
+    };
+    items
+        .iter()
+        .map(|item| {
+            item.as_str()
+                .map(str::to_string)
+                .with_context(|| format!("Expected only strings under {key:?}"))
+        })
+        .collect()
+}
+
+/// A JSON value, as much of it as plugins print
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.get(key),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Parse `text` as a single JSON value, surrounded by nothing but whitespace
+fn parse(text: &str) -> Result<Json> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        at: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.at < parser.text.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Json> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json> {
+        self.at += 1;
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.eat(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            fields.insert(key, self.value()?);
+            self.skip_whitespace();
+            if self.eat(b'}') {
+                return Ok(Json::Object(fields));
+            }
+            if !self.eat(b',') {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json> {
+        self.at += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.eat(b']') {
+                return Ok(Json::Array(items));
+            }
+            if !self.eat(b',') {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.at += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.at += 1;
+            match byte {
+                b'"' => {
+                    return String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"));
+                }
+                b'\\' => {
+                    let Some(escape) = self.peek() else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.at += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    /// The character of a `\u` escape, joining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !(self.eat(b'\\') && self.eat(b'u')) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid character"))
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .text
+            .get(self.at..self.at + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.at += 4;
+        Ok(digits)
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let start = self.at;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.at += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.at])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json> {
+        if self.text[self.at..].starts_with(word.as_bytes()) {
+            self.at += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected word"))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.at += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.at).copied()
+    }
+
+    fn error(&self, what: &str) -> anyhow::Error {
+        anyhow!("Invalid JSON from plugin at byte {}: {what}", self.at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let value = parse(
+            r#" {"title": "a \"b\"", "lines": ["x\ty", "é😀"],
+                "n": -1.5e2, "ok": true, "none": null, "empty": {}} "#,
+        )
+        .unwrap();
+        assert_eq!(value.get("title").and_then(Json::as_str), Some(r#"a "b""#));
+        assert_eq!(
+            strings(&value, "lines").unwrap(),
+            vec!["x\ty".to_string(), "é😀".to_string()]
+        );
+        assert_eq!(value.get("n"), Some(&Json::Number(-150.0)));
+        assert_eq!(value.get("ok"), Some(&Json::Bool(true)));
+        assert_eq!(value.get("none"), Some(&Json::Null));
+        assert_eq!(value.get("empty"), Some(&Json::Object(BTreeMap::new())));
+
+        for invalid in [r#"{"a": 1"#, r#"{"a" 1}"#, "[1,]", r#""\x""#, "{} {}", ""] {
+            assert!(parse(invalid).is_err(), "{invalid}");
+        }
+        assert!(strings(&parse(r#"{"lines": [1]}"#).unwrap(), "lines").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_input_and_reports_failures() {
+        assert_eq!(
+            run("cat", r#"{"path":"/tmp"}"#).unwrap(),
+            r#"{"path":"/tmp"}"#
+        );
+        // Commands that ignore their input still finish
+        assert_eq!(run("echo done", "unread").unwrap(), "done\n");
+        let error = run("echo broken >&2; exit 3", "").unwrap_err();
+        assert!(format!("{error:#}").contains("broken"), "{error:#}");
+        // A process left running with the output open doesn't hold up the result
+        let start = Instant::now();
+        assert_eq!(run("sleep 5 & echo started", "").unwrap(), "started\n");
+        assert!(start.elapsed() < Duration::from_secs(3));
+
+        let previewed = preview(r#"printf '{"lines":["one","two"]}'"#, Path::new("/x")).unwrap();
+        assert_eq!(previewed.title, None);
+        assert_eq!(previewed.lines, ["one", "two"]);
+        assert!(preview("echo not json", Path::new("/x")).is_err());
+    }
+}
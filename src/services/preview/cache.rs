@@ -0,0 +1,102 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use super::PreviewContent;
+use crate::utils::FileItem;
+
+/// Number of generated previews kept around
+const CACHE_CAPACITY: usize = 32;
+
+/// A generated preview, valid as long as the file isn't modified
+struct CachedPreview {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    title: String,
+    content: PreviewContent,
+}
+
+/// Recently generated previews, most recently used first
+///
+/// Going back to an entry or onto a prefetched one shows its preview right
+/// away instead of generating it again.
+static CACHE: Mutex<VecDeque<CachedPreview>> = Mutex::new(VecDeque::new());
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The cached preview of a file, unless it changed since
+pub fn get(file: &FileItem) -> Option<(String, PreviewContent)> {
+    let modified = modified(&file.path);
+    let mut cache = CACHE.lock().unwrap();
+    let index = cache.iter().position(|cached| cached.path == file.path)?;
+    let cached = cache.remove(index)?;
+    if cached.modified != modified || modified.is_none() {
+        return None;
+    }
+    let preview = (cached.title.clone(), cached.content.clone());
+    cache.push_front(cached);
+    Some(preview)
+}
+
+/// Check if a file has a cached preview, without counting it as used
+pub fn contains(file: &FileItem) -> bool {
+    CACHE
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|cached| cached.path == file.path)
+}
+
+/// Drop all cached previews, after a change to how previews are generated
+pub fn clear() {
+    CACHE.lock().unwrap().clear();
+}
+
+/// Remember the preview generated for a file, dropping the least recently used
+pub fn insert(file: &FileItem, title: String, content: PreviewContent) {
+    let cached = CachedPreview {
+        path: file.path.clone(),
+        modified: modified(&file.path),
+        title,
+        content,
+    };
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|other| other.path != cached.path);
+    cache.push_front(cached);
+    cache.truncate(CACHE_CAPACITY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_modified_file_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "draft").unwrap();
+        let file = FileItem::from_path(&path);
+
+        insert(&file, "notes.txt".to_string(), PreviewContent::default());
+        assert!(get(&file).is_some_and(|(title, _)| title == "notes.txt"));
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(get(&file).is_none());
+        assert!(!contains(&file));
+    }
+}
@@ -1,4 +1,8 @@
-use std::fs;
+use std::{
+    fs, io,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use ratatui::{
     style::{Color, Style},
@@ -6,10 +10,27 @@ use ratatui::{
 };
 
 use super::PreviewContent;
-use crate::utils::FileItem;
+use crate::{
+    config::get_config,
+    services::{FilesystemService, LS_COLORS, icons},
+    utils::{FileItem, FileKind},
+};
 
 use super::PreviewGeneratorTrait;
 
+/// Most entries drawn in a tree preview, so huge trees don't take forever
+const TREE_MAX_ENTRIES: usize = 1000;
+
+/// Whether directories are previewed as a tree, `T` toggles it at runtime
+static TREE_VIEW: AtomicBool = AtomicBool::new(false);
+
+/// Switch between the flat and the tree preview of directories
+///
+/// Returns whether the tree is shown now.
+pub fn toggle_tree_view() -> bool {
+    !TREE_VIEW.fetch_xor(true, Ordering::Relaxed)
+}
+
 /// Directory preview generator
 pub struct DirectoryPreviewGenerator;
 
@@ -19,43 +40,21 @@ impl PreviewGeneratorTrait for DirectoryPreviewGenerator {
     }
 
     async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
-        // Special handling for Windows drives view
+        // Special handling for the drives view
         if file.path.to_string_lossy() == "DRIVES:" {
             return Self::generate_drives_preview();
         }
 
-        let title = format!("📁 {}", file.name);
-        let content = match fs::read_dir(&file.path) {
-            Ok(entries) => {
-                let mut items: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-                items.sort_by(|a, b| {
-                    let a_is_dir = a.path().is_dir();
-                    let b_is_dir = b.path().is_dir();
-                    match (a_is_dir, b_is_dir) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.file_name().cmp(&b.file_name()),
-                    }
-                });
+        if TREE_VIEW.load(Ordering::Relaxed) {
+            return Self::generate_tree_preview(file);
+        }
 
+        let title = format!("{} {}", icons::icon_for(file), file.name);
+        let content = match Self::sorted_entries(&file.path) {
+            Ok(items) => {
                 let mut preview_content: Vec<Line<'static>> = items
-                    .iter()
-                    .map(|entry| {
-                        let name = entry.file_name().to_string_lossy().into_owned();
-                        let is_dir = entry.path().is_dir();
-                        let icon = if is_dir { "📁" } else { "📄" };
-                        let style = if is_dir {
-                            Style::default().fg(Color::Cyan)
-                        } else {
-                            Style::default()
-                        };
-
-                        Line::from(vec![
-                            Span::raw(icon.to_string()),
-                            Span::raw(" ".to_string()),
-                            Span::styled(name, style),
-                        ])
-                    })
+                    .into_iter()
+                    .map(|item| Line::from(Self::entry_spans(item)))
                     .collect();
 
                 if preview_content.is_empty() {
@@ -79,53 +78,142 @@ impl PreviewGeneratorTrait for DirectoryPreviewGenerator {
 }
 
 impl DirectoryPreviewGenerator {
-    /// Generate preview content for Windows drives view
-    fn generate_drives_preview() -> (String, PreviewContent) {
-        let title = "💾 Available Drives".to_string();
-
-        #[cfg(windows)]
-        {
-            use crate::services::FilesystemService;
-            match FilesystemService::load_drives() {
-                Ok(drives) => {
-                    if drives.is_empty() {
-                        let content = vec![Line::from(vec![Span::styled(
-                            "No drives found".to_string(),
-                            Style::default().fg(Color::Gray),
-                        )])];
-                        (title, PreviewContent::text(content))
-                    } else {
-                        let content: Vec<Line<'static>> = drives
-                            .iter()
-                            .map(|drive| {
-                                Line::from(vec![
-                                    Span::raw("💾 ".to_string()),
-                                    Span::styled(
-                                        drive.name.clone(),
-                                        Style::default().fg(Color::Cyan),
-                                    ),
-                                ])
-                            })
-                            .collect();
-                        (title, PreviewContent::text(content))
-                    }
-                }
-                Err(e) => {
-                    let content = vec![Line::from(vec![Span::styled(
-                        format!("Error loading drives: {e}"),
-                        Style::default().fg(Color::Red),
-                    )])];
-                    (title, PreviewContent::text(content))
-                }
-            }
+    /// Entries of a directory, directories first
+    fn sorted_entries(path: &Path) -> io::Result<Vec<FileItem>> {
+        let mut items: Vec<FileItem> = fs::read_dir(path)?
+            .filter_map(|e| e.ok())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                FileItem::from_listing(name, entry.path(), entry.file_type().ok())
+            })
+            .collect();
+        items.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => FilesystemService::compare_names(&a.name, &b.name),
+        });
+        Ok(items)
+    }
+
+    /// Icon and colored name of an entry
+    fn entry_spans(item: FileItem) -> Vec<Span<'static>> {
+        let icon = icons::icon_for(&item);
+        let style = LS_COLORS.style_for(&item);
+        vec![
+            Span::raw(icon.to_string()),
+            Span::raw(" ".to_string()),
+            Span::styled(item.name, style),
+        ]
+    }
+
+    /// Preview a directory as a tree, `tree -L <tree_depth>` style
+    fn generate_tree_preview(file: &FileItem) -> (String, PreviewContent) {
+        let depth = get_config().preview.tree_depth.max(1);
+        let title = format!("{} {} - tree", icons::icon_for(file), file.name);
+        let mut lines = Vec::new();
+        if let Err(e) = Self::add_tree_lines(&file.path, "", depth, &mut lines) {
+            lines.push(Line::from(vec![Span::styled(
+                format!("Error reading directory: {e}"),
+                Style::default().fg(Color::Red),
+            )]));
         }
-        #[cfg(not(windows))]
-        {
-            let content = vec![Line::from(vec![Span::styled(
-                "Drive view not available on this platform".to_string(),
+        if lines.len() >= TREE_MAX_ENTRIES {
+            lines.push(Line::from(vec![Span::styled(
+                format!("… stopped after {TREE_MAX_ENTRIES} entries"),
+                Style::default().fg(Color::Yellow),
+            )]));
+        } else if lines.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "Empty directory".to_string(),
                 Style::default().fg(Color::Gray),
-            )])];
-            (title, PreviewContent::text(content))
+            )]));
         }
+        (title, PreviewContent::text(lines))
+    }
+
+    /// Append the entries below `path`, going `depth` levels deep
+    ///
+    /// Symlinked directories are not descended into, so links up the tree
+    /// can't loop.
+    fn add_tree_lines(
+        path: &Path,
+        prefix: &str,
+        depth: usize,
+        lines: &mut Vec<Line<'static>>,
+    ) -> io::Result<()> {
+        let items = Self::sorted_entries(path)?;
+        let count = items.len();
+        for (index, item) in items.into_iter().enumerate() {
+            if lines.len() >= TREE_MAX_ENTRIES {
+                break;
+            }
+            let is_last = index + 1 == count;
+            let branch = if is_last { "└── " } else { "├── " };
+            let descend = depth > 1 && item.is_dir && item.kind != FileKind::Symlink;
+            let child_path = item.path.clone();
+
+            let mut spans = vec![Span::styled(
+                format!("{prefix}{branch}"),
+                Style::default().fg(Color::DarkGray),
+            )];
+            spans.extend(Self::entry_spans(item));
+            lines.push(Line::from(spans));
+
+            if descend {
+                let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+                // Unreadable subdirectories just show up without children
+                let _ = Self::add_tree_lines(&child_path, &child_prefix, depth - 1, lines);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate preview content for the drives view
+    fn generate_drives_preview() -> (String, PreviewContent) {
+        let title = format!("{} Available Drives", icons::drive_icon());
+
+        let content = match FilesystemService::load_drives() {
+            Ok(drives) if drives.is_empty() => vec![Line::from(vec![Span::styled(
+                "No drives found".to_string(),
+                Style::default().fg(Color::Gray),
+            )])],
+            Ok(drives) => drives
+                .iter()
+                .map(|drive| {
+                    Line::from(vec![
+                        Span::raw(format!("{} ", icons::drive_icon())),
+                        Span::styled(drive.name.clone(), Style::default().fg(Color::Cyan)),
+                    ])
+                })
+                .collect(),
+            Err(e) => vec![Line::from(vec![Span::styled(
+                format!("Error loading drives: {e}"),
+                Style::default().fg(Color::Red),
+            )])],
+        };
+        (title, PreviewContent::text(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/deep")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let mut lines = Vec::new();
+        DirectoryPreviewGenerator::add_tree_lines(dir.path(), "", 2, &mut lines).unwrap();
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+        assert_eq!(text.len(), 4);
+        assert!(text[0].starts_with("├── ") && text[0].ends_with("src"));
+        assert!(text[1].starts_with("│   ├── ") && text[1].ends_with("deep"));
+        assert!(text[2].starts_with("│   └── ") && text[2].ends_with("main.rs"));
+        assert!(text[3].starts_with("└── ") && text[3].ends_with("README.md"));
     }
 }
@@ -0,0 +1,112 @@
+use std::{fs, path::Path};
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use super::PreviewContent;
+use crate::{
+    services::{LS_COLORS, icons},
+    utils::{self, FileItem},
+};
+
+/// Width of the bar drawn in front of each entry
+const BAR_WIDTH: usize = 20;
+
+/// Disk usage breakdown of a directory, like `ncdu` or `dua`
+///
+/// Not part of the regular preview chain since walking a large tree takes a
+/// while, it is generated on demand instead.
+pub struct DiskUsagePreviewGenerator;
+
+impl DiskUsagePreviewGenerator {
+    /// Sizes of the entries in `file`, largest first
+    ///
+    /// This walks the whole tree and blocks, so run it off the UI thread.
+    pub fn generate_preview(file: &FileItem) -> (String, PreviewContent) {
+        let title = format!("{} {} - disk usage", icons::icon_for(file), file.name);
+        let entries = match fs::read_dir(&file.path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                let content = vec![Line::from(vec![Span::styled(
+                    format!("Error reading directory: {e}"),
+                    Style::default().fg(Color::Red),
+                )])];
+                return (title, PreviewContent::text(content));
+            }
+        };
+
+        let mut sizes: Vec<(FileItem, u64)> = entries
+            .filter_map(|e| e.ok())
+            .map(|entry| {
+                let size = disk_usage(&entry.path());
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let item = FileItem::from_listing(name, entry.path(), entry.file_type().ok());
+                (item, size)
+            })
+            .collect();
+        sizes.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        let total: u64 = sizes.iter().map(|(_, size)| size).sum();
+        let mut content = vec![
+            Line::from(vec![Span::styled(
+                format!(
+                    "Total: {} in {} entries",
+                    utils::format_size(total),
+                    sizes.len()
+                ),
+                Style::default().fg(Color::Yellow),
+            )]),
+            Line::from(""),
+        ];
+        content.extend(sizes.into_iter().map(|(item, size)| {
+            let fraction = if total == 0 {
+                0.0
+            } else {
+                size as f64 / total as f64
+            };
+            let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>9} ", utils::format_size(size)),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled("█".repeat(filled), Style::default().fg(Color::Green)),
+                Span::styled(
+                    "░".repeat(BAR_WIDTH - filled),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!(" {:>5.1}% ", fraction * 100.0),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::raw(format!("{} ", icons::icon_for(&item))),
+                Span::styled(item.name.clone(), LS_COLORS.style_for(&item)),
+            ])
+        }));
+
+        (title, PreviewContent::text(content))
+    }
+}
+
+/// Total size of a file or directory tree in bytes
+///
+/// Symlinks count as themselves and are not followed, so links pointing back
+/// up the tree can't loop. Unreadable entries count as empty.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
@@ -1,35 +1,95 @@
-use std::sync::Arc;
+use std::{fs, path::Path, sync::Arc};
 
+use anyhow::{Context, Result};
+use image::{DynamicImage, RgbaImage};
+use once_cell::sync::Lazy;
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
-use tokio::sync::Mutex;
 
 use super::PreviewContent;
-use crate::{services::preview::GLOBAL_PICKER, utils::FileItem};
+use crate::{services::icons, utils::FileItem};
 
 use super::PreviewGeneratorTrait;
 
+/// Longest side SVGs are rasterized to, the preview is scaled down from there
+const SVG_RENDER_SIZE: f32 = 1024.0;
+
+/// Fonts for text in SVGs, loading them takes a moment so it happens once
+static SVG_FONTS: Lazy<Arc<resvg::usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = resvg::usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// Decode an image, rasterizing SVGs which the `image` crate can't read
+pub(super) fn load_image(path: &Path) -> Result<DynamicImage> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        ImagePreviewGenerator::rasterize_svg(path)
+    } else {
+        Ok(image::open(path)?)
+    }
+}
+
 /// Image preview generator
 pub struct ImagePreviewGenerator;
 
+impl ImagePreviewGenerator {
+    fn rasterize_svg(path: &Path) -> Result<DynamicImage> {
+        use resvg::{tiny_skia, usvg};
+
+        let data = fs::read(path)?;
+        let options = usvg::Options {
+            // Relative `href`s to embedded images are resolved from the file
+            resources_dir: path.parent().map(Path::to_path_buf),
+            fontdb: SVG_FONTS.clone(),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_data(&data, &options)?;
+
+        // Scale small icons up and huge drawings down to a sensible resolution
+        let size = tree.size();
+        let scale = SVG_RENDER_SIZE / size.width().max(size.height());
+        let width = (size.width() * scale).round().max(1.0) as u32;
+        let height = (size.height() * scale).round().max(1.0) as u32;
+        let mut pixmap =
+            tiny_skia::Pixmap::new(width, height).context("SVG has an invalid size")?;
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        // tiny-skia stores premultiplied alpha, `image` expects it straight
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        let image =
+            RgbaImage::from_raw(width, height, pixels).context("SVG has an invalid size")?;
+        Ok(DynamicImage::ImageRgba8(image))
+    }
+}
+
 impl PreviewGeneratorTrait for ImagePreviewGenerator {
     fn can_handle(&self, file: &FileItem) -> bool {
         file.is_image()
     }
 
     async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
-        let title = format!("🖼️ {}", file.name);
+        let title = format!("{} {}", icons::icon_for(file), file.name);
 
         // Try to load the image
-        match image::open(&file.path) {
-            Ok(img) => {
-                // Create a protocol for the image
-                let protocol = GLOBAL_PICKER.new_resize_protocol(img);
-
-                (title, PreviewContent::image(Arc::new(Mutex::new(protocol))))
-            }
+        match load_image(&file.path) {
+            Ok(img) => (title, PreviewContent::image(file.path.clone(), img)),
             Err(e) => {
                 let content = vec![
                     Line::from(vec![Span::styled(
@@ -47,3 +107,23 @@ impl PreviewGeneratorTrait for ImagePreviewGenerator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_rasterize_svg() {
+        let mut file = tempfile::Builder::new().suffix(".svg").tempfile().unwrap();
+        write!(
+            file,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="8"><rect width="16" height="8" fill="red"/></svg>"#
+        )
+        .unwrap();
+
+        let image = load_image(file.path()).unwrap();
+        assert_eq!((image.width(), image.height()), (1024, 512));
+        assert_eq!(image.to_rgba8().get_pixel(10, 10).0, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,203 @@
+#[cfg(unix)]
+use std::collections::HashMap;
+use std::{
+    fs::{self, Metadata},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+#[cfg(unix)]
+use once_cell::sync::Lazy;
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use crate::utils::{self, FileItem};
+
+/// Lines describing permissions, ownership and timestamps of a file
+///
+/// Shown above the content of text previews, symlinks are described by what
+/// they point to.
+pub fn metadata_header(file: &FileItem) -> Vec<Line<'static>> {
+    let Ok(metadata) = fs::metadata(&file.path).or_else(|_| fs::symlink_metadata(&file.path))
+    else {
+        return Vec::new();
+    };
+
+    let mut summary = vec![
+        Span::styled(permissions(&metadata), Style::default().fg(Color::Green)),
+        Span::raw("  "),
+    ];
+    if let Some(owner) = owner(&metadata) {
+        summary.push(Span::styled(owner, Style::default().fg(Color::Yellow)));
+        summary.push(Span::raw("  "));
+    }
+    if !metadata.is_dir() {
+        summary.push(Span::styled(
+            utils::format_size(metadata.len()),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+
+    let times: Vec<String> = [
+        ("Modified", metadata.modified()),
+        ("Accessed", metadata.accessed()),
+        ("Created", metadata.created()),
+    ]
+    .into_iter()
+    .filter_map(|(label, time)| Some(format!("{label} {}", format_time(time.ok()?))))
+    .collect();
+
+    vec![
+        Line::from(summary),
+        Line::from(vec![Span::styled(
+            times.join("  "),
+            Style::default().fg(Color::DarkGray),
+        )]),
+        Line::from(vec![Span::styled(
+            "─".repeat(50),
+            Style::default().fg(Color::Gray),
+        )]),
+    ]
+}
+
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Mode bits in the style of `ls -l`, like `drwxr-xr-x`
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    mode_string(metadata.is_dir(), metadata.permissions().mode())
+}
+
+/// File attributes, like `readonly, hidden`
+#[cfg(windows)]
+fn permissions(metadata: &Metadata) -> String {
+    use std::os::windows::fs::MetadataExt;
+    const ATTRIBUTES: [(u32, &str); 5] = [
+        (0x1, "readonly"),
+        (0x2, "hidden"),
+        (0x4, "system"),
+        (0x20, "archive"),
+        (0x800, "compressed"),
+    ];
+    let attributes = metadata.file_attributes();
+    let names: Vec<&str> = ATTRIBUTES
+        .iter()
+        .filter(|(bit, _)| attributes & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if names.is_empty() {
+        "normal".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn permissions(metadata: &Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "readonly".to_string()
+    } else {
+        "writable".to_string()
+    }
+}
+
+#[cfg(unix)]
+fn mode_string(is_dir: bool, mode: u32) -> String {
+    let mut text = String::with_capacity(10);
+    text.push(if is_dir { 'd' } else { '-' });
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        // setuid, setgid and sticky replace the execute bit, capitalized without it
+        text.push(match (mode & special != 0, bits & 0o1 != 0) {
+            (true, true) => special_char,
+            (true, false) => special_char.to_ascii_uppercase(),
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    text
+}
+
+/// Names of the users in `/etc/passwd`, read once
+#[cfg(unix)]
+static USER_NAMES: Lazy<HashMap<u32, String>> = Lazy::new(|| read_id_names("/etc/passwd"));
+
+/// Names of the groups in `/etc/group`, read once
+#[cfg(unix)]
+static GROUP_NAMES: Lazy<HashMap<u32, String>> = Lazy::new(|| read_id_names("/etc/group"));
+
+/// `user:group` of a file, falling back to the numeric ids
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    let name = |names: &HashMap<u32, String>, id: u32| {
+        names.get(&id).cloned().unwrap_or_else(|| id.to_string())
+    };
+    let user = name(&USER_NAMES, metadata.uid());
+    let group = name(&GROUP_NAMES, metadata.gid());
+    Some(format!("{user}:{group}"))
+}
+
+#[cfg(not(unix))]
+fn owner(_metadata: &Metadata) -> Option<String> {
+    None
+}
+
+/// Read a `name:password:id:...` database like `/etc/passwd` into names by id
+///
+/// Users from other sources such as LDAP aren't found and show up by id.
+#[cfg(unix)]
+fn read_id_names(database: &str) -> HashMap<u32, String> {
+    fs::read_to_string(database)
+        .map(|content| parse_id_names(&content))
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn parse_id_names(content: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in content.lines() {
+        let mut fields = line.split(':');
+        let (Some(name), Some(id)) = (fields.next(), fields.nth(1)) else {
+            continue;
+        };
+        // The first entry wins, like getpwuid
+        if let Ok(id) = id.parse() {
+            names.entry(id).or_insert_with(|| name.to_string());
+        }
+    }
+    names
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_string() {
+        assert_eq!(mode_string(true, 0o755), "drwxr-xr-x");
+        assert_eq!(mode_string(false, 0o640), "-rw-r-----");
+        assert_eq!(mode_string(false, 0o4755), "-rwsr-xr-x");
+        assert_eq!(mode_string(true, 0o1777), "drwxrwxrwt");
+        assert_eq!(mode_string(false, 0o2644), "-rw-r-Sr--");
+    }
+
+    #[test]
+    fn test_parse_id_names() {
+        let names = parse_id_names(
+            "root:x:0:0::/root:/bin/sh\n# comment\nbroken\nadmin:x:0:0\nuser:x:1000:1000",
+        );
+        assert_eq!(names.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(names.get(&1000).map(String::as_str), Some("user"));
+        assert_eq!(names.len(), 2);
+    }
+}
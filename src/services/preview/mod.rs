@@ -1,20 +1,34 @@
+pub mod cache;
 mod directory_generator;
+mod disk_usage;
 mod image_generator;
+mod metadata_header;
 mod pdf_generator;
+mod plugin_generator;
 mod preview_content;
 mod preview_generator;
 mod text_generator;
 
-pub use directory_generator::DirectoryPreviewGenerator;
+pub use directory_generator::{DirectoryPreviewGenerator, toggle_tree_view};
+pub use disk_usage::DiskUsagePreviewGenerator;
 pub use image_generator::ImagePreviewGenerator;
 use once_cell::sync::Lazy;
 pub use pdf_generator::PdfPreviewGenerator;
-pub use preview_content::PreviewContent;
+pub use plugin_generator::PluginPreviewGenerator;
+pub use preview_content::{ImagePreview, PreviewContent};
 pub use preview_generator::{
-    BinaryPreviewGenerator, PreviewGenerator, PreviewGeneratorTrait, process_special_characters,
+    BinaryPreviewGenerator, PreviewGenerator, PreviewGeneratorTrait, line_number_span,
+    process_special_characters, toggle_line_numbers,
 };
 use ratatui_image::picker::Picker;
-pub use text_generator::TextPreviewGenerator;
+use std::io::IsTerminal;
+pub use text_generator::{TEXT_CHUNK_LINES, TextPreviewGenerator, TextSource};
 
-pub static GLOBAL_PICKER: Lazy<Picker> =
-    Lazy::new(|| Picker::from_query_stdio().unwrap_or_else(|_| Picker::from_fontsize((8, 16))));
+pub static GLOBAL_PICKER: Lazy<Picker> = Lazy::new(|| {
+    // The query is written to stdout, which must not end up in a pipeline
+    if std::io::stdout().is_terminal() {
+        Picker::from_query_stdio().unwrap_or_else(|_| Picker::from_fontsize((8, 16)))
+    } else {
+        Picker::from_fontsize((8, 16))
+    }
+});
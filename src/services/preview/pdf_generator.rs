@@ -6,9 +6,9 @@ use ratatui::{
 };
 
 use super::PreviewContent;
-use crate::utils::FileItem;
+use crate::{services::icons, utils::FileItem};
 
-use super::{PreviewGeneratorTrait, process_special_characters};
+use super::{PreviewGeneratorTrait, line_number_span, process_special_characters};
 
 /// PDF preview generator
 pub struct PdfPreviewGenerator;
@@ -19,45 +19,16 @@ impl PreviewGeneratorTrait for PdfPreviewGenerator {
     }
 
     async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
-        let title = format!("📄 {}", file.name);
+        let title = format!("{} {}", icons::icon_for(file), file.name);
 
         // Try to read the PDF file
         match fs::read(&file.path) {
             Ok(bytes) => {
                 // Extract text from PDF using pdf-extract
-                match pdf_extract::extract_text_from_mem(&bytes) {
-                    Ok(text) => {
-                        let lines_count = text.lines().count();
-                        let size_info = Line::from(vec![Span::styled(
-                            format!("PDF Document - {lines_count} lines extracted"),
-                            Style::default().fg(Color::Cyan),
-                        )]);
-
-                        let mut lines = vec![size_info];
-
-                        lines.push(Line::from(vec![Span::styled(
-                            "─".repeat(50),
-                            Style::default().fg(Color::Gray),
-                        )]));
-
-                        // Process the extracted text
-                        let content_lines: Vec<Line<'static>> = text
-                            .lines()
-                            .enumerate()
-                            .map(|(i, line)| {
-                                Line::from(vec![
-                                    Span::styled(
-                                        format!("{:3} ", i + 1),
-                                        Style::default().fg(Color::DarkGray),
-                                    ),
-                                    Span::raw(process_special_characters(line)),
-                                ])
-                            })
-                            .collect();
-
-                        lines.extend(content_lines);
-
-                        (title, PreviewContent::text(lines))
+                match pdf_extract::extract_text_from_mem_by_pages(&bytes) {
+                    Ok(pages) => {
+                        let (lines, page_starts) = page_lines(&pages);
+                        (title, PreviewContent::Pages(lines, page_starts))
                     }
                     Err(e) => {
                         let content = vec![
@@ -97,3 +68,54 @@ impl PreviewGeneratorTrait for PdfPreviewGenerator {
         }
     }
 }
+
+/// Lay out the text of each page below a page header
+///
+/// Returns the lines along with the index of each page header, line numbers
+/// start over on every page.
+fn page_lines(pages: &[String]) -> (Vec<Line<'static>>, Vec<usize>) {
+    let lines_count: usize = pages.iter().map(|page| page.lines().count()).sum();
+    let mut lines = vec![Line::from(vec![Span::styled(
+        format!(
+            "PDF Document - {} pages, {lines_count} lines extracted",
+            pages.len()
+        ),
+        Style::default().fg(Color::Cyan),
+    )])];
+    let mut page_starts = Vec::with_capacity(pages.len());
+
+    for (page_index, page) in pages.iter().enumerate() {
+        page_starts.push(lines.len());
+        lines.push(Line::from(vec![Span::styled(
+            format!("── Page {} {}", page_index + 1, "─".repeat(40)),
+            Style::default().fg(Color::Gray),
+        )]));
+        lines.extend(page.lines().enumerate().map(|(i, line)| {
+            let mut spans: Vec<_> = line_number_span(i + 1).into_iter().collect();
+            spans.push(Span::raw(process_special_characters(line)));
+            Line::from(spans)
+        }));
+    }
+
+    (lines, page_starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_lines() {
+        let pages = vec![
+            "first\nsecond".to_string(),
+            String::new(),
+            "third".to_string(),
+        ];
+        let (lines, page_starts) = page_lines(&pages);
+
+        assert_eq!(page_starts, vec![1, 4, 5]);
+        assert_eq!(lines.len(), 7);
+        assert!(lines[4].to_string().contains("Page 2"));
+        assert_eq!(lines[6].to_string(), "  1 third");
+    }
+}
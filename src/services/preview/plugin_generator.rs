@@ -0,0 +1,63 @@
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use super::PreviewContent;
+use crate::{
+    services::{icons, plugins},
+    utils::FileItem,
+};
+
+use super::{PreviewGeneratorTrait, line_number_span, process_special_characters};
+
+/// Preview made by a command configured under `[[plugins.previewers]]`
+pub struct PluginPreviewGenerator;
+
+impl PreviewGeneratorTrait for PluginPreviewGenerator {
+    fn can_handle(&self, file: &FileItem) -> bool {
+        !file.is_dir && plugins::previewer_for(file).is_some()
+    }
+
+    async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
+        let title = format!("{} {}", icons::icon_for(file), file.name);
+        let Some(command) = plugins::previewer_for(file) else {
+            return (title, PreviewContent::text(Vec::new()));
+        };
+
+        // The command blocks for up to the plugin timeout, keep it off the async workers
+        let path = file.path.clone();
+        let previewed = tokio::task::spawn_blocking(move || plugins::preview(command, &path))
+            .await
+            .unwrap_or_else(|e| Err(e.into()));
+        match previewed {
+            Ok(preview) => {
+                let lines = preview
+                    .lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| {
+                        let mut spans: Vec<Span> = line_number_span(i + 1).into_iter().collect();
+                        spans.push(Span::raw(process_special_characters(line)));
+                        Line::from(spans)
+                    })
+                    .collect();
+                (preview.title.unwrap_or(title), PreviewContent::text(lines))
+            }
+            Err(e) => {
+                let content = vec![
+                    Line::from(vec![Span::styled(
+                        "Previewer Error".to_string(),
+                        Style::default().fg(Color::Red),
+                    )]),
+                    Line::from(vec![Span::raw("".to_string())]),
+                    Line::from(vec![Span::styled(
+                        format!("{e:#}"),
+                        Style::default().fg(Color::Gray),
+                    )]),
+                ];
+                (title, PreviewContent::text(content))
+            }
+        }
+    }
+}
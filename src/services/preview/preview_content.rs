@@ -1,16 +1,162 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
+use image::DynamicImage;
 use tokio::sync::Mutex;
 
-use ratatui::text::Line;
-use ratatui_image::protocol::StatefulProtocol;
+use ratatui::{layout::Rect, text::Line};
+use ratatui_image::{Resize, ResizeEncodeRender, protocol::StatefulProtocol};
+
+use super::{
+    GLOBAL_PICKER, TextSource, image_generator::load_image, text_generator::truncation_marker,
+};
+
+/// Largest zoom factor, each step doubles it
+const MAX_ZOOM: u32 = 16;
+
+/// Lines of text kept for a preview, the rest of a longer one is left out
+pub const MAX_PREVIEW_LINES: usize = 10_000;
+
+/// An image along with the part of it that is shown
+#[derive(Clone)]
+pub struct ImagePreview {
+    /// The shown part encoded for the terminal, recreated on zooming and panning
+    pub protocol: Arc<Mutex<StatefulProtocol>>,
+    /// The whole image, which owns the decoded pixels, shown again on zooming out
+    whole: Arc<Mutex<StatefulProtocol>>,
+    /// Where the image was read from
+    path: PathBuf,
+    /// The decoded image the zoomed in parts are cut from
+    ///
+    /// The whole image keeps its pixels to itself, so they are decoded again
+    /// on the first zoom instead of copied for every previewed image.
+    source: Option<Arc<DynamicImage>>,
+    /// 1 shows the whole image, 2 half its width and height and so on
+    zoom: u32,
+    /// Center of the shown part as a fraction of the image size
+    center: (f32, f32),
+}
+
+impl ImagePreview {
+    fn new(path: PathBuf, image: DynamicImage) -> Self {
+        let whole = Arc::new(Mutex::new(GLOBAL_PICKER.new_resize_protocol(image)));
+        Self {
+            protocol: whole.clone(),
+            whole,
+            path,
+            source: None,
+            zoom: 1,
+            center: (0.5, 0.5),
+        }
+    }
+
+    /// The same image showing another part, `None` if it can't be read again
+    fn with_view(&self, zoom: u32, center: (f32, f32)) -> Option<Self> {
+        // Keep the shown part inside the image
+        let visible = 1.0 / zoom as f32;
+        let clamp = |c: f32| c.clamp(visible / 2.0, 1.0 - visible / 2.0);
+        let center = (clamp(center.0), clamp(center.1));
+
+        if zoom == 1 {
+            return Some(Self {
+                protocol: self.whole.clone(),
+                zoom,
+                center,
+                ..self.clone()
+            });
+        }
+        let source = match &self.source {
+            Some(source) => source.clone(),
+            None => Arc::new(load_image(&self.path).ok()?),
+        };
+        let (width, height) = (source.width() as f32, source.height() as f32);
+        let part = source.crop_imm(
+            ((center.0 - visible / 2.0) * width).round() as u32,
+            ((center.1 - visible / 2.0) * height).round() as u32,
+            (width * visible).round().max(1.0) as u32,
+            (height * visible).round().max(1.0) as u32,
+        );
+        Some(Self {
+            protocol: Arc::new(Mutex::new(GLOBAL_PICKER.new_resize_protocol(part))),
+            whole: self.whole.clone(),
+            path: self.path.clone(),
+            source: Some(source),
+            zoom,
+            center,
+        })
+    }
+
+    /// How the image is fitted into the preview
+    pub const RESIZE: Resize = Resize::Fit(None);
+
+    /// Check if the image is encoded for `area`, starting the encoding if not
+    ///
+    /// Resizing and encoding a large image takes long enough to stall the UI
+    /// when done while drawing, so it runs on a blocking thread instead and
+    /// the image shows up on a later frame.
+    pub fn prepare(&self, area: Rect) -> bool {
+        let Ok(protocol) = self.protocol.try_lock() else {
+            // Still being encoded
+            return false;
+        };
+        if protocol.needs_resize(&Self::RESIZE, area).is_none() {
+            return true;
+        }
+        drop(protocol);
+
+        let protocol = self.protocol.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut protocol = protocol.blocking_lock();
+            // An earlier frame may have started the same work
+            if let Some(area) = protocol.needs_resize(&Self::RESIZE, area) {
+                protocol.resize_encode(&Self::RESIZE, area);
+            }
+        });
+        false
+    }
+
+    /// Current zoom factor, 1 when the whole image is shown
+    pub fn zoom(&self) -> u32 {
+        self.zoom
+    }
+
+    /// Zoom in or out by a factor of two, `None` at the limit
+    pub fn zoomed(&self, zoom_in: bool) -> Option<Self> {
+        let zoom = if zoom_in {
+            (self.zoom * 2).min(MAX_ZOOM)
+        } else {
+            (self.zoom / 2).max(1)
+        };
+        if zoom == self.zoom {
+            return None;
+        }
+        self.with_view(zoom, self.center)
+    }
+
+    /// Move the shown part by a quarter of its size in each direction, `None`
+    /// when it can't move that way
+    pub fn panned(&self, dx: i8, dy: i8) -> Option<Self> {
+        let step = 0.25 / self.zoom as f32;
+        let moved = self.with_view(
+            self.zoom,
+            (
+                self.center.0 + step * dx as f32,
+                self.center.1 + step * dy as f32,
+            ),
+        )?;
+        (moved.center != self.center).then_some(moved)
+    }
+}
 
 /// Enum representing different types of preview content
 pub enum PreviewContent {
     /// Text content with lines for display
     Text(Vec<Line<'static>>),
+    /// The first lines of a text file, the rest is read from the source on scrolling
+    PartialText(Vec<Line<'static>>, TextSource),
+    /// Text split into pages, with the index of the first line of each page
+    Pages(Vec<Line<'static>>, Vec<usize>),
     /// Image content with protocol for rendering
-    Image(Arc<Mutex<StatefulProtocol>>),
+    Image(ImagePreview),
 }
 
 impl PreviewContent {
@@ -19,14 +165,17 @@ impl PreviewContent {
         Self::Text(lines)
     }
 
-    /// Create image preview content
-    pub fn image(protocol: Arc<Mutex<StatefulProtocol>>) -> Self {
-        Self::Image(protocol)
+    /// Create image preview content showing the whole image read from `path`
+    pub fn image(path: PathBuf, image: DynamicImage) -> Self {
+        Self::Image(ImagePreview::new(path, image))
     }
 
     /// Check if this is text content
     pub fn is_text(&self) -> bool {
-        matches!(self, Self::Text(_))
+        matches!(
+            self,
+            Self::Text(_) | Self::PartialText(..) | Self::Pages(..)
+        )
     }
 
     /// Check if this is image content
@@ -37,7 +186,7 @@ impl PreviewContent {
     /// Get text lines if this is text content
     pub fn as_text(&self) -> Option<&Vec<Line<'static>>> {
         match self {
-            Self::Text(lines) => Some(lines),
+            Self::Text(lines) | Self::PartialText(lines, _) | Self::Pages(lines, _) => Some(lines),
             Self::Image(_) => None,
         }
     }
@@ -45,16 +194,48 @@ impl PreviewContent {
     /// Get image protocol if this is image content
     pub fn as_image(&self) -> Option<&Arc<Mutex<StatefulProtocol>>> {
         match self {
-            Self::Text(_) => None,
-            Self::Image(protocol) => Some(protocol),
+            Self::Text(_) | Self::PartialText(..) | Self::Pages(..) => None,
+            Self::Image(image) => Some(&image.protocol),
         }
     }
 
     /// Get mutable image protocol if this is image content
     pub fn as_image_mut(&mut self) -> Option<&mut Arc<Mutex<StatefulProtocol>>> {
         match self {
-            Self::Text(_) => None,
-            Self::Image(protocol) => Some(protocol),
+            Self::Text(_) | Self::PartialText(..) | Self::Pages(..) => None,
+            Self::Image(image) => Some(&mut image.protocol),
+        }
+    }
+
+    /// Leave out the lines past [`MAX_PREVIEW_LINES`], with a line saying so
+    ///
+    /// A partially read file that reached the limit isn't read any further.
+    pub fn capped(self) -> Self {
+        let cap = |mut lines: Vec<Line<'static>>| {
+            lines.truncate(MAX_PREVIEW_LINES);
+            lines.push(truncation_marker(format!(
+                "… showing the first {MAX_PREVIEW_LINES} lines"
+            )));
+            lines
+        };
+        match self {
+            Self::Text(lines) if lines.len() > MAX_PREVIEW_LINES => Self::Text(cap(lines)),
+            Self::PartialText(lines, _) if lines.len() >= MAX_PREVIEW_LINES => {
+                Self::Text(cap(lines))
+            }
+            Self::Pages(lines, mut starts) if lines.len() > MAX_PREVIEW_LINES => {
+                starts.retain(|&start| start < MAX_PREVIEW_LINES);
+                Self::Pages(cap(lines), starts)
+            }
+            content => content,
+        }
+    }
+
+    /// Index of the first line of each page, empty unless the content has pages
+    pub fn page_starts(&self) -> &[usize] {
+        match self {
+            Self::Pages(_, starts) => starts,
+            _ => &[],
         }
     }
 }
@@ -63,7 +244,7 @@ impl PreviewContent {
     /// Get the length of content (number of lines for text, 1 for image)
     pub fn len(&self) -> usize {
         match self {
-            Self::Text(lines) => lines.len(),
+            Self::Text(lines) | Self::PartialText(lines, _) | Self::Pages(lines, _) => lines.len(),
             Self::Image(_) => 1, // Images take up the full area
         }
     }
@@ -71,7 +252,9 @@ impl PreviewContent {
     /// Check if content is empty
     pub fn is_empty(&self) -> bool {
         match self {
-            Self::Text(lines) => lines.is_empty(),
+            Self::Text(lines) | Self::PartialText(lines, _) | Self::Pages(lines, _) => {
+                lines.is_empty()
+            }
             Self::Image(_) => false, // Images are never considered empty
         }
     }
@@ -81,6 +264,8 @@ impl Clone for PreviewContent {
     fn clone(&self) -> Self {
         match self {
             Self::Text(lines) => Self::Text(lines.clone()),
+            Self::PartialText(lines, source) => Self::PartialText(lines.clone(), source.clone()),
+            Self::Pages(lines, starts) => Self::Pages(lines.clone(), starts.clone()),
             Self::Image(image) => Self::Image(image.clone()),
         }
     }
@@ -99,6 +284,16 @@ impl std::fmt::Debug for PreviewContent {
                 .debug_tuple("Text")
                 .field(&format!("{} lines", lines.len()))
                 .finish(),
+            Self::PartialText(lines, source) => f
+                .debug_tuple("PartialText")
+                .field(&format!("{} lines", lines.len()))
+                .field(source)
+                .finish(),
+            Self::Pages(lines, starts) => f
+                .debug_tuple("Pages")
+                .field(&format!("{} lines", lines.len()))
+                .field(&format!("{} pages", starts.len()))
+                .finish(),
             Self::Image(_) => f.debug_tuple("Image").field(&"StatefulProtocol").finish(),
         }
     }
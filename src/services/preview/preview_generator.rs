@@ -1,11 +1,19 @@
+use once_cell::sync::Lazy;
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
-use std::fs;
+use std::{
+    fs,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use super::PreviewContent;
-use crate::utils::FileItem;
+use super::{PreviewContent, metadata_header::metadata_header};
+use crate::{
+    config::get_config,
+    services::icons,
+    utils::{self, FileItem, FileKind},
+};
 
 /// Trait for preview generators
 pub trait PreviewGeneratorTrait {
@@ -18,11 +26,13 @@ pub trait PreviewGeneratorTrait {
 }
 
 use super::{
-    DirectoryPreviewGenerator, ImagePreviewGenerator, PdfPreviewGenerator, TextPreviewGenerator,
+    DirectoryPreviewGenerator, ImagePreviewGenerator, PdfPreviewGenerator, PluginPreviewGenerator,
+    TextPreviewGenerator,
 };
 
 /// Enum for different preview generators to support async trait methods
 pub enum PreviewGeneratorType {
+    Plugin(PluginPreviewGenerator),
     Directory(DirectoryPreviewGenerator),
     Image(ImagePreviewGenerator),
     Pdf(PdfPreviewGenerator),
@@ -34,6 +44,7 @@ impl PreviewGeneratorType {
     /// Check if this generator can handle the given file
     pub fn can_handle(&self, file: &FileItem) -> bool {
         match self {
+            PreviewGeneratorType::Plugin(generator) => generator.can_handle(file),
             PreviewGeneratorType::Directory(generator) => generator.can_handle(file),
             PreviewGeneratorType::Image(generator) => generator.can_handle(file),
             PreviewGeneratorType::Pdf(generator) => generator.can_handle(file),
@@ -45,6 +56,7 @@ impl PreviewGeneratorType {
     /// Generate preview content for a file
     pub async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
         match self {
+            PreviewGeneratorType::Plugin(generator) => generator.generate_preview(file).await,
             PreviewGeneratorType::Directory(generator) => generator.generate_preview(file).await,
             PreviewGeneratorType::Image(generator) => generator.generate_preview(file).await,
             PreviewGeneratorType::Pdf(generator) => generator.generate_preview(file).await,
@@ -60,8 +72,36 @@ pub struct PreviewGenerator;
 impl PreviewGenerator {
     /// Generate preview content for a file or directory
     pub async fn generate_preview_content(file: &FileItem) -> (String, PreviewContent) {
+        if let Some(preview) = Self::too_large_preview(file) {
+            return preview;
+        }
+        if file.kind == FileKind::BrokenSymlink {
+            return Self::broken_symlink_preview(file);
+        }
+
+        let (title, content) = Self::generate_target_preview(file).await;
+        let content = if get_config().preview.metadata_header {
+            Self::with_metadata_header(file, content)
+        } else {
+            content
+        }
+        .capped();
+        // Previews of links show what they point to in the header
+        match file.kind {
+            FileKind::Symlink => match fs::read_link(&file.path) {
+                Ok(target) => (format!("{title} → {}", target.display()), content),
+                Err(_) => (title, content),
+            },
+            _ => (title, content),
+        }
+    }
+
+    /// Generate the preview of a file, or of what a symlink points to
+    async fn generate_target_preview(file: &FileItem) -> (String, PreviewContent) {
         // Try different file preview generators in order
+        // Configured previewers come first, so they can take over any extension
         let generators = vec![
+            PreviewGeneratorType::Plugin(PluginPreviewGenerator),
             PreviewGeneratorType::Directory(DirectoryPreviewGenerator),
             PreviewGeneratorType::Image(ImagePreviewGenerator),
             PreviewGeneratorType::Pdf(PdfPreviewGenerator),
@@ -78,17 +118,92 @@ impl PreviewGenerator {
         let binary_gen = PreviewGeneratorType::Binary(BinaryPreviewGenerator);
         binary_gen.generate_preview(file).await
     }
+
+    /// Put the permissions and timestamps of the file above text content
+    fn with_metadata_header(file: &FileItem, content: PreviewContent) -> PreviewContent {
+        let mut lines = metadata_header(file);
+        let header_len = lines.len();
+        match content {
+            PreviewContent::Text(text) => {
+                lines.extend(text);
+                PreviewContent::Text(lines)
+            }
+            PreviewContent::PartialText(text, source) => {
+                lines.extend(text);
+                PreviewContent::PartialText(lines, source)
+            }
+            PreviewContent::Pages(text, page_starts) => {
+                lines.extend(text);
+                let page_starts = page_starts.iter().map(|start| start + header_len).collect();
+                PreviewContent::Pages(lines, page_starts)
+            }
+            // There is no room above an image
+            image @ PreviewContent::Image(_) => image,
+        }
+    }
+
+    /// Preview of a symlink whose target doesn't exist
+    fn broken_symlink_preview(file: &FileItem) -> (String, PreviewContent) {
+        let title = format!("{} {}", icons::icon_for(file), file.name);
+        let target = fs::read_link(&file.path)
+            .map(|target| target.display().to_string())
+            .unwrap_or_else(|e| format!("unreadable ({e})"));
+        let content = vec![
+            Line::from(vec![Span::styled(
+                "Broken symlink".to_string(),
+                Style::default().fg(Color::Red),
+            )]),
+            Line::from(vec![Span::raw("".to_string())]),
+            Line::from(vec![Span::styled(
+                format!("Points to: {target}"),
+                Style::default().fg(Color::Gray),
+            )]),
+        ];
+        (title, PreviewContent::text(content))
+    }
+
+    /// Placeholder for files over the configured `max_file_size`
+    fn too_large_preview(file: &FileItem) -> Option<(String, PreviewContent)> {
+        let max_file_size = get_config().preview.max_file_size;
+        if max_file_size == 0 || file.is_dir {
+            return None;
+        }
+        let file_size = fs::metadata(&file.path).ok()?.len();
+        if file_size <= max_file_size {
+            return None;
+        }
+
+        let title = format!("{} {}", icons::icon_for(file), file.name);
+        let content = vec![
+            Line::from(vec![Span::styled(
+                "File too large to preview".to_string(),
+                Style::default().fg(Color::Yellow),
+            )]),
+            Line::from(vec![Span::raw("".to_string())]),
+            Line::from(vec![Span::styled(
+                format!(
+                    "Size: {} (limit {})",
+                    utils::format_size(file_size),
+                    utils::format_size(max_file_size)
+                ),
+                Style::default().fg(Color::Gray),
+            )]),
+        ];
+        Some((title, PreviewContent::text(content)))
+    }
 }
 
 /// Process special characters in text for better display
 pub fn process_special_characters(text: &str) -> String {
     let mut result = String::new();
+    let tab_width = get_config().preview.tab_width.max(1);
 
     for ch in text.chars() {
         match ch {
             '\t' => {
                 // Replace tab with visible representation and spaces
-                result.push_str("→   "); // Arrow symbol followed by 3 spaces for tab width
+                result.push('→');
+                result.extend(std::iter::repeat_n(' ', tab_width - 1));
             }
             '\r' => {
                 // Replace carriage return with visible representation
@@ -112,6 +227,24 @@ pub fn process_special_characters(text: &str) -> String {
     result
 }
 
+/// Whether text previews show line numbers, `#` toggles it at runtime
+static LINE_NUMBERS: Lazy<AtomicBool> =
+    Lazy::new(|| AtomicBool::new(get_config().preview.line_numbers));
+
+/// Show or hide line numbers in text previews generated from now on
+///
+/// Returns whether they are shown now.
+pub fn toggle_line_numbers() -> bool {
+    !LINE_NUMBERS.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// The line number column in front of a line of a text preview, if shown
+pub fn line_number_span(number: usize) -> Option<Span<'static>> {
+    LINE_NUMBERS
+        .load(Ordering::Relaxed)
+        .then(|| Span::styled(format!("{number:3} "), Style::default().fg(Color::DarkGray)))
+}
+
 /// Binary file preview generator (fallback)
 pub struct BinaryPreviewGenerator;
 
@@ -122,7 +255,7 @@ impl PreviewGeneratorTrait for BinaryPreviewGenerator {
     }
 
     async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
-        let title = format!("📄 {}", file.name);
+        let title = format!("{} {}", icons::icon_for(file), file.name);
 
         // Get file metadata
         let metadata = match fs::metadata(&file.path) {
@@ -1,4 +1,8 @@
-use std::fs;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
 
 use ratatui::{
     style::{Color, Style},
@@ -6,9 +10,230 @@ use ratatui::{
 };
 
 use super::PreviewContent;
-use crate::utils::FileItem;
+use crate::{
+    config::{PreviewTruncate, get_config},
+    services::{icons, ls_colors::apply_sgr},
+    utils::FileItem,
+};
+
+use super::{PreviewGeneratorTrait, line_number_span, process_special_characters};
+
+/// Lines read from a text file at a time
+///
+/// Only a screenful is visible, so big files are read in chunks as the
+/// preview is scrolled instead of all at once.
+pub const TEXT_CHUNK_LINES: usize = 200;
+
+/// Characters of a line kept for the preview
+///
+/// The preview doesn't scroll sideways, so the rest of a very long line, like
+/// in minified code, is never seen and isn't kept with its styling either.
+pub const MAX_LINE_CHARS: usize = 1_000;
+
+/// How much of the start of a file is checked to tell text from binary
+const TEXT_SNIFF_SIZE: usize = 8 * 1024;
+
+/// Where to continue reading a partially previewed text file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSource {
+    path: PathBuf,
+    /// Byte offset of the first line not read yet
+    offset: u64,
+    /// Number of the first line not read yet, counted from 1
+    line_number: usize,
+    /// Style set by ANSI escapes in the lines read so far
+    style: Style,
+}
+
+impl TextSource {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            line_number: 1,
+            style: Style::default(),
+        }
+    }
+
+    /// Read the next `max_lines` lines as numbered preview lines
+    ///
+    /// Returns the source to continue from, `None` once the end of the file
+    /// was reached.
+    pub fn read_lines(self, max_lines: usize) -> io::Result<(Vec<Line<'static>>, Option<Self>)> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+
+        let mut lines = Vec::new();
+        let mut offset = self.offset;
+        let mut buffer = Vec::new();
+        let mut style = self.style;
+        while lines.len() < max_lines {
+            buffer.clear();
+            let read = reader.read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                return Ok((lines, None));
+            }
+            offset += read as u64;
+
+            let text = String::from_utf8_lossy(&buffer);
+            let text = text.trim_end_matches(['\n', '\r']);
+            let (text, cut) = match text.char_indices().nth(MAX_LINE_CHARS) {
+                Some((end, _)) => (&text[..end], true),
+                None => (text, false),
+            };
+            let mut spans: Vec<_> = line_number_span(self.line_number + lines.len())
+                .into_iter()
+                .collect();
+            spans.extend(ansi_spans(text, &mut style));
+            if cut {
+                spans.push(Span::styled("…", Style::default().fg(Color::Yellow)));
+            }
+            lines.push(Line::from(spans));
+        }
 
-use super::{PreviewGeneratorTrait, process_special_characters};
+        // Don't offer more when the file ends right after the last line
+        if reader.fill_buf()?.is_empty() {
+            return Ok((lines, None));
+        }
+        let line_number = self.line_number + lines.len();
+        Ok((
+            lines,
+            Some(Self {
+                offset,
+                line_number,
+                style,
+                ..self
+            }),
+        ))
+    }
+}
+
+impl TextSource {
+    /// Read the last `max_lines` lines of the file, skipping what comes before
+    ///
+    /// Also returns how many lines were skipped between this source and the
+    /// lines read.
+    pub fn read_tail(self, max_lines: usize) -> io::Result<(Vec<Line<'static>>, usize)> {
+        let mut file = File::open(&self.path)?;
+        let end = file.metadata()?.len();
+
+        // Walk back from the end until enough line starts are found
+        let mut start = end;
+        let mut found = 0;
+        let mut block = vec![0; TEXT_SNIFF_SIZE];
+        let mut position = end;
+        'search: while position > self.offset {
+            let block_start = position.saturating_sub(block.len() as u64).max(self.offset);
+            let block = &mut block[..(position - block_start) as usize];
+            file.seek(SeekFrom::Start(block_start))?;
+            file.read_exact(block)?;
+            for (i, &byte) in block.iter().enumerate().rev() {
+                let line_end = block_start + i as u64;
+                // The newline ending the last line doesn't start another one
+                if byte == b'\n' && line_end + 1 < end {
+                    found += 1;
+                    start = line_end + 1;
+                    if found == max_lines {
+                        break 'search;
+                    }
+                }
+            }
+            position = block_start;
+        }
+        if found < max_lines {
+            start = self.offset;
+        }
+
+        // Line numbers continue from the skipped part
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut skipped = 0;
+        let mut reader = BufReader::new(file).take(start - self.offset);
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+            skipped += buffer.iter().filter(|&&byte| byte == b'\n').count();
+            let len = buffer.len();
+            reader.consume(len);
+        }
+
+        let tail = Self {
+            offset: start,
+            line_number: self.line_number + skipped,
+            ..self
+        };
+        let (lines, _) = tail.read_lines(max_lines)?;
+        Ok((lines, skipped))
+    }
+}
+
+/// Split a line into spans styled by the ANSI escapes in it
+///
+/// Colors from build logs or `script` recordings are shown instead of the raw
+/// escapes. Escapes other than SGR ones, like cursor movement or hyperlinks,
+/// are dropped. `style` carries over from one line to the next, the same way
+/// a terminal keeps it.
+fn ansi_spans(text: &str, style: &mut Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        if start > 0 {
+            spans.push(Span::styled(
+                process_special_characters(&rest[..start]),
+                *style,
+            ));
+        }
+        let escape = &rest[start + 1..];
+        let consumed = if let Some(csi) = escape.strip_prefix('[') {
+            // Parameters and intermediate bytes up to the final byte
+            match csi.find(|c: char| ('@'..='~').contains(&c)) {
+                Some(end) => {
+                    if csi[end..].starts_with('m') {
+                        *style = apply_sgr(*style, &csi[..end]);
+                    }
+                    1 + end + 1
+                }
+                None => escape.len(),
+            }
+        } else if let Some(osc) = escape.strip_prefix(']') {
+            // Terminated by BEL or ST
+            match osc.find(['\x07', '\x1b']) {
+                Some(end) if osc[end..].starts_with('\x07') => 1 + end + 1,
+                Some(end) => 1 + end + osc[end + 1..].starts_with('\\') as usize + 1,
+                None => escape.len(),
+            }
+        } else {
+            // Not a sequence we know, show the escape character itself
+            spans.push(Span::styled(process_special_characters("\x1b"), *style));
+            0
+        };
+        rest = &escape[consumed..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(process_special_characters(rest), *style));
+    }
+    spans
+}
+
+/// Line standing in for the part of a file that isn't shown
+pub(super) fn truncation_marker(text: String) -> Line<'static> {
+    Line::from(vec![Span::styled(text, Style::default().fg(Color::Yellow))])
+}
+
+/// Whether a file looks like UTF-8 text, judging by its start
+fn is_text_file(path: &std::path::Path) -> bool {
+    let mut buffer = [0; TEXT_SNIFF_SIZE];
+    let Ok(read) = File::open(path).and_then(|mut file| file.read(&mut buffer)) else {
+        return false;
+    };
+    match std::str::from_utf8(&buffer[..read]) {
+        Ok(_) => true,
+        // A multi-byte character cut off at the end of the buffer is fine
+        Err(e) => e.error_len().is_none(),
+    }
+}
 
 /// Text preview generator
 pub struct TextPreviewGenerator;
@@ -16,13 +241,12 @@ pub struct TextPreviewGenerator;
 impl PreviewGeneratorTrait for TextPreviewGenerator {
     fn can_handle(&self, file: &FileItem) -> bool {
         // Handle any file that's not an image or PDF and can be read as text
-        fs::read_to_string(&file.path).is_ok()
+        is_text_file(&file.path)
     }
 
     async fn generate_preview(&self, file: &FileItem) -> (String, PreviewContent) {
-        let title = format!("📄 {}", file.name);
+        let title = format!("{} {}", icons::icon_for(file), file.name);
 
-        // First check file size to avoid reading large files
         let metadata = match fs::metadata(&file.path) {
             Ok(metadata) => metadata,
             Err(e) => {
@@ -33,79 +257,71 @@ impl PreviewGeneratorTrait for TextPreviewGenerator {
                 return (title, PreviewContent::text(content));
             }
         };
-
         let file_size = metadata.len();
-        const MAX_PREVIEW_SIZE: u64 = 5 * 1024 * 1024; // 5MB
-
-        // If file is too large, only show basic information
-        if file_size > MAX_PREVIEW_SIZE {
-            let content = vec![
-                Line::from(vec![Span::styled(
-                    "Large File".to_string(),
-                    Style::default().fg(Color::Yellow),
-                )]),
-                Line::from(vec![Span::raw("".to_string())]),
-                Line::from(vec![Span::styled(
-                    format!(
-                        "Size: {} bytes ({:.2} MB)",
-                        file_size,
-                        file_size as f64 / 1024.0 / 1024.0
-                    ),
-                    Style::default().fg(Color::Gray),
-                )]),
-                Line::from(vec![Span::styled(
-                    "File too large for preview (>5MB)".to_string(),
-                    Style::default().fg(Color::Gray),
-                )]),
-                Line::from(vec![Span::raw("".to_string())]),
-                Line::from(vec![Span::styled(
-                    "Basic file information:".to_string(),
-                    Style::default().fg(Color::Cyan),
-                )]),
-            ];
-            return (title, PreviewContent::text(content));
-        }
 
-        // For files under 5MB, try to read and preview content
-        match fs::read_to_string(&file.path) {
-            Ok(content) => {
-                let size_info = Line::from(vec![Span::styled(
-                    format!(
-                        "Size: {} bytes, {} lines",
-                        content.len(),
-                        content.lines().count()
-                    ),
-                    Style::default().fg(Color::Gray),
-                )]);
-
-                let mut lines = vec![size_info];
-
-                lines.push(Line::from(vec![Span::styled(
-                    "─".repeat(50),
-                    Style::default().fg(Color::Gray),
-                )]));
-
-                let content_lines: Vec<Line<'static>> = content
-                    .lines()
-                    .enumerate()
-                    .map(|(i, line)| {
-                        Line::from(vec![
-                            Span::styled(
-                                format!("{:3} ", i + 1),
-                                Style::default().fg(Color::DarkGray),
-                            ),
-                            Span::raw(process_special_characters(line)),
-                        ])
-                    })
-                    .collect();
+        let config = &get_config().preview;
+        let first_lines = match config.truncate {
+            PreviewTruncate::Stream => TEXT_CHUNK_LINES,
+            PreviewTruncate::Head | PreviewTruncate::HeadTail => config.head_lines,
+        };
+        match TextSource::new(file.path.clone()).read_lines(first_lines) {
+            Ok((mut content_lines, rest)) => {
+                // The line count is only known without reading the whole file
+                // when it fits in the first chunk
+                let mut line_count = rest.is_none().then_some(content_lines.len());
+
+                // Fixed truncation shows the skipped part as a marker instead
+                let rest = match (config.truncate, rest) {
+                    (PreviewTruncate::Head, Some(_)) => {
+                        content_lines.push(truncation_marker(format!(
+                            "… showing the first {first_lines} lines"
+                        )));
+                        None
+                    }
+                    (PreviewTruncate::HeadTail, Some(rest)) => {
+                        match rest.read_tail(config.tail_lines) {
+                            Ok((tail_lines, skipped)) => {
+                                line_count = Some(content_lines.len() + skipped + tail_lines.len());
+                                if skipped > 0 {
+                                    content_lines.push(truncation_marker(format!(
+                                        "… {skipped} lines skipped"
+                                    )));
+                                }
+                                content_lines.extend(tail_lines);
+                            }
+                            Err(e) => content_lines.push(truncation_marker(format!(
+                                "… failed to read the end of the file: {e}"
+                            ))),
+                        }
+                        None
+                    }
+                    (_, rest) => rest,
+                };
 
+                let size_text = match line_count {
+                    Some(line_count) => format!("Size: {file_size} bytes, {line_count} lines"),
+                    None => format!("Size: {file_size} bytes"),
+                };
+                let mut lines = vec![
+                    Line::from(vec![Span::styled(
+                        size_text,
+                        Style::default().fg(Color::Gray),
+                    )]),
+                    Line::from(vec![Span::styled(
+                        "─".repeat(50),
+                        Style::default().fg(Color::Gray),
+                    )]),
+                ];
                 lines.extend(content_lines);
 
-                (title, PreviewContent::text(lines))
+                let content = match rest {
+                    Some(rest) => PreviewContent::PartialText(lines, rest),
+                    None => PreviewContent::text(lines),
+                };
+                (title, content)
             }
-            Err(_) => {
-                // File exists but can't be read as text (likely binary)
-                // This should be handled by BinaryPreviewGenerator, but as fallback
+            Err(e) => {
+                // File exists but can't be read as text
                 let content = vec![
                     Line::from(vec![Span::styled(
                         "Text Read Error".to_string(),
@@ -117,7 +333,7 @@ impl PreviewGeneratorTrait for TextPreviewGenerator {
                         Style::default().fg(Color::Gray),
                     )]),
                     Line::from(vec![Span::styled(
-                        "Cannot read as text".to_string(),
+                        format!("Cannot read as text: {e}"),
                         Style::default().fg(Color::Gray),
                     )]),
                 ];
@@ -126,3 +342,98 @@ impl PreviewGeneratorTrait for TextPreviewGenerator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::preview::preview_content::MAX_PREVIEW_LINES;
+    use ratatui::style::Modifier;
+    use std::io::Write;
+
+    #[test]
+    fn test_read_lines_in_chunks() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "one\r\ntwo\nthree\n").unwrap();
+
+        let source = TextSource::new(file.path().to_path_buf());
+        let (lines, rest) = source.read_lines(2).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].to_string(), "  1 one");
+
+        let (lines, rest) = rest.unwrap().read_lines(2).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].to_string(), "  3 three");
+        assert_eq!(rest, None);
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for i in 1..=10 {
+            writeln!(file, "line {i}").unwrap();
+        }
+
+        let (_, rest) = TextSource::new(file.path().to_path_buf())
+            .read_lines(3)
+            .unwrap();
+        let (lines, skipped) = rest.clone().unwrap().read_tail(2).unwrap();
+        assert_eq!(skipped, 5);
+        assert_eq!(lines[0].to_string(), "  9 line 9");
+        assert_eq!(lines[1].to_string(), " 10 line 10");
+
+        // Asking for more than is left reads everything after the head
+        let (lines, skipped) = rest.unwrap().read_tail(20).unwrap();
+        assert_eq!(skipped, 0);
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0].to_string(), "  4 line 4");
+    }
+
+    #[test]
+    fn test_preview_memory_is_bounded() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{}", "x".repeat(MAX_LINE_CHARS * 5)).unwrap();
+        for i in 0..MAX_PREVIEW_LINES {
+            writeln!(file, "line {i}").unwrap();
+        }
+
+        let (lines, rest) = TextSource::new(file.path().to_path_buf())
+            .read_lines(MAX_PREVIEW_LINES)
+            .unwrap();
+        assert_eq!(lines[0].width(), "  1 ".len() + MAX_LINE_CHARS + 1);
+        assert!(lines[0].to_string().ends_with("x…"));
+
+        // Reaching the limit stops reading the rest of the file
+        let content = PreviewContent::PartialText(lines, rest.unwrap()).capped();
+        let PreviewContent::Text(lines) = content else {
+            panic!("still reading after the limit: {content:?}");
+        };
+        assert_eq!(lines.len(), MAX_PREVIEW_LINES + 1);
+        assert!(
+            lines[MAX_PREVIEW_LINES]
+                .to_string()
+                .contains("first 10000 lines")
+        );
+    }
+
+    #[test]
+    fn test_ansi_spans() {
+        let mut style = Style::default();
+        let spans = ansi_spans("\x1b[1;31merror\x1b[0m: \x1b[Kfailed \x1b[32m", &mut style);
+        let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "error: failed ");
+        assert_eq!(
+            spans[0].style,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(spans[1].style, Style::default());
+        assert_eq!(style, Style::default().fg(Color::Green));
+
+        let spans = ansi_spans(
+            "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07 \x1b(B",
+            &mut style,
+        );
+        let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "link \\x1b(B");
+        assert_eq!(spans[0].style, Style::default().fg(Color::Green));
+    }
+}
@@ -1,6 +1,12 @@
+use std::time::Duration;
+
 use crate::{
     AppState,
-    services::{GlobalPreviewState, PreviewGenerator, preview::PreviewContent},
+    config::get_config,
+    services::{
+        GlobalPreviewState, PreviewGenerator, icons,
+        preview::{self, DiskUsagePreviewGenerator, PreviewContent, cache},
+    },
     utils::{DisplayItem, FileItem},
 };
 use ratatui::{
@@ -8,6 +14,9 @@ use ratatui::{
     text::{Line, Span},
 };
 
+/// Entries on each side of the selection whose previews are prefetched
+const PREFETCH_DISTANCE: usize = 2;
+
 /// Unified preview manager for handling all preview functionality
 pub struct PreviewManager;
 
@@ -15,20 +24,61 @@ impl PreviewManager {
     pub fn preview_for_selected_item(state: &AppState) {
         if let Some(item) = state.get_selected_item() {
             // Get file info for placeholder
-            let file_item = match item {
-                DisplayItem::File(file) => file.clone(),
-                DisplayItem::History(entry) => FileItem::from_path(&entry.path),
-            };
-            Self::update_preview_for_item_async(&file_item);
+            let file_item = Self::file_item_of(&item);
+            Self::update_preview_for_item_async(&file_item, Self::adjacent_items(state));
+        }
+    }
+
+    /// Preview the selected entry unless it already is, for when a search edit
+    /// moved the selection to another match
+    pub fn preview_changed_selection(state: &AppState) {
+        let global_state = GlobalPreviewState::instance();
+        match state.get_selected_item() {
+            Some(item) if global_state.is_current_file_item(&Self::file_item_of(&item)) => {}
+            Some(_) => Self::preview_for_selected_item(state),
+            None => {
+                global_state.set_current_file_item(None);
+                global_state.clear_preview();
+            }
+        }
+    }
+
+    fn file_item_of(item: &DisplayItem) -> FileItem {
+        match item {
+            DisplayItem::File(file) => file.clone(),
+            DisplayItem::History(entry) => FileItem::from_path(&entry.path),
         }
     }
 
+    /// The entries around the selection, the ones after it first
+    fn adjacent_items(state: &AppState) -> Vec<FileItem> {
+        let Some(selected) = state.file_list_state.selected() else {
+            return Vec::new();
+        };
+        let after = (1..=PREFETCH_DISTANCE).map(|distance| selected.checked_add(distance));
+        let before = (1..=PREFETCH_DISTANCE).map(|distance| selected.checked_sub(distance));
+        after
+            .chain(before)
+            .flatten()
+            .filter_map(|index| state.filtered_files.get(index))
+            .filter_map(|&file_index| state.files.get(file_index))
+            .map(|item| Self::file_item_of(&item))
+            .collect()
+    }
+
     /// Update preview for a DisplayItem with non-blocking background generation
-    fn update_preview_for_item_async(file_item: &FileItem) {
+    fn update_preview_for_item_async(file_item: &FileItem, adjacent: Vec<FileItem>) {
         let global_state = GlobalPreviewState::instance();
 
+        if let Some((title, content)) = cache::get(file_item) {
+            global_state.set_current_file_item(Some(file_item.clone()));
+            global_state.update_preview(title, content, Some(file_item.clone()));
+            tokio::spawn(Self::prefetch(file_item.clone(), adjacent));
+            return;
+        }
+
         // Show immediate placeholder content
-        let placeholder_title = format!("📄 {}", file_item.name);
+        let placeholder_title = format!("{} {}", icons::icon_for(file_item), file_item.name);
         let placeholder_content = PreviewContent::text(vec![
             Line::from(vec![Span::styled(
                 "Loading preview...".to_string(),
@@ -48,16 +98,76 @@ impl PreviewManager {
         );
 
         // Start background task to generate actual content
-        let file_path = file_item.path.clone();
+        let file_item = file_item.clone();
+        let debounce = Duration::from_millis(get_config().preview.debounce_ms);
 
         tokio::spawn(async move {
-            let file_item = FileItem::from_path(&file_path);
+            // Skip entries the selection only passed over
+            if !debounce.is_zero() {
+                tokio::time::sleep(debounce).await;
+                if !GlobalPreviewState::instance().is_current_file_item(&file_item) {
+                    return;
+                }
+            }
+            let file_item = FileItem::from_path(&file_item.path);
             let (title, content) = PreviewGenerator::generate_preview_content(&file_item).await;
+            cache::insert(&file_item, title.clone(), content.clone());
 
             // Update the global state with the actual content
             let global_state = GlobalPreviewState::instance();
-            global_state.update_preview(title, content, Some(file_item));
+            global_state.update_preview(title, content, Some(file_item.clone()));
+
+            Self::prefetch(file_item, adjacent).await;
+        });
+    }
+
+    /// Generate the previews of the entries around `selected` into the cache
+    ///
+    /// Runs one entry at a time after the selected preview is done, and stops
+    /// once the selection moves on since it then prefetches around itself.
+    async fn prefetch(selected: FileItem, adjacent: Vec<FileItem>) {
+        for file_item in adjacent {
+            if !GlobalPreviewState::instance().is_current_file_item(&selected) {
+                return;
+            }
+            if cache::contains(&file_item) {
+                continue;
+            }
+            let (title, content) = PreviewGenerator::generate_preview_content(&file_item).await;
+            cache::insert(&file_item, title, content);
+        }
+    }
+
+    /// Show the disk usage of the selected directory
+    ///
+    /// Returns `false` when the selection isn't a directory.
+    pub fn disk_usage_for_selected_item(state: &AppState) -> bool {
+        let Some(item) = state.get_selected_item().filter(DisplayItem::is_directory) else {
+            return false;
+        };
+        let file_item = FileItem::from_path(item.get_path());
+        let global_state = GlobalPreviewState::instance();
+        global_state.set_current_file_item(Some(file_item.clone()));
+        global_state.update_preview(
+            format!(
+                "{} {} - disk usage",
+                icons::icon_for(&file_item),
+                file_item.name
+            ),
+            PreviewContent::text(vec![Line::from(vec![Span::styled(
+                "Calculating sizes...".to_string(),
+                Style::default().fg(Color::Yellow),
+            )])]),
+            Some(file_item.clone()),
+        );
+
+        // Walking the tree blocks, keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            let (title, content) = DiskUsagePreviewGenerator::generate_preview(&file_item);
+            // Dropped if the selection moved on in the meantime
+            GlobalPreviewState::instance().update_preview(title, content, Some(file_item));
         });
+        true
     }
 
     /// Clear preview content
@@ -90,6 +200,74 @@ impl PreviewManager {
         global_state.scroll_page_down(visible_height)
     }
 
+    /// Scroll preview content by `delta` lines, negative values scroll up
+    pub fn scroll_preview_by(delta: isize, visible_height: usize) -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.scroll_by(delta, visible_height)
+    }
+
+    /// Scroll the preview to its first line
+    pub fn scroll_preview_to_top() -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.scroll_to_top()
+    }
+
+    /// Scroll the preview to its last line
+    pub fn scroll_preview_to_bottom(visible_height: usize) -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.scroll_to_bottom(visible_height)
+    }
+
+    /// Scroll the preview to the next (or previous) line containing `pattern`
+    pub fn search_preview(pattern: &str, forward: bool) -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.search(pattern, forward)
+    }
+
+    /// Scroll the preview to the next page of a PDF
+    pub fn scroll_preview_next_page() -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.scroll_next_page()
+    }
+
+    /// Scroll the preview to the previous page of a PDF
+    pub fn scroll_preview_previous_page() -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.scroll_previous_page()
+    }
+
+    /// Zoom the image preview in or out
+    pub fn zoom_preview_image(zoom_in: bool) -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.zoom_image(zoom_in)
+    }
+
+    /// Pan a zoomed in image preview
+    pub fn pan_preview_image(dx: i8, dy: i8) -> bool {
+        let global_state = GlobalPreviewState::instance();
+        global_state.pan_image(dx, dy)
+    }
+
+    /// Show or hide line numbers and regenerate the preview accordingly
+    ///
+    /// Returns whether line numbers are shown now.
+    pub fn toggle_line_numbers(state: &AppState) -> bool {
+        let shown = preview::toggle_line_numbers();
+        cache::clear();
+        Self::preview_for_selected_item(state);
+        shown
+    }
+
+    /// Switch between the flat and the tree preview of directories
+    ///
+    /// Returns whether the tree is shown now.
+    pub fn toggle_tree_view(state: &AppState) -> bool {
+        let shown = preview::toggle_tree_view();
+        cache::clear();
+        Self::preview_for_selected_item(state);
+        shown
+    }
+
     /// Reset preview scroll position to top
     pub fn reset_preview_scroll() {
         let global_state = GlobalPreviewState::instance();
@@ -0,0 +1,253 @@
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use tracing::{debug, instrument};
+
+use crate::services::DirIndex;
+
+/// The kinds of the projects listed in Projects mode
+static PROJECT_KINDS: Lazy<RwLock<HashMap<PathBuf, Vec<ProjectKind>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// A type of project, recognized by its manifest file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProjectKind {
+    Rust,
+    Node,
+    Go,
+    Python,
+    Maven,
+    Gradle,
+    Ruby,
+    Php,
+}
+
+impl ProjectKind {
+    pub const ALL: &[ProjectKind] = &[
+        ProjectKind::Rust,
+        ProjectKind::Node,
+        ProjectKind::Go,
+        ProjectKind::Python,
+        ProjectKind::Maven,
+        ProjectKind::Gradle,
+        ProjectKind::Ruby,
+        ProjectKind::Php,
+    ];
+
+    /// The file that marks a directory as this kind of project
+    pub fn manifest(&self) -> &'static str {
+        match self {
+            ProjectKind::Rust => "Cargo.toml",
+            ProjectKind::Node => "package.json",
+            ProjectKind::Go => "go.mod",
+            ProjectKind::Python => "pyproject.toml",
+            ProjectKind::Maven => "pom.xml",
+            ProjectKind::Gradle => "build.gradle",
+            ProjectKind::Ruby => "Gemfile",
+            ProjectKind::Php => "composer.json",
+        }
+    }
+
+    /// Short label shown next to the project name
+    pub fn badge(&self) -> &'static str {
+        match self {
+            ProjectKind::Rust => "rust",
+            ProjectKind::Node => "node",
+            ProjectKind::Go => "go",
+            ProjectKind::Python => "python",
+            ProjectKind::Maven => "maven",
+            ProjectKind::Gradle => "gradle",
+            ProjectKind::Ruby => "ruby",
+            ProjectKind::Php => "php",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ProjectKind::Rust => Color::LightRed,
+            ProjectKind::Node => Color::Green,
+            ProjectKind::Go => Color::Cyan,
+            ProjectKind::Python => Color::Yellow,
+            ProjectKind::Maven | ProjectKind::Gradle => Color::LightMagenta,
+            ProjectKind::Ruby => Color::Red,
+            ProjectKind::Php => Color::Blue,
+        }
+    }
+
+    /// The name the manifest at `path` gives the project, if it has one
+    fn read_name(&self, path: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        let name = match self {
+            ProjectKind::Rust => toml_string(&content, &["package", "name"])
+                .or_else(|| toml_string(&content, &["workspace", "package", "name"])),
+            ProjectKind::Python => toml_string(&content, &["project", "name"])
+                .or_else(|| toml_string(&content, &["tool", "poetry", "name"])),
+            ProjectKind::Node | ProjectKind::Php => json_top_level_string(&content, "name"),
+            // The last part of the module path, e.g. `cobra` for `github.com/spf13/cobra`
+            ProjectKind::Go => content.lines().find_map(|line| {
+                let module = line.trim().strip_prefix("module ")?;
+                let module = module.trim().trim_matches('"');
+                module.rsplit('/').next().map(str::to_string)
+            }),
+            ProjectKind::Maven | ProjectKind::Gradle | ProjectKind::Ruby => None,
+        };
+        name.filter(|name| !name.trim().is_empty())
+    }
+}
+
+/// A directory with at least one project manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    pub path: PathBuf,
+    /// The name from the first manifest that has one, or the directory name
+    pub name: String,
+    pub kinds: Vec<ProjectKind>,
+}
+
+/// The projects among the indexed directories
+///
+/// Their kinds are kept for [`kinds`] to look up while rendering.
+#[instrument(skip(index))]
+pub fn load_projects(index: &DirIndex) -> Vec<Project> {
+    let mut by_path: BTreeMap<&Path, Vec<ProjectKind>> = BTreeMap::new();
+    for kind in ProjectKind::ALL {
+        for path in index.paths_with_marker(kind.manifest()) {
+            by_path.entry(path).or_default().push(*kind);
+        }
+    }
+
+    let projects: Vec<Project> = by_path
+        .into_iter()
+        .map(|(path, kinds)| project(path, kinds))
+        .collect();
+    debug!(count = projects.len(), "Found projects");
+
+    if let Ok(mut known) = PROJECT_KINDS.write() {
+        *known = projects
+            .iter()
+            .map(|project| (project.path.clone(), project.kinds.clone()))
+            .collect();
+    }
+    projects
+}
+
+fn project(path: &Path, kinds: Vec<ProjectKind>) -> Project {
+    let name = kinds
+        .iter()
+        .find_map(|kind| kind.read_name(&path.join(kind.manifest())))
+        .or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| path.display().to_string());
+    Project {
+        path: path.to_path_buf(),
+        name,
+        kinds,
+    }
+}
+
+/// The kinds of the project at `path`, as found by the last [`load_projects`]
+pub fn kinds(path: &Path) -> Vec<ProjectKind> {
+    PROJECT_KINDS
+        .read()
+        .ok()
+        .and_then(|known| known.get(path).cloned())
+        .unwrap_or_default()
+}
+
+/// The string at `keys` in a TOML document
+fn toml_string(content: &str, keys: &[&str]) -> Option<String> {
+    let mut value: &toml::Value = &toml::from_str::<toml::Value>(content).ok()?;
+    for key in keys {
+        value = value.get(key)?;
+    }
+    value.as_str().map(str::to_string)
+}
+
+/// The string value of `key` in the outermost object of a JSON document
+///
+/// Only strings are parsed, which is enough to skip over nested objects
+/// like `"author": { "name": ... }`.
+fn json_top_level_string(content: &str, key: &str) -> Option<String> {
+    let mut depth = 0;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            '"' => {
+                let text = read_json_string(&mut chars)?;
+                if depth != 1 || text != key {
+                    continue;
+                }
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next_if_eq(&':').is_none() {
+                    continue;
+                }
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                return match chars.next() {
+                    Some('"') => read_json_string(&mut chars),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Read a JSON string after its opening quote
+fn read_json_string(chars: &mut impl Iterator<Item = char>) -> Option<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(text),
+            '\\' => match chars.next()? {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                escaped => text.push(escaped),
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_names_from_manifests() {
+        let root = tempfile::tempdir().unwrap();
+        let web = root.path().join("web");
+        let tool = root.path().join("acme-tool");
+        fs::create_dir_all(&web).unwrap();
+        fs::create_dir_all(&tool).unwrap();
+        fs::write(
+            web.join("package.json"),
+            r#"{ "author": { "name": "me" }, "name": "@acme/web", "version": "1.0.0" }"#,
+        )
+        .unwrap();
+        fs::write(
+            tool.join("go.mod"),
+            "module github.com/acme/tool\n\ngo 1.22\n",
+        )
+        .unwrap();
+        fs::write(tool.join("Cargo.toml"), "[workspace]\nmembers = []\n").unwrap();
+
+        let web = project(&web, vec![ProjectKind::Node]);
+        assert_eq!(web.name, "@acme/web");
+        let tool = project(&tool, vec![ProjectKind::Rust, ProjectKind::Go]);
+        assert_eq!(tool.name, "tool");
+        assert_eq!(
+            toml_string("[package]\nname = \"quickswitch\"", &["package", "name"]).as_deref(),
+            Some("quickswitch")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use once_cell::sync::Lazy;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::RwLock,
+    thread,
+};
+use tracing::{debug, instrument};
+
+use crate::services::DirIndex;
+
+/// What is known about the repositories listed in Repos mode
+static REPO_INFO: Lazy<RwLock<HashMap<PathBuf, RepoInfo>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The branch and state of a git repository or worktree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoInfo {
+    /// The checked out branch, or the short commit id of a detached HEAD
+    pub branch: Option<String>,
+    /// Whether this is a linked worktree of another repository
+    pub worktree: bool,
+    /// Whether there are uncommitted changes, unknown until `git status` ran
+    pub dirty: Option<bool>,
+}
+
+/// The repositories and worktrees among the indexed directories
+///
+/// Worktrees outside the index roots are found through the repositories
+/// they belong to.
+#[instrument(skip(index))]
+pub fn find_repos(index: &DirIndex) -> Vec<PathBuf> {
+    let mut repos: BTreeSet<PathBuf> = index
+        .paths_with_marker(".git")
+        .map(Path::to_path_buf)
+        .collect();
+    let linked: Vec<PathBuf> = repos
+        .iter()
+        .flat_map(|repo| linked_worktrees(repo))
+        .collect();
+    repos.extend(linked);
+    debug!(count = repos.len(), "Found repositories");
+    repos.into_iter().collect()
+}
+
+/// The worktrees added to the repository at `repo` with `git worktree add`
+fn linked_worktrees(repo: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(repo.join(".git").join("worktrees")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        // `gitdir` holds the path of the `.git` file in the worktree
+        .filter_map(|entry| fs::read_to_string(entry.path().join("gitdir")).ok())
+        .filter_map(|gitdir| Path::new(gitdir.trim()).parent().map(Path::to_path_buf))
+        .filter(|worktree| worktree.is_dir())
+        .collect()
+}
+
+/// The git directory of `repo`, following the `.git` file of a worktree
+fn git_dir(repo: &Path) -> Option<(PathBuf, bool)> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some((dot_git, false));
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let gitdir = content.trim().strip_prefix("gitdir:")?.trim();
+    Some((repo.join(gitdir), true))
+}
+
+/// Read the branch and whether `repo` is a worktree, without running git
+pub fn read_info(repo: &Path) -> RepoInfo {
+    let Some((git_dir, worktree)) = git_dir(repo) else {
+        return RepoInfo::default();
+    };
+    let branch = fs::read_to_string(git_dir.join("HEAD")).ok().map(|head| {
+        match head.trim().strip_prefix("ref: ") {
+            Some(reference) => reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+            // A detached HEAD holds the commit id
+            None => head.trim().chars().take(7).collect(),
+        }
+    });
+    RepoInfo {
+        branch,
+        worktree,
+        dirty: None,
+    }
+}
+
+/// Whether `repo` has uncommitted changes, `None` if git can't tell
+fn is_dirty(repo: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["status", "--porcelain", "--untracked-files=normal"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then_some(!output.stdout.is_empty())
+}
+
+/// The known info of the repository at `repo`
+pub fn info(repo: &Path) -> Option<RepoInfo> {
+    REPO_INFO.read().ok()?.get(repo).cloned()
+}
+
+/// Read the branches of `repos` now and check their changes on a background thread
+pub fn load_info(repos: &[PathBuf]) {
+    let infos: HashMap<PathBuf, RepoInfo> = repos
+        .iter()
+        .map(|repo| (repo.clone(), read_info(repo)))
+        .collect();
+    if let Ok(mut known) = REPO_INFO.write() {
+        *known = infos;
+    }
+
+    let repos = repos.to_vec();
+    thread::spawn(move || {
+        for repo in repos {
+            let dirty = is_dirty(&repo);
+            if let Ok(mut known) = REPO_INFO.write()
+                && let Some(info) = known.get_mut(&repo)
+            {
+                info.dirty = dirty;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_info_of_repository_and_worktree() {
+        let root = tempfile::tempdir().unwrap();
+        let repo = root.path().join("app");
+        let worktree = root.path().join("app-fix");
+        let admin = repo.join(".git/worktrees/app-fix");
+        fs::create_dir_all(&admin).unwrap();
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(admin.join("HEAD"), "0123456789abcdef\n").unwrap();
+        fs::write(
+            admin.join("gitdir"),
+            format!("{}\n", worktree.join(".git").display()),
+        )
+        .unwrap();
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", admin.display()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_info(&repo),
+            RepoInfo {
+                branch: Some("main".to_string()),
+                worktree: false,
+                dirty: None,
+            }
+        );
+        assert_eq!(read_info(&worktree).branch.as_deref(), Some("0123456"));
+        assert!(read_info(&worktree).worktree);
+        assert_eq!(linked_worktrees(&repo), vec![worktree]);
+    }
+}
@@ -1,44 +1,326 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
+    cursor::{MoveTo, Show},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        self as crossterm_terminal, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement,
+    },
 };
+use once_cell::sync::Lazy;
 use ratatui::{
-    Frame, Terminal,
+    Frame, Terminal, TerminalOptions, Viewport,
     backend::CrosstermBackend,
+    buffer::Buffer,
     layout::Rect,
+    style::{Color, Modifier},
     widgets::{Block, Borders, Paragraph},
 };
-use std::io;
+use std::{
+    io::{self, BufWriter, IsTerminal, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    App,
+    config::get_config,
+    core::{events, signals},
+    modes::{
+        Renderer, help_overlay::HelpOverlayRenderer, message_overlay::MessageOverlayRenderer,
+        pager::PagerRenderer,
+    },
+    output::OutputFormat,
+    services::{
+        HistoryStore, PreviewManager, create_data_provider, dir_index, history_store,
+        preview::GLOBAL_PICKER,
+    },
+    utils::AppMode,
+};
+
+/// Options for an interactive session
+#[derive(Debug, Clone, Default)]
+pub struct InteractiveOptions {
+    /// Initial search query, applied before the first frame
+    pub query: Option<String>,
+    /// Command to run on the selection after the TUI exits, `{}` marks the paths
+    pub exec: Option<String>,
+    /// Draw inline below the prompt with this height instead of on the alternate screen
+    pub height: Option<Height>,
+    /// Report the selected paths themselves rather than the directory to change to
+    pub print_selection: bool,
+    /// Print the result on stdout and draw the UI on stderr, so shells can
+    /// capture it without redirecting stderr
+    pub stdout: bool,
+    /// Print a bare path or a JSON object describing the selection
+    pub output: OutputFormat,
+    /// End each printed path with NUL instead of a newline
+    pub print0: bool,
+    /// Write the directory to change to into this file instead of printing it
+    pub cd_file: Option<PathBuf>,
+    /// Change to the browsed directory when quitting without a selection
+    pub last_dir_on_exit: bool,
+    /// Directory to start browsing in instead of the current directory
+    pub start_dir: Option<PathBuf>,
+    /// Keep the selection in [`App::selection`] instead of printing it and
+    /// exiting, for a [`crate::Picker`]
+    pub embedded: bool,
+}
+
+/// How long the main loop waits for input before redrawing
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shorter wait while a background search streams in matches
+const FILTER_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Smallest inline viewport, so a percentage of a short terminal stays usable
+const MIN_INLINE_HEIGHT: u16 = 10;
+
+/// Height of the inline viewport given with `--height`, like `40%` or `20`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Lines(u16),
+    Percent(u16),
+}
+
+impl Height {
+    /// Number of rows to use on a terminal with `terminal_rows` rows
+    pub fn rows(self, terminal_rows: u16) -> u16 {
+        let rows = match self {
+            Height::Lines(lines) => lines,
+            Height::Percent(percent) => {
+                let rows = (u32::from(terminal_rows) * u32::from(percent) / 100) as u16;
+                rows.max(MIN_INLINE_HEIGHT)
+            }
+        };
+        rows.clamp(1, terminal_rows.max(1))
+    }
+}
+
+impl FromStr for Height {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid =
+            || format!("invalid height '{value}', expected lines like 20 or a percentage like 40%");
+        let height = match value.trim().strip_suffix('%') {
+            Some(percent) => match percent.parse() {
+                Ok(percent @ 1..=100) => Height::Percent(percent),
+                _ => return Err(invalid()),
+            },
+            None => match value.trim().parse() {
+                Ok(lines @ 1..) => Height::Lines(lines),
+                _ => return Err(invalid()),
+            },
+        };
+        Ok(height)
+    }
+}
 
-use crate::{App, core::events, utils::AppMode};
+pub async fn run_interactive_mode(mode: AppMode, options: InteractiveOptions) -> Result<()> {
+    signals::install_signal_handlers();
+    let result = run_session(mode, options).await;
+    if let Some(code) = signals::exit_code() {
+        std::process::exit(code);
+    }
+    result.map(drop)
+}
+
+/// Set up the terminal, run the app in `mode` until it is left and put the
+/// terminal back, handing back the app as it ended
+pub(crate) async fn run_session(mode: AppMode, options: InteractiveOptions) -> Result<App> {
+    // The picker prints its result on stdout, so the UI goes to stderr like fzf
+    DRAW_ON_STDERR.store(mode == AppMode::Picker || options.stdout, Ordering::SeqCst);
+    let mut terminal = match setup_terminal(options.height) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            // Don't leave the shell in raw mode when setup fails halfway
+            let _ = restore_terminal();
+            return Err(e);
+        }
+    };
+    let result = match App::new(mode, options) {
+        Ok(mut app) => run_app_loop(&mut terminal, &mut app).await.map(|()| app),
+        Err(e) => Err(e),
+    };
+    // After a hangup the terminal may already be gone, so keep going on failure
+    let cleanup = restore_terminal();
+    history_store::flush();
 
-pub async fn run_interactive_mode(mode: AppMode) -> Result<()> {
-    let mut terminal = setup_terminal()?;
-    let mut app = App::new(mode)?;
-    let result = run_app_loop(&mut terminal, &mut app).await;
-    cleanup_terminal(&mut terminal)?;
+    if !signals::shutdown_requested() {
+        cleanup?;
+    }
     result
 }
 
-pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
+/// Whether the terminal was asked to report modifiers on keys like Enter
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the UI is drawn on stderr instead of stdout
+static DRAW_ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Top row of the inline viewport, `None` when drawing on the alternate screen
+static INLINE_VIEWPORT_TOP: Mutex<Option<u16>> = Mutex::new(None);
+
+/// Whether the terminal is set up for the UI, as opposed to restored or
+/// handed to a command
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the next frame has to be drawn in full, after a command drew over the UI
+static REDRAW_ALL: AtomicBool = AtomicBool::new(false);
+
+/// The stream the UI is drawn on
+///
+/// Stderr is unbuffered, so it gets a buffer to keep a frame from turning into
+/// thousands of small writes.
+pub enum TerminalOutput {
+    Stdout(io::Stdout),
+    Stderr(BufWriter<io::Stderr>),
+}
+
+impl TerminalOutput {
+    pub fn new() -> Self {
+        if DRAW_ON_STDERR.load(Ordering::SeqCst) {
+            Self::Stderr(BufWriter::new(io::stderr()))
+        } else {
+            Self::Stdout(io::stdout())
+        }
+    }
+}
+
+impl Default for TerminalOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for TerminalOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+        }
+    }
+}
+
+/// Prepare the terminal, inline below the prompt when a height is given
+pub fn setup_terminal(
+    height: Option<Height>,
+) -> Result<Terminal<CrosstermBackend<TerminalOutput>>> {
+    let mut output = TerminalOutput::new();
+    enter_ui(&mut output, height.is_some())?;
+    let Some(height) = height else {
+        *INLINE_VIEWPORT_TOP.lock().unwrap() = None;
+        return Ok(Terminal::new(CrosstermBackend::new(output))?);
+    };
+
+    let (columns, rows) = crossterm_terminal::size()?;
+    let viewport_rows = height.rows(rows);
+    let viewport = if io::stdout().is_terminal() {
+        Viewport::Inline(viewport_rows)
+    } else {
+        // Finding the cursor for an inline viewport writes a query to stdout,
+        // which is a pipe here, so scroll up and anchor to the bottom instead
+        output.write_all(&b"\n".repeat(viewport_rows as usize))?;
+        output.flush()?;
+        Viewport::Fixed(Rect::new(
+            0,
+            rows.saturating_sub(viewport_rows),
+            columns,
+            viewport_rows,
+        ))
+    };
+    let mut terminal =
+        Terminal::with_options(CrosstermBackend::new(output), TerminalOptions { viewport })?;
+    *INLINE_VIEWPORT_TOP.lock().unwrap() = Some(terminal.get_frame().area().y);
     Ok(terminal)
 }
 
-pub fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+/// Switch to raw mode, mouse capture and the alternate screen unless `inline`
+fn enter_ui(output: &mut TerminalOutput, inline: bool) -> Result<()> {
+    enable_raw_mode()?;
+    if inline {
+        execute!(output, EnableMouseCapture)?;
+    } else {
+        execute!(output, EnterAlternateScreen, EnableMouseCapture)?;
+    }
+    // Needed to tell Ctrl+Enter apart from Enter on terminals that support it.
+    // The support query is written to stdout, so skip it when that is a pipe.
+    if io::stdout().is_terminal() && matches!(supports_keyboard_enhancement(), Ok(true)) {
+        execute!(
+            output,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+        KEYBOARD_ENHANCED.store(true, Ordering::SeqCst);
+    }
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Hand the terminal over to a command run from the UI
+///
+/// Returns whether there was a terminal to hand over, which isn't the case
+/// when the app is drawn on a test backend.
+pub fn suspend_terminal() -> Result<bool> {
+    if !TERMINAL_ACTIVE.load(Ordering::SeqCst) {
+        return Ok(false);
+    }
+    restore_terminal()?;
+    Ok(true)
+}
+
+/// Take the terminal back after [`suspend_terminal`], the next frame is
+/// drawn in full
+pub fn resume_terminal() -> Result<()> {
+    let inline = INLINE_VIEWPORT_TOP.lock().unwrap().is_some();
+    enter_ui(&mut TerminalOutput::new(), inline)?;
+    REDRAW_ALL.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Undo the keyboard enhancement enabled by [`setup_terminal`], if any
+pub fn pop_keyboard_enhancement() -> Result<()> {
+    if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+        execute!(TerminalOutput::new(), PopKeyboardEnhancementFlags)?;
+    }
+    Ok(())
+}
+
+/// Put the terminal back the way [`setup_terminal`] found it
+///
+/// The inline viewport is erased so the prompt continues right where the UI was.
+pub fn restore_terminal() -> Result<()> {
+    TERMINAL_ACTIVE.store(false, Ordering::SeqCst);
+    pop_keyboard_enhancement()?;
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let mut output = TerminalOutput::new();
+    match *INLINE_VIEWPORT_TOP.lock().unwrap() {
+        Some(top) => execute!(
+            output,
+            MoveTo(0, top),
+            Clear(ClearType::FromCursorDown),
+            DisableMouseCapture,
+            Show
+        )?,
+        None => execute!(output, LeaveAlternateScreen, DisableMouseCapture, Show)?,
+    }
     Ok(())
 }
 
@@ -50,29 +332,39 @@ where
     W: std::io::Write,
 {
     loop {
-        // Update layout if terminal size changed
-        let terminal_size = terminal.size()?;
-        let terminal_area = Rect::new(0, 0, terminal_size.width, terminal_size.height);
-
-        if app.state.layout.needs_update(terminal_area) {
-            app.state.update_layout(terminal_area);
+        // Leave the loop so the caller restores the terminal before exiting
+        if signals::shutdown_requested() {
+            break;
         }
 
+        // The inline viewport only covers part of the screen
+        prepare_frame(app, terminal.get_frame().area());
+        if REDRAW_ALL.swap(false, Ordering::SeqCst) {
+            terminal.clear()?;
+        }
         terminal.draw(|f| render_ui(f, app))?;
 
-        if event::poll(std::time::Duration::from_millis(100))? {
+        // Query the image protocol once the first frame is on screen, the
+        // terminal may take a while to answer. Input isn't read until then,
+        // so the answer can't be mistaken for keys.
+        Lazy::force(&GLOBAL_PICKER);
+
+        // Come back sooner while a background search has more to show
+        let timeout = if app.state.is_filtering() {
+            FILTER_POLL_INTERVAL
+        } else {
+            EVENT_POLL_INTERVAL
+        };
+        if event::poll(timeout)? {
             match event::read()? {
-                Event::Key(key) => {
+                Event::Key(key)
                     if key.kind == KeyEventKind::Press
-                        && !events::handle_key_event(app, key).await?
-                    {
-                        break;
-                    }
+                        && !events::handle_key_event(app, key).await? =>
+                {
+                    break;
                 }
-                Event::Mouse(mouse) => {
-                    if !events::handle_mouse_event(app, mouse).await? {
-                        break;
-                    }
+                Event::Mouse(mouse) if !events::handle_mouse_event(app, mouse).await? => {
+                    break;
                 }
                 _ => {}
             }
@@ -81,13 +373,68 @@ where
     Ok(())
 }
 
+/// Catch up on what changed since the last frame, before drawing one on an
+/// `area` of the screen
+pub(crate) fn prepare_frame(app: &mut App, area: Rect) {
+    // Update layout if terminal size changed
+    if app.state.layout.needs_update(area) {
+        app.state.update_layout(area);
+    }
+
+    // Expire notifications that have been on screen long enough
+    app.state.messages.tick();
+
+    // Show what a background search found so far
+    if app.state.poll_filter_job() {
+        PreviewManager::preview_changed_selection(&app.state);
+    }
+
+    // Show the directories a background index refresh found
+    let mode = app.mode_manager.current_mode;
+    if dir_index::take_update()
+        && matches!(mode, AppMode::Jump | AppMode::Repos | AppMode::Projects)
+        && let Err(e) = create_data_provider(&mode).load_data(&mut app.state)
+    {
+        app.state
+            .messages
+            .error(format!("Failed to load the directory index: {e:#}"));
+    }
+
+    // Show the history a mode started reading in the background
+    let store = match mode {
+        AppMode::History => Some(HistoryStore::DIRECTORIES),
+        AppMode::RecentFiles => Some(HistoryStore::RECENT_FILES),
+        _ => None,
+    };
+    if store.is_some_and(HistoryStore::is_loaded)
+        && let Err(e) = create_data_provider(&mode).load_data(&mut app.state)
+    {
+        app.state
+            .messages
+            .error(format!("Failed to load the history: {e:#}"));
+    }
+}
+
 /// Simple UI rendering function that delegates to mode manager
-fn render_ui(f: &mut Frame, app: &App) {
+pub(crate) fn render_ui(f: &mut Frame, app: &App) {
+    // The pager covers everything but notifications
+    if app.state.pager.is_some() {
+        PagerRenderer::new().render(f, f.area(), &app.state);
+        MessageOverlayRenderer::new().render(f, f.area(), &app.state);
+        if !get_config().ui.color {
+            strip_colors(f.buffer_mut());
+        }
+        return;
+    }
+
     // Use the layout manager from app state
     let layout = &app.state.layout;
 
     // Render search box
-    let (title, content, style) = app.mode_manager.get_search_box_config(&app.state);
+    let (mut title, content, style) = app.mode_manager.get_search_box_config(&app.state);
+    if let Some(pending) = app.state.pending_input() {
+        title.push_str(&format!(" [{pending}]"));
+    }
     let search_box = Paragraph::new(content)
         .block(Block::default().borders(Borders::ALL).title(title))
         .style(style);
@@ -96,15 +443,67 @@ fn render_ui(f: &mut Frame, app: &App) {
     // Delegate rendering to app using layout areas
     app.mode_manager
         .render_left_panel(f, layout.get_left_area(), &app.state);
+    if layout.is_preview_visible() {
+        app.mode_manager
+            .render_right_panel(f, layout.get_right_area(), &app.state);
+    }
     app.mode_manager
-        .render_right_panel(f, layout.get_right_area(), &app.state);
+        .render_other_pane(f, layout.get_other_area(), &app.state);
 
-    // Set cursor position when searching
-    if app.state.is_searching {
+    // Set cursor position when typing an alias or searching
+    if let Some(alias) = &app.state.alias_input {
+        let search_area = layout.get_search_area();
+        f.set_cursor_position((search_area.x + alias.width() as u16 + 1, search_area.y + 1));
+    } else if app.state.is_searching {
         let search_area = layout.get_search_area();
         f.set_cursor_position((
-            search_area.x + app.state.search_input.len() as u16 + 1,
+            search_area.x + app.state.search_cursor_width() + 1,
             search_area.y + 1,
         ));
     }
+
+    MessageOverlayRenderer::new().render(f, f.area(), &app.state);
+
+    if app.state.show_help_overlay {
+        HelpOverlayRenderer::new(*app.mode_manager.get_current_mode()).render(
+            f,
+            f.area(),
+            &app.state,
+        );
+    }
+
+    if !get_config().ui.color {
+        strip_colors(f.buffer_mut());
+    }
+}
+
+/// Remove all colors from the rendered buffer
+///
+/// Cells with a background color (selection, search matches, status bars) are
+/// switched to reverse video so they stay distinguishable without color.
+fn strip_colors(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_height() {
+        assert_eq!("40%".parse(), Ok(Height::Percent(40)));
+        assert_eq!("20".parse(), Ok(Height::Lines(20)));
+        assert!("0".parse::<Height>().is_err());
+        assert!("150%".parse::<Height>().is_err());
+
+        assert_eq!(Height::Percent(40).rows(50), 20);
+        assert_eq!(Height::Percent(10).rows(30), MIN_INLINE_HEIGHT);
+        assert_eq!(Height::Lines(100).rows(30), 30);
+    }
 }
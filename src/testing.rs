@@ -0,0 +1,308 @@
+//! Drive the UI without a terminal, for integration tests
+//!
+//! A [`Harness`] runs the app like the main loop does, with keys and mouse
+//! events handed in by the test and frames drawn on a
+//! [`ratatui::backend::TestBackend`]:
+//!
+//! ```no_run
+//! use quickswitch::{AppMode, InteractiveOptions, testing::Harness};
+//!
+//! # async fn test() -> quickswitch::Result<()> {
+//! let options = InteractiveOptions {
+//!     start_dir: Some("/tmp".into()),
+//!     ..InteractiveOptions::default()
+//! };
+//! let mut harness = Harness::new(AppMode::Normal, options, 80, 24)?;
+//! harness.keys("/notes<Enter>").await?;
+//! harness.settle().await?;
+//! assert!(harness.snapshot()?.contains("notes"));
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Context, bail};
+use crossterm::event::{KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    App, Result,
+    core::{events, signals},
+    embed::Selection,
+    services::{dir_index, history_store::HistoryStore, macros},
+    terminal::{self, InteractiveOptions},
+    utils::AppMode,
+};
+
+/// How long [`Harness::settle`] waits for background work
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often [`Harness::settle`] checks on background work
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The app running on a screen of a fixed size, without a terminal
+///
+/// The app is embedded, so leaving it sets [`Harness::selection`] instead of
+/// exiting the process. The preview panel starts hidden: previews are made in
+/// the background and shared by the whole process, so snapshots with them
+/// would depend on timing and on other tests.
+pub struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    running: bool,
+}
+
+impl Harness {
+    /// Start the app in `mode` on a screen of `width` by `height` cells
+    pub fn new(
+        mode: AppMode,
+        options: InteractiveOptions,
+        width: u16,
+        height: u16,
+    ) -> Result<Self> {
+        let options = InteractiveOptions {
+            embedded: true,
+            ..options
+        };
+        let mut app = App::new(mode, options)?;
+        if app.state.layout.is_preview_visible() {
+            app.state.layout.toggle_preview();
+        }
+        Ok(Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            running: true,
+        })
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Whether the app is still running, it stops once something is picked
+    /// or it is left
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// What was picked, once the app stopped with a selection
+    pub fn selection(&self) -> Option<&Selection> {
+        self.app.selection.as_ref()
+    }
+
+    /// Press `key`, after drawing a frame like the main loop does before it
+    /// reads input
+    pub async fn key(&mut self, key: KeyEvent) -> Result<()> {
+        self.ensure_running()?;
+        self.draw()?;
+        let running = events::handle_key_event(&mut self.app, key).await?;
+        self.stop_unless(running);
+        Ok(())
+    }
+
+    /// Press keys written in vim's notation, e.g. `jj/src<Enter>`
+    pub async fn keys(&mut self, notation: &str) -> Result<()> {
+        for key in macros::parse_keys(notation)? {
+            self.key(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Hand in a mouse event, after drawing a frame
+    pub async fn mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        self.ensure_running()?;
+        self.draw()?;
+        let running = events::handle_mouse_event(&mut self.app, mouse).await?;
+        self.stop_unless(running);
+        Ok(())
+    }
+
+    /// Press and release the left button on a cell
+    pub async fn click(&mut self, column: u16, row: u16) -> Result<()> {
+        for kind in [
+            MouseEventKind::Down(MouseButton::Left),
+            MouseEventKind::Up(MouseButton::Left),
+        ] {
+            self.mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Wait until background searches and the history or directory index
+    /// the current mode reads are done, and pick up what they found
+    pub async fn settle(&mut self) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            let busy = self.is_busy();
+            terminal::prepare_frame(&mut self.app, self.terminal.get_frame().area());
+            if !busy {
+                return Ok(());
+            }
+            if start.elapsed() > SETTLE_TIMEOUT {
+                bail!("Background work still running after {SETTLE_TIMEOUT:?}");
+            }
+            tokio::time::sleep(SETTLE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Draw a frame and hand back what is on screen
+    pub fn draw(&mut self) -> Result<&Buffer> {
+        let app = &mut self.app;
+        terminal::prepare_frame(app, self.terminal.get_frame().area());
+        self.terminal
+            .draw(|f| terminal::render_ui(f, app))
+            .context("Failed to draw on the test backend")?;
+        Ok(self.terminal.backend().buffer())
+    }
+
+    /// Draw a frame and hand back its text, one line per row without
+    /// trailing spaces
+    pub fn snapshot(&mut self) -> Result<String> {
+        Ok(buffer_text(self.draw()?))
+    }
+
+    fn is_busy(&self) -> bool {
+        let loading = match self.app.mode_manager.current_mode {
+            AppMode::History => HistoryStore::DIRECTORIES.is_loading(),
+            AppMode::RecentFiles => HistoryStore::RECENT_FILES.is_loading(),
+            AppMode::Jump | AppMode::Repos | AppMode::Projects => dir_index::is_refreshing(),
+            _ => false,
+        };
+        loading || self.app.state.is_filtering()
+    }
+
+    fn ensure_running(&self) -> Result<()> {
+        if !self.running {
+            bail!("The app was already left");
+        }
+        Ok(())
+    }
+
+    fn stop_unless(&mut self, running: bool) {
+        if !running {
+            self.running = false;
+            // Ctrl+C only stops this app, not the tests
+            signals::clear_shutdown_request();
+        }
+    }
+}
+
+/// The text of `buffer`, one line per row without trailing spaces
+///
+/// The cells covered by a wide character are skipped, so the lines read like
+/// the screen.
+pub fn buffer_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut lines = Vec::with_capacity(area.height as usize);
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            covered = symbol.width().saturating_sub(1);
+            line.push_str(symbol);
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modes::picker;
+    use crossterm::event::KeyCode;
+    use ratatui::{layout::Rect, style::Style};
+    use std::{fs, path::PathBuf};
+
+    #[tokio::test]
+    async fn test_search_filters_the_listing() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["alpha", "beta", "gamma"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        let options = InteractiveOptions {
+            start_dir: Some(root.path().to_path_buf()),
+            ..InteractiveOptions::default()
+        };
+        let mut harness = Harness::new(AppMode::Normal, options, 60, 12).unwrap();
+        harness.settle().await.unwrap();
+        let screen = harness.snapshot().unwrap();
+        assert!(screen.contains("(3/3)"), "{screen}");
+
+        harness.keys("/bet").await.unwrap();
+        harness.settle().await.unwrap();
+        let screen = harness.snapshot().unwrap();
+        assert!(screen.contains("(1/3)"), "{screen}");
+        assert!(
+            screen.contains("beta") && !screen.contains("gamma"),
+            "{screen}"
+        );
+
+        harness.keys("<Esc><Esc><Esc>").await.unwrap();
+        assert!(!harness.is_running());
+        assert_eq!(harness.selection(), None);
+    }
+
+    #[tokio::test]
+    async fn test_type_ahead_takes_bound_keys() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["apple", "lib", "sjx", "soap"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        let options = InteractiveOptions {
+            start_dir: Some(root.path().to_path_buf()),
+            ..InteractiveOptions::default()
+        };
+        let mut harness = Harness::new(AppMode::Normal, options, 60, 12).unwrap();
+        harness.settle().await.unwrap();
+
+        // `j` extends the jump to `sj` instead of moving down
+        harness.keys("so").await.unwrap();
+        assert_eq!(harness.app().state.file_list_state.selected(), Some(3));
+        harness.keys("<Esc>sj").await.unwrap();
+        assert_eq!(harness.app().state.file_list_state.selected(), Some(2));
+
+        // `,` starts a jump to a name beginning with the bound `l`
+        harness.keys("<Esc>,li").await.unwrap();
+        assert_eq!(harness.app().state.file_list_state.selected(), Some(1));
+        assert!(harness.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_pick_an_entry() {
+        picker::set_entries(vec!["alpha".to_string(), "beta".to_string()]);
+        let mut harness =
+            Harness::new(AppMode::Picker, InteractiveOptions::default(), 60, 12).unwrap();
+        harness.keys("/bet<Enter>").await.unwrap();
+        assert!(!harness.is_running());
+        let selection = harness.selection().unwrap();
+        assert_eq!(selection.path, PathBuf::from("beta"));
+        assert_eq!(selection.mode, AppMode::Picker);
+        assert_eq!(selection.query, "bet");
+        assert!(harness.key(KeyEvent::from(KeyCode::Enter)).await.is_err());
+    }
+
+    #[test]
+    fn test_buffer_text_skips_covered_cells() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "a文b", Style::default());
+        buffer.set_string(0, 1, "x", Style::default());
+        assert_eq!(buffer_text(&buffer), "a文b\nx");
+    }
+}
@@ -1,18 +1,28 @@
 use anyhow::{Ok, Result};
 use chrono::{DateTime, Utc};
 use clap::ValueEnum;
+use globset::{GlobBuilder, GlobMatcher};
 use ratatui::{
     style::{Color, Style},
     text::Span,
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
+    fs,
     io::IsTerminal,
+    ops::Range,
     path::{Path, PathBuf},
 };
 use tracing::{debug, error, instrument};
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+use crate::{
+    config::{CaseMatching, DecayFunction, HistoryConfig},
+    modes::history::HistoryDataProvider,
+    services::{Aliases, DirIndex, FilesystemService},
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum ShellType {
     /// Bash shell
     Bash,
@@ -32,10 +42,26 @@ pub fn is_tty() -> bool {
         && std::io::stderr().is_terminal()
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum AppMode {
-    Normal,  // Default navigation mode (command mode)
-    History, // History selection mode
+    /// Browse the file system
+    #[default]
+    Normal,
+    /// Visited directories, ranked by frecency
+    History,
+    /// Generic list picker over entries read from stdin
+    #[value(skip)]
+    Picker,
+    /// Recently picked files
+    RecentFiles,
+    /// Directories from the index under the configured roots
+    Jump,
+    /// Git repositories and worktrees found in the index
+    Repos,
+    /// Directories with a project manifest found in the index
+    Projects,
+    /// Mounted filesystems and drives
+    Mounts,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -44,6 +70,9 @@ pub struct HistoryEntry {
     pub frequency: u32,
     pub last_accessed: DateTime<Utc>,
     pub first_accessed: DateTime<Utc>,
+    /// Short name for the entry, stored separately in [`crate::services::Aliases`]
+    #[serde(skip)]
+    pub alias: Option<String>,
 }
 
 impl HistoryEntry {
@@ -54,6 +83,7 @@ impl HistoryEntry {
             frequency: 1,
             last_accessed: now,
             first_accessed: now,
+            alias: None,
         }
     }
 
@@ -63,24 +93,29 @@ impl HistoryEntry {
     }
 
     /// Calculate score for sorting (frequency with time decay)
-    pub fn calculate_score(&self, time_decay_days: u32) -> f64 {
+    pub fn calculate_score(&self, config: &HistoryConfig) -> f64 {
         let frequency_weight = self.frequency as f64;
-        let time_decay = self.calculate_time_decay(time_decay_days);
+        let time_decay = self.calculate_time_decay(config);
         frequency_weight * time_decay
     }
 
-    fn calculate_time_decay(&self, decay_days: u32) -> f64 {
+    fn calculate_time_decay(&self, config: &HistoryConfig) -> f64 {
         let days_since_access = (Utc::now() - self.last_accessed).num_days();
         if days_since_access <= 0 {
-            1.0
-        } else {
-            let decay_factor = days_since_access as f64 / decay_days as f64;
-            (1.0 - decay_factor.min(1.0)).max(0.1) // Minimum 10% weight
+            return 1.0;
         }
+
+        let decay_factor = days_since_access as f64 / config.time_decay_days.max(1) as f64;
+        let weight = match config.decay {
+            DecayFunction::Linear => 1.0 - decay_factor.min(1.0),
+            DecayFunction::Exponential => 0.5_f64.powf(decay_factor),
+        };
+        weight.max(config.min_decay.clamp(0.0, 1.0))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HistorySortMode {
     Frequency,       // Sort by frequency only
     Recent,          // Sort by last accessed time
@@ -107,6 +142,14 @@ impl DisplayItem {
         }
     }
 
+    /// The alias of a history entry
+    pub fn get_alias(&self) -> Option<&str> {
+        match self {
+            DisplayItem::File(_) => None,
+            DisplayItem::History(entry) => entry.alias.as_deref(),
+        }
+    }
+
     pub fn get_path(&self) -> &PathBuf {
         match self {
             DisplayItem::File(file) => &file.path,
@@ -117,33 +160,179 @@ impl DisplayItem {
     pub fn is_directory(&self) -> bool {
         match self {
             DisplayItem::File(file) => file.is_dir,
-            DisplayItem::History(entry) => entry.path.is_dir(),
+            DisplayItem::History(entry) => FilesystemService::is_dir(&entry.path),
         }
     }
 }
 
+/// The kind of filesystem entry, used for coloring
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileKind {
+    #[default]
+    Regular,
+    Directory,
+    Executable,
+    Symlink,
+    BrokenSymlink,
+    /// FIFOs, sockets and device files
+    Special,
+}
+
+impl FileKind {
+    /// The kind of the entry at `path` from its type, not following symlinks,
+    /// and whether it leads to a directory
+    ///
+    /// `metadata` is the entry's own metadata when it was already read. Only
+    /// symlinks and, on Unix, regular files without it need another stat.
+    fn classify(
+        path: &Path,
+        file_type: fs::FileType,
+        metadata: Option<&fs::Metadata>,
+    ) -> (Self, bool) {
+        if file_type.is_symlink() {
+            match path.metadata().ok() {
+                Some(target) => (FileKind::Symlink, target.is_dir()),
+                None => (FileKind::BrokenSymlink, false),
+            }
+        } else if file_type.is_dir() {
+            (FileKind::Directory, true)
+        } else if !file_type.is_file() {
+            (FileKind::Special, false)
+        } else if Self::is_executable(path, metadata) {
+            (FileKind::Executable, false)
+        } else {
+            (FileKind::Regular, false)
+        }
+    }
+
+    /// Whether the file at `path` is executable, reading its metadata only
+    /// when none is given
+    #[cfg(unix)]
+    fn is_executable(path: &Path, metadata: Option<&fs::Metadata>) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = match metadata {
+            Some(metadata) => Some(metadata.permissions().mode()),
+            None => path
+                .symlink_metadata()
+                .ok()
+                .map(|metadata| metadata.permissions().mode()),
+        };
+        mode.is_some_and(|mode| mode & 0o111 != 0)
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(path: &Path, _metadata: Option<&fs::Metadata>) -> bool {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        matches!(
+            extension.as_deref(),
+            Some("exe") | Some("bat") | Some("cmd") | Some("com") | Some("ps1")
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FileItem {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
+    pub kind: FileKind,
+    /// Hidden by a leading dot, or by the hidden or system attribute on Windows
+    pub hidden: bool,
 }
 
 impl FileItem {
-    pub fn from_path(path: &Path) -> Self {
-        let name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or_default()
-            .to_string();
-        let is_dir = path.is_dir();
+    /// Create a file item for a directory entry, detecting its type
+    pub fn new(name: String, path: PathBuf) -> Self {
+        let metadata = path.symlink_metadata().ok();
+        let (kind, is_dir) = match &metadata {
+            Some(metadata) => FileKind::classify(&path, metadata.file_type(), Some(metadata)),
+            None => (FileKind::Regular, false),
+        };
+        let hidden = name.starts_with('.') || Self::has_hidden_attribute(&path, metadata.as_ref());
+        Self {
+            name,
+            path,
+            is_dir,
+            kind,
+            hidden,
+        }
+    }
+
+    /// Create a file item for an entry of a directory listing
+    ///
+    /// Listings report the type of their entries on most platforms, so only
+    /// symlinks, regular files on Unix (for the executable bit) and entries on
+    /// Windows (for the hidden attribute) need another stat. Without a type
+    /// this is the same as [`FileItem::new`].
+    pub fn from_listing(name: String, path: PathBuf, file_type: Option<fs::FileType>) -> Self {
+        let Some(file_type) = file_type else {
+            return Self::new(name, path);
+        };
+        let needs_metadata = cfg!(windows) || (cfg!(unix) && file_type.is_file());
+        let metadata = needs_metadata
+            .then(|| path.symlink_metadata().ok())
+            .flatten();
+        let (kind, is_dir) = FileKind::classify(&path, file_type, metadata.as_ref());
+        let hidden = name.starts_with('.') || Self::has_hidden_attribute(&path, metadata.as_ref());
         Self {
             name,
-            path: path.to_path_buf(),
+            path,
             is_dir,
+            kind,
+            hidden,
         }
     }
 
+    /// Whether the entry has the hidden or system attribute, reading its
+    /// metadata only when none is given
+    #[cfg(windows)]
+    fn has_hidden_attribute(path: &Path, metadata: Option<&fs::Metadata>) -> bool {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+        let attributes = match metadata {
+            Some(metadata) => Some(metadata.file_attributes()),
+            None => path
+                .symlink_metadata()
+                .ok()
+                .map(|metadata| metadata.file_attributes()),
+        };
+        attributes.is_some_and(|attributes| {
+            attributes & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+        })
+    }
+
+    #[cfg(not(windows))]
+    fn has_hidden_attribute(_path: &Path, _metadata: Option<&fs::Metadata>) -> bool {
+        false
+    }
+
+    /// Create a file item for a path, named after its last component
+    ///
+    /// Roots like `\\server\share\` are named after the whole path. A path on
+    /// a share that doesn't answer is assumed to be a directory instead of
+    /// waiting on it.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let owned = path.to_path_buf();
+        let item_name = name.clone();
+        FilesystemService::with_network_timeout(path, move || Self::new(item_name, owned))
+            .unwrap_or_else(|_| Self {
+                name,
+                path: path.to_path_buf(),
+                is_dir: true,
+                kind: FileKind::Directory,
+                hidden: false,
+            })
+    }
+
     /// Check if the file is an image based on its extension
     pub fn is_image(&self) -> bool {
         if self.is_dir {
@@ -187,42 +376,404 @@ impl FileItem {
     }
 }
 
-pub fn highlight_search_term<'a>(text: &'a str, search: &'a str) -> Vec<Span<'a>> {
-    if search.is_empty() {
-        return vec![Span::raw(text)];
+/// Format a byte count for humans, like `1.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
     }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
 
-    let search_lower = search.to_lowercase();
-    let text_lower = text.to_lowercase();
-    let mut spans = Vec::new();
-    let mut last_end = 0;
+/// Compare two strings so that embedded numbers are ordered by value
+///
+/// Runs of ASCII digits are compared numerically (`file2` < `file10`), everything
+/// else is compared character by character.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
 
-    while let Some(start) = text_lower[last_end..].find(&search_lower) {
-        let actual_start = last_end + start;
-        let actual_end = actual_start + search.len();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let a_digits = take_digits(&mut a_chars);
+                let b_digits = take_digits(&mut b_chars);
+                let a_trimmed = a_digits.trim_start_matches('0');
+                let b_trimmed = b_digits.trim_start_matches('0');
+                let ordering = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                if ca != cb {
+                    return ca.cmp(&cb);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
 
-        if actual_start > last_end {
-            spans.push(Span::raw(&text[last_end..actual_start]));
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
         }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+/// Split `text` into spans with the byte `ranges` highlighted
+pub fn highlight_matches(text: &str, ranges: Vec<Range<usize>>) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut last_end = 0;
 
+    for range in ranges {
+        if range.start > last_end {
+            spans.push(Span::raw(&text[last_end..range.start]));
+        }
+        last_end = range.end;
         spans.push(Span::styled(
-            &text[actual_start..actual_end],
+            &text[range],
             Style::default().fg(Color::Black).bg(Color::Yellow),
         ));
-
-        last_end = actual_end;
     }
 
-    if last_end < text.len() {
+    if last_end < text.len() || spans.is_empty() {
         spans.push(Span::raw(&text[last_end..]));
     }
 
     spans
 }
 
-pub fn run_non_interactive() -> Result<()> {
-    println!("{}", std::env::current_dir()?.display());
-    Ok(())
+/// Whether `search` is a glob pattern like `*.rs` or `src/**/*.toml`
+pub fn is_glob_query(search: &str) -> bool {
+    search.contains(['*', '?', '['])
+}
+
+/// Compile a glob query, `None` if it isn't valid glob syntax
+///
+/// Patterns without a separator match names. Ones with a separator match
+/// the end of paths, so `src/*.rs` finds `~/project/src/main.rs`.
+pub fn glob_matcher(search: &str, case: CaseMatching) -> Option<GlobMatcher> {
+    let pattern = if !is_path_query(search) || search.starts_with(['/', '*']) {
+        search.to_string()
+    } else {
+        format!("**/{search}")
+    };
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .case_insensitive(!case.is_sensitive(search))
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .ok()
+}
+
+/// Whether `search` is matched against whole paths instead of names,
+/// which is the case once it contains a path separator like `work/api`
+pub fn is_path_query(search: &str) -> bool {
+    search.contains(['/', std::path::MAIN_SEPARATOR])
+}
+
+/// Find the separator delimited components of `search` in `path`, in order
+///
+/// The last component has to match the final path component, so `work/api`
+/// finds `~/work/api-server` but not `~/work/api/docs`, unless the query ends
+/// with a separator. Returns the byte ranges of the matched components.
+pub fn find_path_components(
+    path: &str,
+    search: &str,
+    case: CaseMatching,
+) -> Option<Vec<Range<usize>>> {
+    // Decide on the case once for the whole query, not per component
+    let case = case.resolve(search);
+    let components: Vec<&str> = search
+        .split(['/', std::path::MAIN_SEPARATOR])
+        .filter(|component| !component.is_empty())
+        .collect();
+    let in_name = !search.ends_with(['/', std::path::MAIN_SEPARATOR]);
+    let name_start = path
+        .trim_end_matches(['/', std::path::MAIN_SEPARATOR])
+        .rfind(['/', std::path::MAIN_SEPARATOR])
+        .map_or(0, |index| index + 1);
+
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for (index, component) in components.iter().enumerate() {
+        if in_name && index == components.len() - 1 {
+            offset = offset.max(name_start);
+        }
+        let range = find_matches(&path[offset..], component, case)
+            .into_iter()
+            .next()?;
+        ranges.push(offset + range.start..offset + range.end);
+        offset += range.end;
+    }
+    Some(ranges)
+}
+
+/// The terms of a query that entries have to contain, leaving out the
+/// `!excluded` ones
+pub fn included_terms(search: &str) -> impl Iterator<Item = &str> {
+    search
+        .split_whitespace()
+        .filter(|term| !term.starts_with('!'))
+}
+
+/// Sort `ranges` and join the overlapping ones, so they can be highlighted
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// A dimmed `(path)` shown after an entry, with the components found by
+/// path query terms highlighted
+pub fn path_spans(path: &Path, search: &str, case: CaseMatching) -> Vec<Span<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let path = path.to_string_lossy();
+    let case = case.resolve(search);
+    let ranges = merge_ranges(
+        included_terms(search)
+            .filter(|term| is_path_query(term))
+            .flat_map(|term| find_path_components(&path, term, case).unwrap_or_default())
+            .collect(),
+    );
+
+    let mut spans = vec![Span::styled("(", dim)];
+    let mut last_end = 0;
+    for range in ranges {
+        spans.push(Span::styled(path[last_end..range.start].to_string(), dim));
+        last_end = range.end;
+        spans.push(Span::styled(
+            path[range].to_string(),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    spans.push(Span::styled(format!("{})", &path[last_end..]), dim));
+    spans
+}
+
+/// Ranges of `name` to highlight for the terms of `search`
+///
+/// Path query terms are matched against `path`, and only the ranges within
+/// its final component `name` are kept.
+pub fn name_matches(
+    name: &str,
+    path: &Path,
+    search: &str,
+    case: CaseMatching,
+) -> Vec<Range<usize>> {
+    let case = case.resolve(search);
+    merge_ranges(
+        included_terms(search)
+            .flat_map(|term| term_name_matches(name, path, term, case))
+            .collect(),
+    )
+}
+
+fn term_name_matches(name: &str, path: &Path, term: &str, case: CaseMatching) -> Vec<Range<usize>> {
+    if !is_path_query(term) {
+        return find_matches(name, term, case);
+    }
+    let path = path.to_string_lossy();
+    let Some(name_start) = path
+        .len()
+        .checked_sub(name.len())
+        .filter(|&start| path.get(start..) == Some(name))
+    else {
+        return Vec::new();
+    };
+    find_path_components(&path, term, case)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|range| range.start >= name_start)
+        .map(|range| range.start - name_start..range.end - name_start)
+        .collect()
+}
+
+/// Find the non-overlapping occurrences of `search` in `text`
+///
+/// Ranges are byte offsets into `text` and always fall on char boundaries, even
+/// when lowercasing changes the byte length of a character.
+pub fn find_matches(text: &str, search: &str, case: CaseMatching) -> Vec<Range<usize>> {
+    let sensitive = case.is_sensitive(search);
+    let needle: Vec<char> = if sensitive {
+        search.chars().collect()
+    } else {
+        search.chars().flat_map(char::to_lowercase).collect()
+    };
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    let mut next_start = 0;
+    for (start, _) in text.char_indices() {
+        if start < next_start {
+            continue;
+        }
+        if let Some(len) = match_len(&text[start..], &needle, sensitive) {
+            matches.push(start..start + len);
+            next_start = start + len;
+        }
+    }
+    matches
+}
+
+/// Byte length of the prefix of `text` that matches `needle`, which is
+/// lowercased unless the match is case-sensitive
+fn match_len(text: &str, needle: &[char], sensitive: bool) -> Option<usize> {
+    let mut matched = 0;
+    for (index, c) in text.char_indices() {
+        let folded: Vec<char> = if sensitive {
+            vec![c]
+        } else {
+            c.to_lowercase().collect()
+        };
+        for folded_char in folded {
+            if needle.get(matched) != Some(&folded_char) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == needle.len() {
+            return Some(index + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Print a directory without starting the TUI
+///
+/// Without a query this is the current directory. With one, it is the best
+/// ranked history entry matching the query, e.g. `cd $(quickswitch --non-interactive proj)`.
+pub fn run_non_interactive(query: Option<&str>) -> Result<()> {
+    let Some(query) = query.filter(|query| !query.trim().is_empty()) else {
+        println!("{}", std::env::current_dir()?.display());
+        return Ok(());
+    };
+    run_query(query)
+}
+
+/// Print the directory an alias stands for, like `quickswitch query qw`
+///
+/// Queries that aren't an alias fall back to the best matching history entry,
+/// and then to the shallowest matching directory in the index.
+pub fn run_query(query: &str) -> Result<()> {
+    if let Some(path) = Aliases::load()?.resolve(query.trim()) {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    if let Some(entry) = HistoryDataProvider.best_match(query)? {
+        println!("{}", entry.path.display());
+        return Ok(());
+    }
+
+    match DirIndex::load()?.best_match(query) {
+        Some(path) => {
+            println!("{}", path.display());
+            Ok(())
+        }
+        None => Err(anyhow::anyhow!(
+            "No alias, history entry or indexed directory matches '{query}'"
+        )),
+    }
+}
+
+/// Key that launches the path-inserting widget set up by `--init --bind-key`
+///
+/// Written like fzf's keys, `ctrl-g` or `alt-g`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidgetKey {
+    pub alt: bool,
+    pub key: char,
+}
+
+impl std::str::FromStr for WidgetKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid key '{value}', expected ctrl-<letter> or alt-<letter>");
+        let lower = value.to_lowercase();
+        let (alt, key) = if let Some(key) = lower.strip_prefix("ctrl-") {
+            (false, key)
+        } else if let Some(key) = lower.strip_prefix("alt-") {
+            (true, key)
+        } else {
+            return Err(invalid());
+        };
+
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(key), None) if key.is_ascii_lowercase() => Some(key),
+            _ => None,
+        };
+        key.map(|key| Self { alt, key }).ok_or_else(invalid)
+    }
+}
+
+impl WidgetKey {
+    /// Key sequence as written for bash's `bind`
+    fn bash(&self) -> String {
+        if self.alt {
+            format!(r"\e{}", self.key)
+        } else {
+            format!(r"\C-{}", self.key)
+        }
+    }
+
+    /// Key sequence as written for zsh's `bindkey`
+    fn zsh(&self) -> String {
+        if self.alt {
+            format!("^[{}", self.key)
+        } else {
+            format!("^{}", self.key.to_ascii_uppercase())
+        }
+    }
+
+    /// Key sequence as written for fish's `bind`
+    fn fish(&self) -> String {
+        if self.alt {
+            format!(r"\e{}", self.key)
+        } else {
+            format!(r"\c{}", self.key)
+        }
+    }
+
+    /// Chord as written for PSReadLine's `Set-PSReadLineKeyHandler`
+    fn powershell(&self) -> String {
+        if self.alt {
+            format!("Alt+{}", self.key)
+        } else {
+            format!("Ctrl+{}", self.key)
+        }
+    }
 }
 
 // Init Bash and Zsh functions for quickswitch
@@ -230,19 +781,21 @@ pub fn run_non_interactive() -> Result<()> {
 fn qs_init_bash_zsh() -> Result<()> {
     let bash_init = r#"
 qs() {
-    local dir
-    dir=$(quickswitch 2>&1 >/dev/tty | tail -n 1)
-    if [ -d "$dir" ]; then
+    local cd_file dir qs_status
+    # The directory comes back through a file, so the UI keeps the terminal
+    cd_file=$(mktemp "${TMPDIR:-/tmp}/quickswitch.XXXXXX") || return
+    quickswitch --cd-file "$cd_file" "$@"
+    qs_status=$?
+    dir=$(cat "$cd_file")
+    rm -f "$cd_file"
+    # Esc exits with 1 and errors with 2, leaving the directory as it is
+    if [ "$qs_status" -eq 0 ] && [ -d "$dir" ]; then
         cd "$dir"
     fi
 }
 
 qshs() {
-    local dir
-    dir=$(quickswitch --mode history 2>&1 >/dev/tty | tail -n 1)
-    if [ -d "$dir" ]; then
-        cd "$dir"
-    fi
+    qs --mode history "$@"
 }
     "#;
     println!("{bash_init}");
@@ -255,10 +808,15 @@ qshs() {
 fn qs_init_fish() -> Result<()> {
     let fish_init = r#"
 function qs
-    set -l result (quickswitch 2>&1 >/dev/tty)
+    # The directory comes back through a file, so the UI keeps the terminal
+    set -l cd_file (mktemp)
+    quickswitch --cd-file $cd_file $argv
+    set -l qs_status $status
+    set -l dir (cat $cd_file)
+    rm -f $cd_file
 
-    if [ -n "$result" ]
-        cd -- $result
+    if [ $qs_status -eq 0 ] && [ -d "$dir" ]
+        cd -- $dir
 
         # Remove last token from commandline.
         commandline -t ""
@@ -269,17 +827,7 @@ function qs
 end
 
 function qshs
-    set -l result (quickswitch --mode history 2>&1 >/dev/tty)
-
-    if [ -n "$result" ]
-        cd -- $result
-
-        # Remove last token from commandline.
-        commandline -t ""
-        commandline -it -- $prefix
-    end
-
-    commandline -f repaint
+    qs --mode history $argv
 end
     "#;
     println!("{fish_init}");
@@ -291,24 +839,36 @@ end
 #[instrument]
 fn qs_init_powershell() -> Result<()> {
     let powershell_init = r#"
+function Invoke-QuickSwitch {
+    # The UI is drawn on stderr, only the result ends up on stdout
+    $encoding = [Console]::OutputEncoding
+    try {
+        [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+        $result = & quickswitch.exe --stdout @args
+    } finally {
+        [Console]::OutputEncoding = $encoding
+    }
+    if ($LASTEXITCODE -eq 0 -and $result) {
+        $result | Select-Object -Last 1
+    }
+}
+
 function qs {
-    $errorFile = [System.IO.Path]::GetTempFileName()
-    Start-Process -FilePath "quickswitch.exe" -NoNewWindow -Wait -RedirectStandardError $errorFile
-    $errorOutput = Get-Content -Path $errorFile -Encoding UTF8
-    Remove-Item $errorFile
-    if ($errorOutput -and (Test-Path $errorOutput)) {
-        cd $errorOutput
+    # The directory comes back through a file, so the UI keeps the console
+    $cdFile = New-TemporaryFile
+    try {
+        & quickswitch.exe --cd-file $cdFile.FullName @args
+        $dir = Get-Content -LiteralPath $cdFile.FullName -Raw -Encoding UTF8
+        if ($LASTEXITCODE -eq 0 -and $dir -and (Test-Path -LiteralPath $dir)) {
+            Set-Location -LiteralPath $dir
+        }
+    } finally {
+        Remove-Item -LiteralPath $cdFile.FullName -ErrorAction SilentlyContinue
     }
 }
 
 function qshs {
-    $errorFile = [System.IO.Path]::GetTempFileName()
-    Start-Process -FilePath "quickswitch.exe" -NoNewWindow -Wait -RedirectStandardError $errorFile -ArgumentList "--mode history"
-    $errorOutput = Get-Content -Path $errorFile -Encoding UTF8
-    Remove-Item $errorFile
-    if ($errorOutput -and (Test-Path $errorOutput)) {
-        cd $errorOutput
-    }
+    qs --mode history @args
 }
     "#;
     println!("{powershell_init}");
@@ -317,18 +877,237 @@ function qshs {
     Ok(())
 }
 
+/// Print a widget bound to `key` that inserts the selected paths at the cursor
+#[instrument]
+fn qs_init_widget(shell: ShellType, key: WidgetKey) -> Result<()> {
+    let widget = match shell {
+        ShellType::Bash => format!(
+            r#"
+__qs_widget() {{
+    local selected
+    selected=$(quickswitch --print-selection 2>&1 >/dev/tty </dev/tty | tail -n 1)
+    READLINE_LINE="${{READLINE_LINE:0:$READLINE_POINT}}$selected${{READLINE_LINE:$READLINE_POINT}}"
+    READLINE_POINT=$((READLINE_POINT + ${{#selected}}))
+}}
+bind -x '"{}": __qs_widget'
+    "#,
+            key.bash()
+        ),
+        ShellType::Zsh => format!(
+            r#"
+__qs_widget() {{
+    local selected
+    selected=$(quickswitch --print-selection 2>&1 >/dev/tty </dev/tty | tail -n 1)
+    LBUFFER="${{LBUFFER}}${{selected}}"
+    zle reset-prompt
+}}
+zle -N __qs_widget
+bindkey '{}' __qs_widget
+    "#,
+            key.zsh()
+        ),
+        ShellType::Fish => format!(
+            r#"
+function __qs_widget
+    set -l selected (quickswitch --print-selection 2>&1 >/dev/tty </dev/tty)
+
+    if [ -n "$selected" ]
+        commandline -i -- $selected
+    end
+
+    commandline -f repaint
+end
+bind {key} __qs_widget
+bind -M insert {key} __qs_widget
+    "#,
+            key = key.fish()
+        ),
+        ShellType::Powershell => format!(
+            r#"
+Set-PSReadLineKeyHandler -Chord '{}' -BriefDescription 'QuickSwitchInsertPath' -ScriptBlock {{
+    $selected = Invoke-QuickSwitch --print-selection
+    [Microsoft.PowerShell.PSConsoleReadLine]::InvokePrompt()
+    if ($selected) {{
+        [Microsoft.PowerShell.PSConsoleReadLine]::Insert($selected)
+    }}
+}}
+    "#,
+            key.powershell()
+        ),
+        ShellType::Cmd => {
+            error!("Key bindings are not supported in CMD.");
+            return Err(anyhow::anyhow!("Key bindings are not supported in CMD"));
+        }
+    };
+    println!("{widget}");
+    debug!("{widget}");
+
+    Ok(())
+}
+
 #[instrument]
 fn qs_init_cmd() -> Result<()> {
     error!("CMD initialization is not implemented yet. Please use PowerShell or another shell.");
     todo!("CMD initialization is not implemented yet");
 }
 
-pub fn qs_init(shell: ShellType) -> Result<()> {
+/// Print the shell functions, plus the path-inserting widget when a key is given
+pub fn qs_init(shell: ShellType, widget_key: Option<WidgetKey>) -> Result<()> {
     match shell {
         ShellType::Bash => qs_init_bash_zsh(),
         ShellType::Zsh => qs_init_bash_zsh(),
         ShellType::Fish => qs_init_fish(),
         ShellType::Powershell => qs_init_powershell(),
         ShellType::Cmd => qs_init_cmd(),
+    }?;
+
+    match widget_key {
+        Some(key) => qs_init_widget(shell, key),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+    }
+
+    #[test]
+    fn test_time_decay() {
+        let mut entry = HistoryEntry::new(PathBuf::from("/tmp"));
+        entry.frequency = 4;
+        entry.last_accessed = Utc::now() - chrono::Duration::days(30);
+
+        let mut config = HistoryConfig::default();
+        assert_eq!(entry.calculate_score(&config), 4.0 * 0.1);
+        config.decay = DecayFunction::Exponential;
+        assert!((entry.calculate_score(&config) - 2.0).abs() < 1e-9);
+        config.min_decay = 0.8;
+        assert!((entry.calculate_score(&config) - 3.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_widget_key() {
+        let key: WidgetKey = "ctrl-g".parse().unwrap();
+        assert_eq!(
+            (key.bash(), key.zsh(), key.fish()),
+            (r"\C-g".into(), "^G".into(), r"\cg".into())
+        );
+        let key: WidgetKey = "Alt-T".parse().unwrap();
+        assert_eq!(key.powershell(), "Alt+t");
+        assert!("ctrl-gg".parse::<WidgetKey>().is_err());
+        assert!("shift-g".parse::<WidgetKey>().is_err());
+    }
+
+    #[test]
+    fn test_natural_cmp_orders_numbers_by_value() {
+        assert_eq!(natural_cmp("file2.txt", "file10.txt"), Ordering::Less);
+        assert_eq!(natural_cmp("file10.txt", "file9.txt"), Ordering::Greater);
+        assert_eq!(natural_cmp("v1.2.10", "v1.2.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_natural_cmp_falls_back_to_plain_comparison() {
+        assert_eq!(natural_cmp("abc", "abd"), Ordering::Less);
+        assert_eq!(natural_cmp("abc", "abc"), Ordering::Equal);
+        assert_eq!(natural_cmp("file", "file1"), Ordering::Less);
+        // Equal values with different zero padding still have a stable order
+        assert_eq!(natural_cmp("file01", "file1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_find_matches_non_ascii() {
+        let smart = CaseMatching::Smart;
+        assert_eq!(find_matches("项目文档", "文档", smart), vec![6..12]);
+        assert_eq!(find_matches("Café CAFÉ", "café", smart), vec![0..5, 6..11]);
+        // Lowercasing 'İ' yields two chars, the range must still cover the original
+        assert_eq!(find_matches("İstanbul", "i̇st", smart), vec![0..4]);
+        assert!(find_matches("abc", "", smart).is_empty());
+    }
+
+    #[test]
+    fn test_find_path_components() {
+        let smart = CaseMatching::Smart;
+        assert_eq!(
+            find_path_components("/home/me/work/api-server", "work/api", smart),
+            Some(vec![9..13, 14..17])
+        );
+        // The last component has to be in the name of the entry
+        assert_eq!(
+            find_path_components("/home/me/work/api/docs", "work/api", smart),
+            None
+        );
+        assert_eq!(
+            find_path_components("/home/me/work/api/docs", "work/api/", smart),
+            Some(vec![9..13, 14..17])
+        );
+        assert_eq!(
+            find_path_components("/home/me/api/work", "work/api", smart),
+            None
+        );
+        assert_eq!(
+            name_matches(
+                "api-server",
+                Path::new("/home/me/work/api-server"),
+                "work/api",
+                smart
+            ),
+            vec![0..3]
+        );
+    }
+
+    #[test]
+    fn test_name_matches_every_included_term() {
+        let smart = CaseMatching::Smart;
+        let path = Path::new("/src/test_helpers.rs");
+        assert_eq!(
+            name_matches("test_helpers.rs", path, "help test !snap", smart),
+            vec![0..4, 5..9]
+        );
+        assert_eq!(
+            name_matches("test_helpers.rs", path, "test est", smart),
+            vec![0..4]
+        );
+    }
+
+    #[test]
+    fn test_glob_matcher() {
+        let smart = CaseMatching::Smart;
+        let rust = glob_matcher("*.rs", smart).unwrap();
+        assert!(rust.is_match("main.RS"));
+        assert!(!rust.is_match("main.rs.bak"));
+
+        let toml = glob_matcher("src/**/*.toml", smart).unwrap();
+        assert!(toml.is_match("/home/me/project/src/config/app.toml"));
+        assert!(toml.is_match("/home/me/project/src/app.toml"));
+        assert!(!toml.is_match("/home/me/project/app.toml"));
+
+        assert!(!is_glob_query("main.rs"));
+        assert!(glob_matcher("[a", smart).is_none());
+    }
+
+    #[test]
+    fn test_find_matches_case() {
+        let text = "Makefile makefile";
+        assert_eq!(
+            find_matches(text, "make", CaseMatching::Smart),
+            vec![0..4, 9..13]
+        );
+        // An uppercase letter makes a smart-case query match case
+        assert_eq!(find_matches(text, "Make", CaseMatching::Smart), vec![0..4]);
+        assert_eq!(
+            find_matches(text, "make", CaseMatching::Sensitive),
+            vec![9..13]
+        );
+        assert_eq!(
+            find_matches(text, "Make", CaseMatching::Insensitive),
+            vec![0..4, 9..13]
+        );
     }
 }